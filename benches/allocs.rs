@@ -0,0 +1,93 @@
+//! Allocations made decoding each packet in `tests/corpus`. Run with & without the
+//! `smallvec` feature to compare:
+//!
+//! ```text
+//! cargo bench --bench allocs
+//! cargo bench --bench allocs --features smallvec
+//! ```
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use dhcproto::{v4, v6, Decodable};
+
+// counts allocations & reallocations, frees aren't counted
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// hex bytes separated by whitespace, `#` starts a comment
+fn read_hex(path: &Path) -> Vec<u8> {
+    let hex = fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(str::split_whitespace)
+        .collect::<String>();
+    hex::decode(hex).unwrap()
+}
+
+fn allocs<T: Decodable>(bytes: &[u8]) -> usize {
+    let before = ALLOCS.load(Ordering::Relaxed);
+    let msg = T::from_bytes(bytes).unwrap();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - before;
+    drop(msg);
+    allocs
+}
+
+fn report<T: Decodable>(dir: &str) -> usize {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/corpus")
+        .join(dir);
+    let mut paths = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "hex"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    let mut total = 0;
+    for path in paths {
+        let bytes = read_hex(&path);
+        let allocs = allocs::<T>(&bytes);
+        total += allocs;
+        let name = path.file_stem().unwrap().to_string_lossy();
+        println!("{name:>24} {allocs:>4}");
+    }
+    total
+}
+
+fn main() {
+    println!(
+        "allocations per decode, smallvec {}",
+        if cfg!(feature = "smallvec") {
+            "on"
+        } else {
+            "off"
+        }
+    );
+    let v4 = report::<v4::Message>("v4");
+    let v6 = report::<v6::Message>("v6");
+    println!("{:>24} {v4:>4}\n{:>24} {v6:>4}", "v4 total", "v6 total");
+}
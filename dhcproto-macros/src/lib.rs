@@ -1,10 +1,13 @@
-use proc_macro::{Group, Ident, TokenTree};
+use proc_macro::{Delimiter, Group, Ident, TokenTree};
 
 struct Entry {
     code: u8,
     id: Ident,
     description: String,
+    deprecated: bool,
+    name: String,
     data_type: Option<Group>,
+    example: Option<Vec<u8>>,
 }
 
 fn parse_input(input: proc_macro::TokenStream) -> Vec<Entry> {
@@ -34,81 +37,280 @@ fn parse_input(input: proc_macro::TokenStream) -> Vec<Entry> {
                 panic!("expected description");
             };
 
-            let data_type = match tokens.next() {
-                Some(TokenTree::Group(x)) => Some(x),
+            let mut next = tokens.next();
+            let deprecated = matches!(&next, Some(TokenTree::Ident(i)) if i.to_string() == "deprecated");
+            if deprecated {
+                next = tokens.next();
+            }
+            let name = if let Some(TokenTree::Literal(name)) = &next {
+                let name = name.to_string();
+                next = tokens.next();
+                name[1..name.len() - 1].to_owned()
+            } else {
+                kebab_case(&id.to_string())
+            };
+            let data_type = match next {
+                Some(TokenTree::Group(x)) if x.delimiter() == Delimiter::Parenthesis => {
+                    next = tokens.next();
+                    Some(x)
+                }
+                _ => None,
+            };
+            let example = match next {
+                Some(TokenTree::Group(x)) if x.delimiter() == Delimiter::Bracket => {
+                    Some(parse_example(code, &id, &x))
+                }
                 None => None,
-                e => panic!("expected nothing or id not {e:?}"),
+                e => panic!("expected nothing, types or example bytes not {e:?}"),
             };
             entries.push(Entry {
                 code,
                 id,
                 description,
+                deprecated,
+                name,
                 data_type,
+                example,
             })
         }
     }
     entries
 }
 
+// `[6, 4, 1, 1, 1, 1]` -> the wire bytes of the option, checking they start with its code & length
+fn parse_example(code: u8, id: &Ident, group: &Group) -> Vec<u8> {
+    let bytes = group
+        .stream()
+        .into_iter()
+        .filter_map(|t| match t {
+            TokenTree::Literal(lit) => Some(
+                lit.to_string()
+                    .parse::<u8>()
+                    .unwrap_or_else(|_| panic!("{id} example: {lit} isn't a u8")),
+            ),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bytes.first(),
+        Some(&code),
+        "{id} example doesn't start with its code"
+    );
+    if bytes.len() > 1 {
+        assert_eq!(
+            bytes[1] as usize,
+            bytes.len() - 2,
+            "{id} example length doesn't match its data"
+        );
+    }
+    bytes
+}
+
+// a doc example decoding the entry's example bytes to its variant & encoding them again
+fn example_doc(e: &Entry) -> String {
+    let Some(bytes) = &e.example else {
+        return String::new();
+    };
+    let id = &e.id;
+    let bytes = bytes
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let pattern = if e.data_type.is_some() { "(..)" } else { "" };
+    format!(
+        "
+        ///
+        /// ```
+        /// # use dhcproto::{{v4::DhcpOption, Decodable, Decoder, Encodable}};
+        /// let bytes = [{bytes}];
+        /// let opt = DhcpOption::decode(&mut Decoder::new(&bytes)).unwrap();
+        /// assert!(matches!(opt, DhcpOption::{id}{pattern}));
+        /// assert_eq!(opt.to_vec().unwrap(), bytes);
+        /// ```
+        "
+    )
+}
+
+// a doc paragraph for entries marked `deprecated`
+fn deprecated_doc(e: &Entry) -> &'static str {
+    if e.deprecated {
+        "\n///\n/// **Deprecated**, shouldn't be sent, see [`OptionMetadata::deprecated`](crate::metadata::OptionMetadata::deprecated)"
+    } else {
+        ""
+    }
+}
+
+// `DomainNameServer` -> `domain-name-server`, `TFTPServerName` -> `tftp-server-name`
+fn kebab_case(id: &str) -> String {
+    let chars = id.trim_start_matches('_').chars().collect::<Vec<_>>();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if !prev.is_ascii_uppercase() || next_lower {
+                out.push('-');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
 fn generate_optioncode_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = String> + 'a {
-    let enum_impl = std::iter::once(
+    let struct_impl = std::iter::once(
         "
-        /// DHCP Options
-        #[cfg_attr(feature = \"serde\", derive(Serialize, Deserialize))]
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-        pub enum OptionCode {"
+        /// DHCP option code, a number with a constant for each option this crate knows,
+        /// e.g. `OptionCode::Router` is `OptionCode::new(3)`
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[repr(transparent)]
+        pub struct OptionCode(u8);
+
+        #[allow(non_upper_case_globals)]
+        impl OptionCode {"
             .to_owned(),
     )
     .chain(entries.iter().map(|e| {
         let description = &e.description[1..&e.description.len() - 1];
         let id = &e.id;
         let code = e.code;
-        format!("/// {code} - {description}\n{id},")
+        let deprecated = deprecated_doc(e);
+        format!("/// {code} - {description}{deprecated}\npub const {id}: OptionCode = OptionCode({code});")
     }))
+    .chain(std::iter::once("}".to_owned()));
+
+    let known = std::iter::once(
+        "
+        impl OptionCode {
+            /// every option code known to this crate, in code order
+            pub const KNOWN: &'static [OptionCode] = &["
+            .to_owned(),
+    )
+    .chain(entries.iter().map(|e| format!("OptionCode::{},", e.id)))
     .chain(std::iter::once(
+        "];
+
+        /// get the option code for `n`, usable in const context
+        pub const fn from_u8(x: u8) -> Self {
+            Self(x)
+        }
+
+        /// get the numeric code, usable in const context
+        pub const fn as_u8(self) -> u8 {
+            self.0
+        }
         "
-    /// Unknown code
-    Unknown(u8),
-    }
-    "
         .to_owned(),
     ));
 
-    let impl_option_from_u8 = std::iter::once(
+    let variant_name = std::iter::once(
         "
-        impl std::convert::From<u8> for OptionCode {
-        fn from(x : u8) -> Self{
-            match x {
+        // the variant name, `None` for unknown codes
+        fn variant_name(self) -> Option<&'static str> {
+            match self {
         "
         .to_owned(),
     )
     .chain(entries.iter().map(|e| {
         let id = &e.id;
-        let code = e.code;
-        format!("{code} => Self::{id},")
+        format!("Self::{id} => Some(\"{id}\"),")
     }))
-    .chain(std::iter::once("_ => Self::Unknown(x)}}}".to_owned()));
+    .chain(std::iter::once("_ => None }}".to_owned()));
 
-    let impl_u8_from_option = std::iter::once(
+    let name = std::iter::once(
+        "
+        /// the canonical (ISC/kea style) option name, e.g. `domain-name-servers`.
+        /// `None` for unknown codes
+        pub const fn name(self) -> Option<&'static str> {
+            match self {
         "
+        .to_owned(),
+    )
+    .chain(entries.iter().map(|e| {
+        let id = &e.id;
+        let name = &e.name;
+        format!("Self::{id} => Some(\"{name}\"),")
+    }))
+    .chain(std::iter::once("_ => None }}}".to_owned()));
+
+    let impl_from = std::iter::once(
+        "
+        impl std::convert::From<u8> for OptionCode {
+            fn from(x: u8) -> Self {
+                Self::from_u8(x)
+            }
+        }
+
         impl std::convert::From<OptionCode> for u8 {
-        fn from(x : OptionCode) -> Self{
-            match x {
+            fn from(x: OptionCode) -> Self {
+                x.as_u8()
+            }
+        }
+        "
+        .to_owned(),
+    );
+
+    let metadata = std::iter::once(
+        "
+        impl OptionCode {
+            /// the name, RFC link & value kind of this option, `None` for unknown codes
+            pub const fn metadata(self) -> Option<crate::metadata::OptionMetadata> {
+                use crate::metadata::{OptionMetadata, ValueKind};
+                match self {
         "
         .to_owned(),
     )
     .chain(entries.iter().map(|e| {
         let id = &e.id;
-        let code = e.code;
-        format!("OptionCode::{id} => {code},")
+        let name = &e.name;
+        let kind = value_kind(e);
+        let rfc = match rfc_link(e) {
+            Some(link) => format!("Some(\"{link}\")"),
+            None => "None".to_owned(),
+        };
+        let deprecated = e.deprecated;
+        format!(
+            "Self::{id} => Some(OptionMetadata {{ name: \"{name}\", rfc: {rfc}, kind: ValueKind::{kind}, deprecated: {deprecated} }}),"
+        )
     }))
-    .chain(std::iter::once(
-        "OptionCode::Unknown(code) => code }}}".to_owned(),
-    ));
+    .chain(std::iter::once("_ => None }}}".to_owned()));
+
+    struct_impl
+        .chain(known)
+        .chain(variant_name)
+        .chain(name)
+        .chain(impl_from)
+        .chain(metadata)
+}
+
+// the `ValueKind` variant for the entry's data type, `Structured` for anything with its own type
+fn value_kind(e: &Entry) -> &'static str {
+    let types = field_types(e);
+    match types.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => "Empty",
+        ["bool"] => "Bool",
+        ["u8"] => "U8",
+        ["u16"] => "U16",
+        ["u32"] | ["LeaseTime"] => "U32",
+        ["i32"] => "I32",
+        ["Ipv4Addr"] => "Ipv4Addr",
+        ["Vec<Ipv4Addr>"] => "Ipv4Addrs",
+        ["String"] | ["ByteString"] => "String",
+        ["Vec<u8>"] => "Bytes",
+        ["Domain"] => "Domain",
+        ["Vec<Domain>"] => "Domains",
+        ["Uri"] => "Uri",
+        ["Vec<Uri>"] => "Uris",
+        _ => "Structured",
+    }
+}
 
-    enum_impl
-        .chain(impl_option_from_u8)
-        .chain(impl_u8_from_option)
+// the first `<http..>` link in the entry's description
+fn rfc_link(e: &Entry) -> Option<&str> {
+    let start = e.description.find("<http")? + 1;
+    let len = e.description[start..].find('>')?;
+    Some(&e.description[start..start + len])
 }
 
 fn generate_dhcpoption_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = String> + 'a {
@@ -116,7 +318,8 @@ fn generate_dhcpoption_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = St
         "
         /// DHCP Options
         #[cfg_attr(feature = \"serde\", derive(Serialize, Deserialize))]
-        #[derive(Debug, Clone, PartialEq, Eq)]
+        #[cfg_attr(not(feature = \"redact\"), derive(Debug))]
+        #[derive(Clone, PartialEq, Eq)]
         pub enum DhcpOption {"
             .to_owned(),
     )
@@ -124,10 +327,12 @@ fn generate_dhcpoption_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = St
         let description = &e.description[1..&e.description.len() - 1];
         let id = &e.id;
         let code = e.code;
+        let deprecated = deprecated_doc(e);
+        let example = example_doc(e);
         if let Some(data_description) = &e.data_type {
-            format!("/// {code} - {description}\n{id}{data_description},")
+            format!("/// {code} - {description}{deprecated}{example}\n{id}{data_description},")
         } else {
-            format!("/// {code} - {description}\n{id},")
+            format!("/// {code} - {description}{deprecated}{example}\n{id},")
         }
     }))
     .chain(std::iter::once(
@@ -167,19 +372,325 @@ fn generate_dhcpoption_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = St
         format!("O::{id}{var_field} => OptionCode::{id},")
     }))
     .chain(std::iter::once(
-        "O::Unknown(n) => OptionCode::Unknown(n.code)}}}".to_owned(),
+        "O::Unknown(n) => n.code.into()}}}".to_owned(),
+    ));
+
+    // with the `redact` feature, Debug masks the fields of any option for which
+    // `OptionCode::is_redacted` returns true
+    let impl_debug_dhcpoption = std::iter::once(
+        "
+        #[cfg(feature = \"redact\")]
+        impl std::fmt::Debug for DhcpOption {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                use DhcpOption as O;
+                match self {
+        "
+        .to_owned(),
+    )
+    .chain(entries.iter().map(|e| {
+        let id = &e.id;
+        match field_count(e) {
+            0 => format!("O::{id} => f.write_str(\"{id}\"),"),
+            n => {
+                let fields = (0..n).map(|i| format!("a{i}")).collect::<Vec<_>>();
+                let debug_fields = fields
+                    .iter()
+                    .map(|a| format!(".field(&crate::redact::Redacted::new({a}, redact))"))
+                    .collect::<String>();
+                format!(
+                    "O::{id}({}) => {{
+                        let redact = OptionCode::{id}.is_redacted();
+                        f.debug_tuple(\"{id}\"){debug_fields}.finish()
+                    }}",
+                    fields.join(",")
+                )
+            }
+        }
+    }))
+    .chain(std::iter::once(
+        "O::Unknown(n) => f.debug_tuple(\"Unknown\").field(n).finish()}}}".to_owned(),
     ));
 
-    impl_dhcp_option.chain(impl_optioncode_from_dhcpoption_ref)
+    impl_dhcp_option
+        .chain(impl_optioncode_from_dhcpoption_ref)
+        .chain(impl_debug_dhcpoption)
+}
+
+// number of fields in the variant, one more than the top level commas in its data type
+fn field_count(e: &Entry) -> usize {
+    e.data_type.as_ref().map_or(0, |data_description| {
+        1 + data_description
+            .stream()
+            .into_iter()
+            .filter(|e| matches!(e, TokenTree::Punct(p) if p.as_char() == ','))
+            .count()
+    })
+}
+
+// split the variant's data type on top level commas, one string per field
+fn field_types(e: &Entry) -> Vec<String> {
+    let mut fields = Vec::new();
+    if let Some(data_description) = &e.data_type {
+        let mut field = String::new();
+        for token in data_description.stream() {
+            match token {
+                TokenTree::Punct(p) if p.as_char() == ',' => {
+                    fields.push(std::mem::take(&mut field));
+                }
+                // keep `Vec<u8>` & `url::Url` together, space out everything else
+                TokenTree::Punct(p) => field.push(p.as_char()),
+                other => {
+                    if field.ends_with(|c: char| c.is_alphanumeric()) {
+                        field.push(' ');
+                    }
+                    field.push_str(&other.to_string());
+                }
+            }
+        }
+        if !field.is_empty() {
+            fields.push(field);
+        }
+    }
+    fields
+}
+
+// generates a marker type per option in the `typed` module & its `TypedOption` impl
+fn generate_typed_code<'a>(entries: &'a [Entry]) -> impl Iterator<Item = String> + 'a {
+    let markers = std::iter::once(
+        "
+        /// Marker types for each option, for use with [`DhcpOptions::get_typed`](super::DhcpOptions::get_typed)
+        pub mod typed {"
+            .to_owned(),
+    )
+    .chain(entries.iter().map(|e| {
+        let id = &e.id;
+        let code = e.code;
+        format!(
+            "/// {code} - marker for [`DhcpOption::{id}`](super::DhcpOption::{id})
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub struct {id};"
+        )
+    }))
+    .chain(std::iter::once("}".to_owned()));
+
+    let impls = entries.iter().map(|e| {
+        let id = &e.id;
+        let types = field_types(e);
+        let (value, pattern, ret) = match types.len() {
+            0 => ("()".to_owned(), "".to_owned(), "()".to_owned()),
+            1 => (
+                format!("&'a {}", types[0]),
+                "(a0)".to_owned(),
+                "a0".to_owned(),
+            ),
+            n => {
+                let fields = (0..n)
+                    .map(|i| format!("a{i}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let value = types
+                    .iter()
+                    .map(|ty| format!("&'a {ty}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                (
+                    format!("({value})"),
+                    format!("({fields})"),
+                    format!("({fields})"),
+                )
+            }
+        };
+        format!(
+            "impl TypedOption for typed::{id} {{
+                const CODE: OptionCode = OptionCode::{id};
+                type Value<'a> = {value};
+                fn value(opt: &DhcpOption) -> Option<Self::Value<'_>> {{
+                    match opt {{
+                        DhcpOption::{id}{pattern} => Some({ret}),
+                        _ => None,
+                    }}
+                }}
+            }}"
+        )
+    });
+
+    markers.chain(impls)
+}
+
+// an optional `roundtrip_tests = path::to::Sample,` before the entries -> the trait path
+fn parse_roundtrip_tests(input: proc_macro::TokenStream) -> Option<String> {
+    let mut input = input.into_iter();
+    match input.next() {
+        Some(TokenTree::Ident(id)) if id.to_string() == "roundtrip_tests" => {}
+        _ => return None,
+    }
+    match input.next() {
+        Some(TokenTree::Punct(p)) if p.as_char() == '=' => {}
+        e => panic!("expected `=` after roundtrip_tests, not {e:?}"),
+    }
+    let path = input
+        .take_while(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == ','))
+        .map(|t| t.to_string())
+        .collect::<String>();
+    assert!(!path.is_empty(), "expected the sample trait path");
+    Some(path)
+}
+
+// generates a test per option that encodes a sample value, decodes it & compares,
+// `sample` is a trait with `fn sample() -> Self` implemented for every field type
+fn generate_roundtrip_tests<'a>(
+    entries: &'a [Entry],
+    sample: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let header = std::iter::once(format!(
+        "
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod declared_roundtrip {{
+            use super::*;
+            use {sample};
+
+            fn roundtrip(opt: DhcpOption) {{
+                let bytes = opt.to_vec().unwrap();
+                let decoded = DhcpOption::decode(&mut Decoder::new(&bytes)).unwrap();
+                assert_eq!(decoded, opt);
+                assert_eq!(decoded.to_vec().unwrap(), bytes);
+            }}
+        "
+    ));
+    let tests = entries.iter().map(|e| {
+        let id = &e.id;
+        let value = match field_count(e) {
+            0 => "".to_owned(),
+            n => format!("({})", vec!["Sample::sample()"; n].join(",")),
+        };
+        format!(
+            "#[test]
+            fn {id}() {{
+                roundtrip(DhcpOption::{id}{value});
+            }}"
+        )
+    });
+    header.chain(tests).chain(std::iter::once("}".to_owned()))
+}
+
+struct HTypeEntry {
+    code: u32,
+    id: Ident,
+    description: String,
+}
+
+// `u8, {1, Eth, "Ethernet"}, ..` -> the representation & the entries
+fn parse_htypes(input: proc_macro::TokenStream) -> (Ident, Vec<HTypeEntry>) {
+    let mut input = input.into_iter();
+    let repr = match input.next() {
+        Some(TokenTree::Ident(repr)) => repr,
+        e => panic!("expected the representation type, not {e:?}"),
+    };
+    let max = match repr.to_string().as_str() {
+        "u8" => u8::MAX as u32,
+        "u16" => u16::MAX as u32,
+        e => panic!("expected u8 or u16, not {e}"),
+    };
+    let mut entries = Vec::new();
+    for x in input {
+        if let TokenTree::Group(group) = x {
+            let mut tokens = group
+                .stream()
+                .into_iter()
+                .filter(|x| !matches!(x, TokenTree::Punct(_)));
+            let code = match tokens.next() {
+                Some(TokenTree::Literal(lit)) => lit.to_string().parse::<u32>().unwrap(),
+                _ => panic!("expected code"),
+            };
+            assert!(code <= max, "htype {code} doesn't fit in {repr}");
+            let id = match tokens.next() {
+                Some(TokenTree::Ident(id)) => id,
+                _ => panic!("expected id"),
+            };
+            let description = match tokens.next() {
+                Some(TokenTree::Literal(description)) => description.to_string(),
+                _ => panic!("expected description"),
+            };
+            entries.push(HTypeEntry {
+                code,
+                id,
+                description,
+            });
+        }
+    }
+    (repr, entries)
+}
+
+// declares hardware types from the IANA registry
+// <https://www.iana.org/assignments/arp-parameters/arp-parameters.xhtml#arp-parameters-2>
+// generates the `HType` enum, From<repr> for HType & From<HType> for repr
+#[proc_macro]
+pub fn declare_htypes(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (repr, entries) = parse_htypes(input);
+    let variants = entries
+        .iter()
+        .map(|e| {
+            let description = &e.description[1..e.description.len() - 1];
+            format!("/// {} {description}\n{},", e.code, e.id)
+        })
+        .collect::<String>();
+    let from_repr = entries
+        .iter()
+        .map(|e| format!("{} => HType::{},", e.code, e.id))
+        .collect::<String>();
+    let into_repr = entries
+        .iter()
+        .map(|e| format!("HType::{} => {},", e.id, e.code))
+        .collect::<String>();
+    format!(
+        "
+        /// Hardware type of message
+        #[cfg_attr(feature = \"serde\", derive(Serialize, Deserialize))]
+        #[derive(Debug, Copy, Hash, Clone, PartialEq, Eq)]
+        pub enum HType {{
+            {variants}
+            /// Unknown or not yet implemented htype
+            Unknown({repr}),
+        }}
+
+        impl From<{repr}> for HType {{
+            fn from(n: {repr}) -> Self {{
+                match n {{
+                    {from_repr}
+                    n => HType::Unknown(n),
+                }}
+            }}
+        }}
+
+        impl From<HType> for {repr} {{
+            fn from(n: HType) -> Self {{
+                match n {{
+                    {into_repr}
+                    HType::Unknown(n) => n,
+                }}
+            }}
+        }}
+        "
+    )
+    .parse()
+    .unwrap()
 }
 
 #[proc_macro]
 pub fn declare_codes(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let sample = parse_roundtrip_tests(input.clone());
     let entries = parse_input(input);
     let enum_code = generate_optioncode_code(&entries);
     let dhcpoption_code = generate_dhcpoption_code(&entries);
+    let typed_code = generate_typed_code(&entries);
+    let roundtrip_code = sample
+        .iter()
+        .flat_map(|sample| generate_roundtrip_tests(&entries, sample));
     enum_code
         .chain(dhcpoption_code)
+        .chain(typed_code)
+        .chain(roundtrip_code)
         .collect::<String>()
         .parse()
         .unwrap()
@@ -0,0 +1,167 @@
+//! Address state bookkeeping for servers
+//!
+//! Servers probe an address (ICMP echo, ARP or NDP) before offering it and quarantine
+//! addresses that clients decline or that are found in use
+//! <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.1>. [`AddrState`] and
+//! [`ProbeResult`] give that lifecycle a common vocabulary, with serde support for
+//! persisting it. Storage is left to the server, the types only hold wall clock times.
+//!
+//! ```rust
+//! use std::time::{Duration, SystemTime};
+//! use dhcproto::addrstate::{AddrState, ProbeResult};
+//!
+//! let now = SystemTime::now();
+//! let quarantine = Duration::from_secs(600);
+//!
+//! let state = AddrState::Available.probed(ProbeResult::InUse, now, quarantine);
+//! assert_eq!(state, AddrState::Quarantined { until: now + quarantine });
+//! assert!(!state.is_available(now));
+//! assert!(state.is_available(now + quarantine));
+//! ```
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The outcome of checking whether an address is in use before offering it
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProbeResult {
+    /// nothing answered, the address looks free
+    NoReply,
+    /// something answered, the address is in use
+    InUse,
+    /// the probe couldn't be sent or completed
+    Inconclusive,
+}
+
+/// Where an address is in its lifecycle
+///
+/// Timed states end on their own: once their time has passed the address is
+/// available again, see [`AddrState::current`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddrState {
+    /// free to offer
+    #[default]
+    Available,
+    /// offered to a client, held until `expires` for its request
+    Offered {
+        /// when the offer lapses
+        expires: SystemTime,
+    },
+    /// leased to a client
+    Leased {
+        /// when the lease ends
+        until: SystemTime,
+    },
+    /// declined by a client or found in use, not offered until `until`
+    Quarantined {
+        /// when the address can be offered again
+        until: SystemTime,
+    },
+}
+
+impl AddrState {
+    /// the time a timed state ends, `None` for [`AddrState::Available`]
+    pub fn expiry(&self) -> Option<SystemTime> {
+        match self {
+            AddrState::Available => None,
+            AddrState::Offered { expires: time }
+            | AddrState::Leased { until: time }
+            | AddrState::Quarantined { until: time } => Some(*time),
+        }
+    }
+
+    /// the state at `now`, [`AddrState::Available`] if a timed state has ended
+    pub fn current(self, now: SystemTime) -> Self {
+        match self.expiry() {
+            Some(time) if time <= now => AddrState::Available,
+            _ => self,
+        }
+    }
+
+    /// `true` if the address can be offered at `now`
+    pub fn is_available(&self, now: SystemTime) -> bool {
+        self.current(now) == AddrState::Available
+    }
+
+    /// offer the address, holding it for `hold`
+    pub fn offer(now: SystemTime, hold: Duration) -> Self {
+        AddrState::Offered {
+            expires: saturating_add(now, hold),
+        }
+    }
+
+    /// lease the address for `lease`. Times past what a `SystemTime` can hold, like
+    /// `Duration::MAX` for an infinite lease, end far in the future instead of panicking
+    pub fn lease(now: SystemTime, lease: Duration) -> Self {
+        AddrState::Leased {
+            until: saturating_add(now, lease),
+        }
+    }
+
+    /// quarantine the address for `quarantine`, e.g. after a DHCPDECLINE
+    pub fn quarantine(now: SystemTime, quarantine: Duration) -> Self {
+        AddrState::Quarantined {
+            until: saturating_add(now, quarantine),
+        }
+    }
+
+    /// the state after probing the address: quarantined for `quarantine` if it's in use,
+    /// unchanged otherwise
+    pub fn probed(self, result: ProbeResult, now: SystemTime, quarantine: Duration) -> Self {
+        match result {
+            ProbeResult::InUse => AddrState::quarantine(now, quarantine),
+            ProbeResult::NoReply | ProbeResult::Inconclusive => self,
+        }
+    }
+}
+
+// `now + d`, or a time far in the future if that doesn't fit in a `SystemTime`, e.g. for
+// a `Duration::MAX` infinite lease. The platform's range is unknown, halve `d` until it
+// fits
+fn saturating_add(now: SystemTime, mut d: Duration) -> SystemTime {
+    loop {
+        match now.checked_add(d) {
+            Some(time) => return time,
+            None => d /= 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let secs = Duration::from_secs;
+        assert!(AddrState::default().is_available(now));
+
+        let offered = AddrState::offer(now, secs(5));
+        assert_eq!(offered.expiry(), Some(now + secs(5)));
+        assert!(!offered.is_available(now + secs(4)));
+        assert_eq!(offered.current(now + secs(5)), AddrState::Available);
+
+        let leased = AddrState::lease(now, secs(3600));
+        assert_eq!(leased.probed(ProbeResult::NoReply, now, secs(60)), leased);
+        assert_eq!(
+            leased.probed(ProbeResult::Inconclusive, now, secs(60)),
+            leased
+        );
+        assert_eq!(
+            leased.probed(ProbeResult::InUse, now, secs(60)),
+            AddrState::Quarantined {
+                until: now + secs(60)
+            }
+        );
+        assert!(AddrState::quarantine(now, secs(60)).is_available(now + secs(60)));
+
+        // an infinite lease doesn't overflow
+        let forever = AddrState::lease(now, Duration::MAX);
+        assert!(forever.expiry() > Some(now + secs(100 * 365 * 24 * 3600)));
+        assert!(!forever.is_available(now + secs(u32::MAX.into())));
+    }
+}
@@ -0,0 +1,197 @@
+//! Helpers for verifying authentication options
+//!
+//! Both families authenticate a message with a MAC computed over the whole encoded
+//! message, with the MAC itself zeroed: the v4 authentication option (90) of
+//! RFC 3118 and the v6 `Authentication` option, e.g. RKAP in Reconfigure messages.
+//! The MAC has to be checked against the bytes on the wire, so these work on the
+//! encoded message: find where the MAC is, make the canonical copy to run the MAC
+//! over, and compare the result without leaking timing.
+//!
+//! ```rust
+//! use dhcproto::{auth, v6::{self, reconfigure}, Decodable, Encodable};
+//!
+//! let auth = reconfigure::rkap_auth(1);
+//! let msg = v6::Message::reconfigure(vec![1], vec![2], v6::MessageType::Renew, auth).unwrap();
+//! let mut bytes = msg.to_vec()?;
+//! let mac = auth::v6_mac_range(&bytes).unwrap();
+//! // the server writes the HMAC-MD5 of the canonical message
+//! let canonical = auth::v6_canonical(&bytes).unwrap();
+//! # let hmac_md5 = |_: &[u8]| [7; 16];
+//! let digest = hmac_md5(&canonical);
+//! bytes[mac].copy_from_slice(&digest);
+//!
+//! // the client recomputes it and compares in constant time
+//! let received = v6::Message::from_bytes(&bytes)?;
+//! let opt = received.opts().get(v6::OptionCode::Authentication);
+//! let Some(v6::DhcpOption::Authentication(opt)) = opt else {
+//!     panic!("no authentication");
+//! };
+//! assert!(auth::ct_eq(opt.mac(), &hmac_md5(&auth::v6_canonical(&bytes).unwrap())));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use std::ops::Range;
+
+use crate::v6::reconfigure::RKAP_PROTOCOL;
+
+// v4 header up to the options, and where `hops` & `giaddr` are
+const V4_VENDOR: usize = 240;
+const V4_HOPS: usize = 3;
+const V4_GIADDR: Range<usize> = 24..28;
+const V4_AUTH: u8 = 90;
+// v6 msg-type & transaction id
+const V6_HEADER: usize = 4;
+const V6_AUTH: u16 = 11;
+// protocol, algorithm, RDM & replay detection
+const AUTH_FIXED: usize = 11;
+
+/// Compare two MACs in time that only depends on their length
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y));
+    // keep the compiler from short-circuiting the fold
+    std::hint::black_box(diff) == 0
+}
+
+/// The range of the authentication information in the v4 authentication option (90) of
+/// the encoded message `bytes`, the MAC RFC 3118 HMAC-MD5 writes there. `None` if
+/// there's no authentication option.
+/// <https://datatracker.ietf.org/doc/html/rfc3118#section-2>
+pub fn v4_mac_range(bytes: &[u8]) -> Option<Range<usize>> {
+    let mut pos = V4_VENDOR;
+    loop {
+        match *bytes.get(pos)? {
+            0 => pos += 1,
+            255 => return None,
+            code => {
+                let len = *bytes.get(pos + 1)? as usize;
+                let end = pos + 2 + len;
+                if end > bytes.len() {
+                    return None;
+                }
+                if code == V4_AUTH {
+                    return (len >= AUTH_FIXED).then_some(pos + 2 + AUTH_FIXED..end);
+                }
+                pos = end;
+            }
+        }
+    }
+}
+
+/// A copy of the encoded v4 message `bytes` to compute the MAC over: `hops`, `giaddr`
+/// and the authentication information are zeroed, as relays may change the first two.
+/// `None` if there's no authentication option.
+/// <https://datatracker.ietf.org/doc/html/rfc3118#section-5.2>
+pub fn v4_canonical(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mac = v4_mac_range(bytes)?;
+    let mut canonical = bytes.to_vec();
+    canonical[V4_HOPS] = 0;
+    canonical[V4_GIADDR].fill(0);
+    canonical[mac].fill(0);
+    Some(canonical)
+}
+
+/// The range of the MAC in the `Authentication` option of the encoded v6 message
+/// `bytes`. For RKAP that's the HMAC-MD5 digest after the type byte, for other
+/// protocols the whole authentication information. `None` if there's no
+/// `Authentication` option.
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.11>
+pub fn v6_mac_range(bytes: &[u8]) -> Option<Range<usize>> {
+    let mut pos = V6_HEADER;
+    loop {
+        let header = bytes.get(pos..pos + 4)?;
+        let code = u16::from_be_bytes([header[0], header[1]]);
+        let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let end = pos + 4 + len;
+        if end > bytes.len() {
+            return None;
+        }
+        if code == V6_AUTH {
+            if len < AUTH_FIXED {
+                return None;
+            }
+            let info = pos + 4 + AUTH_FIXED;
+            // RKAP info is a type byte then the key or digest
+            return Some(if bytes[pos + 4] == RKAP_PROTOCOL {
+                (info + 1).min(end)..end
+            } else {
+                info..end
+            });
+        }
+        pos = end;
+    }
+}
+
+/// A copy of the encoded v6 message `bytes` with the MAC zeroed, to compute the MAC
+/// over. `None` if there's no `Authentication` option.
+pub fn v6_canonical(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mac = v6_mac_range(bytes)?;
+    let mut canonical = bytes.to_vec();
+    canonical[mac].fill(0);
+    Some(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encoder::Encodable, v4};
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!ct_eq(&[1, 2], &[1, 2, 3]));
+        assert!(ct_eq(&[], &[]));
+    }
+
+    #[test]
+    fn test_v4() {
+        let mut msg = v4::Message::default();
+        msg.set_hops(2).set_giaddr([10, 0, 0, 1]);
+        msg.opts_mut()
+            .insert(v4::DhcpOption::MessageType(v4::MessageType::Request));
+        let mut bytes = msg.to_vec().unwrap();
+        assert_eq!(v4_mac_range(&bytes), None);
+        bytes.pop();
+        // protocol 1, HMAC-MD5, RDM 0, replay detection, secret id & 16 byte MAC
+        bytes.extend([90, 31, 1, 1, 0]);
+        bytes.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+        bytes.extend([0, 0, 0, 9]);
+        bytes.extend([0xaa; 16]);
+        bytes.push(255);
+
+        let mac = v4_mac_range(&bytes).unwrap();
+        assert_eq!(mac.len(), 20);
+        assert_eq!(bytes[mac.end - 1], 0xaa);
+        let canonical = v4_canonical(&bytes).unwrap();
+        assert_eq!(canonical[V4_HOPS], 0);
+        assert_eq!(canonical[V4_GIADDR], [0; 4]);
+        assert!(canonical[mac.clone()].iter().all(|b| *b == 0));
+        assert_eq!(canonical[mac.end..], bytes[mac.end..]);
+    }
+
+    #[test]
+    fn test_v6() {
+        let mut msg = crate::v6::Message::new(crate::v6::MessageType::Reply);
+        msg.opts_mut()
+            .insert(crate::v6::DhcpOption::ServerId(vec![1, 2]));
+        let bytes = msg.to_vec().unwrap();
+        assert_eq!(v6_mac_range(&bytes), None);
+        msg.opts_mut().insert(crate::v6::DhcpOption::Authentication(
+            crate::v6::Authentication {
+                proto: 2,
+                algo: 1,
+                rdm: 0,
+                replay_detection: 1,
+                info: vec![5; 8],
+            },
+        ));
+        let bytes = msg.to_vec().unwrap();
+        let mac = v6_mac_range(&bytes).unwrap();
+        assert_eq!(&bytes[mac.clone()], [5; 8]);
+        assert_eq!(mac.end, bytes.len());
+        let canonical = v6_canonical(&bytes).unwrap();
+        assert!(canonical[mac].iter().all(|b| *b == 0));
+    }
+}
@@ -0,0 +1,241 @@
+//! Text options as sent on the wire
+//!
+//! v4 options like `Hostname` & `DomainName` are declared as NVT ASCII, but clients send
+//! Latin-1 or garbage often enough that refusing non UTF-8 values loses data.
+//! [`ByteString`] keeps the exact bytes & offers string views of them.
+//!
+//! ```rust
+//! use dhcproto::ByteString;
+//!
+//! let name = ByteString::from("printer");
+//! assert_eq!(name.as_str(), Some("printer"));
+//!
+//! // Latin-1 'é'
+//! let name = ByteString::from(vec![b'c', b'a', b'f', 0xe9]);
+//! assert_eq!(name.as_str(), None);
+//! assert_eq!(name.to_string_lossy(), "caf\u{fffd}");
+//! assert_eq!(name.as_bytes(), b"caf\xe9");
+//! ```
+use std::{borrow::Cow, fmt, ops::Deref, str};
+
+/// Bytes that are usually, but not always, a UTF-8 string
+#[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteString(Vec<u8>);
+
+impl ByteString {
+    /// wrap `bytes`, no checks are done
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// the bytes as sent on the wire
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// the bytes as a `&str`, `None` if they're not UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        str::from_utf8(&self.0).ok()
+    }
+
+    /// the bytes as a string, with invalid UTF-8 replaced by `U+FFFD`
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+
+    /// the inner bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// convert to a `String`, returning `self` if the bytes aren't UTF-8
+    pub fn into_string(self) -> Result<String, Self> {
+        String::from_utf8(self.0).map_err(|e| Self(e.into_bytes()))
+    }
+
+    /// drop any trailing NULs
+    pub fn trim_nuls(&mut self) {
+        let len = self.0.len() - self.0.iter().rev().take_while(|b| **b == 0).count();
+        self.0.truncate(len);
+    }
+
+    /// lowercase ASCII letters in place, other bytes are untouched
+    pub fn make_ascii_lowercase(&mut self) {
+        self.0.make_ascii_lowercase();
+    }
+}
+
+impl fmt::Debug for ByteString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_str() {
+            Some(s) => fmt::Debug::fmt(s, f),
+            None => write!(f, "b\"{}\"", self.0.escape_ascii()),
+        }
+    }
+}
+
+impl fmt::Display for ByteString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+impl Deref for ByteString {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for ByteString {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<&str> for ByteString {
+    fn from(s: &str) -> Self {
+        Self(s.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for ByteString {
+    fn from(s: String) -> Self {
+        Self(s.into_bytes())
+    }
+}
+
+impl From<&[u8]> for ByteString {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for ByteString {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ByteString> for Vec<u8> {
+    fn from(s: ByteString) -> Self {
+        s.0
+    }
+}
+
+impl PartialEq<str> for ByteString {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for ByteString {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<[u8]> for ByteString {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0 == other
+    }
+}
+
+// a string when the bytes are UTF-8, so existing JSON keeps its shape, bytes otherwise.
+// Binary formats always get bytes, they can't tell the two apart when deserializing
+#[cfg(feature = "serde")]
+impl serde::Serialize for ByteString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.as_str() {
+            Some(s) if serializer.is_human_readable() => serializer.serialize_str(s),
+            _ => serializer.serialize_bytes(&self.0),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ByteString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = ByteString;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string or bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<ByteString, E> {
+                Ok(s.into())
+            }
+
+            fn visit_string<E: serde::de::Error>(self, s: String) -> Result<ByteString, E> {
+                Ok(s.into())
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, b: &[u8]) -> Result<ByteString, E> {
+                Ok(b.into())
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, b: Vec<u8>) -> Result<ByteString, E> {
+                Ok(b.into())
+            }
+
+            // self-describing formats like JSON give bytes as a sequence
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<ByteString, A::Error> {
+                // the hint comes from the input, don't trust it for large allocations
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(4096));
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(ByteString(bytes))
+            }
+        }
+
+        // deserialize_any needs a self-describing format
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            deserializer.deserialize_byte_buf(Visitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_string() {
+        let mut s = ByteString::from(&b"Caf\xe9\0\0"[..]);
+        assert_eq!(s.as_str(), None);
+        assert_eq!(format!("{s:?}"), r#"b"Caf\xe9\x00\x00""#);
+        s.trim_nuls();
+        s.make_ascii_lowercase();
+        assert_eq!(s.as_bytes(), b"caf\xe9");
+        assert_eq!(s.to_string(), "caf\u{fffd}");
+        assert_eq!(s.clone().into_string(), Err(s));
+
+        let s = ByteString::from("host");
+        assert_eq!(s, "host");
+        assert_eq!(format!("{s:?}"), r#""host""#);
+        assert_eq!(s.into_string().as_deref(), Ok("host"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() -> Result<(), serde_json::Error> {
+        let s = ByteString::from("host");
+        assert_eq!(serde_json::to_string(&s)?, r#""host""#);
+        let s = ByteString::from(vec![0xff, 1]);
+        let json = serde_json::to_string(&s)?;
+        assert_eq!(json, "[255,1]");
+        assert_eq!(serde_json::from_str::<ByteString>(&json)?, s);
+        assert_eq!(serde_json::from_str::<ByteString>(r#""a""#)?, "a");
+        Ok(())
+    }
+}
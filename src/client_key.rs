@@ -0,0 +1,204 @@
+//! A stable key for a client, for indexing lease stores
+//!
+//! Clients are keyed by the best identifier their message carries, as recommended by
+//! <https://datatracker.ietf.org/doc/html/rfc4361#section-6> &
+//! <https://datatracker.ietf.org/doc/html/rfc6221>:
+//!
+//! * v4: the `ClientIdentifier` (61), else `chaddr` on the link of `giaddr`
+//! * v6: the `ClientId` DUID & the IAID of the first IA
+//! * the relay agent's remote-id when the message has neither
+//!
+//! A v4 client identifier of type 255 holds an IAID & DUID, so it gives the same key
+//! as the v6 messages of a dual-stack client.
+//!
+//! ```rust
+//! use dhcproto::{client_key::ClientKey, dual_stack, v4, v6};
+//!
+//! let duid = vec![0, 3, 0, 1, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+//! let mut msg4 = v4::Message::default();
+//! msg4.opts_mut().insert(dual_stack::v4_client_id(1, &duid));
+//!
+//! let mut msg6 = v6::Message::new(v6::MessageType::Solicit);
+//! msg6.opts_mut().insert(v6::DhcpOption::ClientId(duid.clone()));
+//! msg6.opts_mut().insert(v6::DhcpOption::IANA(v6::IANA {
+//!     id: 1,
+//!     t1: 0,
+//!     t2: 0,
+//!     opts: Default::default(),
+//! }));
+//!
+//! assert_eq!(ClientKey::from_v4(&msg4), ClientKey::from_v6(&msg6));
+//! assert_eq!(ClientKey::from_v6(&msg6), Some(ClientKey::Duid { duid, iaid: Some(1) }));
+//! ```
+use std::net::Ipv4Addr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dual_stack,
+    v4::{
+        self,
+        relay::{RelayCode, RelayInfo},
+        HType,
+    },
+    v6,
+};
+
+/// The identifier a client is keyed by, see the [module docs](self)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    /// a DUID & the IAID it was sent with, from a v6 message or a v4 client identifier
+    /// of type 255. `None` for v6 messages without an IA, like Information-request
+    Duid { duid: Vec<u8>, iaid: Option<u32> },
+    /// a v4 client identifier (61) that doesn't hold a DUID
+    ClientId(Vec<u8>),
+    /// a v4 hardware address, only unique on the link of `giaddr`
+    HwAddr {
+        htype: HType,
+        chaddr: Vec<u8>,
+        giaddr: Ipv4Addr,
+    },
+    /// a relay agent's remote-id, v4 agent remote-id sub-option or v6 `RemoteId`
+    RemoteId(Vec<u8>),
+}
+
+impl ClientKey {
+    /// The key for a v4 message, `None` if it has no client identifier, an empty or
+    /// zeroed `chaddr` and no agent remote-id
+    pub fn from_v4(msg: &v4::Message) -> Option<Self> {
+        if let Some(v4::DhcpOption::ClientIdentifier(id)) =
+            msg.opts().get(v4::OptionCode::ClientIdentifier)
+        {
+            if let Some((iaid, duid)) = dual_stack::split_v4_client_id(id) {
+                return Some(ClientKey::Duid {
+                    duid: duid.to_vec(),
+                    iaid: Some(iaid),
+                });
+            }
+            if !id.is_empty() {
+                return Some(ClientKey::ClientId(id.clone()));
+            }
+        }
+        let chaddr = msg.chaddr();
+        if chaddr.iter().any(|b| *b != 0) {
+            return Some(ClientKey::HwAddr {
+                htype: msg.htype(),
+                chaddr: chaddr.to_vec(),
+                giaddr: msg.giaddr(),
+            });
+        }
+        match msg.opts().get(v4::OptionCode::RelayAgentInformation) {
+            Some(v4::DhcpOption::RelayAgentInformation(info)) => {
+                match info.get(RelayCode::AgentRemoteId) {
+                    Some(RelayInfo::AgentRemoteId(id)) if !id.is_empty() => {
+                        Some(ClientKey::RemoteId(id.clone()))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The key for a v6 client message, `None` if it has no `ClientId`. The IAID is that
+    /// of the first `IA_NA`, else the first `IA_PD`
+    pub fn from_v6(msg: &v6::Message) -> Option<Self> {
+        let duid = msg.client_id().filter(|duid| !duid.is_empty())?;
+        let iaid = msg
+            .iana()
+            .map(|ia| ia.id)
+            .chain(msg.iapd().map(|ia| ia.id))
+            .next();
+        Some(ClientKey::Duid {
+            duid: duid.to_vec(),
+            iaid,
+        })
+    }
+
+    /// The key for a v6 client message relayed in `relay`, falling back to the relay's
+    /// `RemoteId` if the message has no `ClientId`. `relay` is the relay closest to the
+    /// client, the one that adds the remote-id
+    pub fn from_v6_relayed(msg: &v6::Message, relay: &v6::RelayMessage) -> Option<Self> {
+        Self::from_v6(msg).or_else(|| match relay.opts().get(v6::OptionCode::RemoteId) {
+            Some(v6::DhcpOption::RemoteId(remote)) if !remote.id.is_empty() => {
+                // the id is only unique within the vendor's enterprise number
+                let mut id = remote.num.to_be_bytes().to_vec();
+                id.extend(&remote.id);
+                Some(ClientKey::RemoteId(id))
+            }
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::relay::RelayAgentInformation;
+
+    #[test]
+    fn test_v4_fallbacks() {
+        let giaddr = Ipv4Addr::new(10, 0, 0, 1);
+        let mac = [1, 2, 3, 4, 5, 6];
+        let mut msg = v4::Message::new(
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            Ipv4Addr::UNSPECIFIED,
+            giaddr,
+            &mac,
+        );
+        assert_eq!(
+            ClientKey::from_v4(&msg),
+            Some(ClientKey::HwAddr {
+                htype: HType::Eth,
+                chaddr: mac.to_vec(),
+                giaddr
+            })
+        );
+
+        msg.opts_mut()
+            .insert(v4::DhcpOption::ClientIdentifier(vec![1, 1, 2, 3, 4, 5, 6]));
+        assert_eq!(
+            ClientKey::from_v4(&msg),
+            Some(ClientKey::ClientId(vec![1, 1, 2, 3, 4, 5, 6]))
+        );
+
+        let mut msg = v4::Message::default();
+        msg.set_chaddr(&[0; 6]);
+        assert_eq!(ClientKey::from_v4(&msg), None);
+        let mut info = RelayAgentInformation::default();
+        info.insert(RelayInfo::AgentRemoteId(vec![0xaa, 0xbb]));
+        msg.opts_mut()
+            .insert(v4::DhcpOption::RelayAgentInformation(info));
+        assert_eq!(
+            ClientKey::from_v4(&msg),
+            Some(ClientKey::RemoteId(vec![0xaa, 0xbb]))
+        );
+    }
+
+    #[test]
+    fn test_v6_relayed() {
+        // Relay-forward with a RemoteId (37) of enterprise 9 & id [1, 2] and no RelayMsg
+        let bytes = [
+            12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 37, 0, 6, 0, 0, 0, 9, 1, 2,
+        ];
+        let relay = v6::RelayMessage::try_from(&bytes[..]).unwrap();
+        let mut msg = v6::Message::new(v6::MessageType::InformationRequest);
+        assert_eq!(
+            ClientKey::from_v6_relayed(&msg, &relay),
+            Some(ClientKey::RemoteId(vec![0, 0, 0, 9, 1, 2]))
+        );
+        msg.opts_mut()
+            .insert(v6::DhcpOption::ClientId(vec![0, 3, 0, 1]));
+        assert_eq!(
+            ClientKey::from_v6_relayed(&msg, &relay),
+            Some(ClientKey::Duid {
+                duid: vec![0, 3, 0, 1],
+                iaid: None
+            })
+        );
+    }
+}
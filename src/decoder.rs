@@ -1,26 +1,65 @@
 //! Decodable trait & Decoder
-use hickory_proto::{
-    rr::Name,
-    serialize::binary::{BinDecodable, BinDecoder},
+//!
+//! These are public API, covered by semver like the message types, so crates can
+//! decode their own options & vendor data with the same primitives this crate uses. The
+//! [`encoder`](crate::encoder) module is the encoding half.
+//!
+//! ```rust
+//! use dhcproto::{error::DecodeResult, Decodable, Decoder};
+//!
+//! // a vendor sub-option: 1 byte type, 2 byte length & data
+//! #[derive(Debug, PartialEq)]
+//! struct VendorTlv {
+//!     kind: u8,
+//!     data: Vec<u8>,
+//! }
+//!
+//! impl Decodable for VendorTlv {
+//!     fn decode(d: &mut Decoder<'_>) -> DecodeResult<Self> {
+//!         let kind = d.read_u8()?;
+//!         let len = d.read_u16()? as usize;
+//!         Ok(VendorTlv { kind, data: d.read_slice(len)?.to_vec() })
+//!     }
+//! }
+//!
+//! let tlv = VendorTlv::from_bytes(&[7, 0, 2, 0xab, 0xcd])?;
+//! assert_eq!(tlv, VendorTlv { kind: 7, data: vec![0xab, 0xcd] });
+//! # Ok::<(), dhcproto::error::DecodeError>(())
+//! ```
+use crate::{
+    error::{DecodeError, DecodeResult},
+    v4, v6, ByteString, Domain,
 };
 
-use crate::error::{DecodeError, DecodeResult};
-
 use std::{
     array::TryFromSliceError,
     convert::TryInto,
     ffi::{CStr, CString},
-    mem,
+    fmt, mem,
     net::{Ipv4Addr, Ipv6Addr},
     str,
+    sync::{Arc, Mutex},
 };
 
-/// A trait for types which are serializable to and from DHCP binary formats
+/// A trait for types which are deserializable from DHCP binary formats
+///
+/// Only `decode` has to be implemented, every implementor gets `from_bytes` to decode
+/// a whole buffer with a lenient [`Decoder`]
+///
+/// ```rust
+/// use dhcproto::{v4, v6, Decodable};
+///
+/// let opt = v4::DhcpOption::from_bytes(&[53, 1, 1])?;
+/// assert_eq!(opt, v4::DhcpOption::MessageType(v4::MessageType::Discover));
+/// let opt = v6::DhcpOption::from_bytes(&[0, 7, 0, 1, 255])?;
+/// assert_eq!(opt, v6::DhcpOption::Preference(255));
+/// # Ok::<(), dhcproto::error::DecodeError>(())
+/// ```
 pub trait Decodable: Sized {
     /// Read the type from the stream
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self>;
 
-    /// Returns the object in binary form
+    /// Decode the type from a byte slice
     fn from_bytes(bytes: &[u8]) -> DecodeResult<Self> {
         let mut decoder = Decoder::new(bytes);
         Self::decode(&mut decoder)
@@ -28,15 +67,397 @@ pub trait Decodable: Sized {
 }
 
 /// Decoder type. Wraps a buffer which only contains bytes that have not been read yet
+///
+/// By default decoding is lenient, values that the RFCs say a receiver should ignore
+/// are kept and can be checked on the decoded type. A strict decoder returns an error
+/// for them instead.
+///
+/// v4 options 128-223 were site-local before RFC 3942 made them standard, by default
+/// the ones this crate implements are decoded into their typed variant. A deployment
+/// still using them for site-local data can keep them opaque with
+/// [`Decoder::site_local_opaque`].
+///
+/// Windows & some embedded clients terminate string options like `Hostname` with a NUL,
+/// it's dropped when decoding unless [`Decoder::keep_trailing_nul`] is set.
+///
+/// A lenient decoder stops at the first malformed option or sub-option in a container,
+/// keeping the ones before it. One set to [`Decoder::recover`] skips over it instead
+/// and records the error, so the rest of the container is still decoded. A v4 message's
+/// own options are always skipped over by a lenient decoder when only their value is
+/// malformed, the error is recorded when recovering and reported to the observer. A
+/// strict decoder returns the first malformed option's error.
+///
+/// A [`DecodeObserver`] set with [`Decoder::observer`] is told about each option
+/// decoded, e.g. to count them per code.
 #[derive(Debug)]
 pub struct Decoder<'a> {
+    // the whole buffer this decoder reads, for seeking
+    start: &'a [u8],
     buffer: &'a [u8],
+    strict: bool,
+    site_local_opaque: bool,
+    keep_trailing_nul: bool,
+    // address of the first byte of the outermost buffer, for error offsets
+    origin: usize,
+    // errors skipped over in recovery mode, shared with sub decoders
+    errors: Option<Arc<Mutex<Skipped>>>,
+    // number of option containers being decoded & the limit
+    depth: usize,
+    max_depth: usize,
+    observer: Option<Observer>,
+}
+
+/// Callbacks for the options a [`Decoder`] decodes, set with [`Decoder::observer`], so
+/// servers can gather per-option metrics without walking every decoded message again.
+/// Every method does nothing by default.
+///
+/// Options in a v4 or v6 `DhcpOptions` are reported, including the options nested in v6
+/// IAs & relayed messages. A v4 option split over several parts (RFC 3396) is reported
+/// once. `Pad` & `End` aren't reported.
+///
+/// ```rust
+/// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+/// use dhcproto::{decoder::{DecodeObserver, ObservedCode}, v4, Decodable, Decoder};
+///
+/// #[derive(Default)]
+/// struct Counter(AtomicUsize);
+///
+/// impl DecodeObserver for Counter {
+///     fn on_option(&self, code: ObservedCode) {
+///         if code == ObservedCode::V4(v4::OptionCode::MessageType) {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let counter = Arc::new(Counter::default());
+/// let bytes = [53, 1, 1, 12, 4, b'h', b'o', b's', b't', 255];
+/// v4::DhcpOptions::decode(&mut Decoder::new(&bytes).observer(counter.clone()))?;
+/// assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+/// # Ok::<(), dhcproto::error::DecodeError>(())
+/// ```
+pub trait DecodeObserver: Send + Sync {
+    /// an option was decoded into its typed variant
+    fn on_option(&self, code: ObservedCode) {
+        let _ = code;
+    }
+    /// an option was decoded as `DhcpOption::Unknown`, because this crate doesn't
+    /// implement it or the decoder keeps it opaque
+    fn on_unknown(&self, code: ObservedCode) {
+        let _ = code;
+    }
+    /// a malformed option or sub-option stopped decoding, or was skipped over by a
+    /// recovering decoder
+    fn on_error(&self, err: &DecodeError) {
+        let _ = err;
+    }
+}
+
+/// The code of an option reported to a [`DecodeObserver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObservedCode {
+    /// a DHCPv4 option
+    V4(v4::OptionCode),
+    /// a DHCPv6 option
+    V6(v6::OptionCode),
+}
+
+// `Decoder` is `Debug`, observers don't have to be
+#[derive(Clone)]
+struct Observer(Arc<dyn DecodeObserver>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DecodeObserver")
+    }
+}
+
+/// Default limit for [`Decoder::max_depth`]
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+// `(offset, error)` of each option skipped in recovery mode
+type Skipped = Vec<(usize, DecodeError)>;
+
+// the list is only pushed to, a panic while holding it can't leave it inconsistent
+fn lock(errors: &Mutex<Skipped>) -> std::sync::MutexGuard<'_, Skipped> {
+    errors.lock().unwrap_or_else(|err| err.into_inner())
 }
 
 impl<'a> Decoder<'a> {
     /// Create a new Decoder
     pub fn new(buffer: &'a [u8]) -> Self {
-        Decoder { buffer }
+        Decoder {
+            start: buffer,
+            buffer,
+            strict: false,
+            site_local_opaque: false,
+            keep_trailing_nul: false,
+            origin: buffer.as_ptr() as usize,
+            errors: None,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            observer: None,
+        }
+    }
+
+    /// Create a new Decoder which errors on out of spec values instead of keeping them
+    pub fn new_strict(buffer: &'a [u8]) -> Self {
+        Decoder {
+            strict: true,
+            ..Decoder::new(buffer)
+        }
+    }
+
+    /// Decode v4 options in [`v4::RFC3942_RECLASSIFIED`](crate::v4::RFC3942_RECLASSIFIED)
+    /// as `DhcpOption::Unknown` when `opaque` is `true`, instead of their typed variant
+    /// <https://www.rfc-editor.org/rfc/rfc3942.html>
+    ///
+    /// ```rust
+    /// use dhcproto::{v4::{DhcpOption, OptionCode}, Decodable, Decoder};
+    ///
+    /// // TFTP server address, or site-local data before RFC 3942
+    /// let bytes = [150, 4, 192, 168, 0, 1];
+    /// let opt = DhcpOption::decode(&mut Decoder::new(&bytes).site_local_opaque(true)).unwrap();
+    /// assert!(matches!(opt, DhcpOption::Unknown(_)));
+    /// assert_eq!(OptionCode::from(&opt), OptionCode::TFTPServerAddress);
+    /// ```
+    pub fn site_local_opaque(mut self, opaque: bool) -> Self {
+        self.site_local_opaque = opaque;
+        self
+    }
+
+    /// Keep the NUL some clients end v4 string options with, instead of dropping it, for
+    /// callers that need the bytes as sent. See [`Decoder::read_option_string`].
+    /// [`v4::iter_raw`](crate::v4::iter_raw) gives the raw bytes of any option.
+    ///
+    /// ```rust
+    /// use dhcproto::{v4::DhcpOption, Decodable, Decoder};
+    ///
+    /// let bytes = [12, 5, b'h', b'o', b's', b't', 0];
+    /// let opt = DhcpOption::decode(&mut Decoder::new(&bytes))?;
+    /// assert_eq!(opt, DhcpOption::Hostname("host".into()));
+    /// let opt = DhcpOption::decode(&mut Decoder::new(&bytes).keep_trailing_nul(true))?;
+    /// assert_eq!(opt, DhcpOption::Hostname("host\0".into()));
+    /// # Ok::<(), dhcproto::error::DecodeError>(())
+    /// ```
+    pub fn keep_trailing_nul(mut self, keep: bool) -> Self {
+        self.keep_trailing_nul = keep;
+        self
+    }
+
+    /// Skip malformed sub-options and v6 options instead of stopping at them, recording
+    /// each error with its offset from the start of the buffer. A strict decoder still
+    /// returns errors where it would have.
+    ///
+    /// ```rust
+    /// use dhcproto::{v6, Decodable, Decoder};
+    ///
+    /// // Preference with a missing byte between a ClientId and an ElapsedTime
+    /// let bytes = [0, 1, 0, 1, 9, 0, 7, 0, 0, 0, 8, 0, 2, 0, 5];
+    /// let mut decoder = Decoder::new(&bytes).recover(true);
+    /// let opts = v6::DhcpOptions::decode(&mut decoder)?;
+    /// assert_eq!(opts.iter().count(), 2);
+    /// let errors = decoder.take_errors();
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].0, 5);
+    /// # Ok::<(), dhcproto::error::DecodeError>(())
+    /// ```
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.errors = recover.then(Default::default);
+        self
+    }
+
+    /// Limit how deeply option containers can nest, e.g. v6 options in an IA or in a
+    /// relayed message, so a crafted packet can't exhaust the stack. Decoding past the
+    /// limit returns [`DecodeError::TooDeep`]. A message's own options are at depth 1,
+    /// the default is [`DEFAULT_MAX_DEPTH`].
+    ///
+    /// ```rust
+    /// use dhcproto::{error::DecodeError, v6, Decodable, Decoder, Encodable};
+    ///
+    /// let mut ia = v6::IANA { id: 1, t1: 0, t2: 0, opts: Default::default() };
+    /// ia.opts.insert(v6::DhcpOption::StatusCode(v6::StatusCode {
+    ///     status: v6::Status::Success,
+    ///     msg: String::new(),
+    /// }));
+    /// let mut msg = v6::Message::new(v6::MessageType::Reply);
+    /// msg.opts_mut().insert(v6::DhcpOption::IANA(ia));
+    /// let buf = msg.to_vec()?;
+    ///
+    /// let mut decoder = Decoder::new_strict(&buf).max_depth(1);
+    /// let err = v6::Message::decode(&mut decoder).unwrap_err();
+    /// assert!(matches!(err, DecodeError::TooDeep(1)));
+    /// assert!(v6::Message::decode(&mut Decoder::new_strict(&buf).max_depth(2)).is_ok());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// Report the options decoded to `observer`, see [`DecodeObserver`]. Sub decoders
+    /// report to the same observer.
+    pub fn observer(mut self, observer: Arc<dyn DecodeObserver>) -> Self {
+        self.observer = Some(Observer(observer));
+        self
+    }
+
+    // the observer to report decoded options to, if there is one
+    pub(crate) fn observed(&self) -> Option<&dyn DecodeObserver> {
+        self.observer.as_ref().map(|o| &*o.0)
+    }
+
+    // report an error that stopped decoding an option container. A strict decoder
+    // returns it through every container it's nested in, so it's reported once from the
+    // outermost one
+    pub(crate) fn stopped(&self, err: &DecodeError) {
+        if let Some(observer) = self.observed() {
+            if !self.strict || self.depth <= 1 {
+                observer.on_error(err);
+            }
+        }
+    }
+
+    /// Run `f` one option container deeper, see [`Decoder::max_depth`]
+    pub(crate) fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> DecodeResult<T>,
+    ) -> DecodeResult<T> {
+        if self.depth >= self.max_depth {
+            return Err(DecodeError::TooDeep(self.max_depth));
+        }
+        self.depth += 1;
+        let res = f(self);
+        self.depth -= 1;
+        res
+    }
+
+    /// Returns `true` if malformed options are skipped, see [`Decoder::recover`]
+    pub fn is_recovering(&self) -> bool {
+        self.errors.is_some()
+    }
+
+    /// Take the errors skipped over so far as `(offset, error)`, see
+    /// [`Decoder::recover`]. Sub decoders share the list with the decoder they were
+    /// created from.
+    pub fn take_errors(&mut self) -> Vec<(usize, DecodeError)> {
+        self.errors
+            .as_ref()
+            .map(|errors| mem::take(&mut *lock(errors)))
+            .unwrap_or_default()
+    }
+
+    // offset of the unread data from the start of the outermost buffer
+    pub(crate) fn offset(&self) -> usize {
+        self.buffer.as_ptr() as usize - self.origin
+    }
+
+    // record an error skipped over at `offset`, returns `false` if not recovering
+    pub(crate) fn skipped(&self, offset: usize, err: DecodeError) -> bool {
+        if let Some(observer) = self.observed() {
+            observer.on_error(&err);
+        }
+        match &self.errors {
+            Some(errors) => {
+                lock(errors).push((offset, err));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if this decoder was created with [`Decoder::new_strict`]
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Returns `true` if v4 options 128-223 are kept opaque, see
+    /// [`Decoder::site_local_opaque`]
+    pub fn is_site_local_opaque(&self) -> bool {
+        self.site_local_opaque
+    }
+
+    /// Returns `true` if a trailing NUL is kept in string options, see
+    /// [`Decoder::keep_trailing_nul`]
+    pub fn is_keeping_trailing_nul(&self) -> bool {
+        self.keep_trailing_nul
+    }
+
+    // a decoder over `buffer`, which isn't part of this decoder's buffer, with the same
+    // settings. Errors skipped in it aren't recorded, their offsets would be meaningless
+    pub(crate) fn detached<'b>(&self, buffer: &'b [u8]) -> Decoder<'b> {
+        Decoder {
+            strict: self.strict,
+            site_local_opaque: self.site_local_opaque,
+            keep_trailing_nul: self.keep_trailing_nul,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            observer: self.observer.clone(),
+            ..Decoder::new(buffer)
+        }
+    }
+
+    /// Read the next `len` bytes into a new Decoder with the same settings
+    pub fn sub_decoder(&mut self, len: usize) -> DecodeResult<Decoder<'a>> {
+        let buffer = self.read_slice(len)?;
+        Ok(Decoder {
+            start: buffer,
+            buffer,
+            strict: self.strict,
+            site_local_opaque: self.site_local_opaque,
+            keep_trailing_nul: self.keep_trailing_nul,
+            origin: self.origin,
+            errors: self.errors.clone(),
+            depth: self.depth,
+            max_depth: self.max_depth,
+            observer: self.observer.clone(),
+        })
+    }
+
+    /// The number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The number of bytes read so far, counted from the start of this decoder's
+    /// buffer. A sub decoder starts at 0.
+    pub fn position(&self) -> usize {
+        self.start.len() - self.buffer.len()
+    }
+
+    /// Move to `pos` bytes from the start of this decoder's buffer, forwards or back.
+    /// Returns [`DecodeError::Truncated`] without moving if `pos` is past the end.
+    ///
+    /// ```rust
+    /// use dhcproto::Decoder;
+    ///
+    /// let mut decoder = Decoder::new(&[1, 2, 3, 4]);
+    /// decoder.seek(2)?;
+    /// assert_eq!(decoder.read_u8()?, 3);
+    /// decoder.rewind();
+    /// assert_eq!((decoder.position(), decoder.remaining()), (0, 4));
+    /// assert!(decoder.seek(5).is_err());
+    /// # Ok::<(), dhcproto::error::DecodeError>(())
+    /// ```
+    pub fn seek(&mut self, pos: usize) -> DecodeResult<()> {
+        self.buffer = self.start.get(pos..).ok_or(DecodeError::Truncated)?;
+        Ok(())
+    }
+
+    /// Move back to the start of this decoder's buffer, to read it again
+    pub fn rewind(&mut self) {
+        self.buffer = self.start;
+    }
+
+    /// peek at the next `len` bytes without advancing the internal pointer
+    pub fn peek_slice(&self, len: usize) -> DecodeResult<&'a [u8]> {
+        self.buffer.get(..len).ok_or(DecodeError::Truncated)
+    }
+
+    /// fill `buf` with the next `buf.len()` bytes
+    pub fn read_exact_into(&mut self, buf: &mut [u8]) -> DecodeResult<()> {
+        buf.copy_from_slice(self.read_slice(buf.len())?);
+        Ok(())
     }
 
     /// peek at the next byte without advancing the internal pointer
@@ -80,7 +501,7 @@ impl<'a> Decoder<'a> {
     /// read a `N` bytes into slice
     pub fn read<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
         if N > self.buffer.len() {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         let (slice, remaining) = self.buffer.split_at(N);
         self.buffer = remaining;
@@ -91,7 +512,7 @@ impl<'a> Decoder<'a> {
     /// peek a `N` bytes into slice
     pub fn peek<const N: usize>(&self) -> DecodeResult<[u8; N]> {
         if N > self.buffer.len() {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         // can't panic-- condition checked above
         Ok(self.buffer[..N].try_into().unwrap())
@@ -109,6 +530,7 @@ impl<'a> Decoder<'a> {
         }
     }
 
+    /// read `MAX` bytes, returning them up to & including the first nul
     pub fn read_nul_bytes<const MAX: usize>(&mut self) -> DecodeResult<Option<Vec<u8>>> {
         let bytes = self.read::<MAX>()?;
         let nul_idx = bytes.iter().position(|&b| b == 0);
@@ -131,7 +553,7 @@ impl<'a> Decoder<'a> {
     /// read a slice of bytes determined at runtime
     pub fn read_slice(&mut self, len: usize) -> DecodeResult<&'a [u8]> {
         if len > self.buffer.len() {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         let (slice, remaining) = self.buffer.split_at(len);
         self.buffer = remaining;
@@ -148,10 +570,23 @@ impl<'a> Decoder<'a> {
         Ok(str::from_utf8(self.read_slice(len)?)?)
     }
 
+    /// Read a string option, dropping a single trailing NUL unless
+    /// [`Decoder::keep_trailing_nul`] is set. The bytes are kept as is, they need not
+    /// be UTF-8.
+    pub fn read_option_string(&mut self, len: usize) -> DecodeResult<ByteString> {
+        let mut bytes = self.read_slice(len)?;
+        if !self.keep_trailing_nul {
+            if let [rest @ .., 0] = bytes {
+                bytes = rest;
+            }
+        }
+        Ok(bytes.into())
+    }
+
     /// Read an ipv4 addr
     pub fn read_ipv4(&mut self, length: usize) -> DecodeResult<Ipv4Addr> {
         if length != 4 {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         let bytes = self.read::<4>()?;
         Ok(bytes.into())
@@ -161,7 +596,7 @@ impl<'a> Decoder<'a> {
     pub fn read_ipv4s(&mut self, length: usize) -> DecodeResult<Vec<Ipv4Addr>> {
         // must be multiple of 4
         if length % 4 != 0 {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         let ips = self.read_slice(length)?;
         Ok(ips
@@ -174,7 +609,7 @@ impl<'a> Decoder<'a> {
     pub fn read_ipv6s(&mut self, length: usize) -> DecodeResult<Vec<Ipv6Addr>> {
         // must be multiple of 16
         if length % 16 != 0 {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         let ips = self.read_slice(length)?;
         // type annotations needed below
@@ -188,7 +623,7 @@ impl<'a> Decoder<'a> {
     pub fn read_pair_ipv4s(&mut self, length: usize) -> DecodeResult<Vec<(Ipv4Addr, Ipv4Addr)>> {
         // must be multiple of 8
         if length % 8 != 0 {
-            return Err(DecodeError::NotEnoughBytes);
+            return Err(DecodeError::Truncated);
         }
         let ips = self.read_slice(length)?;
         Ok(ips
@@ -202,11 +637,12 @@ impl<'a> Decoder<'a> {
             .collect())
     }
 
-    /// Read a list of domain `Name`s
-    pub fn read_domains(&mut self, length: usize) -> DecodeResult<Vec<Name>> {
-        let mut name_decoder = BinDecoder::new(self.read_slice(length)?);
+    /// Read a list of [`Domain`]s, names may be compressed
+    pub fn read_domains(&mut self, length: usize) -> DecodeResult<Vec<Domain>> {
+        let buf = self.read_slice(length)?;
+        let mut pos = 0;
         let mut names = Vec::new();
-        while let Ok(name) = Name::read(&mut name_decoder) {
+        while let Ok(name) = Domain::read(buf, &mut pos) {
             names.push(name);
         }
         Ok(names)
@@ -222,3 +658,99 @@ impl<'a> Decoder<'a> {
         self.buffer
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek() -> DecodeResult<()> {
+        let mut decoder = Decoder::new(&[1, 2, 3, 4, 5]);
+        decoder.read_u8()?;
+        let mut sub = decoder.sub_decoder(3)?;
+        assert_eq!((sub.position(), sub.remaining()), (0, 3));
+        assert_eq!(sub.peek_slice(2)?, [2, 3]);
+        assert!(sub.peek_slice(4).is_err());
+        let mut buf = [0; 2];
+        sub.read_exact_into(&mut buf)?;
+        assert_eq!(buf, [2, 3]);
+        assert_eq!(sub.position(), 2);
+        // seeking is limited to the sub decoder's own window
+        assert!(sub.seek(4).is_err());
+        assert_eq!(sub.position(), 2);
+        sub.rewind();
+        assert_eq!(sub.read_u8()?, 2);
+        assert_eq!(decoder.position(), 4);
+        assert_eq!(decoder.read_u8()?, 5);
+        assert!(sub.read_exact_into(&mut [0; 3]).is_err());
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct Log(Mutex<Vec<String>>);
+
+    impl DecodeObserver for Log {
+        fn on_option(&self, code: ObservedCode) {
+            lock_log(&self.0).push(format!("{code:?}"));
+        }
+        fn on_unknown(&self, code: ObservedCode) {
+            lock_log(&self.0).push(format!("unknown {code:?}"));
+        }
+        fn on_error(&self, err: &DecodeError) {
+            lock_log(&self.0).push(format!("error {err}"));
+        }
+    }
+
+    fn lock_log(log: &Mutex<Vec<String>>) -> std::sync::MutexGuard<'_, Vec<String>> {
+        log.lock().unwrap()
+    }
+
+    #[test]
+    fn test_observer() -> DecodeResult<()> {
+        use crate::Decodable;
+
+        // MessageType, a Router split in two, an unknown & a truncated option
+        let bytes = [
+            53, 1, 1, 3, 4, 10, 0, 0, 1, 3, 4, 10, 0, 0, 2, 224, 1, 9, 6, 8, 1,
+        ];
+        let log = Arc::new(Log::default());
+        let opts = v4::DhcpOptions::decode(&mut Decoder::new(&bytes).observer(log.clone()))?;
+        assert_eq!(opts.len(), 3);
+        assert_eq!(
+            *lock_log(&log.0),
+            [
+                "V4(MessageType)",
+                "V4(Router)",
+                "unknown V4(Unknown(224))",
+                "error parser ran out of data-- not enough bytes",
+            ]
+        );
+        // no `End` isn't an error
+        let log = Arc::new(Log::default());
+        v4::DhcpOptions::decode(&mut Decoder::new(&bytes[..3]).observer(log.clone()))?;
+        assert_eq!(*lock_log(&log.0), ["V4(MessageType)"]);
+
+        // ElapsedTime, an IA_NA holding a malformed Preference & an unknown option
+        let bytes = [
+            0, 8, 0, 2, 0, 5, 0, 3, 0, 16, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 253,
+            232, 0, 0,
+        ];
+        let log = Arc::new(Log::default());
+        let mut decoder = Decoder::new(&bytes).observer(log.clone());
+        v6::DhcpOptions::decode(&mut decoder)?;
+        assert_eq!(lock_log(&log.0).len(), 4);
+        assert_eq!(lock_log(&log.0)[0], "V6(ElapsedTime)");
+        assert!(lock_log(&log.0)[1].starts_with("error"));
+        assert_eq!(
+            lock_log(&log.0)[2..],
+            ["V6(IANA)", "unknown V6(Unknown(65000))"]
+        );
+
+        // reported once, not by each container it's returned through
+        let log = Arc::new(Log::default());
+        let mut decoder = Decoder::new_strict(&bytes).observer(log.clone());
+        assert!(v6::DhcpOptions::decode(&mut decoder).is_err());
+        assert_eq!(lock_log(&log.0).len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+//! Differences between two sets of options
+//!
+//! Returned by `DhcpOptions::diff` in both [`v4`](crate::v4::DhcpOptions::diff) and
+//! [`v6`](crate::v6::DhcpOptions::diff)
+use std::fmt;
+
+/// The options added, removed, or changed going from one set of options to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionsDiff<'a, T> {
+    /// options only present in the new set
+    pub added: Vec<&'a T>,
+    /// options only present in the old set
+    pub removed: Vec<&'a T>,
+    /// options present in both with a different value, as `(old, new)`
+    pub changed: Vec<(&'a T, &'a T)>,
+}
+
+impl<'a, T> Default for OptionsDiff<'a, T> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T> OptionsDiff<'a, T> {
+    /// Returns `true` if both sets of options are the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Display for OptionsDiff<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for opt in &self.removed {
+            writeln!(f, "- {opt:?}")?;
+        }
+        for opt in &self.added {
+            writeln!(f, "+ {opt:?}")?;
+        }
+        for (old, new) in &self.changed {
+            writeln!(f, "~ {old:?} -> {new:?}")?;
+        }
+        Ok(())
+    }
+}
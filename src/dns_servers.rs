@@ -0,0 +1,103 @@
+//! Cleaning up DNS server lists before sending them
+//!
+//! v4 `DomainNameServer` (6) & v6 `DomainNameServers` (23) list servers in order of
+//! preference. Clients typically use only the first 2 or 3, so repeats & addresses that
+//! can't be a server waste the slots that matter.
+//!
+//! ```rust
+//! use std::net::Ipv4Addr;
+//! use dhcproto::{dns_servers::{self, DnsServerError}, v4::DhcpOption};
+//!
+//! let a = Ipv4Addr::new(10, 0, 0, 53);
+//! let b = Ipv4Addr::new(10, 0, 1, 53);
+//! let c = Ipv4Addr::new(10, 0, 2, 53);
+//! assert_eq!(dns_servers::clean(&[a, b, a, c], 2)?, [a, b]);
+//! assert_eq!(
+//!     DhcpOption::dns_servers(&[a, b, a, c], dns_servers::DEFAULT_MAX)?,
+//!     DhcpOption::DomainNameServer(vec![a, b, c])
+//! );
+//! assert!(matches!(
+//!     dns_servers::clean(&[a, Ipv4Addr::UNSPECIFIED], 3),
+//!     Err(DnsServerError::Unspecified(_))
+//! ));
+//! # Ok::<(), DnsServerError>(())
+//! ```
+use std::{collections::HashSet, hash::Hash, net::IpAddr};
+
+use thiserror::Error;
+
+/// Servers kept by [`DhcpOption::dns_servers`](crate::v4::DhcpOption::dns_servers)
+pub const DEFAULT_MAX: usize = 3;
+
+/// Returned for DNS server lists that shouldn't be sent
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsServerError {
+    /// `0.0.0.0` or `::`
+    #[error("unspecified address {0} in DNS server list")]
+    Unspecified(IpAddr),
+    /// a multicast address
+    #[error("multicast address {0} in DNS server list")]
+    Multicast(IpAddr),
+    /// no servers are left, the option can't be empty
+    #[error("DNS server list is empty")]
+    Empty,
+}
+
+/// Returns an error if `addr` can't be a DNS server
+pub fn check(addr: impl Into<IpAddr>) -> Result<(), DnsServerError> {
+    let addr = addr.into();
+    if addr.is_unspecified() {
+        Err(DnsServerError::Unspecified(addr))
+    } else if addr.is_multicast() {
+        Err(DnsServerError::Multicast(addr))
+    } else {
+        Ok(())
+    }
+}
+
+/// The first `max` distinct servers of `servers`, in order, after [`check`]ing each.
+/// Returns [`DnsServerError::Empty`] if there are none.
+pub fn clean<A>(servers: &[A], max: usize) -> Result<Vec<A>, DnsServerError>
+where
+    A: Copy + Eq + Hash + Into<IpAddr>,
+{
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for &addr in servers {
+        check(addr)?;
+        if out.len() < max && seen.insert(addr) {
+            out.push(addr);
+        }
+    }
+    if out.is_empty() {
+        return Err(DnsServerError::Empty);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        let a: Ipv6Addr = "2001:db8::53".parse().unwrap();
+        let b: Ipv6Addr = "2001:db8::54".parse().unwrap();
+        assert_eq!(clean(&[b, a, b, a], 3), Ok(vec![b, a]));
+        assert_eq!(clean(&[b, a], 1), Ok(vec![b]));
+        assert_eq!(clean::<Ipv6Addr>(&[], 3), Err(DnsServerError::Empty));
+        assert_eq!(clean(&[a], 0), Err(DnsServerError::Empty));
+        // checked past the cap too
+        let ff02: Ipv6Addr = "ff02::1".parse().unwrap();
+        assert_eq!(
+            clean(&[a, b, ff02], 2),
+            Err(DnsServerError::Multicast(ff02.into()))
+        );
+        assert_eq!(
+            check(Ipv6Addr::UNSPECIFIED),
+            Err(DnsServerError::Unspecified(Ipv6Addr::UNSPECIFIED.into()))
+        );
+    }
+}
@@ -0,0 +1,706 @@
+//! Domain names in DHCP options
+//!
+//! Options like v4 `DomainSearch` (119), `ClientFQDN` (81) or the v6 `DomainSearchList`
+//! (24) carry domain names in DNS wire format. [`Domain`] is a small, dependency free
+//! type for those, enable the `hickory` feature to convert to and from
+//! `hickory_proto::rr::Name`.
+//!
+//! Names compare and hash ignoring ASCII case like DNS does, but keep the case they
+//! were created with. Internationalized names are carried as their punycode `xn--`
+//! labels, [`Domain::from_unicode`] & [`Domain::to_unicode`] convert them.
+//!
+//! ```rust
+//! use dhcproto::Domain;
+//!
+//! let domain = "eng.example.com".parse::<Domain>()?;
+//! assert_eq!(domain.to_string(), "eng.example.com.");
+//! assert_eq!(domain.labels().count(), 3);
+//! assert_eq!(domain, "ENG.Example.com.".parse()?);
+//!
+//! let idn = Domain::from_unicode("bücher.example")?;
+//! assert_eq!(idn.to_string(), "xn--bcher-kva.example.");
+//! assert_eq!(idn.to_unicode(), "bücher.example.");
+//! # Ok::<(), dhcproto::domain::DomainError>(())
+//! ```
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+/// longest label allowed <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
+pub const MAX_LABEL_LEN: usize = 63;
+/// longest name allowed in wire format, including the length bytes & root label
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Returned for malformed domain names
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainError {
+    /// a label other than the root is empty
+    #[error("domain name has an empty label")]
+    EmptyLabel,
+    /// a label is longer than 63 bytes
+    #[error("label of length {0} exceeds the maximum of 63")]
+    LabelTooLong(usize),
+    /// the name is longer than 255 bytes in wire format
+    #[error("domain name of length {0} exceeds the maximum of 255")]
+    TooLong(usize),
+    /// a compression pointer doesn't point to an earlier name
+    #[error("invalid compression pointer to {0}")]
+    BadPointer(usize),
+    /// the name ends before its root label
+    #[error("domain name is truncated")]
+    Truncated,
+}
+
+/// A fully qualified domain name
+///
+/// `Eq`, `Ord` & `Hash` ignore ASCII case
+/// <https://datatracker.ietf.org/doc/html/rfc4343>
+#[derive(Default, Clone)]
+pub struct Domain {
+    // labels from the leftmost, the root label isn't stored
+    labels: Vec<Vec<u8>>,
+}
+
+impl Domain {
+    /// the root domain, `.`
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// create a domain from its labels, leftmost first. The root label is implied
+    pub fn from_labels<I, L>(labels: I) -> Result<Self, DomainError>
+    where
+        I: IntoIterator<Item = L>,
+        L: AsRef<[u8]>,
+    {
+        let mut domain = Self::root();
+        for label in labels {
+            domain.push_label(label.as_ref())?;
+        }
+        Ok(domain)
+    }
+
+    /// the labels of the domain, leftmost first, without the root label
+    pub fn labels(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.labels.iter().map(Vec::as_slice)
+    }
+
+    /// `true` for the root domain
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// the domain with its labels ASCII lowercased, the canonical form for display
+    pub fn to_ascii_lowercase(&self) -> Self {
+        Domain {
+            labels: self.labels.iter().map(|l| l.to_ascii_lowercase()).collect(),
+        }
+    }
+
+    /// Create a domain from a name that may have non-ASCII labels, encoding them with
+    /// punycode as `xn--` labels <https://datatracker.ietf.org/doc/html/rfc3492>.
+    /// ASCII labels are parsed like [`Domain::from_str`].
+    ///
+    /// Labels are only lowercased, not mapped or normalized. Run the name through a
+    /// full IDNA/UTS #46 implementation first if it can hold such characters.
+    pub fn from_unicode(s: &str) -> Result<Self, DomainError> {
+        if s.is_ascii() {
+            return s.parse();
+        }
+        let mut ascii = Vec::new();
+        for label in s.split('.') {
+            if label.is_ascii() {
+                ascii.push(label.to_owned());
+            } else {
+                let label = label.to_lowercase().chars().collect::<Vec<_>>();
+                let encoded =
+                    punycode::encode(&label).ok_or(DomainError::LabelTooLong(label.len()))?;
+                ascii.push(format!("xn--{encoded}"));
+            }
+        }
+        ascii.join(".").parse()
+    }
+
+    /// The name with its punycode `xn--` labels decoded, in the format of `Display`.
+    /// Labels that aren't valid punycode are left as they are.
+    pub fn to_unicode(&self) -> String {
+        if self.is_root() {
+            return ".".to_owned();
+        }
+        let mut s = String::new();
+        for label in &self.labels {
+            let decoded = label
+                .get(..4)
+                .filter(|prefix| prefix.eq_ignore_ascii_case(b"xn--"))
+                .and_then(|_| std::str::from_utf8(&label[4..]).ok())
+                .and_then(punycode::decode);
+            match decoded {
+                Some(decoded) => s.push_str(&decoded),
+                None => write_label(&mut s, label).expect("writing to a String can't fail"),
+            }
+            s.push('.');
+        }
+        s
+    }
+
+    /// `true` if `self` is `parent` or a name under it
+    pub fn is_subdomain_of(&self, parent: &Domain) -> bool {
+        let Some(skip) = self.labels.len().checked_sub(parent.labels.len()) else {
+            return false;
+        };
+        self.labels[skip..]
+            .iter()
+            .zip(&parent.labels)
+            .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+
+    /// length of the name in uncompressed wire format
+    pub fn wire_len(&self) -> usize {
+        self.labels
+            .iter()
+            .map(|label| label.len() + 1)
+            .sum::<usize>()
+            + 1
+    }
+
+    fn push_label(&mut self, label: &[u8]) -> Result<(), DomainError> {
+        if label.is_empty() {
+            return Err(DomainError::EmptyLabel);
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(DomainError::LabelTooLong(label.len()));
+        }
+        let len = self.wire_len() + label.len() + 1;
+        if len > MAX_NAME_LEN {
+            return Err(DomainError::TooLong(len));
+        }
+        self.labels.push(label.to_vec());
+        Ok(())
+    }
+
+    /// read a name starting at `*pos` in `buf`, following compression pointers to
+    /// earlier names in `buf`. `*pos` is left after the name
+    pub(crate) fn read(buf: &[u8], pos: &mut usize) -> Result<Self, DomainError> {
+        let mut domain = Self::root();
+        let mut cur = *pos;
+        // pointers must go backwards, so following them always ends
+        let mut limit = *pos;
+        let mut jumped = false;
+        loop {
+            let len = *buf.get(cur).ok_or(DomainError::Truncated)? as usize;
+            match len {
+                0 => {
+                    if !jumped {
+                        *pos = cur + 1;
+                    }
+                    return Ok(domain);
+                }
+                len if len & 0xC0 == 0xC0 => {
+                    let low = *buf.get(cur + 1).ok_or(DomainError::Truncated)? as usize;
+                    let ptr = (len & 0x3F) << 8 | low;
+                    if ptr >= limit {
+                        return Err(DomainError::BadPointer(ptr));
+                    }
+                    if !jumped {
+                        *pos = cur + 2;
+                        jumped = true;
+                    }
+                    limit = ptr;
+                    cur = ptr;
+                }
+                len if len <= MAX_LABEL_LEN => {
+                    let label = buf
+                        .get(cur + 1..cur + 1 + len)
+                        .ok_or(DomainError::Truncated)?;
+                    domain.push_label(label)?;
+                    cur += 1 + len;
+                }
+                // 0x40 & 0x80 label types are reserved
+                len => return Err(DomainError::LabelTooLong(len)),
+            }
+        }
+    }
+}
+
+/// Writes names in wire format, optionally compressing them with pointers to earlier
+/// names written by the same writer <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+#[derive(Debug)]
+pub(crate) struct DomainWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    start: usize,
+    compress: bool,
+    // each suffix written so far & its offset from `start`
+    written: Vec<(Vec<Vec<u8>>, u16)>,
+}
+
+impl<'a> DomainWriter<'a> {
+    pub(crate) fn new(buf: &'a mut Vec<u8>, compress: bool) -> Self {
+        let start = buf.len();
+        Self {
+            buf,
+            start,
+            compress,
+            written: Vec::new(),
+        }
+    }
+
+    pub(crate) fn write(&mut self, domain: &Domain) {
+        let labels = &domain.labels;
+        for i in 0..labels.len() {
+            let suffix = &labels[i..];
+            if self.compress {
+                if let Some((_, offset)) = self.written.iter().find(|(s, _)| s == suffix) {
+                    self.buf.extend((0xC000 | offset).to_be_bytes());
+                    return;
+                }
+                // pointers only have 14 bits
+                if let Ok(offset @ 0..=0x3FFF) = u16::try_from(self.buf.len() - self.start) {
+                    self.written.push((suffix.to_vec(), offset));
+                }
+            }
+            self.buf.push(labels[i].len() as u8);
+            self.buf.extend(&labels[i]);
+        }
+        self.buf.push(0);
+    }
+}
+
+impl PartialEq for Domain {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(&other.labels)
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+}
+
+impl Eq for Domain {}
+
+impl Hash for Domain {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.labels.len());
+        for label in &self.labels {
+            state.write_usize(label.len());
+            for b in label {
+                state.write_u8(b.to_ascii_lowercase());
+            }
+        }
+    }
+}
+
+impl PartialOrd for Domain {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Domain {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lower = |label: &Vec<u8>| label.iter().map(u8::to_ascii_lowercase).collect::<Vec<_>>();
+        self.labels
+            .iter()
+            .map(lower)
+            .cmp(other.labels.iter().map(lower))
+    }
+}
+
+/// Remove repeated names from a search list, keeping the first of each. Names are
+/// compared ignoring case.
+pub fn dedup_search_list(list: &mut Vec<Domain>) {
+    let mut seen = std::collections::HashSet::new();
+    list.retain(|domain| seen.insert(domain.clone()));
+}
+
+// write a label as `Display` does, escaping `.`, `\` and non-printable bytes
+fn write_label(f: &mut impl fmt::Write, label: &[u8]) -> fmt::Result {
+    for &b in label {
+        match b {
+            b'.' | b'\\' => write!(f, "\\{}", b as char)?,
+            b'!'..=b'~' => write!(f, "{}", b as char)?,
+            _ => write!(f, "\\{b:03}")?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Debug for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Domain").field(&self.to_string()).finish()
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+        for label in &self.labels {
+            write_label(f, label)?;
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Domain {
+    type Err = DomainError;
+
+    /// parse a domain, a trailing `.` is optional. `\.` and `\DDD` escapes are
+    /// accepted in labels
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut domain = Self::root();
+        if s == "." {
+            return Ok(domain);
+        }
+        let mut label = Vec::new();
+        let mut bytes = s.bytes();
+        while let Some(b) = bytes.next() {
+            match b {
+                b'.' => domain.push_label(&std::mem::take(&mut label))?,
+                b'\\' => {
+                    let b = bytes.next().ok_or(DomainError::Truncated)?;
+                    if b.is_ascii_digit() {
+                        let digits = [Some(b), bytes.next(), bytes.next()];
+                        let n = digits.iter().try_fold(0u16, |n, d| match d {
+                            Some(d @ b'0'..=b'9') => Some(n * 10 + u16::from(d - b'0')),
+                            _ => None,
+                        });
+                        label.push(
+                            n.and_then(|n| u8::try_from(n).ok())
+                                .ok_or(DomainError::Truncated)?,
+                        );
+                    } else {
+                        label.push(b);
+                    }
+                }
+                b => label.push(b),
+            }
+        }
+        if !label.is_empty() {
+            domain.push_label(&label)?;
+        }
+        Ok(domain)
+    }
+}
+
+impl TryFrom<&str> for Domain {
+    type Error = DomainError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Domain {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Domain {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "hickory")]
+impl From<&hickory_proto::rr::Name> for Domain {
+    fn from(name: &hickory_proto::rr::Name) -> Self {
+        // hickory enforces the same label & name limits
+        Self {
+            labels: name.iter().map(<[u8]>::to_vec).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "hickory")]
+impl From<hickory_proto::rr::Name> for Domain {
+    fn from(name: hickory_proto::rr::Name) -> Self {
+        Self::from(&name)
+    }
+}
+
+#[cfg(feature = "hickory")]
+impl TryFrom<&Domain> for hickory_proto::rr::Name {
+    type Error = hickory_proto::error::ProtoError;
+
+    fn try_from(domain: &Domain) -> Result<Self, Self::Error> {
+        let mut name = hickory_proto::rr::Name::from_labels(domain.labels())?;
+        name.set_fqdn(true);
+        Ok(name)
+    }
+}
+
+#[cfg(feature = "hickory")]
+impl TryFrom<Domain> for hickory_proto::rr::Name {
+    type Error = hickory_proto::error::ProtoError;
+
+    fn try_from(domain: Domain) -> Result<Self, Self::Error> {
+        Self::try_from(&domain)
+    }
+}
+
+// punycode, RFC 3492 <https://datatracker.ietf.org/doc/html/rfc3492#section-6>
+mod punycode {
+    const BASE: u32 = 36;
+    const TMIN: u32 = 1;
+    const TMAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+    const INITIAL_BIAS: u32 = 72;
+    const INITIAL_N: u32 = 128;
+
+    fn threshold(k: u32, bias: u32) -> u32 {
+        if k <= bias {
+            TMIN
+        } else if k >= bias + TMAX {
+            TMAX
+        } else {
+            k - bias
+        }
+    }
+
+    fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+        let mut delta = if first { delta / DAMP } else { delta / 2 };
+        delta += delta / points;
+        let mut k = 0;
+        while delta > ((BASE - TMIN) * TMAX) / 2 {
+            delta /= BASE - TMIN;
+            k += BASE;
+        }
+        k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+    }
+
+    fn digit(d: u32) -> char {
+        match d {
+            0..=25 => (b'a' + d as u8) as char,
+            _ => (b'0' + (d - 26) as u8) as char,
+        }
+    }
+
+    fn value(b: u8) -> Option<u32> {
+        match b {
+            b'a'..=b'z' => Some((b - b'a') as u32),
+            b'A'..=b'Z' => Some((b - b'A') as u32),
+            b'0'..=b'9' => Some((b - b'0') as u32 + 26),
+            _ => None,
+        }
+    }
+
+    /// encode `input`, `None` on overflow
+    pub(super) fn encode(input: &[char]) -> Option<String> {
+        let mut output = input.iter().filter(|c| c.is_ascii()).collect::<String>();
+        let basic = output.len() as u32;
+        let mut handled = basic;
+        if basic > 0 {
+            output.push('-');
+        }
+        let (mut n, mut delta, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+        while (handled as usize) < input.len() {
+            let m = input.iter().map(|&c| c as u32).filter(|&c| c >= n).min()?;
+            delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+            n = m;
+            for c in input.iter().map(|&c| c as u32) {
+                if c < n {
+                    delta = delta.checked_add(1)?;
+                }
+                if c == n {
+                    let mut q = delta;
+                    let mut k = BASE;
+                    loop {
+                        let t = threshold(k, bias);
+                        if q < t {
+                            break;
+                        }
+                        output.push(digit(t + (q - t) % (BASE - t)));
+                        q = (q - t) / (BASE - t);
+                        k += BASE;
+                    }
+                    output.push(digit(q));
+                    bias = adapt(delta, handled + 1, handled == basic);
+                    delta = 0;
+                    handled += 1;
+                }
+            }
+            delta = delta.checked_add(1)?;
+            n += 1;
+        }
+        Some(output)
+    }
+
+    /// decode `input`, `None` if it isn't valid punycode
+    pub(super) fn decode(input: &str) -> Option<String> {
+        let (basic, extended) = match input.rfind('-') {
+            Some(i) => (&input[..i], &input[i + 1..]),
+            None => ("", input),
+        };
+        if !basic.is_ascii() {
+            return None;
+        }
+        let mut output = basic.chars().collect::<Vec<_>>();
+        let (mut n, mut i, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+        let mut bytes = extended.bytes().peekable();
+        while bytes.peek().is_some() {
+            let old_i = i;
+            let mut w = 1u32;
+            let mut k = BASE;
+            loop {
+                let d = value(bytes.next()?)?;
+                i = i.checked_add(d.checked_mul(w)?)?;
+                let t = threshold(k, bias);
+                if d < t {
+                    break;
+                }
+                w = w.checked_mul(BASE - t)?;
+                k += BASE;
+            }
+            let len = output.len() as u32 + 1;
+            bias = adapt(i - old_i, len, old_i == 0);
+            n = n.checked_add(i / len)?;
+            i %= len;
+            output.insert(i as usize, char::from_u32(n)?);
+            i += 1;
+        }
+        Some(output.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(names: &[Domain], compress: bool) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut writer = DomainWriter::new(&mut buf, compress);
+        for name in names {
+            writer.write(name);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), DomainError> {
+        let domain = Domain::from_str("www.example.com.")?;
+        assert_eq!(domain, Domain::from_str("www.example.com")?);
+        assert_eq!(domain, Domain::from_labels(["www", "example", "com"])?);
+        assert_eq!(domain.wire_len(), 17);
+        assert!(Domain::from_str(".")?.is_root());
+        assert_eq!(Domain::root().to_string(), ".");
+
+        let escaped = Domain::from_str(r"a\.b.c\032d")?;
+        assert_eq!(
+            escaped.labels().collect::<Vec<_>>(),
+            vec![&b"a.b"[..], b"c d"]
+        );
+        assert_eq!(escaped.to_string(), r"a\.b.c\032d.");
+
+        assert_eq!(Domain::from_str("a..b"), Err(DomainError::EmptyLabel));
+        assert_eq!(
+            Domain::from_str(&"a".repeat(64)),
+            Err(DomainError::LabelTooLong(64))
+        );
+        let long = vec!["a".repeat(63); 4].join(".");
+        assert_eq!(Domain::from_str(&long), Err(DomainError::TooLong(257)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_wire() -> Result<(), DomainError> {
+        let names = [
+            Domain::from_str("eng.apple.com")?,
+            Domain::from_str("marketing.apple.com")?,
+        ];
+        let buf = write(&names, true);
+        assert_eq!(
+            buf,
+            [
+                &[3][..],
+                b"eng",
+                &[5],
+                b"apple",
+                &[3],
+                b"com",
+                &[0, 9],
+                b"marketing",
+                &[0xC0, 0x04]
+            ]
+            .concat()
+        );
+        let mut pos = 0;
+        assert_eq!(Domain::read(&buf, &mut pos)?, names[0]);
+        assert_eq!(Domain::read(&buf, &mut pos)?, names[1]);
+        assert_eq!(pos, buf.len());
+
+        assert_eq!(write(&names, false).len(), names[0].wire_len() * 2 + 6);
+        // pointers must point backwards
+        assert_eq!(
+            Domain::read(&[0xC0, 0x00], &mut 0),
+            Err(DomainError::BadPointer(0))
+        );
+        assert_eq!(
+            Domain::read(&[3, b'c', b'o'], &mut 0),
+            Err(DomainError::Truncated)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_case_insensitive() -> Result<(), DomainError> {
+        use std::collections::HashSet;
+
+        let lower = Domain::from_str("www.example.com")?;
+        let upper = Domain::from_str("WWW.Example.COM")?;
+        assert_eq!(lower, upper);
+        assert_eq!(lower.cmp(&upper), Ordering::Equal);
+        assert_eq!(HashSet::from([lower.clone(), upper.clone()]).len(), 1);
+        // case is kept
+        assert_eq!(upper.to_string(), "WWW.Example.COM.");
+        assert!(Domain::from_str("a.b")? < Domain::from_str("B.a")?);
+
+        assert!(lower.is_subdomain_of(&Domain::from_str("EXAMPLE.com")?));
+        assert!(lower.is_subdomain_of(&Domain::root()));
+        assert!(!Domain::root().is_subdomain_of(&lower));
+
+        let mut list = vec![lower, Domain::from_str("example.com")?, upper];
+        dedup_search_list(&mut list);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].to_string(), "www.example.com.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_punycode() -> Result<(), DomainError> {
+        let idn = Domain::from_unicode("例え.テスト")?;
+        assert_eq!(idn.to_string(), "xn--r8jz45g.xn--zckzah.");
+        assert_eq!(idn.to_unicode(), "例え.テスト.");
+        assert_eq!(
+            Domain::from_unicode("MÜNCHEN.de")?.to_string(),
+            "xn--mnchen-3ya.de."
+        );
+        // not punycode, left alone
+        assert_eq!(Domain::from_str("xn--!.com")?.to_unicode(), "xn--!.com.");
+        assert_eq!(Domain::root().to_unicode(), ".");
+        Ok(())
+    }
+
+    #[cfg(feature = "hickory")]
+    #[test]
+    fn test_hickory() -> Result<(), Box<dyn std::error::Error>> {
+        let name = hickory_proto::rr::Name::from_str("www.example.com.")?;
+        let domain = Domain::from(&name);
+        assert_eq!(domain, Domain::from_str("www.example.com.")?);
+        assert_eq!(hickory_proto::rr::Name::try_from(&domain)?, name);
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+//! Conversions between the v4 & v6 forms of the same client state, for dual-stack
+//! servers that keep one record per client
+//!
+//! * client identifiers: a v4 `ClientIdentifier` of type 255 holds an IAID & the
+//!   client's DUID, its v6 `ClientId` <https://datatracker.ietf.org/doc/html/rfc4361#section-6.1>
+//! * client FQDNs: v4 `ClientFQDN` (81) & v6 `ClientFqdn` (39)
+//!   <https://datatracker.ietf.org/doc/html/rfc4704#section-4>
+//! * DNS servers: v4 `DomainNameServer` & v6 `DomainNameServers`
+//!
+//! ```rust
+//! use dhcproto::{dual_stack, v4, v6};
+//!
+//! let duid = [0, 3, 0, 1, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+//! let opt = dual_stack::v4_client_id(1, &duid);
+//! let v4::DhcpOption::ClientIdentifier(id) = &opt else { unreachable!() };
+//! assert_eq!(dual_stack::split_v4_client_id(id), Some((1, &duid[..])));
+//! assert_eq!(dual_stack::v6_client_id(id), Some(v6::DhcpOption::ClientId(duid.to_vec())));
+//! ```
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{
+    v4::{self, fqdn::ClientFQDN},
+    v6,
+};
+
+/// The v4 client identifier type holding an IAID & DUID
+pub const DUID_CLIENT_ID_TYPE: u8 = 255;
+
+/// A v4 `ClientIdentifier` for `iaid` & `duid`
+pub fn v4_client_id(iaid: u32, duid: &[u8]) -> v4::DhcpOption {
+    let mut id = Vec::with_capacity(5 + duid.len());
+    id.push(DUID_CLIENT_ID_TYPE);
+    id.extend(iaid.to_be_bytes());
+    id.extend(duid);
+    v4::DhcpOption::ClientIdentifier(id)
+}
+
+/// The IAID & DUID of a v4 client identifier, `None` if it isn't of type 255 or has no
+/// DUID
+pub fn split_v4_client_id(id: &[u8]) -> Option<(u32, &[u8])> {
+    match id {
+        [DUID_CLIENT_ID_TYPE, a, b, c, d, duid @ ..] if !duid.is_empty() => {
+            Some((u32::from_be_bytes([*a, *b, *c, *d]), duid))
+        }
+        _ => None,
+    }
+}
+
+/// The v6 `ClientId` of the client sending the v4 client identifier `id`, see
+/// [`split_v4_client_id`]
+pub fn v6_client_id(id: &[u8]) -> Option<v6::DhcpOption> {
+    let (_, duid) = split_v4_client_id(id)?;
+    Some(v6::DhcpOption::ClientId(duid.to_vec()))
+}
+
+/// The v6 `ClientFqdn` for a v4 `ClientFQDN`. The v6 option has no `E` flag or RCODEs,
+/// its name is always in wire format.
+///
+/// ```rust
+/// use dhcproto::{dual_stack, v4::fqdn::{ClientFQDN, FqdnFlags}};
+///
+/// let fqdn = ClientFQDN::new(FqdnFlags::default().set_s(true), "host.example".parse()?);
+/// let opt = dual_stack::v6_fqdn(&fqdn);
+/// let back = dual_stack::v4_fqdn(&opt).unwrap();
+/// assert_eq!(back.domain(), fqdn.domain());
+/// assert!(back.flags().s() && back.flags().e());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn v6_fqdn(fqdn: &ClientFQDN) -> v6::DhcpOption {
+    let flags = fqdn.flags().set_e(false);
+    v6::DhcpOption::ClientFqdn(v6::ClientFqdn::new(flags, fqdn.domain().clone()))
+}
+
+/// The v4 `ClientFQDN` for a v6 `ClientFqdn`, with the `E` flag set and RCODEs of 255.
+/// `None` if `opt` isn't a client FQDN.
+pub fn v4_fqdn(opt: &v6::DhcpOption) -> Option<ClientFQDN> {
+    let v6::DhcpOption::ClientFqdn(fqdn) = opt else {
+        return None;
+    };
+    Some(ClientFQDN::new(fqdn.flags.set_e(true), fqdn.domain.clone()))
+}
+
+/// Split a list of DNS servers into the v4 `DomainNameServer` & v6 `DomainNameServers`
+/// options, keeping their order. IPv4-mapped v6 addresses go in the v4 option. An
+/// option is `None` if it would be empty.
+///
+/// ```rust
+/// use dhcproto::{dual_stack, v4, v6};
+///
+/// let servers = ["192.0.2.53".parse()?, "2001:db8::53".parse()?, "::ffff:192.0.2.54".parse()?];
+/// let (dns4, dns6) = dual_stack::split_dns_servers(&servers);
+/// assert_eq!(
+///     dns4,
+///     Some(v4::DhcpOption::DomainNameServer(vec![[192, 0, 2, 53].into(), [192, 0, 2, 54].into()]))
+/// );
+/// assert_eq!(dns6, Some(v6::DhcpOption::DomainNameServers(vec!["2001:db8::53".parse()?])));
+/// # Ok::<(), std::net::AddrParseError>(())
+/// ```
+pub fn split_dns_servers(servers: &[IpAddr]) -> (Option<v4::DhcpOption>, Option<v6::DhcpOption>) {
+    let mut v4s = Vec::new();
+    let mut v6s = Vec::new();
+    for server in servers {
+        match server.to_canonical() {
+            IpAddr::V4(ip) => v4s.push(ip),
+            IpAddr::V6(ip) => v6s.push(ip),
+        }
+    }
+    (
+        (!v4s.is_empty()).then_some(v4::DhcpOption::DomainNameServer(v4s)),
+        (!v6s.is_empty()).then_some(v6::DhcpOption::DomainNameServers(v6s)),
+    )
+}
+
+/// Join v4 & v6 DNS server lists into one, v4 servers first
+pub fn join_dns_servers(v4s: &[Ipv4Addr], v6s: &[Ipv6Addr]) -> Vec<IpAddr> {
+    v4s.iter()
+        .copied()
+        .map(IpAddr::V4)
+        .chain(v6s.iter().copied().map(IpAddr::V6))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::fqdn::FqdnFlags;
+
+    #[test]
+    fn test_client_id() {
+        let v4::DhcpOption::ClientIdentifier(id) = v4_client_id(0x0102_0304, &[0, 4, 9]) else {
+            panic!("expected ClientIdentifier");
+        };
+        assert_eq!(id, [255, 1, 2, 3, 4, 0, 4, 9]);
+        // a MAC client id & one missing its DUID
+        assert_eq!(split_v4_client_id(&[1, 0, 1, 2, 3, 4, 5]), None);
+        assert_eq!(split_v4_client_id(&[255, 1, 2, 3, 4]), None);
+        assert_eq!(v6_client_id(&[]), None);
+    }
+
+    #[test]
+    fn test_fqdn() {
+        let flags = FqdnFlags::default().set_n(true).set_o(true).set_e(true);
+        let fqdn = ClientFQDN::new(flags, "a.example".parse().unwrap());
+        let opt = v6_fqdn(&fqdn);
+        let v6::DhcpOption::ClientFqdn(v6_fqdn) = &opt else {
+            panic!("expected ClientFqdn");
+        };
+        assert_eq!(v6_fqdn.flags, flags.set_e(false));
+        assert_eq!(&v6_fqdn.domain, fqdn.domain());
+        assert_eq!(v4_fqdn(&opt), Some(fqdn));
+        assert_eq!(v4_fqdn(&v6::DhcpOption::ElapsedTime(0)), None);
+    }
+
+    #[test]
+    fn test_dns_servers() {
+        assert_eq!(split_dns_servers(&[]), (None, None));
+        let v4s = [Ipv4Addr::new(192, 0, 2, 1)];
+        let v6s = ["2001:db8::1".parse().unwrap()];
+        let joined = join_dns_servers(&v4s, &v6s);
+        assert_eq!(
+            split_dns_servers(&joined),
+            (
+                Some(v4::DhcpOption::DomainNameServer(v4s.to_vec())),
+                Some(v6::DhcpOption::DomainNameServers(v6s.to_vec()))
+            )
+        );
+    }
+}
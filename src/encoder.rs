@@ -1,7 +1,49 @@
 //! Encodable trait & Encoder
+//!
+//! The encoding half of the [`decoder`](crate::decoder) API, with the same semver
+//! guarantees.
+//!
+//! ```rust
+//! use dhcproto::{error::{EncodeError, EncodeResult}, Encodable, Encoder};
+//!
+//! // a vendor sub-option: 1 byte type, 2 byte length & data
+//! struct VendorTlv {
+//!     kind: u8,
+//!     data: Vec<u8>,
+//! }
+//!
+//! impl Encodable for VendorTlv {
+//!     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+//!         let len = u16::try_from(self.data.len()).map_err(|_| EncodeError::TooLong {
+//!             len: self.data.len(),
+//!             max: u16::MAX as usize,
+//!         })?;
+//!         e.write_u8(self.kind)?;
+//!         e.write_u16(len)?;
+//!         e.write_slice(&self.data)
+//!     }
+//! }
+//!
+//! let tlv = VendorTlv { kind: 7, data: vec![0xab, 0xcd] };
+//! assert_eq!(tlv.to_vec()?, [7, 0, 2, 0xab, 0xcd]);
+//! # Ok::<(), dhcproto::error::EncodeError>(())
+//! ```
 use crate::error::{EncodeError, EncodeResult};
 
-/// A trait for types which are deserializable to DHCP binary formats
+/// A trait for types which are serializable to DHCP binary formats
+///
+/// Only `encode` has to be implemented, every implementor gets `to_vec` to encode
+/// without setting up an [`Encoder`]
+///
+/// ```rust
+/// use dhcproto::{v4, v6, Encodable};
+///
+/// let opt = v4::DhcpOption::MessageType(v4::MessageType::Discover);
+/// assert_eq!(opt.to_vec()?, [53, 1, 1]);
+/// let opt = v6::DhcpOption::Preference(255);
+/// assert_eq!(opt.to_vec()?, [0, 7, 0, 1, 255]);
+/// # Ok::<(), dhcproto::error::EncodeError>(())
+/// ```
 pub trait Encodable {
     /// encode type to buffer in Encoder
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()>;
@@ -13,6 +55,44 @@ pub trait Encodable {
         self.encode(&mut encoder)?;
         Ok(buffer)
     }
+
+    /// encode this type into its binary form as [`bytes::Bytes`]
+    #[cfg(feature = "bytes")]
+    fn to_bytes(&self) -> EncodeResult<bytes::Bytes> {
+        self.to_vec().map(bytes::Bytes::from)
+    }
+
+    /// the number of bytes this type encodes to with the default [`EncodeProfile`],
+    /// calculated by running the encoder without writing to a buffer. Useful for sizing
+    /// buffers up front or checking against a client's maximum message size.
+    ///
+    /// This is the length [`Encoder::new`] writes, the padding & long option handling
+    /// of another profile aren't counted, use [`Encodable::encoded_len_with`] for the
+    /// profile the message will be encoded with.
+    ///
+    /// It does the same work as encoding: options that build their value before
+    /// writing it, like domain name lists, long v4 options split into chunks or nested
+    /// options, still allocate for it. Only the output buffer is saved.
+    fn encoded_len(&self) -> EncodeResult<usize> {
+        self.encoded_len_with(&EncodeProfile::default())
+    }
+
+    /// the number of bytes this type encodes to with `profile`, see
+    /// [`Encodable::encoded_len`]
+    ///
+    /// ```rust
+    /// use dhcproto::{v4, EncodeProfile, Encodable};
+    ///
+    /// let msg = v4::Message::default();
+    /// assert!(msg.encoded_len()? < v4::MIN_PACKET_SIZE);
+    /// assert_eq!(msg.encoded_len_with(&EncodeProfile::interop())?, v4::MIN_PACKET_SIZE);
+    /// # Ok::<(), dhcproto::error::EncodeError>(())
+    /// ```
+    fn encoded_len_with(&self, profile: &EncodeProfile) -> EncodeResult<usize> {
+        let mut encoder = Encoder::counter().profile(*profile);
+        self.encode(&mut encoder)?;
+        Ok(encoder.len_filled())
+    }
 }
 
 /// Encoder type, holds a mut ref to a buffer
@@ -21,26 +101,140 @@ pub trait Encodable {
 ///
 /// This will start writing from the beginning of the buffer, *not* from the end.
 /// The buffer will be grown as needed.
+///
+/// A padded encoder pads v4 messages with zeros after the `End` option up to
+/// [`MIN_PACKET_SIZE`](crate::v4::MIN_PACKET_SIZE) bytes, as some clients and relays
+/// drop shorter BOOTP packets. Padding & the other choices an encoder makes are its
+/// [`EncodeProfile`].
 #[derive(Debug)]
 pub struct Encoder<'a> {
-    buffer: &'a mut Vec<u8>,
+    // `None` only counts the bytes written
+    buffer: Option<&'a mut Vec<u8>>,
     offset: usize,
+    profile: EncodeProfile,
+}
+
+/// The choices an [`Encoder`] makes where the RFCs allow more than one encoding or
+/// clients disagree with them, set with [`Encoder::profile`]. The default is what
+/// [`Encoder::new`] has always done: unpadded, long options split & no trailing NULs.
+/// v4 options are always written in code order, with `RelayAgentInformation` last, so
+/// the same options encode to the same bytes with every profile.
+///
+/// ```rust
+/// use dhcproto::{v4, EncodeProfile, Encodable, Encoder};
+///
+/// let mut msg = v4::Message::default();
+/// msg.opts_mut().insert(v4::DhcpOption::Hostname("host".into()));
+/// msg.opts_mut().insert(v4::DhcpOption::MessageType(v4::MessageType::Offer));
+///
+/// let mut buf = Vec::new();
+/// msg.encode(&mut Encoder::new(&mut buf).profile(EncodeProfile::interop()))?;
+/// assert_eq!(buf.len(), v4::MIN_PACKET_SIZE);
+/// // options in code order, the hostname NUL terminated
+/// assert_eq!(&buf[240..251], [12, 5, b'h', b'o', b's', b't', 0, 53, 1, 2, 255]);
+/// # Ok::<(), dhcproto::error::EncodeError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodeProfile {
+    /// pad v4 messages to [`MIN_PACKET_SIZE`](crate::v4::MIN_PACKET_SIZE) bytes
+    pub pad: bool,
+    /// split v4 options longer than 255 bytes into several options
+    /// <https://datatracker.ietf.org/doc/html/rfc3396>, instead of returning
+    /// [`EncodeError::TooLong`]. Some clients only read the first part
+    pub split_long_options: bool,
+    /// end v4 string options like `Hostname` & `DomainName` with a NUL, which
+    /// RFC 2132 says not to do but some clients expect
+    pub trailing_nul: bool,
+}
+
+impl Default for EncodeProfile {
+    fn default() -> Self {
+        Self {
+            pad: false,
+            split_long_options: true,
+            trailing_nul: false,
+        }
+    }
+}
+
+impl EncodeProfile {
+    /// Only what the RFCs describe: no padding, no trailing NULs & long options split
+    pub const fn strict() -> Self {
+        Self {
+            pad: false,
+            split_long_options: true,
+            trailing_nul: false,
+        }
+    }
+
+    /// For clients & relays that don't follow the RFCs: padded to the minimum BOOTP size,
+    /// NUL terminated strings & long options rejected instead of split
+    pub const fn interop() -> Self {
+        Self {
+            pad: true,
+            split_long_options: false,
+            trailing_nul: true,
+        }
+    }
 }
 
 impl<'a> Encoder<'a> {
     /// Create a new Encoder from a mutable buffer
     pub fn new(buffer: &'a mut Vec<u8>) -> Self {
-        Self { buffer, offset: 0 }
+        Self {
+            buffer: Some(buffer),
+            offset: 0,
+            profile: EncodeProfile::default(),
+        }
+    }
+
+    /// Create a new Encoder which pads v4 messages to the minimum BOOTP packet size
+    pub fn new_padded(buffer: &'a mut Vec<u8>) -> Self {
+        Self::new(buffer).profile(EncodeProfile {
+            pad: true,
+            ..EncodeProfile::default()
+        })
+    }
+
+    /// Create an Encoder that doesn't write anything, only counts the number of bytes
+    /// written in [`Encoder::len_filled`]. The buffer of a counter is always empty, the
+    /// types encoded with it may still allocate, see [`Encodable::encoded_len`].
+    pub fn counter() -> Self {
+        Self {
+            buffer: None,
+            offset: 0,
+            profile: EncodeProfile::default(),
+        }
+    }
+
+    /// Encode with `profile`, see [`EncodeProfile`]
+    pub fn profile(mut self, profile: EncodeProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Returns the [`EncodeProfile`] this encoder was created with
+    pub fn encode_profile(&self) -> EncodeProfile {
+        self.profile
+    }
+
+    /// Returns `true` if this encoder pads v4 messages, it was created with
+    /// [`Encoder::new_padded`] or its profile has [`EncodeProfile::pad`] set
+    pub fn is_padded(&self) -> bool {
+        self.profile.pad
     }
 
     /// Get a reference to the underlying buffer
     pub fn buffer(&self) -> &[u8] {
-        self.buffer
+        self.buffer.as_deref().map_or(&[], |buf| buf)
     }
 
     /// Returns the slice of the underlying buffer that has been filled.
     pub fn buffer_filled(&self) -> &[u8] {
-        &self.buffer[..self.offset]
+        match &self.buffer {
+            Some(buf) => &buf[..self.offset],
+            None => &[],
+        }
     }
 
     /// Returns the number of bytes that have been written to the buffer.
@@ -53,18 +247,20 @@ impl<'a> Encoder<'a> {
     ///     number of bytes written
     pub fn write_slice(&mut self, bytes: &[u8]) -> EncodeResult<()> {
         let additional = bytes.len();
-        // space already reserved, we may not need this
-        if self.offset + additional <= self.buffer.len() {
-            // if self.offset == self.buffer.len() indexing can panic
-            for (byte, b) in self.buffer[self.offset..].iter_mut().zip(bytes.iter()) {
-                *byte = *b;
-            }
-        } else {
-            let expected_len = self.buffer.len() + additional;
-            self.buffer.reserve(additional);
-            self.buffer.extend_from_slice(bytes);
+        if let Some(buffer) = self.buffer.as_deref_mut() {
+            // space already reserved, we may not need this
+            if self.offset + additional <= buffer.len() {
+                // if self.offset == self.buffer.len() indexing can panic
+                for (byte, b) in buffer[self.offset..].iter_mut().zip(bytes.iter()) {
+                    *byte = *b;
+                }
+            } else {
+                let expected_len = buffer.len() + additional;
+                buffer.reserve(additional);
+                buffer.extend_from_slice(bytes);
 
-            debug_assert!(self.buffer.len() == expected_len);
+                debug_assert!(buffer.len() == expected_len);
+            }
         }
 
         let index = self
@@ -80,17 +276,19 @@ impl<'a> Encoder<'a> {
         // TODO: refactor this and above method?
         // only difference is zip & extend
         let additional = bytes.len();
-        // space already reserved, we may not need this
-        if self.offset + additional <= self.buffer.len() {
-            // if self.offset == self.buffer.len() indexing can panic
-            for (byte, b) in self.buffer[self.offset..].iter_mut().zip(bytes) {
-                *byte = b;
+        if let Some(buffer) = self.buffer.as_deref_mut() {
+            // space already reserved, we may not need this
+            if self.offset + additional <= buffer.len() {
+                // if self.offset == self.buffer.len() indexing can panic
+                for (byte, b) in buffer[self.offset..].iter_mut().zip(bytes) {
+                    *byte = b;
+                }
+            } else {
+                let expected_len = buffer.len() + additional;
+                buffer.reserve(additional);
+                buffer.extend(bytes);
+                debug_assert!(buffer.len() == expected_len);
             }
-        } else {
-            let expected_len = self.buffer.len() + additional;
-            self.buffer.reserve(additional);
-            self.buffer.extend(bytes);
-            debug_assert!(self.buffer.len() == expected_len);
         }
 
         let index = self
@@ -125,13 +323,73 @@ impl<'a> Encoder<'a> {
     pub fn write_i32(&mut self, data: i32) -> EncodeResult<()> {
         self.write(data.to_be_bytes())
     }
+    /// Write a v4 option: `code`, its length in 1 byte and the payload `f` writes. The
+    /// length is measured from what `f` writes and filled in afterwards, so it can't
+    /// disagree with the payload.
+    ///
+    /// Returns [`EncodeError::TooLong`] if the payload is over 255 bytes. Options that
+    /// are split instead (RFC 3396) are written with
+    /// [`encode_long_opt_bytes`](crate::v4::encode_long_opt_bytes).
+    ///
+    /// ```rust
+    /// use dhcproto::encoder::Encoder;
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut e = Encoder::new(&mut buf);
+    /// e.write_opt_v4(57, |e| e.write_u16(1500))?;
+    /// assert_eq!(buf, [57, 2, 0x05, 0xdc]);
+    /// # Ok::<(), dhcproto::error::EncodeError>(())
+    /// ```
+    pub fn write_opt_v4<F>(&mut self, code: u8, f: F) -> EncodeResult<()>
+    where
+        F: FnOnce(&mut Self) -> EncodeResult<()>,
+    {
+        self.write_u8(code)?;
+        self.write_with_len::<1, _>(f)
+    }
+
+    /// Write a v6 option: `code`, its length in 2 bytes and the payload `f` writes. The
+    /// length is measured from what `f` writes and filled in afterwards, so it can't
+    /// disagree with the payload.
+    ///
+    /// Returns [`EncodeError::TooLong`] if the payload is over 65535 bytes.
+    pub fn write_opt_v6<F>(&mut self, code: u16, f: F) -> EncodeResult<()>
+    where
+        F: FnOnce(&mut Self) -> EncodeResult<()>,
+    {
+        self.write_u16(code)?;
+        self.write_with_len::<2, _>(f)
+    }
+
+    // write an `N` byte length, then the payload `f` writes, and backfill the length
+    fn write_with_len<const N: usize, F>(&mut self, f: F) -> EncodeResult<()>
+    where
+        F: FnOnce(&mut Self) -> EncodeResult<()>,
+    {
+        let start = self.offset;
+        self.write([0; N])?;
+        f(self)?;
+        let len = self.offset - start - N;
+        let max = (1 << (8 * N)) - 1;
+        if len > max {
+            return Err(EncodeError::TooLong { len, max });
+        }
+        if let Some(buffer) = self.buffer.as_deref_mut() {
+            buffer[start..start + N].copy_from_slice(&(len as u64).to_be_bytes()[8 - N..]);
+        }
+        Ok(())
+    }
+
     /// Writes bytes to buffer and pads with 0 bytes up to some fill_len
     ///
     /// Returns
     ///    Err - if bytes.len() is greater then fill_len
     pub fn write_fill_bytes(&mut self, bytes: &[u8], fill_len: usize) -> EncodeResult<()> {
         if bytes.len() > fill_len {
-            return Err(EncodeError::StringSizeTooBig { len: bytes.len() });
+            return Err(EncodeError::TooLong {
+                len: bytes.len(),
+                max: fill_len,
+            });
         }
         let nul_len = fill_len - bytes.len();
         self.write_slice(bytes)?;
@@ -178,11 +436,11 @@ mod tests {
         enc.offset = 4;
         // write already reserved space
         enc.write_slice(&[5, 6])?;
-        assert_eq!(enc.buffer, &mut vec![0, 1, 2, 3, 5, 6]);
+        assert_eq!(enc.buffer(), &[0, 1, 2, 3, 5, 6]);
         assert_eq!(enc.offset, 6);
         // reserve extra space
         enc.write_slice(&[7, 8])?;
-        assert_eq!(enc.buffer, &mut vec![0, 1, 2, 3, 5, 6, 7, 8]);
+        assert_eq!(enc.buffer(), &[0, 1, 2, 3, 5, 6, 7, 8]);
         assert_eq!(enc.offset, 8);
 
         // start w/ empty buf
@@ -190,8 +448,18 @@ mod tests {
         let mut enc = Encoder::new(&mut buf);
         // reserve space & write
         enc.write_slice(&[0, 1, 2, 3])?;
-        assert_eq!(enc.buffer, &mut vec![0, 1, 2, 3]);
+        assert_eq!(enc.buffer(), &[0, 1, 2, 3]);
         assert_eq!(enc.offset, 4);
         Ok(())
     }
+
+    #[test]
+    fn counter_encode() -> EncodeResult<()> {
+        let mut enc = Encoder::counter();
+        enc.write_slice(&[0, 1, 2, 3])?;
+        enc.write_u32(5)?;
+        assert_eq!(enc.len_filled(), 8);
+        assert!(enc.buffer_filled().is_empty());
+        Ok(())
+    }
 }
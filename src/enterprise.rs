@@ -0,0 +1,195 @@
+//! IANA Private Enterprise Numbers
+//!
+//! Vendor options (v6 `VendorOpts` & `VendorClass`, DUID-EN) identify the vendor with
+//! its enterprise number <https://www.iana.org/assignments/enterprise-numbers>.
+//! [`EnterpriseNumber`] wraps it for logging, with constants like
+//! [`EnterpriseNumber::CABLELABS`] for a short, hand-picked list of vendors seen in
+//! DHCP vendor options (not the full IANA registry). With the `vendor-names` feature
+//! it displays and parses the organization name of those vendors, otherwise it's the
+//! number.
+//!
+//! ```rust
+//! use dhcproto::enterprise::EnterpriseNumber;
+//!
+//! let cablelabs = EnterpriseNumber::CABLELABS;
+//! assert_eq!(cablelabs.get(), 4491);
+//! assert_eq!("4491".parse(), Ok(cablelabs));
+//! # #[cfg(feature = "vendor-names")]
+//! # {
+//! assert_eq!(cablelabs.to_string(), "CableLabs");
+//! assert_eq!("cablelabs".parse(), Ok(cablelabs));
+//! # }
+//! ```
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::ParseNameError;
+
+/// An IANA Private Enterprise Number
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EnterpriseNumber(u32);
+
+impl EnterpriseNumber {
+    /// 2 - IBM
+    pub const IBM: Self = Self(2);
+    /// 9 - Cisco
+    pub const CISCO: Self = Self(9);
+    /// 11 - Hewlett-Packard
+    pub const HEWLETT_PACKARD: Self = Self(11);
+    /// 43 - 3Com
+    pub const THREE_COM: Self = Self(43);
+    /// 311 - Microsoft
+    pub const MICROSOFT: Self = Self(311);
+    /// 674 - Dell
+    pub const DELL: Self = Self(674);
+    /// 1916 - Extreme Networks
+    pub const EXTREME_NETWORKS: Self = Self(1916);
+    /// 2011 - Huawei
+    pub const HUAWEI: Self = Self(2011);
+    /// 2636 - Juniper Networks
+    pub const JUNIPER_NETWORKS: Self = Self(2636);
+    /// 3375 - F5 Networks
+    pub const F5_NETWORKS: Self = Self(3375);
+    /// 3561 - Broadband Forum
+    pub const BROADBAND_FORUM: Self = Self(3561);
+    /// 4413 - Broadcom
+    pub const BROADCOM: Self = Self(4413);
+    /// 4491 - CableLabs
+    pub const CABLELABS: Self = Self(4491);
+    /// 4526 - Netgear
+    pub const NETGEAR: Self = Self(4526);
+    /// 6527 - Nokia
+    pub const NOKIA: Self = Self(6527);
+    /// 6876 - VMware
+    pub const VMWARE: Self = Self(6876);
+    /// 8072 - net-snmp
+    pub const NET_SNMP: Self = Self(8072);
+    /// 11129 - Google
+    pub const GOOGLE: Self = Self(11129);
+    /// 12356 - Fortinet
+    pub const FORTINET: Self = Self(12356);
+    /// 14988 - MikroTik
+    pub const MIKROTIK: Self = Self(14988);
+    /// 25461 - Palo Alto Networks
+    pub const PALO_ALTO_NETWORKS: Self = Self(25461);
+    /// 30065 - Arista Networks
+    pub const ARISTA_NETWORKS: Self = Self(30065);
+    /// 41112 - Ubiquiti
+    pub const UBIQUITI: Self = Self(41112);
+
+    /// create a new enterprise number
+    pub const fn new(num: u32) -> Self {
+        Self(num)
+    }
+
+    /// the number
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// the organization's name, `None` if it isn't in the hand-picked list or the
+    /// `vendor-names` feature is off
+    pub fn name(self) -> Option<&'static str> {
+        #[cfg(feature = "vendor-names")]
+        {
+            KNOWN
+                .binary_search_by_key(&self, |(num, _)| *num)
+                .ok()
+                .map(|i| KNOWN[i].1)
+        }
+        #[cfg(not(feature = "vendor-names"))]
+        {
+            None
+        }
+    }
+}
+
+impl From<u32> for EnterpriseNumber {
+    fn from(num: u32) -> Self {
+        Self(num)
+    }
+}
+
+impl From<EnterpriseNumber> for u32 {
+    fn from(num: EnterpriseNumber) -> Self {
+        num.0
+    }
+}
+
+/// Prints the organization name if known, the number otherwise
+impl fmt::Display for EnterpriseNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Parses the number, or an organization name in the table ignoring case
+impl FromStr for EnterpriseNumber {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(num) = s.parse::<u32>() {
+            return Ok(Self(num));
+        }
+        #[cfg(feature = "vendor-names")]
+        if let Some((num, _)) = KNOWN.iter().find(|(_, name)| name.eq_ignore_ascii_case(s)) {
+            return Ok(*num);
+        }
+        Err(ParseNameError::new(s))
+    }
+}
+
+// the named vendors, sorted by number
+#[cfg(feature = "vendor-names")]
+const KNOWN: &[(EnterpriseNumber, &str)] = &[
+    (EnterpriseNumber::IBM, "IBM"),
+    (EnterpriseNumber::CISCO, "Cisco"),
+    (EnterpriseNumber::HEWLETT_PACKARD, "Hewlett-Packard"),
+    (EnterpriseNumber::THREE_COM, "3Com"),
+    (EnterpriseNumber::MICROSOFT, "Microsoft"),
+    (EnterpriseNumber::DELL, "Dell"),
+    (EnterpriseNumber::EXTREME_NETWORKS, "Extreme Networks"),
+    (EnterpriseNumber::HUAWEI, "Huawei"),
+    (EnterpriseNumber::JUNIPER_NETWORKS, "Juniper Networks"),
+    (EnterpriseNumber::F5_NETWORKS, "F5 Networks"),
+    (EnterpriseNumber::BROADBAND_FORUM, "Broadband Forum"),
+    (EnterpriseNumber::BROADCOM, "Broadcom"),
+    (EnterpriseNumber::CABLELABS, "CableLabs"),
+    (EnterpriseNumber::NETGEAR, "Netgear"),
+    (EnterpriseNumber::NOKIA, "Nokia"),
+    (EnterpriseNumber::VMWARE, "VMware"),
+    (EnterpriseNumber::NET_SNMP, "net-snmp"),
+    (EnterpriseNumber::GOOGLE, "Google"),
+    (EnterpriseNumber::FORTINET, "Fortinet"),
+    (EnterpriseNumber::MIKROTIK, "MikroTik"),
+    (EnterpriseNumber::PALO_ALTO_NETWORKS, "Palo Alto Networks"),
+    (EnterpriseNumber::ARISTA_NETWORKS, "Arista Networks"),
+    (EnterpriseNumber::UBIQUITI, "Ubiquiti"),
+];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enterprise_number() {
+        let num = EnterpriseNumber::new(99999);
+        assert_eq!(num.to_string(), "99999");
+        assert_eq!("99999".parse(), Ok(num));
+        assert!("not a vendor".parse::<EnterpriseNumber>().is_err());
+        #[cfg(feature = "vendor-names")]
+        {
+            assert!(KNOWN.windows(2).all(|w| w[0].0 < w[1].0));
+            assert_eq!(EnterpriseNumber::CISCO.name(), Some("Cisco"));
+            assert_eq!(
+                "juniper networks".parse(),
+                Ok(EnterpriseNumber::JUNIPER_NETWORKS)
+            );
+        }
+    }
+}
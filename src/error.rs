@@ -1,6 +1,11 @@
 //! Error types for Encoding/Decoding
-use std::io;
-
+//!
+//! Both error types are grouped by category: the data ran out ([`DecodeError::Truncated`]),
+//! a value isn't allowed (`InvalidValue`), an option isn't supported
+//! (`UnsupportedOption`), or something failed underneath (`Utf8`,
+//! `Domain`, `Uri`). Errors from other crates are kept as the [`source`] of the error.
+//!
+//! [`source`]: std::error::Error::source
 use thiserror::Error;
 
 /// Convenience type for decode errors
@@ -8,68 +13,134 @@ pub type DecodeResult<T> = Result<T, DecodeError>;
 
 /// Returned from types that decode
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum DecodeError {
-    /// add overflow
-    #[error("decoder checked_add failed")]
-    AddOverflow,
-
     /// ran out of bytes
     #[error("parser ran out of data-- not enough bytes")]
-    NotEnoughBytes,
+    Truncated,
+
+    /// value is malformed or outside of the range allowed by the RFC
+    #[error("invalid value{}: {reason}", fmt_code(*.code))]
+    InvalidValue {
+        /// code of the option the value was in, `None` if it wasn't in an option
+        code: Option<u16>,
+        /// what was wrong with the value
+        reason: &'static str,
+    },
 
-    /// error converting from slice
-    #[error("error converting from slice {0}")]
-    SliceError(#[from] std::array::TryFromSliceError),
+    /// option is recognized but decoding it isn't supported, only returned by strict
+    /// decoders, e.g. a v4 `OptionOverload` whose options in `sname`/`file` aren't
+    /// decoded
+    #[error("unsupported option {0}")]
+    UnsupportedOption(u16),
 
-    /// error finding nul in string
-    #[error("error getting null terminated string {0}")]
-    NulError(#[from] std::ffi::FromBytesWithNulError),
+    /// options are nested deeper than the decoder's limit, see
+    /// [`Decoder::max_depth`](crate::Decoder::max_depth)
+    #[error("options nested more than {0} deep")]
+    TooDeep(usize),
 
-    /// error converting to utf-8
-    #[error("error converting to UTF-8 {0}")]
-    Utf8Error(#[from] std::str::Utf8Error),
+    /// v4 magic cookie is missing or wrong, only returned by strict decoders
+    #[error("invalid magic cookie {0:?}")]
+    InvalidMagic([u8; 4]),
 
-    /// io error
-    #[error("io error {0}")]
-    IoError(#[from] io::Error),
+    /// error converting to utf-8
+    #[error("invalid UTF-8")]
+    Utf8(#[from] std::str::Utf8Error),
 
-    /// url parse error
-    #[error("url parse error")]
-    UrlParseError(#[from] url::ParseError),
+    /// uri parse error
+    #[error("invalid URI")]
+    Uri(#[from] crate::uri::UriError),
 
     /// domain parse error
-    #[error("domain parse error {0}")]
-    DomainParseError(#[from] hickory_proto::error::ProtoError),
+    #[error("invalid domain name")]
+    Domain(#[from] crate::domain::DomainError),
 
     /// Unknown decode error
     #[error("unknown error")]
-    Unknown(Box<dyn std::error::Error + Send + Sync + 'static>),
+    Unknown(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+impl DecodeError {
+    /// an [`DecodeError::InvalidValue`] not yet tied to an option code
+    pub(crate) fn invalid(reason: &'static str) -> Self {
+        DecodeError::InvalidValue { code: None, reason }
+    }
+
+    /// fill in the option code of an [`DecodeError::InvalidValue`], if it doesn't
+    /// have one. Nested options keep the innermost code
+    pub(crate) fn with_code(self, code: u16) -> Self {
+        match self {
+            DecodeError::InvalidValue { code: None, reason } => DecodeError::InvalidValue {
+                code: Some(code),
+                reason,
+            },
+            err => err,
+        }
+    }
+}
+
+// fixed size reads check the length first, a failed conversion means the data ran out
+impl From<std::array::TryFromSliceError> for DecodeError {
+    fn from(_: std::array::TryFromSliceError) -> Self {
+        DecodeError::Truncated
+    }
+}
+
+impl From<std::ffi::FromBytesWithNulError> for DecodeError {
+    fn from(_: std::ffi::FromBytesWithNulError) -> Self {
+        DecodeError::invalid("string is not nul terminated")
+    }
+}
+
+fn fmt_code(code: Option<u16>) -> String {
+    code.map(|code| format!(" in option {code}"))
+        .unwrap_or_default()
 }
 
 /// Returned from types that encode
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum EncodeError {
     /// addition overflow
     #[error("encoder checked_add failed")]
     AddOverflow,
 
-    /// string exceeds bounds
-    #[error(
-        "message is trying to write a string to the message that exceeds the max size of {len}"
-    )]
-    StringSizeTooBig {
-        /// size of string
+    /// length of data does not fit in its length field
+    #[error("data of length {len} exceeds the maximum length {max} for this field")]
+    TooLong {
+        /// length of the data
         len: usize,
+        /// largest length the field can hold
+        max: usize,
     },
 
-    /// io error
-    #[error("io error {0}")]
-    IoError(#[from] io::Error),
+    /// value can't be encoded, it's outside of the range allowed by the RFC
+    #[error("invalid value{}: {reason}", fmt_code(*.code))]
+    InvalidValue {
+        /// code of the option the value was in, `None` if it wasn't in an option
+        code: Option<u16>,
+        /// what was wrong with the value
+        reason: &'static str,
+    },
+}
 
-    /// DNS encoding error from hickory-dns
-    #[error("domain encoding error {0}")]
-    DomainEncodeError(#[from] hickory_proto::error::ProtoError),
+impl EncodeError {
+    /// an [`EncodeError::InvalidValue`] not tied to an option code
+    pub(crate) fn invalid(reason: &'static str) -> Self {
+        EncodeError::InvalidValue { code: None, reason }
+    }
 }
 
 /// Convenience type for encode errors
 pub type EncodeResult<T> = Result<T, EncodeError>;
+
+/// Returned when parsing an unknown option, message type or enterprise name
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown name {0:?}")]
+pub struct ParseNameError(String);
+
+impl ParseNameError {
+    pub(crate) fn new(name: &str) -> Self {
+        Self(name.to_owned())
+    }
+}
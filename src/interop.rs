@@ -0,0 +1,547 @@
+//! ISC dhcpd / Kea option data interop
+//!
+//! Converts option values as written in ISC dhcpd or Kea configuration into
+//! [`v4::DhcpOption`] or [`v6::DhcpOption`] and back. Kea's `option-data` entries have a
+//! `data` string and a `csv-format` flag: with `csv-format` set the data is a comma
+//! separated list of values (`"192.168.1.1, 192.168.1.2"`), otherwise it is hex
+//! (`"0A0B0C"`, `"0a:0b:0c"` or `"0x0a0b0c"`).
+//!
+//! Values are only understood in csv format for options with a simple type (addresses,
+//! integers, booleans, strings and domain lists), every other option is read and
+//! written as hex. Option names from the configuration can be turned into codes with
+//! the `FromStr` impl on `OptionCode`.
+//!
+//! ```rust
+//! use dhcproto::{interop, v4};
+//!
+//! let code = "domain-name-servers".parse::<v4::OptionCode>()?;
+//! let opt = interop::parse_v4(code, "192.168.1.1, 192.168.1.2", true)?;
+//! assert_eq!(
+//!     opt,
+//!     v4::DhcpOption::DomainNameServer(vec![[192, 168, 1, 1].into(), [192, 168, 1, 2].into()])
+//! );
+//! assert_eq!(interop::render_v4(&opt)?.data, "192.168.1.1, 192.168.1.2");
+//!
+//! // options without a csv format use hex
+//! let opt = interop::parse_v4(v4::OptionCode::ClientIdentifier, "01:aa:bb", false)?;
+//! assert_eq!(opt, v4::DhcpOption::ClientIdentifier(vec![1, 0xaa, 0xbb]));
+//! assert_eq!(interop::render_v4(&opt)?.data, "01AABB");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    domain::DomainWriter,
+    encoder::Encodable,
+    error::{DecodeError, EncodeError},
+    v4, v6, Domain,
+};
+
+/// Returned when option data can't be converted
+#[derive(Error, Debug)]
+pub enum InteropError {
+    /// a value in the data doesn't match the option's type
+    #[error("invalid option data {0:?}")]
+    InvalidData(String),
+
+    /// data isn't valid hex
+    #[error("invalid hex data {0}")]
+    Hex(#[from] hex::FromHexError),
+
+    /// the data didn't decode as the option
+    #[error("error decoding option {0}")]
+    Decode(#[from] DecodeError),
+
+    /// the option couldn't be encoded
+    #[error("error encoding option {0}")]
+    Encode(#[from] EncodeError),
+}
+
+/// Option data as written in a Kea `option-data` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionData {
+    /// the option value
+    pub data: String,
+    /// `true` if `data` is comma separated values, `false` if it is hex
+    pub csv_format: bool,
+}
+
+// the value types options can have in csv format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Empty,
+    Ipv4,
+    Ipv4List,
+    Ipv6,
+    Ipv6List,
+    Bool,
+    U8,
+    U8List,
+    U16,
+    U16List,
+    U32,
+    I32,
+    Str,
+    Fqdns,
+}
+
+fn v4_format(code: v4::OptionCode) -> Option<Format> {
+    use v4::OptionCode as C;
+    Some(match code {
+        C::SubnetMask
+        | C::SwapServer
+        | C::BroadcastAddr
+        | C::RouterSolicitationAddr
+        | C::RequestedIpAddress
+        | C::ServerIdentifier
+        | C::SubnetSelection
+        | C::TFTPServerAddress => Format::Ipv4,
+        C::Router
+        | C::TimeServer
+        | C::NameServer
+        | C::DomainNameServer
+        | C::LogServer
+        | C::QuoteServer
+        | C::LprServer
+        | C::ImpressServer
+        | C::ResourceLocationServer
+        | C::PolicyFilter
+        | C::StaticRoutingTable
+        | C::NisServers
+        | C::NtpServers
+        | C::NetBiosNameServers
+        | C::NetBiosDatagramDistributionServer
+        | C::XFontServer
+        | C::XDisplayManager
+        | C::NispServers
+        | C::MobileIpHomeAgent
+        | C::SmtpServer
+        | C::Pop3Server
+        | C::NntpServer
+        | C::WwwServer
+        | C::DefaultFingerServer
+        | C::IrcServer
+        | C::StreetTalkServer
+        | C::StreetTalkDirectoryAssistance
+        | C::BcmsControllerAddrs
+        | C::AssociatedIp => Format::Ipv4List,
+        C::Hostname
+        | C::MeritDumpFile
+        | C::DomainName
+        | C::RootPath
+        | C::ExtensionsPath
+        | C::NisDomain
+        | C::NetBiosScope
+        | C::Message
+        | C::NwipDomainName
+        | C::NispServiceDomain
+        | C::TFTPServerName
+        | C::BootfileName
+        | C::TZPosixString
+        | C::TZDatabaseString
+        | C::CaptivePortal => Format::Str,
+        C::IpForwarding
+        | C::NonLocalSrcRouting
+        | C::AllSubnetsLocal
+        | C::PerformMaskDiscovery
+        | C::MaskSupplier
+        | C::PerformRouterDiscovery
+        | C::TrailerEncapsulated
+        | C::EthernetEncapsulation
+        | C::TcpKeepaliveGarbage => Format::Bool,
+        C::DefaultIpTtl
+        | C::DefaultTcpTtl
+        | C::NetBiosNodeType
+        | C::OptionOverload
+        | C::MessageType
+        | C::DisableSLAAC => Format::U8,
+        C::ParameterRequestList => Format::U8List,
+        C::BootFileSize | C::MaxDatagramSize | C::InterfaceMtu | C::MaxMessageSize => Format::U16,
+        C::PathMtuPlateauTable => Format::U16List,
+        C::PathMtuAgingTimeout
+        | C::ArpCacheTimeout
+        | C::TcpKeepaliveInterval
+        | C::AddressLeaseTime
+        | C::Renewal
+        | C::Rebinding
+        | C::ClientLastTransactionTime
+        | C::Ipv6OnlyPreferred
+        | C::BulkLeaseQueryBaseTime
+        | C::BulkLeasQueryStartTimeOfState
+        | C::BulkLeaseQueryQueryStartTime
+        | C::BulkLeaseQueryQueryEndTime => Format::U32,
+        C::TimeOffset => Format::I32,
+        C::DomainSearch | C::BcmsControllerNames => Format::Fqdns,
+        C::RapidCommit => Format::Empty,
+        _ => return None,
+    })
+}
+
+fn v6_format(code: v6::OptionCode) -> Option<Format> {
+    use v6::OptionCode as C;
+    Some(match code {
+        C::ServerUnicast | C::LinkAddress => Format::Ipv6,
+        C::SipServerA
+        | C::DomainNameServers
+        | C::NisServers
+        | C::NispServers
+        | C::SntpServers
+        | C::PcpServer => Format::Ipv6List,
+        C::Preference | C::ReconfMsg => Format::U8,
+        C::ElapsedTime => Format::U16,
+        C::ORO => Format::U16List,
+        C::InformationRefreshTime | C::SolMaxRt | C::InfMaxRt => Format::U32,
+        C::NewPosixTimezone | C::NewTzdbTimezone | C::OptBootfileUrl => Format::Str,
+        C::SipServerD | C::DomainSearchList | C::AftrName => Format::Fqdns,
+        C::RapidCommit | C::ReconfAccept => Format::Empty,
+        _ => return None,
+    })
+}
+
+/// Parse ISC dhcpd/Kea `data` for the v4 option `code`. With `csv_format` the data is
+/// comma separated values if the option has a csv format, otherwise it is hex.
+pub fn parse_v4(
+    code: v4::OptionCode,
+    data: &str,
+    csv_format: bool,
+) -> Result<v4::DhcpOption, InteropError> {
+    let payload = payload(v4_format(code), data, csv_format)?;
+    let len = u8::try_from(payload.len()).map_err(|_| EncodeError::TooLong {
+        len: payload.len(),
+        max: u8::MAX as usize,
+    })?;
+    let mut buf = vec![code.into(), len];
+    buf.extend(payload);
+    Ok(v4::DhcpOption::decode(&mut Decoder::new_strict(&buf))?)
+}
+
+/// Render a v4 option as ISC dhcpd/Kea option data, in csv format when the option has
+/// one and hex otherwise
+pub fn render_v4(opt: &v4::DhcpOption) -> Result<OptionData, InteropError> {
+    let buf = opt.to_vec()?;
+    // long options are split over several code/len pairs (RFC 3396)
+    let mut payload = Vec::new();
+    let mut rest = buf.get(1..).unwrap_or_default();
+    while let [len, data @ ..] = rest {
+        let (chunk, next) = data.split_at((*len as usize).min(data.len()));
+        payload.extend_from_slice(chunk);
+        rest = next.get(1..).unwrap_or_default();
+    }
+    Ok(render(v4_format(v4::OptionCode::from(opt)), &payload))
+}
+
+/// Parse ISC dhcpd/Kea `data` for the v6 option `code`. With `csv_format` the data is
+/// comma separated values if the option has a csv format, otherwise it is hex.
+pub fn parse_v6(
+    code: v6::OptionCode,
+    data: &str,
+    csv_format: bool,
+) -> Result<v6::DhcpOption, InteropError> {
+    let payload = payload(v6_format(code), data, csv_format)?;
+    let len = u16::try_from(payload.len()).map_err(|_| EncodeError::TooLong {
+        len: payload.len(),
+        max: u16::MAX as usize,
+    })?;
+    let mut buf = Vec::with_capacity(payload.len() + 4);
+    buf.extend(u16::from(code).to_be_bytes());
+    buf.extend(len.to_be_bytes());
+    buf.extend(payload);
+    Ok(v6::DhcpOption::decode(&mut Decoder::new_strict(&buf))?)
+}
+
+/// Render a v6 option as Kea option data, in csv format when the option has one and hex
+/// otherwise
+pub fn render_v6(opt: &v6::DhcpOption) -> Result<OptionData, InteropError> {
+    let buf = opt.to_vec()?;
+    Ok(render(
+        v6_format(v6::OptionCode::from(opt)),
+        buf.get(4..).unwrap_or_default(),
+    ))
+}
+
+fn payload(format: Option<Format>, data: &str, csv_format: bool) -> Result<Vec<u8>, InteropError> {
+    match format {
+        Some(format) if csv_format => from_csv(format, data),
+        _ => from_hex(data),
+    }
+}
+
+fn render(format: Option<Format>, payload: &[u8]) -> OptionData {
+    match format.and_then(|format| to_csv(format, payload)) {
+        Some(data) => OptionData {
+            data,
+            csv_format: true,
+        },
+        None => OptionData {
+            data: hex::encode_upper(payload),
+            csv_format: false,
+        },
+    }
+}
+
+// accepts `0a0b`, `0x0a0b`, `0a:0b`, `a:b` & `0a 0b`
+fn from_hex(data: &str) -> Result<Vec<u8>, InteropError> {
+    let data = data.trim();
+    let data = data
+        .strip_prefix("0x")
+        .or_else(|| data.strip_prefix("0X"))
+        .unwrap_or(data);
+    if data.contains([':', ' ']) {
+        return data
+            .split([':', ' '])
+            .filter(|byte| !byte.is_empty())
+            .map(|byte| {
+                u8::from_str_radix(byte, 16)
+                    .ok()
+                    .filter(|_| byte.len() <= 2)
+                    .ok_or_else(|| InteropError::InvalidData(byte.to_owned()))
+            })
+            .collect();
+    }
+    if data.len() % 2 == 1 {
+        Ok(hex::decode(format!("0{data}"))?)
+    } else {
+        Ok(hex::decode(data)?)
+    }
+}
+
+fn field<T: FromStr>(field: &str) -> Result<T, InteropError> {
+    field
+        .parse()
+        .map_err(|_| InteropError::InvalidData(field.to_owned()))
+}
+
+fn from_csv(format: Format, data: &str) -> Result<Vec<u8>, InteropError> {
+    let fields = data
+        .split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .collect::<Vec<_>>();
+    let single = || match fields[..] {
+        [f] => Ok(f),
+        _ => Err(InteropError::InvalidData(data.to_owned())),
+    };
+    let mut buf = Vec::new();
+    match format {
+        Format::Empty if fields.is_empty() => {}
+        Format::Empty => return Err(InteropError::InvalidData(data.to_owned())),
+        Format::Ipv4 => buf.extend(field::<Ipv4Addr>(single()?)?.octets()),
+        Format::Ipv6 => buf.extend(field::<Ipv6Addr>(single()?)?.octets()),
+        Format::Bool => buf.push(match single()? {
+            "true" | "1" => 1,
+            "false" | "0" => 0,
+            f => return Err(InteropError::InvalidData(f.to_owned())),
+        }),
+        Format::U8 => buf.push(field(single()?)?),
+        Format::U16 => buf.extend(field::<u16>(single()?)?.to_be_bytes()),
+        Format::U32 => buf.extend(field::<u32>(single()?)?.to_be_bytes()),
+        Format::I32 => buf.extend(field::<i32>(single()?)?.to_be_bytes()),
+        Format::Str => buf.extend(data.as_bytes()),
+        Format::Ipv4List | Format::Ipv6List | Format::U8List | Format::U16List
+            if fields.is_empty() =>
+        {
+            return Err(InteropError::InvalidData(data.to_owned()))
+        }
+        Format::Ipv4List => {
+            for f in fields {
+                buf.extend(field::<Ipv4Addr>(f)?.octets());
+            }
+        }
+        Format::Ipv6List => {
+            for f in fields {
+                buf.extend(field::<Ipv6Addr>(f)?.octets());
+            }
+        }
+        Format::U8List => {
+            for f in fields {
+                buf.push(field(f)?);
+            }
+        }
+        Format::U16List => {
+            for f in fields {
+                buf.extend(field::<u16>(f)?.to_be_bytes());
+            }
+        }
+        Format::Fqdns => {
+            let mut writer = DomainWriter::new(&mut buf, false);
+            for f in fields {
+                let name =
+                    Domain::from_str(f).map_err(|_| InteropError::InvalidData(f.to_owned()))?;
+                writer.write(&name);
+            }
+        }
+    }
+    Ok(buf)
+}
+
+// `None` if the payload doesn't fit the format, it's rendered as hex instead
+fn to_csv(format: Format, payload: &[u8]) -> Option<String> {
+    let join = |values: Vec<String>| Some(values.join(", "));
+    match (format, payload) {
+        (Format::Empty, []) => Some(String::new()),
+        (Format::Ipv4, &[a, b, c, d]) => Some(Ipv4Addr::new(a, b, c, d).to_string()),
+        (Format::Ipv6, _) => Some(Ipv6Addr::from(<[u8; 16]>::try_from(payload).ok()?).to_string()),
+        (Format::Bool, [0]) => Some("false".to_owned()),
+        (Format::Bool, [1]) => Some("true".to_owned()),
+        (Format::U8, [n]) => Some(n.to_string()),
+        (Format::U16, &[a, b]) => Some(u16::from_be_bytes([a, b]).to_string()),
+        (Format::U32, &[a, b, c, d]) => Some(u32::from_be_bytes([a, b, c, d]).to_string()),
+        (Format::I32, &[a, b, c, d]) => Some(i32::from_be_bytes([a, b, c, d]).to_string()),
+        (Format::Str, _) => std::str::from_utf8(payload)
+            .ok()
+            .filter(|s| !s.contains(','))
+            .map(str::to_owned),
+        (Format::Ipv4List, _) if !payload.is_empty() && payload.len() % 4 == 0 => join(
+            payload
+                .chunks_exact(4)
+                .map(|ip| Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]).to_string())
+                .collect(),
+        ),
+        (Format::Ipv6List, _) if !payload.is_empty() && payload.len() % 16 == 0 => join(
+            payload
+                .chunks_exact(16)
+                .map(|ip| Ipv6Addr::from(<[u8; 16]>::try_from(ip).unwrap()).to_string())
+                .collect(),
+        ),
+        (Format::U8List, [_, ..]) => join(payload.iter().map(u8::to_string).collect()),
+        (Format::U16List, _) if !payload.is_empty() && payload.len() % 2 == 0 => join(
+            payload
+                .chunks_exact(2)
+                .map(|n| u16::from_be_bytes([n[0], n[1]]).to_string())
+                .collect(),
+        ),
+        (Format::Fqdns, [_, ..]) => join(
+            Decoder::new(payload)
+                .read_domains(payload.len())
+                .ok()?
+                .iter()
+                .map(Domain::to_string)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn roundtrip_v4(name: &str, data: &str, expected: v4::DhcpOption) -> Result<()> {
+        let opt = parse_v4(name.parse()?, data, true)?;
+        assert_eq!(opt, expected);
+        let rendered = render_v4(&opt)?;
+        assert!(rendered.csv_format);
+        assert_eq!(parse_v4(name.parse()?, &rendered.data, true)?, opt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v4_csv() -> Result<()> {
+        roundtrip_v4(
+            "routers",
+            "10.0.0.1,10.0.0.2",
+            v4::DhcpOption::Router(vec![[10, 0, 0, 1].into(), [10, 0, 0, 2].into()]),
+        )?;
+        roundtrip_v4(
+            "subnet-mask",
+            "255.255.255.0",
+            v4::DhcpOption::SubnetMask([255, 255, 255, 0].into()),
+        )?;
+        roundtrip_v4(
+            "dhcp-lease-time",
+            "3600",
+            v4::DhcpOption::AddressLeaseTime(3600.into()),
+        )?;
+        roundtrip_v4("ip-forwarding", "true", v4::DhcpOption::IpForwarding(true))?;
+        roundtrip_v4("time-offset", "-3600", v4::DhcpOption::TimeOffset(-3600))?;
+        roundtrip_v4(
+            "domain-name",
+            "example.com",
+            v4::DhcpOption::DomainName("example.com".into()),
+        )?;
+        roundtrip_v4(
+            "domain-search",
+            "example.com., eng.example.com.",
+            v4::DhcpOption::DomainSearch(vec![
+                Domain::from_str("example.com.")?,
+                Domain::from_str("eng.example.com.")?,
+            ]),
+        )?;
+        roundtrip_v4(
+            "dhcp-parameter-request-list",
+            "1, 3, 6",
+            v4::DhcpOption::ParameterRequestList(vec![
+                v4::OptionCode::SubnetMask,
+                v4::OptionCode::Router,
+                v4::OptionCode::DomainNameServer,
+            ]),
+        )?;
+
+        assert!(parse_v4(v4::OptionCode::Router, "10.0.0.256", true).is_err());
+        assert!(parse_v4(v4::OptionCode::SubnetMask, "1.1.1.1, 2.2.2.2", true).is_err());
+        assert!(parse_v4(v4::OptionCode::IpForwarding, "yes", true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hex() -> Result<()> {
+        for data in ["0a0b0c", "0x0A0B0C", "a:b:c", "0a 0b 0c", "a0b0c"] {
+            assert_eq!(
+                parse_v4(v4::OptionCode::ClientIdentifier, data, true)?,
+                v4::DhcpOption::ClientIdentifier(vec![0xa, 0xb, 0xc]),
+            );
+        }
+        // hex is always accepted without csv-format
+        assert_eq!(
+            parse_v4(v4::OptionCode::Router, "0A000001", false)?,
+            v4::DhcpOption::Router(vec![[10, 0, 0, 1].into()])
+        );
+        assert!(parse_v4(v4::OptionCode::ClientIdentifier, "0g", false).is_err());
+
+        let long = v4::DhcpOption::ClientIdentifier(vec![0xab; 300]);
+        assert_eq!(
+            render_v4(&long)?,
+            OptionData {
+                data: "AB".repeat(300),
+                csv_format: false
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_v6() -> Result<()> {
+        let opt = parse_v6("dns-servers".parse()?, "2001:db8::1, 2001:db8::2", true)?;
+        assert_eq!(
+            opt,
+            v6::DhcpOption::DomainNameServers(vec!["2001:db8::1".parse()?, "2001:db8::2".parse()?])
+        );
+        assert_eq!(render_v6(&opt)?.data, "2001:db8::1, 2001:db8::2");
+
+        let opt = parse_v6(v6::OptionCode::Preference, "255", true)?;
+        assert_eq!(opt, v6::DhcpOption::Preference(255));
+        assert_eq!(
+            parse_v6(v6::OptionCode::RapidCommit, "", true)?,
+            v6::DhcpOption::RapidCommit
+        );
+
+        let opt = parse_v6(v6::OptionCode::InterfaceId, "01:02", true)?;
+        assert_eq!(opt, v6::DhcpOption::InterfaceId(vec![1, 2].into()));
+        assert_eq!(
+            render_v6(&opt)?,
+            OptionData {
+                data: "0102".to_owned(),
+                csv_format: false
+            }
+        );
+        Ok(())
+    }
+}
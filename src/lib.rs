@@ -83,14 +83,181 @@
 //! #    }
 //! ```
 
+// Display for small enums, for logging: the variant name, or the number for `Unknown`
+macro_rules! display_unknown {
+    ($($ty:ty),* $(,)?) => {$(
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::Unknown(n) => write!(f, "{n}"),
+                    _ => write!(f, "{self:?}"),
+                }
+            }
+        }
+    )*};
+}
+
+// the parts of the v4 & v6 `OptionCode` that don't depend on the code table: `Debug` &
+// serde in the format of the enum with an `Unknown` variant they used to be, and a
+// deprecated `Unknown` constructor for code written against it
+macro_rules! option_code_common {
+    ($ty:ident, $int:ty) => {
+        impl $ty {
+            /// the option code `code`, known to this crate or not
+            pub const fn new(code: $int) -> Self {
+                Self(code)
+            }
+
+            /// `true` if this crate knows the code, i.e. it has a name & constant
+            pub const fn is_known(self) -> bool {
+                self.name().is_some()
+            }
+
+            /// the option code `code`. Codes used to be an enum with an `Unknown` variant
+            /// for codes this crate doesn't know, now every code is a plain number.
+            #[deprecated(
+                since = "0.13.0",
+                note = "use `OptionCode::new` or `From`, and `is_known` instead of matching `Unknown`"
+            )]
+            #[allow(non_snake_case)]
+            pub const fn Unknown(code: $int) -> Self {
+                Self(code)
+            }
+        }
+
+        impl std::fmt::Debug for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self.variant_name() {
+                    Some(name) => f.write_str(name),
+                    None => f.debug_tuple("Unknown").field(&self.0).finish(),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let name = stringify!($ty);
+                match ($ty::KNOWN.binary_search(self), self.variant_name()) {
+                    (Ok(idx), Some(variant)) => {
+                        serializer.serialize_unit_variant(name, idx as u32, variant)
+                    }
+                    _ => serializer.serialize_newtype_variant(
+                        name,
+                        $ty::KNOWN.len() as u32,
+                        "Unknown",
+                        &self.0,
+                    ),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                use serde::de::{self, VariantAccess};
+
+                // a variant of the old enum, by name or index
+                enum Variant {
+                    Known($ty),
+                    Unknown,
+                }
+
+                struct VariantVisitor;
+
+                impl<'de> de::Visitor<'de> for VariantVisitor {
+                    type Value = Variant;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("an option code variant")
+                    }
+
+                    fn visit_u64<E: de::Error>(self, idx: u64) -> Result<Variant, E> {
+                        match $ty::KNOWN.get(idx as usize) {
+                            Some(code) => Ok(Variant::Known(*code)),
+                            None if idx as usize == $ty::KNOWN.len() => Ok(Variant::Unknown),
+                            None => Err(E::invalid_value(de::Unexpected::Unsigned(idx), &self)),
+                        }
+                    }
+
+                    fn visit_str<E: de::Error>(self, s: &str) -> Result<Variant, E> {
+                        if s == "Unknown" {
+                            return Ok(Variant::Unknown);
+                        }
+                        $ty::iter_known()
+                            .find(|code| code.variant_name() == Some(s))
+                            .map(Variant::Known)
+                            .ok_or_else(|| E::unknown_variant(s, &[]))
+                    }
+                }
+
+                impl<'de> de::Deserialize<'de> for Variant {
+                    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                        d.deserialize_identifier(VariantVisitor)
+                    }
+                }
+
+                struct Visitor;
+
+                impl<'de> de::Visitor<'de> for Visitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("an option code")
+                    }
+
+                    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<$ty, A::Error> {
+                        match data.variant()? {
+                            (Variant::Known(code), variant) => {
+                                variant.unit_variant()?;
+                                Ok(code)
+                            }
+                            (Variant::Unknown, variant) => variant.newtype_variant().map($ty),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_enum(stringify!($ty), &[], Visitor)
+            }
+        }
+    };
+}
+
 pub use decoder::{Decodable, Decoder};
-pub use encoder::{Encodable, Encoder};
+pub use encoder::{Encodable, EncodeProfile, Encoder};
 
+pub mod addrstate;
+pub mod auth;
+pub mod byte_string;
+pub mod client_key;
 pub mod decoder;
+pub mod diff;
+pub mod dns_servers;
+pub mod domain;
+pub mod dual_stack;
 pub mod encoder;
+pub mod enterprise;
 pub mod error;
+#[cfg(feature = "interop")]
+pub mod interop;
+pub mod merge;
+pub mod metadata;
+pub mod ntp;
+pub mod redact;
+pub mod sub_options;
+pub mod subnet;
+mod sztp;
+pub mod time;
+pub mod tz;
+pub mod uri;
 pub mod v4;
 pub mod v6;
+pub mod vendor_class;
 
+pub use byte_string::ByteString;
+pub use domain::Domain;
+#[cfg(feature = "hickory")]
 pub use hickory_proto::error::ProtoError as NameError;
+#[cfg(feature = "hickory")]
 pub use hickory_proto::rr::Name;
+pub use uri::Uri;
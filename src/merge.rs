@@ -0,0 +1,92 @@
+//! Layering sets of options
+//!
+//! Servers often build a reply's options from several levels of configuration, e.g.
+//! global, then subnet, then host. `DhcpOptions::merge` in both
+//! [`v4`](crate::v4::DhcpOptions::merge) and [`v6`](crate::v6::DhcpOptions::merge)
+//! adds one set to another, a [`MergePolicy`] decides which wins for codes in both.
+//!
+//! ```rust
+//! use dhcproto::{merge::{MergePolicy, Precedence}, v4::{DhcpOption, DhcpOptions, OptionCode}};
+//!
+//! let mut opts = DhcpOptions::new();
+//! opts.insert(DhcpOption::DomainNameServer(vec![[10, 0, 0, 53].into()]));
+//! opts.insert(DhcpOption::Router(vec![[10, 0, 0, 1].into()]));
+//!
+//! let mut host = DhcpOptions::new();
+//! host.insert(DhcpOption::DomainNameServer(vec![[10, 1, 0, 53].into()]));
+//! host.insert(DhcpOption::Router(vec![[10, 1, 0, 1].into()]));
+//!
+//! // the host's options win, except for the router
+//! let policy = MergePolicy::prefer_other().with_override(OptionCode::Router, Precedence::KeepExisting);
+//! opts.merge(&host, &policy);
+//! assert_eq!(opts.get(OptionCode::DomainNameServer), host.get(OptionCode::DomainNameServer));
+//! assert_eq!(opts.get(OptionCode::Router), Some(&DhcpOption::Router(vec![[10, 0, 0, 1].into()])));
+//! ```
+
+/// Which option is kept when both sets have the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Precedence {
+    /// keep the options already in the set being merged into
+    KeepExisting,
+    /// replace them with the options of the set being merged in
+    PreferOther,
+}
+
+/// A default [`Precedence`] with overrides for some codes. `C` is the v4 or v6
+/// `OptionCode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergePolicy<C> {
+    default: Precedence,
+    overrides: Vec<(C, Precedence)>,
+}
+
+impl<C: PartialEq> MergePolicy<C> {
+    /// `precedence` for every code
+    pub fn new(precedence: Precedence) -> Self {
+        Self {
+            default: precedence,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// keep existing options, only add codes that are missing
+    pub fn keep_existing() -> Self {
+        Self::new(Precedence::KeepExisting)
+    }
+
+    /// options being merged in replace existing ones
+    pub fn prefer_other() -> Self {
+        Self::new(Precedence::PreferOther)
+    }
+
+    /// use `precedence` for `code`, replacing any earlier override
+    pub fn with_override(mut self, code: C, precedence: Precedence) -> Self {
+        self.overrides.retain(|(c, _)| *c != code);
+        self.overrides.push((code, precedence));
+        self
+    }
+
+    /// the precedence for `code`
+    pub fn precedence(&self, code: &C) -> Precedence {
+        self.overrides
+            .iter()
+            .find(|(c, _)| c == code)
+            .map_or(self.default, |(_, precedence)| *precedence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        let policy = MergePolicy::keep_existing()
+            .with_override(1, Precedence::PreferOther)
+            .with_override(2, Precedence::PreferOther)
+            .with_override(2, Precedence::KeepExisting);
+        assert_eq!(policy.precedence(&1), Precedence::PreferOther);
+        assert_eq!(policy.precedence(&2), Precedence::KeepExisting);
+        assert_eq!(policy.precedence(&3), Precedence::KeepExisting);
+    }
+}
@@ -0,0 +1,76 @@
+//! Documentation about option codes, for tools that display options
+//!
+//! Generated from the same tables as the v4 & v6 `OptionCode`s, see
+//! [`v4::OptionCode::metadata`](crate::v4::OptionCode::metadata) and
+//! [`v6::OptionCode::metadata`](crate::v6::OptionCode::metadata).
+//!
+//! ```rust
+//! use dhcproto::{metadata::ValueKind, v4, v6};
+//!
+//! let meta = v4::OptionCode::DomainNameServer.metadata().unwrap();
+//! assert_eq!(meta.name, "domain-name-servers");
+//! assert_eq!(meta.kind, ValueKind::Ipv4Addrs);
+//!
+//! let meta = v6::OptionCode::DomainNameServers.metadata().unwrap();
+//! assert_eq!(meta.rfc, Some("https://www.rfc-editor.org/rfc/rfc3646.html"));
+//! assert!(v6::OptionCode::new(65000).metadata().is_none());
+//! ```
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// The name, defining document & value kind of an option
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OptionMetadata {
+    /// the canonical (ISC/kea style) option name
+    pub name: &'static str,
+    /// link to the RFC defining the option, if there is one
+    pub rfc: Option<&'static str>,
+    /// what this crate decodes the option's value to
+    pub kind: ValueKind,
+    /// `true` if the option is deprecated & shouldn't be sent, e.g. v4 `TimeOffset`
+    pub deprecated: bool,
+}
+
+/// The shape of an option's value, as decoded by this crate
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ValueKind {
+    /// no value, the option is a flag
+    Empty,
+    /// a single byte boolean
+    Bool,
+    /// an 8 bit integer
+    U8,
+    /// a 16 bit integer
+    U16,
+    /// a 32 bit integer, including times in seconds
+    U32,
+    /// a signed 32 bit integer
+    I32,
+    /// an IPv4 address
+    Ipv4Addr,
+    /// a list of IPv4 addresses
+    Ipv4Addrs,
+    /// an IPv6 address
+    Ipv6Addr,
+    /// a list of IPv6 addresses
+    Ipv6Addrs,
+    /// a text string
+    String,
+    /// an opaque byte string with a meaning, e.g. a client identifier
+    Bytes,
+    /// a domain name
+    Domain,
+    /// a list of domain names
+    Domains,
+    /// a URI
+    Uri,
+    /// a list of URIs
+    Uris,
+    /// a value with its own type, e.g. encapsulated options or an enum
+    Structured,
+    /// not decoded by this crate, kept as the raw bytes of `Unknown`
+    Opaque,
+}
@@ -0,0 +1,167 @@
+//! NTP server lists across v4 & v6
+//!
+//! DHCPv4 option 42 carries a plain list of IPv4 NTP servers
+//! (<https://datatracker.ietf.org/doc/html/rfc2132#section-8.3>), while DHCPv6 option 56
+//! carries sub-options for unicast addresses, multicast addresses, or FQDNs
+//! (<https://datatracker.ietf.org/doc/html/rfc5908>). [`NtpServers`] gets either as a
+//! list of [`NtpEndpoint`], in the order they were sent.
+//!
+//! Note that v4 option 4 (`TimeServer`) is the RFC 868 time protocol, not NTP/SNTP, and
+//! is not included.
+//!
+//! ```rust
+//! use dhcproto::{ntp::{NtpEndpoint, NtpServers}, v4};
+//!
+//! let mut msg = v4::Message::default();
+//! msg.opts_mut()
+//!     .insert(v4::DhcpOption::NtpServers(vec![[10, 0, 0, 1].into()]));
+//! assert_eq!(
+//!     msg.ntp_servers(),
+//!     vec![NtpEndpoint::Addr([10, 0, 0, 1].into())]
+//! );
+//! ```
+use std::net::IpAddr;
+
+use crate::{v4, v6, Domain};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An NTP server, from either v4 option 42 or v6 option 56
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NtpEndpoint {
+    /// unicast server address
+    Addr(IpAddr),
+    /// multicast group address
+    Multicast(IpAddr),
+    /// server FQDN, must be resolved by the client
+    Fqdn(Domain),
+}
+
+impl From<&v6::NtpSuboption> for Option<NtpEndpoint> {
+    fn from(opt: &v6::NtpSuboption) -> Self {
+        match opt {
+            v6::NtpSuboption::ServerAddress(addr) => Some(NtpEndpoint::Addr((*addr).into())),
+            v6::NtpSuboption::MulticastAddress(addr) => {
+                Some(NtpEndpoint::Multicast((*addr).into()))
+            }
+            v6::NtpSuboption::FQDN(name) => Some(NtpEndpoint::Fqdn(name.clone())),
+            v6::NtpSuboption::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<&NtpEndpoint> for Option<v6::NtpSuboption> {
+    fn from(endpoint: &NtpEndpoint) -> Self {
+        match endpoint {
+            NtpEndpoint::Addr(IpAddr::V6(addr)) => Some(v6::NtpSuboption::ServerAddress(*addr)),
+            NtpEndpoint::Multicast(IpAddr::V6(addr)) => {
+                Some(v6::NtpSuboption::MulticastAddress(*addr))
+            }
+            NtpEndpoint::Fqdn(name) => Some(v6::NtpSuboption::FQDN(name.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Types that carry NTP servers
+pub trait NtpServers {
+    /// the NTP servers in the options, in order of preference
+    fn ntp_servers(&self) -> Vec<NtpEndpoint>;
+}
+
+impl NtpServers for v4::DhcpOptions {
+    fn ntp_servers(&self) -> Vec<NtpEndpoint> {
+        match self.get(v4::OptionCode::NtpServers) {
+            Some(v4::DhcpOption::NtpServers(ips)) => ips
+                .iter()
+                .map(|ip| NtpEndpoint::Addr((*ip).into()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl NtpServers for v4::Message {
+    fn ntp_servers(&self) -> Vec<NtpEndpoint> {
+        self.opts().ntp_servers()
+    }
+}
+
+impl NtpServers for v6::DhcpOptions {
+    fn ntp_servers(&self) -> Vec<NtpEndpoint> {
+        self.get_all(v6::OptionCode::NtpServer)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|opt| match opt {
+                v6::DhcpOption::NtpServer(subopts) => Some(subopts.iter()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|(_, subopt)| subopt.into())
+            .collect()
+    }
+}
+
+impl NtpServers for v6::Message {
+    fn ntp_servers(&self) -> Vec<NtpEndpoint> {
+        self.opts().ntp_servers()
+    }
+}
+
+/// build v4 option 42 from `endpoints`, only IPv4 unicast addresses can be sent
+pub fn v4_option(endpoints: &[NtpEndpoint]) -> v4::DhcpOption {
+    v4::DhcpOption::NtpServers(
+        endpoints
+            .iter()
+            .filter_map(|endpoint| match endpoint {
+                NtpEndpoint::Addr(IpAddr::V4(ip)) => Some(*ip),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+/// build v6 option 56 from `endpoints`, IPv4 addresses are skipped
+pub fn v6_option(endpoints: &[NtpEndpoint]) -> v6::DhcpOption {
+    v6::DhcpOption::NtpServer(
+        endpoints
+            .iter()
+            .filter_map(Option::<v6::NtpSuboption>::from)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{net::Ipv6Addr, str::FromStr};
+
+    #[test]
+    fn test_ntp_servers() -> Result<(), Box<dyn std::error::Error>> {
+        let endpoints = vec![
+            NtpEndpoint::Fqdn(Domain::from_str("pool.ntp.org.")?),
+            NtpEndpoint::Addr(Ipv6Addr::LOCALHOST.into()),
+            NtpEndpoint::Addr([10, 0, 0, 1].into()),
+            NtpEndpoint::Multicast("ff05::101".parse::<Ipv6Addr>()?.into()),
+        ];
+
+        let mut msg = v6::Message::default();
+        msg.opts_mut().insert(v6_option(&endpoints));
+        let v6_servers = msg.ntp_servers();
+        assert_eq!(
+            v6_servers,
+            [&endpoints[0], &endpoints[1], &endpoints[3]]
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+
+        let mut msg = v4::Message::default();
+        msg.opts_mut().insert(v4_option(&endpoints));
+        assert_eq!(msg.ntp_servers(), vec![endpoints[2].clone()]);
+        assert!(v4::Message::default().ntp_servers().is_empty());
+        Ok(())
+    }
+}
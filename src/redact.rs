@@ -0,0 +1,162 @@
+//! Masking of sensitive option contents in `Debug`/`Display` output
+//!
+//! With the `redact` feature enabled, the contents of options that identify a client or
+//! carry key material are replaced with `<redacted>` when formatted:
+//! * v4 `ClientIdentifier` (61) & `Authentication` (90), which decodes as unknown
+//! * v4 relay agent `AgentRemoteId`, `SubscriberId` & `Authentication` sub-options (82)
+//! * v6 `ClientId` (1) DUID
+//! * v6 `Authentication` (11) info
+//!
+//! Wrapping a value in [`reveal`] formats it with everything shown. Without the
+//! feature nothing is masked and [`reveal`] has no effect.
+//!
+//! ```rust
+//! use dhcproto::{redact, v4::DhcpOption};
+//!
+//! let opt = DhcpOption::ClientIdentifier(vec![1, 2, 3]);
+//! # #[cfg(feature = "redact")]
+//! assert_eq!(format!("{opt:?}"), "ClientIdentifier(<redacted>)");
+//! assert_eq!(format!("{:?}", redact::reveal(&opt)), "ClientIdentifier([1, 2, 3])");
+//! ```
+use std::fmt;
+
+#[cfg(feature = "redact")]
+thread_local! {
+    static REVEAL: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Formats the wrapped value without masking, created with [`reveal`]
+#[derive(Clone, Copy)]
+pub struct Reveal<'a, T: ?Sized>(&'a T);
+
+/// Wrap `value` so its `Debug`/`Display` output shows sensitive contents
+pub fn reveal<T: ?Sized>(value: &T) -> Reveal<'_, T> {
+    Reveal(value)
+}
+
+impl<'a, T: ?Sized> Reveal<'a, T> {
+    #[cfg(feature = "redact")]
+    fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        // restore the previous state so nested reveals behave
+        let prev = REVEAL.with(|r| r.replace(true));
+        let res = f(self.0);
+        REVEAL.with(|r| r.set(prev));
+        res
+    }
+
+    #[cfg(not(feature = "redact"))]
+    fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(self.0)
+    }
+}
+
+impl<'a, T: fmt::Debug + ?Sized> fmt::Debug for Reveal<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with(|v| v.fmt(f))
+    }
+}
+
+impl<'a, T: fmt::Display + ?Sized> fmt::Display for Reveal<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with(|v| v.fmt(f))
+    }
+}
+
+/// Debug formats `value` as `<redacted>` when `redact` is set, unless inside a [`Reveal`]
+#[cfg(feature = "redact")]
+pub(crate) struct Redacted<'a, T: ?Sized> {
+    value: &'a T,
+    redact: bool,
+}
+
+#[cfg(feature = "redact")]
+impl<'a, T: ?Sized> Redacted<'a, T> {
+    pub(crate) fn new(value: &'a T, redact: bool) -> Self {
+        Self { value, redact }
+    }
+
+    pub(crate) fn always(value: &'a T) -> Self {
+        Self::new(value, true)
+    }
+}
+
+#[cfg(feature = "redact")]
+impl<'a, T: fmt::Debug + ?Sized> fmt::Debug for Redacted<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.redact && !REVEAL.with(|r| r.get()) {
+            f.write_str("<redacted>")
+        } else {
+            self.value.fmt(f)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "redact"))]
+mod tests {
+    use super::*;
+    use crate::{v4, v6};
+
+    #[test]
+    fn test_redact_v4() {
+        let mut msg = v4::Message::default();
+        msg.opts_mut()
+            .insert(v4::DhcpOption::ClientIdentifier(vec![0xde, 0xad]));
+        let mut info = v4::relay::RelayAgentInformation::default();
+        info.insert(v4::relay::RelayInfo::AgentCircuitId(vec![1]));
+        info.insert(v4::relay::RelayInfo::AgentRemoteId(vec![0xbe, 0xef]));
+        info.insert(v4::relay::RelayInfo::Unknown(v4::relay::UnknownInfo::new(
+            v4::relay::RelayCode::Authentication,
+            vec![0xca, 0xfe],
+        )));
+        msg.opts_mut()
+            .insert(v4::DhcpOption::RelayAgentInformation(info));
+        msg.opts_mut()
+            .insert(v4::DhcpOption::Unknown(v4::UnknownOption::from_raw(
+                90,
+                vec![0xba, 0xbe],
+            )));
+
+        let s = msg.to_string();
+        assert!(s.contains("ClientIdentifier(<redacted>)"));
+        assert!(s.contains("AgentRemoteId(<redacted>)"));
+        assert!(!s.contains("202, 254") && !s.contains("186, 190"));
+        assert!(s.contains("AgentCircuitId([1])"));
+        assert!(!s.contains("222, 173"));
+
+        let s = reveal(&msg).to_string();
+        assert!(s.contains("ClientIdentifier([222, 173])"));
+        assert!(s.contains("AgentRemoteId([190, 239])"));
+        assert!(s.contains("186, 190"));
+        // reveal only applies while formatting
+        assert!(format!("{msg:?}").contains("<redacted>"));
+    }
+
+    #[test]
+    fn test_redact_v6() {
+        let mut msg = v6::Message::new_with_id(v6::MessageType::Solicit, [1, 2, 3]);
+        msg.opts_mut().insert(v6::DhcpOption::ClientId(vec![0, 1]));
+        msg.opts_mut().insert(v6::DhcpOption::ServerId(vec![2, 3]));
+        msg.opts_mut()
+            .insert(v6::DhcpOption::Authentication(v6::Authentication {
+                proto: 3,
+                algo: 1,
+                rdm: 0,
+                replay_detection: 1,
+                info: vec![0xaa; 4],
+            }));
+        let s = format!("{msg:?}");
+        assert!(s.contains("ClientId(<redacted>)"));
+        assert!(s.contains("ServerId([2, 3])"));
+        assert!(s.contains("info: <redacted>"));
+        assert!(!s.contains("170"));
+        // the other options print as derived
+        msg.opts_mut().insert(v6::DhcpOption::RapidCommit);
+        msg.opts_mut().insert(v6::DhcpOption::Preference(7));
+        let s = format!("{msg:?}");
+        assert!(s.contains("RapidCommit") && s.contains("Preference(7)"));
+
+        let s = format!("{:?}", reveal(&msg));
+        assert!(s.contains("ClientId([0, 1])"));
+        assert!(s.contains("info: [170, 170, 170, 170]"));
+    }
+}
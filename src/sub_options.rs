@@ -0,0 +1,451 @@
+//! Generic container for encapsulated sub-options
+//!
+//! Many DHCP options carry their own list of code/length/value encoded
+//! sub-options (relay agent information, vendor options, NTP server, ...).
+//! [`SubOptions`] implements the shared TLV handling once so each container only
+//! has to describe how the value of a single sub-option is decoded/encoded.
+//!
+//! ```rust
+//! use dhcproto::{sub_options::{RawSubOption, SubOptions}, Decodable, Encodable};
+//!
+//! // [code, len, data..] with a 1 byte code & 1 byte length
+//! let bytes = [1, 2, 0xaa, 0xbb, 7, 1, 0xcc];
+//! let opts = SubOptions::<u8, RawSubOption<u8>>::from_bytes(&bytes).unwrap();
+//! assert_eq!(opts.get(7).unwrap().data(), &[0xcc]);
+//! assert_eq!(opts.to_vec().unwrap(), bytes);
+//! ```
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeError, EncodeResult},
+};
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+}
+
+/// Width of the code and length fields of a sub-option on the wire.
+///
+/// Implemented for `u8` (DHCPv4 style) and `u16` (DHCPv6 style).
+pub trait CodeWidth: Copy + sealed::Sealed {
+    /// largest length that can be written in a length field
+    const MAX_LEN: usize;
+    /// read a code or length field
+    fn read(decoder: &mut Decoder<'_>) -> DecodeResult<Self>;
+    /// write a code or length field
+    fn write(self, e: &mut Encoder<'_>) -> EncodeResult<()>;
+    /// convert a length field to `usize`
+    fn to_len(self) -> usize;
+    /// convert a `usize` to a length field, `None` if it does not fit
+    fn from_len(len: usize) -> Option<Self>;
+}
+
+impl CodeWidth for u8 {
+    const MAX_LEN: usize = u8::MAX as usize;
+    fn read(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        decoder.read_u8()
+    }
+    fn write(self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u8(self)
+    }
+    fn to_len(self) -> usize {
+        self as usize
+    }
+    fn from_len(len: usize) -> Option<Self> {
+        u8::try_from(len).ok()
+    }
+}
+
+impl CodeWidth for u16 {
+    const MAX_LEN: usize = u16::MAX as usize;
+    fn read(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        decoder.read_u16()
+    }
+    fn write(self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u16(self)
+    }
+    fn to_len(self) -> usize {
+        self as usize
+    }
+    fn from_len(len: usize) -> Option<Self> {
+        u16::try_from(len).ok()
+    }
+}
+
+/// A code identifying a sub-option within an encapsulating option
+pub trait SubOptionCode: Copy + Eq + From<Self::Raw> + Into<Self::Raw> {
+    /// wire representation of the code, also used for the length field
+    type Raw: CodeWidth;
+}
+
+impl SubOptionCode for u8 {
+    type Raw = u8;
+}
+
+impl SubOptionCode for u16 {
+    type Raw = u16;
+}
+
+/// A single sub-option value that can be stored in [`SubOptions`]
+///
+/// Implementors only deal with the value portion, the code & length
+/// are read/written by the container.
+pub trait SubOption<C: SubOptionCode>: Sized {
+    /// the code this sub-option is encoded with
+    fn code(&self) -> C;
+    /// decode the value for `code`, `decoder` contains exactly the value bytes.
+    /// Unrecognized codes should be preserved rather than rejected.
+    fn decode_value(code: C, decoder: &mut Decoder<'_>) -> DecodeResult<Self>;
+    /// encode the value of this sub-option, without code or length
+    fn encode_value(&self, e: &mut Encoder<'_>) -> EncodeResult<()>;
+}
+
+/// An ordered collection of sub-options, kept in the order they were
+/// inserted or decoded.
+///
+/// `insert` replaces an existing sub-option with the same code, `push` allows
+/// repeated codes for containers where that's valid (e.g. multiple NTP server addresses)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq)]
+pub struct SubOptions<C, V>(Vec<(C, V)>);
+
+impl<C, V> Default for SubOptions<C, V> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<C, V: fmt::Debug> fmt::Debug for SubOptions<C, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(_, v)| v))
+            .finish()
+    }
+}
+
+impl<C, V> SubOptions<C, V>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    /// Create an empty container
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Get the first sub-option with this code
+    pub fn get(&self, code: C) -> Option<&V> {
+        self.0.iter().find(|(c, _)| *c == code).map(|(_, v)| v)
+    }
+    /// Get the first sub-option with this code mutably
+    pub fn get_mut(&mut self, code: C) -> Option<&mut V> {
+        self.0.iter_mut().find(|(c, _)| *c == code).map(|(_, v)| v)
+    }
+    /// Get all sub-options with this code
+    pub fn get_all(&self, code: C) -> impl Iterator<Item = &V> {
+        self.0
+            .iter()
+            .filter(move |(c, _)| *c == code)
+            .map(|(_, v)| v)
+    }
+    /// Returns `true` if a sub-option with this code is present
+    pub fn contains(&self, code: C) -> bool {
+        self.0.iter().any(|(c, _)| *c == code)
+    }
+    /// insert a sub-option, replacing the first one with the same code in place.
+    /// Returns the replaced sub-option if there was one.
+    pub fn insert(&mut self, opt: V) -> Option<V> {
+        let code = opt.code();
+        match self.0.iter_mut().find(|(c, _)| *c == code) {
+            Some((_, v)) => Some(std::mem::replace(v, opt)),
+            None => {
+                self.0.push((code, opt));
+                None
+            }
+        }
+    }
+    /// append a sub-option, even if one with the same code is already present
+    pub fn push(&mut self, opt: V) {
+        self.0.push((opt.code(), opt));
+    }
+    /// remove the first sub-option with this code
+    pub fn remove(&mut self, code: C) -> Option<V> {
+        let i = self.0.iter().position(|(c, _)| *c == code)?;
+        Some(self.0.remove(i).1)
+    }
+    /// iterate over entries
+    pub fn iter(&self) -> impl Iterator<Item = (&C, &V)> {
+        self.0.iter().map(|(c, v)| (c, v))
+    }
+    /// iterate mutably over entries
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&C, &mut V)> {
+        self.0.iter_mut().map(|(c, v)| (&*c, v))
+    }
+    /// Retains only the elements specified by the predicate
+    pub fn retain<F>(&mut self, mut pred: F)
+    where
+        F: FnMut(&C, &mut V) -> bool,
+    {
+        self.0.retain_mut(|(c, v)| pred(c, v))
+    }
+    /// clear all sub-options
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+    /// Returns `true` if there are no sub-options
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Returns number of sub-options
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<C, V> Decodable for SubOptions<C, V>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let mut opts = Vec::new();
+        // a malformed sub-option is an error for a strict decoder. Otherwise it stops
+        // parsing but keeps everything before it, and when recovering only running out
+        // of data stops it
+        while !decoder.buffer().is_empty() {
+            let offset = decoder.offset();
+            let (code, mut value) = match read_sub_option::<C>(decoder) {
+                Ok(sub_option) => sub_option,
+                Err(err) if decoder.is_strict() => return Err(err),
+                Err(err) => {
+                    decoder.skipped(offset, err);
+                    break;
+                }
+            };
+            match V::decode_value(code.into(), &mut value) {
+                Ok(opt) => opts.push((opt.code(), opt)),
+                Err(err) if decoder.is_strict() => return Err(err),
+                Err(err) => {
+                    if !decoder.skipped(offset, err) {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(Self(opts))
+    }
+}
+
+impl<C, V> Encodable for SubOptions<C, V>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        self.0
+            .iter()
+            .try_for_each(|(_, opt)| encode_sub_option::<C, V>(opt, e))
+    }
+}
+
+impl<C, V> FromIterator<V> for SubOptions<C, V>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    fn from_iter<T: IntoIterator<Item = V>>(iter: T) -> Self {
+        Self(iter.into_iter().map(|opt| (opt.code(), opt)).collect())
+    }
+}
+
+impl<C, V> From<Vec<V>> for SubOptions<C, V>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    fn from(opts: Vec<V>) -> Self {
+        opts.into_iter().collect()
+    }
+}
+
+impl<C, V> IntoIterator for SubOptions<C, V> {
+    type Item = (C, V);
+    type IntoIter = std::vec::IntoIter<(C, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// decode a single code/length/value sub-option
+pub(crate) fn decode_sub_option<C, V>(decoder: &mut Decoder<'_>) -> DecodeResult<V>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    let (code, mut value) = read_sub_option::<C>(decoder)?;
+    V::decode_value(code.into(), &mut value)
+}
+
+// read the code & length of a sub-option, returning a decoder over its value
+fn read_sub_option<'a, C: SubOptionCode>(
+    decoder: &mut Decoder<'a>,
+) -> DecodeResult<(C::Raw, Decoder<'a>)> {
+    let code = C::Raw::read(decoder)?;
+    let len = C::Raw::read(decoder)?.to_len();
+    Ok((code, decoder.sub_decoder(len)?))
+}
+
+/// encode a single code/length/value sub-option
+pub(crate) fn encode_sub_option<C, V>(opt: &V, e: &mut Encoder<'_>) -> EncodeResult<()>
+where
+    C: SubOptionCode,
+    V: SubOption<C>,
+{
+    let mut buf = Vec::new();
+    opt.encode_value(&mut Encoder::new(&mut buf))?;
+    let len = C::Raw::from_len(buf.len()).ok_or(EncodeError::TooLong {
+        len: buf.len(),
+        max: C::Raw::MAX_LEN,
+    })?;
+    opt.code().into().write(e)?;
+    len.write(e)?;
+    e.write_slice(&buf)
+}
+
+/// A sub-option whose value is kept as uninterpreted bytes
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawSubOption<C> {
+    code: C,
+    data: Vec<u8>,
+}
+
+impl<C: Copy> RawSubOption<C> {
+    /// create a new sub-option from its code and value
+    pub fn new(code: C, data: Vec<u8>) -> Self {
+        Self { code, data }
+    }
+    /// return the sub-option code
+    pub fn code(&self) -> C {
+        self.code
+    }
+    /// return the value of this sub-option
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// consume into parts
+    pub fn into_parts(self) -> (C, Vec<u8>) {
+        (self.code, self.data)
+    }
+}
+
+impl<C: SubOptionCode> SubOption<C> for RawSubOption<C> {
+    fn code(&self) -> C {
+        self.code
+    }
+    fn decode_value(code: C, decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let len = decoder.buffer().len();
+        Ok(Self {
+            code,
+            data: decoder.read_slice(len)?.to_vec(),
+        })
+    }
+    fn encode_value(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_slice(&self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Raw = SubOptions<u16, RawSubOption<u16>>;
+
+    #[test]
+    fn test_roundtrip_wide() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0x01, 0x00, 0x02, 0xaa, 0xbb,
+            0x01, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x00, 0x01, 0xcc,
+        ];
+        let opts = Raw::from_bytes(&bytes).unwrap();
+        assert_eq!(opts.len(), 3);
+        assert_eq!(opts.get(1).unwrap().data(), &[0xaa, 0xbb]);
+        assert_eq!(opts.get_all(1).count(), 2);
+        assert!(opts.get(256).unwrap().data().is_empty());
+        // order & duplicates preserved
+        assert_eq!(opts.to_vec().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_truncated() {
+        // second sub-option claims more bytes than available
+        let bytes = [1, 1, 0xaa, 2, 5, 0xbb];
+        let opts = SubOptions::<u8, RawSubOption<u8>>::from_bytes(&bytes).unwrap();
+        assert_eq!(opts.len(), 1);
+        assert!(opts.contains(1));
+        assert!(!opts.contains(2));
+        // an error for a strict decoder
+        let res = SubOptions::<u8, RawSubOption<u8>>::decode(&mut Decoder::new_strict(&bytes));
+        assert!(res.is_err());
+
+        // the same for containers built on it, e.g. v6 NtpServer
+        let bytes = [0, 56, 0, 7, 0, 1, 0, 16, 0, 0, 0];
+        let opt = crate::v6::DhcpOption::decode(&mut Decoder::new(&bytes)).unwrap();
+        assert_eq!(opt, crate::v6::DhcpOption::NtpServer(SubOptions::new()));
+        assert!(crate::v6::DhcpOption::decode(&mut Decoder::new_strict(&bytes)).is_err());
+    }
+
+    #[test]
+    fn test_recover() {
+        use crate::v4::relay::{RelayAgentInformation, RelayCode};
+        // circuit id, a link selection with 2 of its 4 bytes, then remote id
+        let bytes = [1, 1, 0xaa, 5, 2, 1, 2, 2, 1, 0xbb, 6];
+        let info = RelayAgentInformation::from_bytes(&bytes).unwrap();
+        assert!(info.get(RelayCode::AgentRemoteId).is_none());
+
+        let mut decoder = Decoder::new(&bytes).recover(true);
+        let info = RelayAgentInformation::decode(&mut decoder).unwrap();
+        assert!(info.get(RelayCode::AgentCircuitId).is_some());
+        assert!(info.get(RelayCode::LinkSelection).is_none());
+        assert!(info.get(RelayCode::AgentRemoteId).is_some());
+        let errors = decoder.take_errors();
+        // the bad link selection, then the trailing byte that isn't a whole sub-option
+        assert_eq!(
+            errors.iter().map(|(offset, _)| *offset).collect::<Vec<_>>(),
+            [3, 10]
+        );
+        assert!(decoder.take_errors().is_empty());
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut opts = Raw::new();
+        opts.push(RawSubOption::new(5, vec![1]));
+        opts.push(RawSubOption::new(2, vec![2]));
+        let old = opts.insert(RawSubOption::new(5, vec![3]));
+        assert_eq!(old.unwrap().data(), &[1]);
+        // replaced in place
+        assert_eq!(opts.iter().map(|(c, _)| *c).collect::<Vec<_>>(), vec![5, 2]);
+        assert_eq!(opts.remove(5).unwrap().data(), &[3]);
+        assert_eq!(opts.len(), 1);
+    }
+
+    #[test]
+    fn test_too_long() {
+        let opts: SubOptions<u8, RawSubOption<u8>> =
+            vec![RawSubOption::new(1, vec![0; 256])].into();
+        assert!(matches!(
+            opts.to_vec(),
+            Err(EncodeError::TooLong { len: 256, max: 255 })
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Finding the link a client is on, for subnet & pool selection
+//!
+//! A server picks the subnet for a client from the link it is on. Relayed messages
+//! carry that link in several places, [`SubnetSelector::link`] checks them in order:
+//!
+//! v4:
+//! 1. Subnet Selection option (118) <https://datatracker.ietf.org/doc/html/rfc3011>
+//! 2. relay agent Link Selection sub-option (82.5) <https://datatracker.ietf.org/doc/html/rfc3527>
+//! 3. `giaddr`
+//! 4. `ciaddr`, for renewing clients talking to the server directly
+//!
+//! v6, using the relay closest to the client:
+//! 1. Link Address option (80) <https://datatracker.ietf.org/doc/html/rfc6977#section-4.2>
+//! 2. `link-address`, if it isn't `::` <https://datatracker.ietf.org/doc/html/rfc8415#section-9>
+//! 3. Interface-Id option (18) <https://datatracker.ietf.org/doc/html/rfc8415#section-21.18>
+//!
+//! ```rust
+//! use dhcproto::{subnet::{Link, SubnetSelector}, v4};
+//!
+//! let mut msg = v4::Message::default();
+//! assert_eq!(msg.link(), Link::Local);
+//! msg.set_giaddr([10, 0, 0, 1]);
+//! assert_eq!(msg.link(), Link::Addr([10, 0, 0, 1].into()));
+//! msg.opts_mut()
+//!     .insert(v4::DhcpOption::SubnetSelection([192, 168, 0, 1].into()));
+//! assert_eq!(msg.link(), Link::Addr([192, 168, 0, 1].into()));
+//! ```
+use std::net::IpAddr;
+
+use crate::{v4, v6};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The link a client is on
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Link {
+    /// an address on the client's link
+    Addr(IpAddr),
+    /// the interface id a v6 relay received the message on, there was no address for
+    /// the link
+    InterfaceId(v6::InterfaceId),
+    /// the message wasn't relayed, the client is on the link the message was received on
+    Local,
+}
+
+/// Messages that identify the link the client is on
+pub trait SubnetSelector {
+    /// the link the client is on, see the [module docs](self) for the order things
+    /// are checked in
+    fn link(&self) -> Link;
+}
+
+impl SubnetSelector for v4::Message {
+    fn link(&self) -> Link {
+        let opts = self.opts();
+        if let Some(v4::DhcpOption::SubnetSelection(addr)) =
+            opts.get(v4::OptionCode::SubnetSelection)
+        {
+            return Link::Addr((*addr).into());
+        }
+        if let Some(v4::DhcpOption::RelayAgentInformation(info)) =
+            opts.get(v4::OptionCode::RelayAgentInformation)
+        {
+            if let Some(v4::relay::RelayInfo::LinkSelection(addr)) =
+                info.get(v4::relay::RelayCode::LinkSelection)
+            {
+                return Link::Addr((*addr).into());
+            }
+        }
+        [self.giaddr(), self.ciaddr()]
+            .into_iter()
+            .find(|addr| !addr.is_unspecified())
+            .map_or(Link::Local, |addr| Link::Addr(addr.into()))
+    }
+}
+
+impl SubnetSelector for v6::RelayMessage {
+    fn link(&self) -> Link {
+        // the relay closest to the client is the one on the client's link
+        let mut relay = self;
+        while let Some(v6::DhcpOption::RelayMsg(inner)) = relay.opts().get(v6::OptionCode::RelayMsg)
+        {
+            if inner.msg_type() != v6::MessageType::RelayForw {
+                break;
+            }
+            relay = inner;
+        }
+        let opts = relay.opts();
+        if let Some(v6::DhcpOption::LinkAddress(addr)) = opts.get(v6::OptionCode::LinkAddress) {
+            return Link::Addr((*addr).into());
+        }
+        if !relay.link_addr().is_unspecified() {
+            return Link::Addr(relay.link_addr().into());
+        }
+        match opts.get(v6::OptionCode::InterfaceId) {
+            Some(v6::DhcpOption::InterfaceId(id)) => Link::InterfaceId(id.clone()),
+            _ => Link::Local,
+        }
+    }
+}
+
+impl SubnetSelector for v6::Message {
+    fn link(&self) -> Link {
+        Link::Local
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+
+    use super::*;
+    use crate::{Decodable, Decoder, Encodable};
+
+    #[test]
+    fn test_v4_link() {
+        let mut msg = v4::Message::default();
+        msg.set_ciaddr([10, 0, 0, 9]);
+        assert_eq!(msg.link(), Link::Addr([10, 0, 0, 9].into()));
+        msg.set_giaddr([10, 0, 0, 1]);
+        assert_eq!(msg.link(), Link::Addr([10, 0, 0, 1].into()));
+
+        let mut info = v4::relay::RelayAgentInformation::default();
+        info.insert(v4::relay::RelayInfo::LinkSelection([172, 16, 0, 1].into()));
+        msg.opts_mut()
+            .insert(v4::DhcpOption::RelayAgentInformation(info));
+        assert_eq!(msg.link(), Link::Addr([172, 16, 0, 1].into()));
+
+        msg.opts_mut()
+            .insert(v4::DhcpOption::SubnetSelection([192, 168, 0, 1].into()));
+        assert_eq!(msg.link(), Link::Addr([192, 168, 0, 1].into()));
+    }
+
+    fn relay_forw(link_addr: Ipv6Addr, opts: Vec<v6::DhcpOption>) -> v6::RelayMessage {
+        // build through the wire format, RelayMessage has no constructor
+        let mut buf = vec![u8::from(v6::MessageType::RelayForw), 0];
+        buf.extend(link_addr.octets());
+        buf.extend(Ipv6Addr::UNSPECIFIED.octets());
+        let mut relay = v6::RelayMessage::decode(&mut Decoder::new(&buf)).unwrap();
+        for opt in opts {
+            relay.opts_mut().insert(opt);
+        }
+        relay
+    }
+
+    #[test]
+    fn test_v6_link() {
+        let addr = "2001:db8::1".parse::<Ipv6Addr>().unwrap();
+        let id = v6::InterfaceId::from("eth0");
+        let inner = relay_forw(
+            Ipv6Addr::UNSPECIFIED,
+            vec![v6::DhcpOption::InterfaceId(id.clone())],
+        );
+        assert_eq!(inner.link(), Link::InterfaceId(id));
+
+        // the outer relay's link address is ignored
+        let outer = relay_forw(addr, vec![v6::DhcpOption::RelayMsg(inner.clone())]);
+        assert!(matches!(outer.link(), Link::InterfaceId(_)));
+
+        let other = "2001:db8:1::1".parse::<Ipv6Addr>().unwrap();
+        let inner = relay_forw(addr, vec![v6::DhcpOption::LinkAddress(other)]);
+        assert_eq!(inner.link(), Link::Addr(other.into()));
+        let inner = v6::RelayMessage::decode(&mut Decoder::new(&inner.to_vec().unwrap())).unwrap();
+        assert_eq!(inner.link(), Link::Addr(other.into()));
+        assert_eq!(relay_forw(addr, vec![]).link(), Link::Addr(addr.into()));
+    }
+}
@@ -0,0 +1,42 @@
+//! SZTP redirect options, v4 143 & v6 136, a list of bootstrap server URIs each
+//! prefixed by a 2 byte length. URIs must use the `https` scheme.
+//! <https://datatracker.ietf.org/doc/html/rfc8572#section-8.3>
+use crate::{
+    error::{DecodeError, DecodeResult, EncodeError, EncodeResult},
+    Decoder, Encoder, Uri,
+};
+
+/// Read the remaining buffer of `decoder` as a list of bootstrap server URIs
+pub(crate) fn read_uris(decoder: &mut Decoder<'_>) -> DecodeResult<Vec<Uri>> {
+    let mut uris = Vec::new();
+    while !decoder.buffer().is_empty() {
+        let len = decoder.read_u16()? as usize;
+        let uri: Uri = decoder.read_str(len)?.parse()?;
+        if !uri.scheme().eq_ignore_ascii_case("https") {
+            return Err(DecodeError::invalid(
+                "SZTP bootstrap server URI must use https",
+            ));
+        }
+        uris.push(uri);
+    }
+    Ok(uris)
+}
+
+/// Write `uris` as a list of bootstrap server URIs
+pub(crate) fn write_uris(e: &mut Encoder<'_>, uris: &[Uri]) -> EncodeResult<()> {
+    for uri in uris {
+        if !uri.scheme().eq_ignore_ascii_case("https") {
+            return Err(EncodeError::invalid(
+                "SZTP bootstrap server URI must use https",
+            ));
+        }
+        let uri = uri.as_str().as_bytes();
+        let len = u16::try_from(uri.len()).map_err(|_| EncodeError::TooLong {
+            len: uri.len(),
+            max: u16::MAX as usize,
+        })?;
+        e.write_u16(len)?;
+        e.write_slice(uri)?;
+    }
+    Ok(())
+}
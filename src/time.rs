@@ -0,0 +1,201 @@
+//! Lease times & lifetimes, and absolute times on the wire
+//!
+//! Most times in DHCP are relative, seconds from when the message was sent. The
+//! leasequery times are the exception: the RFC 6926 base time and query start & end
+//! times are absolute, seconds since the Unix epoch in 32 bits, converted with
+//! [`from_epoch_secs`] & [`to_epoch_secs`].
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A lease time or lifetime in seconds, where `0xffffffff` means infinity
+///
+/// Used for v4 `AddressLeaseTime`, `Renewal` & `Rebinding`
+/// (<https://datatracker.ietf.org/doc/html/rfc2131#section-3.3>) and the v6 preferred &
+/// valid lifetimes (<https://datatracker.ietf.org/doc/html/rfc8415#section-7.7>).
+/// Arithmetic saturates at [`LeaseTime::MAX_FINITE`], so a finite time never becomes
+/// infinite, and an infinite time stays infinite.
+///
+/// ```rust
+/// use dhcproto::time::LeaseTime;
+/// use std::time::Duration;
+///
+/// let lease = LeaseTime::new(3600);
+/// assert_eq!(lease.renewal(), LeaseTime::new(1800));
+/// assert_eq!(lease.rebinding(), LeaseTime::new(3150));
+/// assert_eq!(lease.as_duration(), Some(Duration::from_secs(3600)));
+///
+/// assert!(LeaseTime::INFINITE.renewal().is_infinite());
+/// assert_eq!(LeaseTime::INFINITE.as_duration(), None);
+/// assert_eq!(LeaseTime::new(u32::MAX - 1).saturating_add(10), LeaseTime::MAX_FINITE);
+/// assert!(LeaseTime::INFINITE.saturating_add(10).is_infinite());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LeaseTime(u32);
+
+impl LeaseTime {
+    /// infinite lease time
+    pub const INFINITE: LeaseTime = LeaseTime(u32::MAX);
+    /// the longest finite lease time, where arithmetic on finite times saturates
+    pub const MAX_FINITE: LeaseTime = LeaseTime(u32::MAX - 1);
+
+    /// create a new lease time of `secs` seconds
+    pub const fn new(secs: u32) -> Self {
+        Self(secs)
+    }
+
+    /// lease time in seconds, `u32::MAX` if infinite
+    pub const fn secs(self) -> u32 {
+        self.0
+    }
+
+    /// `true` if this is `0xffffffff`
+    pub const fn is_infinite(self) -> bool {
+        self.0 == u32::MAX
+    }
+
+    /// the lease time as a `Duration`, or `None` if infinite
+    pub fn as_duration(self) -> Option<Duration> {
+        (!self.is_infinite()).then(|| Duration::from_secs(self.0 as u64))
+    }
+
+    /// lease time from a `Duration`, saturating at [`LeaseTime::MAX_FINITE`].
+    /// Fractions of a second are dropped.
+    pub fn from_duration(duration: Duration) -> Self {
+        Self::finite(duration.as_secs())
+    }
+
+    // a finite lease time of `secs`, saturating at `MAX_FINITE`
+    fn finite(secs: u64) -> Self {
+        Self(secs.min(Self::MAX_FINITE.0 as u64) as u32)
+    }
+
+    /// add `secs`, saturating at [`LeaseTime::MAX_FINITE`]. Infinite stays infinite.
+    pub fn saturating_add(self, secs: u32) -> Self {
+        if self.is_infinite() {
+            self
+        } else {
+            Self::finite(self.0 as u64 + secs as u64)
+        }
+    }
+
+    /// subtract `secs` (e.g. time elapsed) saturating at zero. Infinite stays infinite.
+    pub fn saturating_sub(self, secs: u32) -> Self {
+        if self.is_infinite() {
+            self
+        } else {
+            Self(self.0.saturating_sub(secs))
+        }
+    }
+
+    /// `self * num / den` without overflow, saturating at [`LeaseTime::MAX_FINITE`].
+    /// Infinite stays infinite.
+    ///
+    /// # Panics
+    /// if `den` is zero
+    pub fn scale(self, num: u32, den: u32) -> Self {
+        if self.is_infinite() {
+            return self;
+        }
+        Self::finite(self.0 as u64 * num as u64 / den as u64)
+    }
+
+    /// default renewal time (T1), 0.5 * lease
+    /// <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.5>
+    pub fn renewal(self) -> Self {
+        self.scale(1, 2)
+    }
+
+    /// default rebinding time (T2), 0.875 * lease
+    /// <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.5>
+    pub fn rebinding(self) -> Self {
+        self.scale(7, 8)
+    }
+}
+
+impl From<u32> for LeaseTime {
+    fn from(secs: u32) -> Self {
+        Self(secs)
+    }
+}
+
+impl From<LeaseTime> for u32 {
+    fn from(time: LeaseTime) -> Self {
+        time.0
+    }
+}
+
+impl fmt::Display for LeaseTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_infinite() {
+            f.write_str("infinite")
+        } else {
+            write!(f, "{}s", self.0)
+        }
+    }
+}
+
+/// `secs` seconds since the Unix epoch as a `SystemTime`
+pub fn from_epoch_secs(secs: u32) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64)
+}
+
+/// `time` as seconds since the Unix epoch, saturating at 0 before the epoch and at
+/// `u32::MAX` after 2106. Fractions of a second are dropped.
+pub fn to_epoch_secs(time: SystemTime) -> u32 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since) => u32::try_from(since.as_secs()).unwrap_or(u32::MAX),
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_secs() {
+        let time = from_epoch_secs(1_700_000_000);
+        assert_eq!(to_epoch_secs(time), 1_700_000_000);
+        assert_eq!(
+            to_epoch_secs(time + Duration::from_millis(999)),
+            1_700_000_000
+        );
+        assert_eq!(
+            to_epoch_secs(SystemTime::UNIX_EPOCH - Duration::from_secs(1)),
+            0
+        );
+        assert_eq!(
+            to_epoch_secs(from_epoch_secs(u32::MAX) + Duration::from_secs(1)),
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn test_lease_time() {
+        let lease = LeaseTime::new(u32::MAX - 1);
+        assert!(!lease.is_infinite());
+        // no overflow on the multiply
+        assert_eq!(lease.rebinding(), LeaseTime::new(3_758_096_382));
+        assert_eq!(lease.saturating_sub(u32::MAX), LeaseTime::new(0));
+        assert!(LeaseTime::INFINITE.saturating_sub(100).is_infinite());
+        assert_eq!(LeaseTime::new(10).scale(u32::MAX, 1), LeaseTime::MAX_FINITE);
+        assert_eq!(
+            LeaseTime::from_duration(Duration::from_secs(1 << 40)),
+            LeaseTime::MAX_FINITE
+        );
+        assert_eq!(lease.saturating_add(u32::MAX), LeaseTime::MAX_FINITE);
+        assert!(LeaseTime::INFINITE.saturating_add(1).is_infinite());
+        assert_eq!(
+            LeaseTime::from_duration(Duration::from_millis(1500)),
+            LeaseTime::new(1)
+        );
+        assert_eq!(LeaseTime::INFINITE.to_string(), "infinite");
+        assert_eq!(LeaseTime::new(60).to_string(), "60s");
+    }
+}
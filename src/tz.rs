@@ -0,0 +1,216 @@
+//! Timezone string validation for the timezone options
+//!
+//! DHCP carries timezones as strings, either a POSIX `TZ` string
+//! (v4 option 100, v6 option 41) or a TZ database name (v4 option 101, v6 option 42).
+//! The options themselves accept any string, these functions can be used to check
+//! a value before it is sent or after it is received.
+//!
+//! <https://datatracker.ietf.org/doc/html/rfc4833>
+//!
+//! ```rust
+//! use dhcproto::tz;
+//!
+//! assert!(tz::is_posix_tz("EST5EDT4,M3.2.0/02:00:00,M11.1.0/02:00:00"));
+//! assert!(tz::is_tzdb_name("Europe/Zurich"));
+//! assert!(!tz::is_tzdb_name("../etc/passwd"));
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A client's timezone, from whichever option a server sent, see
+/// [`v4::DhcpOptions::timezone`](crate::v4::DhcpOptions::timezone)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Timezone {
+    /// a TZ database name, e.g. `Europe/Zurich`
+    Tzdb(String),
+    /// a POSIX `TZ` string, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`
+    Posix(String),
+    /// a fixed offset from UTC in seconds, east is positive, from the deprecated v4
+    /// `TimeOffset`
+    Offset(i32),
+}
+
+/// Returns `true` if `s` is a valid POSIX `TZ` string as described in IEEE 1003.1
+/// section 8.3, i.e. `std offset [dst [offset] [,start[/time],end[/time]]]`
+pub fn is_posix_tz(s: &str) -> bool {
+    let mut rest = s.as_bytes();
+    // std name & offset are required
+    if !(tz_name(&mut rest) && tz_time(&mut rest)) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    if !tz_name(&mut rest) {
+        return false;
+    }
+    // dst offset is optional
+    if !rest.is_empty() && rest[0] != b',' && !tz_time(&mut rest) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    // start & end rules
+    for _ in 0..2 {
+        if !(eat(&mut rest, b',') && tz_rule(&mut rest)) {
+            return false;
+        }
+        if eat(&mut rest, b'/') && !tz_time(&mut rest) {
+            return false;
+        }
+    }
+    rest.is_empty()
+}
+
+/// Returns `true` if `s` is a well formed TZ database name, like `Europe/Zurich`
+/// or `America/Argentina/Buenos_Aires`
+///
+/// This checks the naming rules of the tz database, not that the zone exists.
+/// <https://data.iana.org/time-zones/theory.html#naming>
+pub fn is_tzdb_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('/').all(|part| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && !part.starts_with('-')
+                && part
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'+' | b'.'))
+        })
+}
+
+fn eat(s: &mut &[u8], c: u8) -> bool {
+    match s.split_first() {
+        Some((first, rest)) if *first == c => {
+            *s = rest;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn digits(s: &mut &[u8], max: usize) -> Option<u32> {
+    let n = s
+        .iter()
+        .take(max)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if n == 0 {
+        return None;
+    }
+    let val = s[..n].iter().fold(0, |acc, b| acc * 10 + (b - b'0') as u32);
+    *s = &s[n..];
+    Some(val)
+}
+
+// `<+0330>` quoted or at least 3 alphabetic chars
+fn tz_name(s: &mut &[u8]) -> bool {
+    if eat(s, b'<') {
+        let n = s
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-'))
+            .count();
+        *s = &s[n..];
+        n >= 3 && eat(s, b'>')
+    } else {
+        let n = s.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+        *s = &s[n..];
+        n >= 3
+    }
+}
+
+// `[+-]hh[:mm[:ss]]`
+fn tz_time(s: &mut &[u8]) -> bool {
+    if !eat(s, b'+') {
+        eat(s, b'-');
+    }
+    let Some(hours) = digits(s, 3) else {
+        return false;
+    };
+    if hours > 167 {
+        return false;
+    }
+    for _ in 0..2 {
+        if !eat(s, b':') {
+            break;
+        }
+        match digits(s, 2) {
+            Some(n) if n < 60 => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+// `Jn`, `n` or `Mm.w.d`
+fn tz_rule(s: &mut &[u8]) -> bool {
+    if eat(s, b'J') {
+        matches!(digits(s, 3), Some(1..=365))
+    } else if eat(s, b'M') {
+        matches!(digits(s, 2), Some(1..=12))
+            && eat(s, b'.')
+            && matches!(digits(s, 1), Some(1..=5))
+            && eat(s, b'.')
+            && matches!(digits(s, 1), Some(0..=6))
+    } else {
+        matches!(digits(s, 3), Some(0..=365))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posix_tz() {
+        for tz in [
+            "UTC0",
+            "EST5EDT",
+            "CET-1CEST,M3.5.0,M10.5.0/3",
+            "<+0330>-3:30",
+            "NZST-12NZDT-13,M9.5.0/2:45:00,M4.1.0/3",
+            "AAA3BBB,J60/2,300",
+        ] {
+            assert!(is_posix_tz(tz), "{tz}");
+        }
+        for tz in [
+            "",
+            "UTC",
+            "EST",
+            "ES5",
+            "CET-1CEST,M3.5.0",
+            "CET-1CEST,M13.5.0,M10.5.0",
+            "EST5EDT,M3.2.0,M11.1.0 ",
+            "<+03>",
+        ] {
+            assert!(!is_posix_tz(tz), "{tz}");
+        }
+    }
+
+    #[test]
+    fn test_tzdb_name() {
+        for tz in [
+            "UTC",
+            "Europe/Zurich",
+            "America/Argentina/Buenos_Aires",
+            "Etc/GMT+5",
+        ] {
+            assert!(is_tzdb_name(tz), "{tz}");
+        }
+        for tz in [
+            "",
+            "Europe/",
+            "/UTC",
+            "a/../b",
+            "Europe/Zürich",
+            "-x",
+            "a b",
+        ] {
+            assert!(!is_tzdb_name(tz), "{tz}");
+        }
+    }
+}
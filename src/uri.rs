@@ -0,0 +1,213 @@
+//! URIs in DHCP options
+//!
+//! The v4 `CaptivePortal` (114), v6 `OptBootfileUrl` and v4/v6 `SztpRedirect` options
+//! carry URIs. [`Uri`]
+//! keeps them as the string sent on the wire, after checking it's an absolute URI.
+//! Enable the `url` feature to convert to and from `url::Url`.
+//!
+//! ```rust
+//! use dhcproto::Uri;
+//!
+//! let uri = "https://portal.example/login".parse::<Uri>()?;
+//! assert_eq!(uri.scheme(), "https");
+//! assert!("not a uri".parse::<Uri>().is_err());
+//! # Ok::<(), dhcproto::uri::UriError>(())
+//! ```
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+/// Returned for strings that aren't absolute URIs
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UriError {
+    /// missing or invalid scheme <https://datatracker.ietf.org/doc/html/rfc3986#section-3.1>
+    #[error("URI has no valid scheme")]
+    Scheme,
+    /// contains whitespace, control or non-ASCII characters
+    #[error("invalid character at {0} in URI")]
+    InvalidChar(usize),
+}
+
+/// An absolute URI
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uri(String);
+
+impl Uri {
+    /// check `uri` is an absolute URI
+    pub fn new(uri: impl Into<String>) -> Result<Self, UriError> {
+        let uri = uri.into();
+        if let Some(idx) = uri.bytes().position(|b| !b.is_ascii_graphic()) {
+            return Err(UriError::InvalidChar(idx));
+        }
+        let scheme = uri.split(':').next().unwrap_or_default();
+        let valid = uri.contains(':')
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'));
+        if !valid {
+            return Err(UriError::Scheme);
+        }
+        Ok(Self(uri))
+    }
+
+    /// the URI as sent on the wire
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// the scheme, e.g. `https`
+    pub fn scheme(&self) -> &str {
+        // `new` checked there is a scheme
+        self.0.split(':').next().unwrap_or_default()
+    }
+
+    /// `true` if a network boot client can fetch a boot file from the URI: a `tftp`,
+    /// `http` or `https` URL with a host and a path that doesn't end in `/`. For the v6
+    /// `OptBootfileUrl` (<https://datatracker.ietf.org/doc/html/rfc5970#section-3.1>),
+    /// and the v4 `BootfileName` sent to UEFI HTTP boot clients.
+    ///
+    /// ```rust
+    /// use dhcproto::Uri;
+    ///
+    /// let uri = "http://[2001:db8::1]/efi/boot.efi".parse::<Uri>()?;
+    /// assert!(uri.is_boot_file_url());
+    /// assert!(!"https://boot.example/efi/".parse::<Uri>()?.is_boot_file_url());
+    /// assert!(!"ftp://boot.example/boot.efi".parse::<Uri>()?.is_boot_file_url());
+    /// # Ok::<(), dhcproto::uri::UriError>(())
+    /// ```
+    pub fn is_boot_file_url(&self) -> bool {
+        let scheme = self.scheme();
+        if !["tftp", "http", "https"]
+            .iter()
+            .any(|s| scheme.eq_ignore_ascii_case(s))
+        {
+            return false;
+        }
+        let Some(rest) = self.0[scheme.len() + 1..].strip_prefix("//") else {
+            return false;
+        };
+        let Some((host, path)) = rest.split_once('/') else {
+            return false;
+        };
+        // ignore the query & fragment
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        !host.is_empty() && !path.is_empty() && !path.ends_with('/')
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Uri {
+    type Err = UriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Uri {
+    type Error = UriError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<Uri> for String {
+    fn from(uri: Uri) -> Self {
+        uri.0
+    }
+}
+
+impl AsRef<str> for Uri {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Uri {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Uri {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "url")]
+impl From<url::Url> for Uri {
+    fn from(url: url::Url) -> Self {
+        // `Url` is always absolute & serializes to ASCII
+        Self(url.into())
+    }
+}
+
+#[cfg(feature = "url")]
+impl TryFrom<&Uri> for url::Url {
+    type Error = url::ParseError;
+
+    fn try_from(uri: &Uri) -> Result<Self, Self::Error> {
+        uri.0.parse()
+    }
+}
+
+#[cfg(feature = "url")]
+impl TryFrom<Uri> for url::Url {
+    type Error = url::ParseError;
+
+    fn try_from(uri: Uri) -> Result<Self, Self::Error> {
+        Self::try_from(&uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri() {
+        let uri = Uri::new("https://foobar.com").unwrap();
+        // kept as sent, no normalization
+        assert_eq!(uri.as_str(), "https://foobar.com");
+        assert_eq!(uri.scheme(), "https");
+        assert!(Uri::new("urn:ietf:params:xml:ns:yang").is_ok());
+
+        assert_eq!(Uri::new("foobar.com"), Err(UriError::Scheme));
+        assert_eq!(Uri::new("1http://a"), Err(UriError::Scheme));
+        assert_eq!(Uri::new(":a"), Err(UriError::Scheme));
+        assert_eq!(Uri::new("https://a b"), Err(UriError::InvalidChar(9)));
+        assert_eq!(Uri::new("https://é"), Err(UriError::InvalidChar(8)));
+    }
+
+    #[test]
+    fn test_boot_file_url() {
+        let boot = |uri: &str| Uri::new(uri).unwrap().is_boot_file_url();
+        assert!(boot("tftp://10.0.0.1/pxelinux.0"));
+        assert!(boot("HTTPS://boot.example/a/b.efi?arch=7"));
+        assert!(!boot("http://boot.example"));
+        assert!(!boot("http:///boot.efi"));
+        assert!(!boot("http:boot.efi"));
+        assert!(!boot("http://boot.example/?arch=7"));
+        assert!(!boot("urn:ietf:params:xml:ns:yang"));
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_url() -> Result<(), url::ParseError> {
+        let url = url::Url::parse("https://foobar.com")?;
+        let uri = Uri::from(url.clone());
+        assert_eq!(uri.as_str(), "https://foobar.com/");
+        assert_eq!(url::Url::try_from(&uri)?, url);
+        Ok(())
+    }
+}
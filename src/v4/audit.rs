@@ -0,0 +1,300 @@
+//! Non-fatal problems in an encoded message
+//!
+//! Decoding is lenient, a packet with oddities still decodes and the oddities are lost.
+//! [`Message::audit`] walks the options area of the raw packet and reports them, along
+//! with reserved flag bits, for tools that debug DHCP traffic.
+//!
+//! ```rust
+//! use dhcproto::{v4::{self, audit::Warning, OptionCode}, Encodable};
+//!
+//! let mut msg = v4::Message::default();
+//! msg.opts_mut()
+//!     .insert(v4::DhcpOption::MessageType(v4::MessageType::Discover));
+//! let mut raw = msg.to_vec()?;
+//! // drop `End`
+//! raw.pop();
+//! // `TimeOffset` with 6 bytes instead of 4
+//! raw.extend([2, 6, 0, 0, 0, 1, 0, 0, 255]);
+//!
+//! assert_eq!(
+//!     v4::Message::audit(&raw)?,
+//!     [Warning::NonCanonicalLength {
+//!         code: OptionCode::TimeOffset,
+//!         len: 6,
+//!         canonical: 4
+//!     }]
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    decoder::Decodable,
+    encoder::Encodable,
+    error::DecodeResult,
+    v4::{DhcpOption, Message, OptionCode, UnknownOption, MIN_PACKET_SIZE},
+};
+
+// start of the options, after the magic cookie
+const VENDOR: usize = 240;
+// runs of `Pad` longer than this are more than word alignment needs
+const ALIGN: usize = 3;
+
+/// A problem found by [`Message::audit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Warning {
+    /// reserved bits are set in `flags`, they're kept when decoding & re-encoding. See
+    /// [`Flags::normalized`](crate::v4::Flags::normalized)
+    ReservedFlags {
+        /// the reserved bits that are set
+        bits: u16,
+    },
+    /// the option decoded but its length isn't the one it encodes to, extra bytes were
+    /// ignored
+    NonCanonicalLength {
+        /// option code
+        code: OptionCode,
+        /// length in the packet, summed over all parts of a split option
+        len: usize,
+        /// length of the decoded option when encoded
+        canonical: usize,
+    },
+    /// the option's data isn't valid. A lenient decoder skips it and decodes the options
+    /// after it, a strict decoder returns an error
+    Undecodable {
+        /// option code
+        code: OptionCode,
+        /// length in the packet
+        len: usize,
+    },
+    /// the option's length runs past the end of the packet
+    Truncated {
+        /// option code
+        code: OptionCode,
+    },
+    /// the option appears again, not directly after itself, only the last one is kept
+    Duplicate {
+        /// option code
+        code: OptionCode,
+    },
+    /// there is no `End` option
+    MissingEnd,
+    /// non-zero bytes after `End`
+    TrailingBytes {
+        /// number of bytes after `End`
+        len: usize,
+    },
+    /// `Pad` beyond what word alignment needs, or zeros after `End` beyond the minimum
+    /// packet size
+    ExcessivePadding {
+        /// number of padding bytes
+        len: usize,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::ReservedFlags { bits } => write!(f, "reserved flag bits {bits:#06x} set"),
+            Warning::NonCanonicalLength {
+                code,
+                len,
+                canonical,
+            } => write!(f, "{code:?} has length {len}, expected {canonical}"),
+            Warning::Undecodable { code, len } => {
+                write!(f, "{code:?} with length {len} can't be decoded")
+            }
+            Warning::Truncated { code } => write!(f, "{code:?} runs past the end of the packet"),
+            Warning::Duplicate { code } => write!(f, "{code:?} appears more than once"),
+            Warning::MissingEnd => f.write_str("options aren't terminated by End"),
+            Warning::TrailingBytes { len } => write!(f, "{len} bytes after End"),
+            Warning::ExcessivePadding { len } => write!(f, "{len} bytes of excess padding"),
+        }
+    }
+}
+
+impl Message {
+    /// Report non-fatal problems in the flags & options of the encoded message `bytes`,
+    /// in the order they appear. The `sname` & `file` fields aren't checked for
+    /// overloaded options.
+    ///
+    /// Returns an error if the header can't be decoded, a message without the magic
+    /// cookie has no options and so no warnings.
+    pub fn audit(bytes: &[u8]) -> DecodeResult<Vec<Warning>> {
+        let msg = Message::from_bytes(bytes)?;
+        let mut warnings = Vec::new();
+        if !msg.flags().is_valid() {
+            warnings.push(Warning::ReservedFlags {
+                bits: msg.flags().reserved(),
+            });
+        }
+        if !msg.is_rfc2132_compliant() {
+            return Ok(warnings);
+        }
+        let mut seen = HashSet::new();
+        let mut padding = 0;
+        let mut pos = VENDOR;
+        let end = loop {
+            let Some(&code) = bytes.get(pos) else {
+                warnings.push(Warning::MissingEnd);
+                break None;
+            };
+            match code {
+                0 => {
+                    let run = bytes[pos..].iter().take_while(|b| **b == 0).count();
+                    padding += run.saturating_sub(ALIGN);
+                    pos += run;
+                    continue;
+                }
+                255 => break Some(pos + 1),
+                _ => {}
+            }
+            // join the parts of a split option, RFC 3396
+            let mut data = Vec::new();
+            let mut truncated = false;
+            while bytes.get(pos) == Some(&code) {
+                let Some(&len) = bytes.get(pos + 1) else {
+                    truncated = true;
+                    break;
+                };
+                let Some(part) = bytes.get(pos + 2..pos + 2 + len as usize) else {
+                    truncated = true;
+                    break;
+                };
+                data.extend_from_slice(part);
+                pos += 2 + len as usize;
+            }
+            let code = OptionCode::from(code);
+            if truncated {
+                warnings.push(Warning::Truncated { code });
+                break None;
+            }
+            if !seen.insert(code) {
+                warnings.push(Warning::Duplicate { code });
+            }
+            if !code.is_known() {
+                continue;
+            }
+            let len = data.len();
+            match DhcpOption::Unknown(UnknownOption::new(code, data)).into_known() {
+                DhcpOption::Unknown(_) => {
+                    warnings.push(Warning::Undecodable { code, len });
+                }
+                opt => {
+                    let canonical = opt.to_vec().map(|buf| value_len(&buf)).unwrap_or(len);
+                    if canonical != len {
+                        warnings.push(Warning::NonCanonicalLength {
+                            code,
+                            len,
+                            canonical,
+                        });
+                    }
+                }
+            }
+        };
+        if let Some(end) = end {
+            let rest = &bytes[end..];
+            if rest.iter().any(|b| *b != 0) {
+                warnings.push(Warning::TrailingBytes { len: rest.len() });
+            } else {
+                padding += bytes.len().saturating_sub(MIN_PACKET_SIZE.max(end));
+            }
+        }
+        if padding > 0 {
+            warnings.push(Warning::ExcessivePadding { len: padding });
+        }
+        Ok(warnings)
+    }
+}
+
+// length of the value of an encoded option, summed over all its parts
+fn value_len(buf: &[u8]) -> usize {
+    let mut len = 0;
+    let mut pos = 0;
+    while let Some(&part) = buf.get(pos + 1) {
+        len += part as usize;
+        pos += 2 + part as usize;
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::MessageType;
+
+    fn raw() -> Vec<u8> {
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        let mut raw = msg.to_vec().unwrap();
+        // drop `End`
+        raw.pop();
+        raw
+    }
+
+    #[test]
+    fn test_audit_clean() {
+        let mut raw = raw();
+        raw.push(255);
+        assert_eq!(Message::audit(&raw).unwrap(), []);
+        // padded to the minimum size & aligned
+        raw.pop();
+        raw.extend([0, 0, 0, 255]);
+        raw.resize(MIN_PACKET_SIZE, 0);
+        assert_eq!(Message::audit(&raw).unwrap(), []);
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut raw = raw();
+        // SubnetMask is 4 bytes
+        raw.extend([1, 5, 255, 255, 255, 0, 0]);
+        // MessageType again
+        raw.extend([53, 1, 1]);
+        raw.extend([0; 6]);
+        raw.push(255);
+        raw.extend([1, 2]);
+        assert_eq!(
+            Message::audit(&raw).unwrap(),
+            [
+                Warning::Undecodable {
+                    code: OptionCode::SubnetMask,
+                    len: 5
+                },
+                Warning::Duplicate {
+                    code: OptionCode::MessageType
+                },
+                Warning::TrailingBytes { len: 2 },
+                Warning::ExcessivePadding { len: 3 },
+            ]
+        );
+
+        let mut raw = self::raw();
+        raw.extend([12, 10, b'a']);
+        assert_eq!(
+            Message::audit(&raw).unwrap(),
+            [Warning::Truncated {
+                code: OptionCode::Hostname
+            }]
+        );
+        let mut raw = self::raw();
+        // flags
+        raw[10..12].copy_from_slice(&[0x80, 0x01]);
+        raw.push(255);
+        assert_eq!(
+            Message::audit(&raw).unwrap(),
+            [Warning::ReservedFlags { bits: 0x00_01 }]
+        );
+        let msg = Message::from_bytes(&raw).unwrap();
+        assert_eq!(msg.flags().bits(), 0x80_01);
+        assert_eq!(msg.to_vec().unwrap(), raw);
+
+        let mut raw = self::raw();
+        raw.extend([0; 400]);
+        assert_eq!(
+            Message::audit(&raw).unwrap(),
+            [Warning::MissingEnd, Warning::ExcessivePadding { len: 397 }]
+        );
+    }
+}
@@ -1,7 +1,41 @@
-use std::fmt;
+//! Bulk leasequery - <https://www.rfc-editor.org/rfc/rfc6926.html>
+//!
+//! [`BulkQuery`] builds a DHCPBULKLEASEQUERY and [`ReplyStream`] classifies the replies
+//! the server sends back for it. Bulk leasequery runs over TCP, framing the messages
+//! with a 2 byte length is left to the caller. [`Lease`] reads the lease in a
+//! DHCPLEASEACTIVE, which is also the reply to a single leasequery (RFC 4388), and
+//! [`group_by_client`] collects the leases of each client.
+//!
+//! ```rust
+//! use dhcproto::v4::{self, bulk_query::{BulkQuery, QueryType, ReplyStream, BulkReply}};
+//!
+//! let query = BulkQuery::new(QueryType::ClientId(vec![1, 2, 3]))
+//!     .query_start_time(1_600_000_000)
+//!     .build()?;
+//! let mut replies = ReplyStream::new(query.xid());
+//!
+//! let mut done = v4::Message::default();
+//! done.set_xid(query.xid())
+//!     .opts_mut()
+//!     .insert(v4::DhcpOption::MessageType(v4::MessageType::LeaseQueryDone));
+//! assert_eq!(replies.next_reply(&done), Ok(BulkReply::Done));
+//! assert!(replies.is_done());
+//! # Ok::<(), v4::bulk_query::QueryError>(())
+//! ```
+use std::{collections::HashMap, fmt, net::Ipv4Addr, time::SystemTime};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    client_key::ClientKey,
+    time,
+    v4::{
+        relay::{RelayAgentInformation, RelayCode, RelayInfo, UnknownInfo},
+        DhcpOption, HType, Message, MessageType, Opcode, OptionCode,
+    },
+};
 
 /// Lease query data source flags
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -49,20 +83,33 @@ impl From<DataSourceFlags> for u8 {
     }
 }
 
+/// Lease state in the BLQ DHCP state option (156)
+/// <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.7>
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum QueryState {
+    /// 1 - address is available to be leased
     Available,
+    /// 2 - address is leased to a client
     Active,
+    /// 3 - lease expired
     Expired,
+    /// 4 - client released the lease
     Release,
+    /// 5 - address was abandoned, e.g. after a DHCPDECLINE
     Abandoned,
+    /// 6 - lease was reset by an administrator
     Reset,
+    /// 7 - address is managed by a failover peer
     Remote,
+    /// 8 - lease is moving between states
     Transitioning,
+    /// unknown state
     Unknown(u8),
 }
 
+display_unknown!(QueryState);
+
 impl From<u8> for QueryState {
     fn from(n: u8) -> Self {
         use QueryState::*;
@@ -97,17 +144,27 @@ impl From<QueryState> for u8 {
     }
 }
 
+/// Status code in the BLQ status-code option (151)
+/// <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.2>
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Code {
+    /// 0 - success
     Success,
+    /// 1 - failure, for reasons not covered by other codes
     UnspecFail,
+    /// 2 - query was terminated by the server
     QueryTerminated,
+    /// 3 - query was not understood
     MalformedQuery,
+    /// 4 - requester is not allowed to query
     NotAllowed,
+    /// unknown code
     Unknown(u8),
 }
 
+display_unknown!(Code);
+
 impl From<u8> for Code {
     fn from(n: u8) -> Self {
         use Code::*;
@@ -136,6 +193,370 @@ impl From<Code> for u8 {
     }
 }
 
+/// What a bulk leasequery asks for
+/// <https://www.rfc-editor.org/rfc/rfc6926.html#section-7.2>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryType {
+    /// leases of a hardware address, in `htype` & `chaddr`
+    MacAddress {
+        /// hardware type
+        htype: HType,
+        /// hardware address, up to 16 bytes
+        chaddr: Vec<u8>,
+    },
+    /// leases of a client identifier (option 61)
+    ClientId(Vec<u8>),
+    /// leases received through a relay with this relay-id, relay agent sub-option 12
+    /// <https://www.rfc-editor.org/rfc/rfc6925.html>
+    RelayId(Vec<u8>),
+    /// leases with this remote-id, relay agent sub-option 2
+    RemoteId(Vec<u8>),
+    /// every lease the server has
+    All,
+}
+
+/// Builds a DHCPBULKLEASEQUERY message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkQuery {
+    query: QueryType,
+    xid: u32,
+    start: Option<u32>,
+    end: Option<u32>,
+    params: Vec<OptionCode>,
+}
+
+impl BulkQuery {
+    /// a query with a random xid
+    pub fn new(query: QueryType) -> Self {
+        Self {
+            query,
+            xid: rand::random(),
+            start: None,
+            end: None,
+            params: Vec::new(),
+        }
+    }
+
+    /// set the xid, replies are matched to the query by it
+    pub fn xid(mut self, xid: u32) -> Self {
+        self.xid = xid;
+        self
+    }
+
+    /// only return leases changed at or after `secs` since the epoch
+    pub fn query_start_time(mut self, secs: u32) -> Self {
+        self.start = Some(secs);
+        self
+    }
+
+    /// only return leases changed at or before `secs` since the epoch
+    pub fn query_end_time(mut self, secs: u32) -> Self {
+        self.end = Some(secs);
+        self
+    }
+
+    /// only return leases changed at or after `time`, see [`time::to_epoch_secs`]
+    pub fn query_start(self, time: SystemTime) -> Self {
+        self.query_start_time(time::to_epoch_secs(time))
+    }
+
+    /// only return leases changed at or before `time`, see [`time::to_epoch_secs`]
+    pub fn query_end(self, time: SystemTime) -> Self {
+        self.query_end_time(time::to_epoch_secs(time))
+    }
+
+    /// options to return for each lease, sent in `ParameterRequestList`
+    pub fn parameters(mut self, params: Vec<OptionCode>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// build the query, an error if a [`QueryType::MacAddress`] `chaddr` doesn't fit in
+    /// the 16 byte field
+    pub fn build(&self) -> Result<Message, QueryError> {
+        let mut msg = Message::default();
+        msg.set_opcode(Opcode::BootRequest).set_xid(self.xid);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::MessageType(MessageType::BulkLeaseQuery));
+        match &self.query {
+            QueryType::MacAddress { htype, chaddr } => {
+                if chaddr.len() > 16 {
+                    return Err(QueryError::ChaddrTooLong(chaddr.len()));
+                }
+                msg.set_htype(*htype).set_chaddr(chaddr);
+            }
+            QueryType::ClientId(id) => {
+                opts.insert(DhcpOption::ClientIdentifier(id.clone()));
+            }
+            QueryType::RelayId(id) => {
+                let mut info = RelayAgentInformation::default();
+                info.insert(RelayInfo::Unknown(UnknownInfo::new(
+                    RelayCode::Unknown(12),
+                    id.clone(),
+                )));
+                opts.insert(DhcpOption::RelayAgentInformation(info));
+            }
+            QueryType::RemoteId(id) => {
+                let mut info = RelayAgentInformation::default();
+                info.insert(RelayInfo::AgentRemoteId(id.clone()));
+                opts.insert(DhcpOption::RelayAgentInformation(info));
+            }
+            QueryType::All => {}
+        }
+        let opts = msg.opts_mut();
+        if let Some(start) = self.start {
+            opts.insert(DhcpOption::BulkLeaseQueryQueryStartTime(start));
+        }
+        if let Some(end) = self.end {
+            opts.insert(DhcpOption::BulkLeaseQueryQueryEndTime(end));
+        }
+        if !self.params.is_empty() {
+            opts.insert(DhcpOption::ParameterRequestList(self.params.clone()));
+        }
+        Ok(msg)
+    }
+}
+
+/// Returned when a [`BulkQuery`] can't be built
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryError {
+    /// a [`QueryType::MacAddress`] `chaddr` is longer than the 16 byte field
+    #[error("chaddr of {0} bytes is longer than 16")]
+    ChaddrTooLong(usize),
+}
+
+/// A lease in a DHCPLEASEACTIVE or DHCPLEASEUNASSIGNED reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease<'a> {
+    /// the leased address, `ciaddr` of the reply
+    pub addr: Ipv4Addr,
+    /// lease state, if the server sent it
+    pub state: Option<QueryState>,
+    /// the server's time when it sent the reply, in seconds since the epoch
+    pub base_time: Option<u32>,
+    /// when the lease entered its current state, in seconds since the epoch. Needs
+    /// the base time
+    pub state_start: Option<u32>,
+    /// when the client last talked to the server, in seconds since the epoch. Needs the
+    /// base time
+    pub last_transaction: Option<u32>,
+    /// the client holding the lease, `None` if the reply doesn't identify it
+    pub client: Option<ClientKey>,
+    /// other addresses leased to the client, from `AssociatedIp` (92)
+    pub associated_ips: &'a [Ipv4Addr],
+    /// relay agent information the server stored with the lease
+    pub relay_info: Option<&'a RelayAgentInformation>,
+    /// the reply
+    pub msg: &'a Message,
+}
+
+impl<'a> Lease<'a> {
+    /// the lease in `msg`, `None` if it isn't a DHCPLEASEACTIVE or DHCPLEASEUNASSIGNED
+    /// <https://www.rfc-editor.org/rfc/rfc4388.html#section-6.4.2>
+    pub fn from_reply(msg: &'a Message) -> Option<Self> {
+        match msg.opts().msg_type() {
+            Some(MessageType::LeaseActive | MessageType::LeaseUnassigned) => Some(Self::new(msg)),
+            _ => None,
+        }
+    }
+
+    fn new(msg: &'a Message) -> Self {
+        let opts = msg.opts();
+        let base_time = match opts.get(OptionCode::BulkLeaseQueryBaseTime) {
+            Some(DhcpOption::BulkLeaseQueryBaseTime(t)) => Some(*t),
+            _ => None,
+        };
+        // times are sent in seconds before the base time
+        let before_base = |code| match opts.get(code) {
+            Some(
+                DhcpOption::BulkLeasQueryStartTimeOfState(secs)
+                | DhcpOption::ClientLastTransactionTime(secs),
+            ) => base_time?.checked_sub(*secs),
+            _ => None,
+        };
+        Self {
+            addr: msg.ciaddr(),
+            state: match opts.get(OptionCode::BulkLeaseQueryDhcpState) {
+                Some(DhcpOption::BulkLeaseQueryDhcpState(state)) => Some(*state),
+                _ => None,
+            },
+            base_time,
+            state_start: before_base(OptionCode::BulkLeasQueryStartTimeOfState),
+            last_transaction: before_base(OptionCode::ClientLastTransactionTime),
+            client: ClientKey::from_v4(msg),
+            associated_ips: match opts.get(OptionCode::AssociatedIp) {
+                Some(DhcpOption::AssociatedIp(ips)) => ips,
+                _ => &[],
+            },
+            relay_info: match opts.get(OptionCode::RelayAgentInformation) {
+                Some(DhcpOption::RelayAgentInformation(info)) => Some(info),
+                _ => None,
+            },
+            msg,
+        }
+    }
+
+    /// the leased address followed by the associated addresses, without duplicates
+    pub fn addrs(&self) -> impl Iterator<Item = Ipv4Addr> + '_ {
+        let addr = self.addr;
+        std::iter::once(addr).chain(
+            self.associated_ips
+                .iter()
+                .copied()
+                .filter(move |ip| *ip != addr),
+        )
+    }
+
+    /// [`Lease::base_time`] as a `SystemTime`
+    pub fn base_system_time(&self) -> Option<SystemTime> {
+        self.base_time.map(time::from_epoch_secs)
+    }
+
+    /// [`Lease::state_start`] as a `SystemTime`
+    pub fn state_start_time(&self) -> Option<SystemTime> {
+        self.state_start.map(time::from_epoch_secs)
+    }
+
+    /// [`Lease::last_transaction`] as a `SystemTime`
+    pub fn last_transaction_time(&self) -> Option<SystemTime> {
+        self.last_transaction.map(time::from_epoch_secs)
+    }
+}
+
+/// Group the DHCPLEASEACTIVE replies in `replies` by client, in the order each client
+/// was first seen. Other messages, and leases without a client, are skipped, check
+/// them with a [`ReplyStream`].
+///
+/// ```rust
+/// use dhcproto::{client_key::ClientKey, v4::{self, bulk_query}};
+///
+/// let lease = |addr: [u8; 4], chaddr: &[u8]| {
+///     let mut msg = v4::Message::default();
+///     msg.set_ciaddr(addr).set_chaddr(chaddr);
+///     msg.opts_mut()
+///         .insert(v4::DhcpOption::MessageType(v4::MessageType::LeaseActive));
+///     msg
+/// };
+/// let replies = [
+///     lease([10, 0, 0, 1], &[1; 6]),
+///     lease([10, 0, 0, 2], &[2; 6]),
+///     lease([10, 0, 0, 3], &[1; 6]),
+/// ];
+/// let clients = bulk_query::group_by_client(&replies);
+/// assert_eq!(clients.len(), 2);
+/// assert_eq!(
+///     clients[0].0,
+///     ClientKey::HwAddr {
+///         htype: v4::HType::Eth,
+///         chaddr: vec![1; 6],
+///         giaddr: [0, 0, 0, 0].into(),
+///     }
+/// );
+/// assert_eq!(clients[0].1.len(), 2);
+/// ```
+pub fn group_by_client<'a, I>(replies: I) -> Vec<(ClientKey, Vec<Lease<'a>>)>
+where
+    I: IntoIterator<Item = &'a Message>,
+{
+    let mut clients: Vec<(ClientKey, Vec<Lease<'a>>)> = Vec::new();
+    let mut index = HashMap::new();
+    let active = replies
+        .into_iter()
+        .filter(|msg| msg.opts().msg_type() == Some(MessageType::LeaseActive));
+    for lease in active.map(Lease::new) {
+        let Some(client) = lease.client.clone() else {
+            continue;
+        };
+        let i = *index.entry(client.clone()).or_insert_with(|| {
+            clients.push((client, Vec::new()));
+            clients.len() - 1
+        });
+        clients[i].1.push(lease);
+    }
+    clients
+}
+
+/// A reply to a bulk leasequery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkReply<'a> {
+    /// DHCPLEASEACTIVE - an active lease
+    Active(Lease<'a>),
+    /// DHCPLEASEUNASSIGNED - an address that isn't leased
+    Unassigned(Lease<'a>),
+    /// DHCPLEASEQUERYDONE - all leases were sent, the query is finished
+    Done,
+    /// DHCPLEASEQUERYSTATUS - the query failed, it's finished
+    Status(Code, &'a str),
+}
+
+/// Returned for replies that don't belong in the reply stream
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyError {
+    /// the reply isn't for this query
+    #[error("reply xid {got:#x} doesn't match the query xid {expected:#x}")]
+    XidMismatch {
+        /// xid of the query
+        expected: u32,
+        /// xid of the reply
+        got: u32,
+    },
+    /// not a bulk leasequery reply
+    #[error("unexpected message type {0:?} in a bulk leasequery reply")]
+    UnexpectedMessage(Option<MessageType>),
+    /// the query already finished
+    #[error("reply received after the query finished")]
+    Finished,
+}
+
+/// Classifies the replies to a bulk leasequery, in the order they're received
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplyStream {
+    xid: u32,
+    done: bool,
+}
+
+impl ReplyStream {
+    /// replies to the query with `xid`
+    pub fn new(xid: u32) -> Self {
+        Self { xid, done: false }
+    }
+
+    /// `true` once a DHCPLEASEQUERYDONE or DHCPLEASEQUERYSTATUS was received
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// classify the next reply
+    /// <https://www.rfc-editor.org/rfc/rfc6926.html#section-7.7>
+    pub fn next_reply<'a>(&mut self, msg: &'a Message) -> Result<BulkReply<'a>, ReplyError> {
+        if self.done {
+            return Err(ReplyError::Finished);
+        }
+        if msg.xid() != self.xid {
+            return Err(ReplyError::XidMismatch {
+                expected: self.xid,
+                got: msg.xid(),
+            });
+        }
+        let reply = match msg.opts().msg_type() {
+            Some(MessageType::LeaseActive) => BulkReply::Active(Lease::new(msg)),
+            Some(MessageType::LeaseUnassigned) => BulkReply::Unassigned(Lease::new(msg)),
+            Some(MessageType::LeaseQueryDone) => BulkReply::Done,
+            Some(MessageType::LeaseQueryStatus) => {
+                match msg.opts().get(OptionCode::BulkLeaseQueryStatusCode) {
+                    Some(DhcpOption::BulkLeaseQueryStatusCode(code, text)) => {
+                        BulkReply::Status(*code, text)
+                    }
+                    _ => BulkReply::Status(Code::UnspecFail, ""),
+                }
+            }
+            msg_type => return Err(ReplyError::UnexpectedMessage(msg_type)),
+        };
+        self.done = matches!(reply, BulkReply::Done | BulkReply::Status(..));
+        Ok(reply)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,4 +572,172 @@ mod tests {
         let flag = DataSourceFlags::new(0x80).set_remote();
         assert_eq!(flag.0, 0x81);
     }
+
+    #[test]
+    fn test_bulk_query() {
+        let query = BulkQuery::new(QueryType::MacAddress {
+            htype: HType::Eth,
+            chaddr: vec![1, 2, 3, 4, 5, 6],
+        })
+        .xid(7)
+        .query_start_time(100)
+        .query_end_time(200)
+        .parameters(vec![OptionCode::Hostname])
+        .build()
+        .unwrap();
+        assert_eq!(query.xid(), 7);
+        assert_eq!(query.chaddr(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(query.opts().msg_type(), Some(MessageType::BulkLeaseQuery));
+        assert_eq!(query.opts().len(), 4);
+
+        let query = BulkQuery::new(QueryType::RelayId(vec![9])).build().unwrap();
+        let Some(DhcpOption::RelayAgentInformation(info)) =
+            query.opts().get(OptionCode::RelayAgentInformation)
+        else {
+            panic!("missing relay agent information");
+        };
+        assert!(info.get(RelayCode::Unknown(12)).is_some());
+        let query = BulkQuery::new(QueryType::RemoteId(vec![9]))
+            .build()
+            .unwrap();
+        assert!(query
+            .opts()
+            .get(OptionCode::RelayAgentInformation)
+            .is_some());
+        let query = BulkQuery::new(QueryType::ClientId(vec![9]))
+            .build()
+            .unwrap();
+        assert!(query.opts().get(OptionCode::ClientIdentifier).is_some());
+        let query = BulkQuery::new(QueryType::All)
+            .query_start(time::from_epoch_secs(100))
+            .build()
+            .unwrap();
+        let start = query.opts().get(OptionCode::BulkLeaseQueryQueryStartTime);
+        assert_eq!(
+            start.and_then(DhcpOption::as_system_time),
+            Some(time::from_epoch_secs(100))
+        );
+        let query = BulkQuery::new(QueryType::All).build().unwrap();
+        assert_eq!(query.opts().len(), 1);
+        assert!(query.chaddr().is_empty());
+
+        let query = BulkQuery::new(QueryType::MacAddress {
+            htype: HType::Eth,
+            chaddr: vec![0; 17],
+        });
+        assert_eq!(query.build(), Err(QueryError::ChaddrTooLong(17)));
+    }
+
+    #[test]
+    fn test_reply_stream() {
+        let reply = |msg_type| {
+            let mut msg = Message::default();
+            msg.set_xid(7)
+                .opts_mut()
+                .insert(DhcpOption::MessageType(msg_type));
+            msg
+        };
+        let mut stream = ReplyStream::new(7);
+
+        let mut active = reply(MessageType::LeaseActive);
+        active.set_ciaddr([10, 0, 0, 1]);
+        let opts = active.opts_mut();
+        opts.insert(DhcpOption::BulkLeaseQueryBaseTime(1000));
+        opts.insert(DhcpOption::BulkLeasQueryStartTimeOfState(100));
+        opts.insert(DhcpOption::ClientLastTransactionTime(10));
+        opts.insert(DhcpOption::BulkLeaseQueryDhcpState(QueryState::Active));
+        assert_eq!(
+            stream.next_reply(&active),
+            Ok(BulkReply::Active(Lease {
+                addr: [10, 0, 0, 1].into(),
+                state: Some(QueryState::Active),
+                base_time: Some(1000),
+                state_start: Some(900),
+                last_transaction: Some(990),
+                client: None,
+                associated_ips: &[],
+                relay_info: None,
+                msg: &active,
+            }))
+        );
+        let Ok(BulkReply::Active(lease)) = stream.next_reply(&active) else {
+            panic!("expected active");
+        };
+        assert_eq!(lease.state_start_time(), Some(time::from_epoch_secs(900)));
+        let unassigned = reply(MessageType::LeaseUnassigned);
+        let Ok(BulkReply::Unassigned(lease)) = stream.next_reply(&unassigned) else {
+            panic!("expected unassigned");
+        };
+        assert_eq!((lease.base_time, lease.state_start), (None, None));
+
+        assert!(Lease::from_reply(&unassigned).is_some());
+        assert!(Lease::from_reply(&reply(MessageType::LeaseQueryDone)).is_none());
+
+        let mut other = reply(MessageType::Ack);
+        assert_eq!(
+            stream.next_reply(&other),
+            Err(ReplyError::UnexpectedMessage(Some(MessageType::Ack)))
+        );
+        other.set_xid(8);
+        assert!(matches!(
+            stream.next_reply(&other),
+            Err(ReplyError::XidMismatch {
+                expected: 7,
+                got: 8
+            })
+        ));
+        assert!(!stream.is_done());
+
+        let mut status = reply(MessageType::LeaseQueryStatus);
+        status
+            .opts_mut()
+            .insert(DhcpOption::BulkLeaseQueryStatusCode(
+                Code::NotAllowed,
+                "denied".into(),
+            ));
+        assert_eq!(
+            stream.next_reply(&status),
+            Ok(BulkReply::Status(Code::NotAllowed, "denied"))
+        );
+        assert!(stream.is_done());
+        assert_eq!(
+            stream.next_reply(&reply(MessageType::LeaseQueryDone)),
+            Err(ReplyError::Finished)
+        );
+    }
+
+    #[test]
+    fn test_lease_client() {
+        let mut msg = Message::default();
+        msg.set_ciaddr([10, 0, 0, 1]).set_chaddr(&[1; 6]);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::MessageType(MessageType::LeaseActive));
+        opts.insert(DhcpOption::ClientIdentifier(vec![9]));
+        opts.insert(DhcpOption::AssociatedIp(vec![
+            [10, 0, 0, 2].into(),
+            [10, 0, 0, 1].into(),
+        ]));
+        let mut info = RelayAgentInformation::default();
+        info.insert(RelayInfo::AgentRemoteId(vec![7]));
+        opts.insert(DhcpOption::RelayAgentInformation(info.clone()));
+
+        let lease = Lease::from_reply(&msg).unwrap();
+        assert_eq!(lease.client, Some(ClientKey::ClientId(vec![9])));
+        assert_eq!(lease.relay_info, Some(&info));
+        assert_eq!(lease.last_transaction, None);
+        assert_eq!(
+            lease.addrs().collect::<Vec<_>>(),
+            [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)]
+        );
+
+        // same client id, different chaddr
+        let mut other = msg.clone();
+        other.set_chaddr(&[2; 6]).set_ciaddr([10, 0, 0, 3]);
+        let mut done = Message::default();
+        done.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::LeaseQueryDone));
+        let clients = group_by_client([&msg, &done, &other]);
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].1[1].addr, Ipv4Addr::new(10, 0, 0, 3));
+    }
 }
@@ -0,0 +1,336 @@
+//! Building client messages from a server's DHCPACK, and checking the DHCPACK itself
+use std::{fmt, net::Ipv4Addr};
+
+use crate::v4::{DhcpOption, Message, MessageType, Opcode, OptionCode};
+
+impl Message {
+    /// Build a DHCPDECLINE for the address in `ack`, after the client found it's already
+    /// in use (e.g. with ARP). <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.1>
+    ///
+    /// Per RFC 2131 table 5 the message has a new xid, the hardware address of the ack and
+    /// all other header fields zeroed. `RequestedIpAddress` is set to the ack's `yiaddr`,
+    /// `ServerIdentifier` is copied and `ClientIdentifier` is copied when the ack echoed
+    /// it. No other options are included.
+    ///
+    /// Returns `None` if `ack` isn't a DHCPACK, has no `yiaddr` or no `ServerIdentifier`.
+    ///
+    /// ```rust
+    /// use dhcproto::v4::{self, DhcpOption, OptionCode};
+    ///
+    /// let mut ack = v4::Message::default();
+    /// ack.set_opcode(v4::Opcode::BootReply)
+    ///     .set_yiaddr([192, 168, 0, 10])
+    ///     .set_chaddr(&[1, 2, 3, 4, 5, 6]);
+    /// ack.opts_mut().insert(DhcpOption::MessageType(v4::MessageType::Ack));
+    /// ack.opts_mut().insert(DhcpOption::ServerIdentifier([192, 168, 0, 1].into()));
+    ///
+    /// let decline = v4::Message::decline(&ack).unwrap();
+    /// assert_eq!(decline.opts().msg_type(), Some(v4::MessageType::Decline));
+    /// assert_eq!(
+    ///     decline.opts().get(OptionCode::RequestedIpAddress),
+    ///     Some(&DhcpOption::RequestedIpAddress([192, 168, 0, 10].into()))
+    /// );
+    /// ```
+    pub fn decline(ack: &Message) -> Option<Message> {
+        let mut msg = from_ack(ack, MessageType::Decline)?;
+        msg.opts_mut()
+            .insert(DhcpOption::RequestedIpAddress(ack.yiaddr()));
+        Some(msg)
+    }
+
+    /// Build a DHCPRELEASE for the lease in `ack`.
+    /// <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.6>
+    ///
+    /// Per RFC 2131 table 5 the message has a new xid, `ciaddr` set to the leased address
+    /// (the ack's `yiaddr`), the hardware address of the ack and all other header fields
+    /// zeroed. `ServerIdentifier` is copied and `ClientIdentifier` is copied when the ack
+    /// echoed it. No other options are included.
+    ///
+    /// Returns `None` if `ack` isn't a DHCPACK, has no `yiaddr` or no `ServerIdentifier`.
+    pub fn release_from(ack: &Message) -> Option<Message> {
+        let mut msg = from_ack(ack, MessageType::Release)?;
+        msg.set_ciaddr(ack.yiaddr());
+        Some(msg)
+    }
+}
+
+// a client message for the lease in `ack`, with the options common to DHCPDECLINE &
+// DHCPRELEASE
+fn from_ack(ack: &Message, msg_type: MessageType) -> Option<Message> {
+    let opts = ack.opts();
+    if opts.msg_type() != Some(MessageType::Ack) || ack.yiaddr().is_unspecified() {
+        return None;
+    }
+    let server_id = opts.get(OptionCode::ServerIdentifier)?;
+
+    let mut msg = Message::default();
+    msg.set_opcode(Opcode::BootRequest)
+        .set_htype(ack.htype())
+        .set_chaddr(ack.chaddr());
+    let client_opts = msg.opts_mut();
+    client_opts.insert(DhcpOption::MessageType(msg_type));
+    client_opts.insert(server_id.clone());
+    if let Some(client_id) = opts.get(OptionCode::ClientIdentifier) {
+        client_opts.insert(client_id.clone());
+    }
+    Some(msg)
+}
+
+/// A way a DHCPACK doesn't match the request it answers, see [`verify_ack`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Discrepancy {
+    /// the reply isn't a DHCPACK, it has this message type
+    NotAck(Option<MessageType>),
+    /// the xid isn't the request's
+    XidMismatch {
+        /// the request's xid
+        expected: u32,
+        /// the reply's xid
+        got: u32,
+    },
+    /// `yiaddr` isn't the address the client asked for, in `RequestedIpAddress` or
+    /// `ciaddr`
+    AddressMismatch {
+        /// the address asked for
+        requested: Ipv4Addr,
+        /// the reply's `yiaddr`
+        got: Ipv4Addr,
+    },
+    /// `ServerIdentifier` is missing or isn't the server of the offer the client selected
+    ServerIdMismatch {
+        /// the server the request was sent to, `None` when renewing or rebinding
+        expected: Option<Ipv4Addr>,
+        /// the reply's server
+        got: Option<Ipv4Addr>,
+    },
+    /// there is no `AddressLeaseTime`
+    MissingLeaseTime,
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::NotAck(msg_type) => write!(f, "reply is {msg_type:?}, not an Ack"),
+            Discrepancy::XidMismatch { expected, got } => {
+                write!(f, "xid {got:#x} doesn't match request xid {expected:#x}")
+            }
+            Discrepancy::AddressMismatch { requested, got } => {
+                write!(f, "yiaddr {got} isn't the requested address {requested}")
+            }
+            Discrepancy::ServerIdMismatch { expected, got } => {
+                write!(f, "server identifier {got:?} doesn't match {expected:?}")
+            }
+            Discrepancy::MissingLeaseTime => f.write_str("no lease time"),
+        }
+    }
+}
+
+/// Check a DHCPACK against the DHCPREQUEST or DHCPINFORM it answers, returning every
+/// discrepancy found, none if the ack can be used.
+/// <https://datatracker.ietf.org/doc/html/rfc2131#section-4.4.1>
+///
+/// * the xid matches
+/// * `yiaddr` is the address asked for, in `RequestedIpAddress` (selecting or
+///   init-reboot) or `ciaddr` (renewing or rebinding)
+/// * `ServerIdentifier` is present and, if the request named one, it's the selected
+///   server
+/// * `AddressLeaseTime` is present
+///
+/// The address & lease time aren't checked in the answer to a DHCPINFORM.
+///
+/// ```rust
+/// use dhcproto::v4::{self, client::{verify_ack, Discrepancy}, DhcpOption};
+///
+/// let mut request = v4::Message::default();
+/// request.opts_mut()
+///     .insert(DhcpOption::MessageType(v4::MessageType::Request));
+/// request.opts_mut()
+///     .insert(DhcpOption::RequestedIpAddress([192, 168, 0, 10].into()));
+///
+/// let mut ack = request.derive_reply();
+/// ack.set_yiaddr([192, 168, 0, 11]);
+/// ack.opts_mut().insert(DhcpOption::MessageType(v4::MessageType::Ack));
+/// ack.opts_mut().insert(DhcpOption::ServerIdentifier([192, 168, 0, 1].into()));
+/// ack.opts_mut().insert(DhcpOption::AddressLeaseTime(3600.into()));
+///
+/// assert_eq!(
+///     verify_ack(&request, &ack),
+///     [Discrepancy::AddressMismatch {
+///         requested: [192, 168, 0, 10].into(),
+///         got: [192, 168, 0, 11].into()
+///     }]
+/// );
+/// ```
+pub fn verify_ack(request: &Message, ack: &Message) -> Vec<Discrepancy> {
+    let (req_opts, opts) = (request.opts(), ack.opts());
+    let mut found = Vec::new();
+    if opts.msg_type() != Some(MessageType::Ack) {
+        found.push(Discrepancy::NotAck(opts.msg_type()));
+    }
+    if ack.xid() != request.xid() {
+        found.push(Discrepancy::XidMismatch {
+            expected: request.xid(),
+            got: ack.xid(),
+        });
+    }
+    let server_id = |opts: &crate::v4::DhcpOptions| match opts.get(OptionCode::ServerIdentifier) {
+        Some(DhcpOption::ServerIdentifier(id)) => Some(*id),
+        _ => None,
+    };
+    let (expected, got) = (server_id(req_opts), server_id(opts));
+    if got.is_none() || (expected.is_some() && expected != got) {
+        found.push(Discrepancy::ServerIdMismatch { expected, got });
+    }
+    if req_opts.has_msg_type(MessageType::Inform) {
+        return found;
+    }
+    let requested = match req_opts.get(OptionCode::RequestedIpAddress) {
+        Some(DhcpOption::RequestedIpAddress(addr)) => *addr,
+        _ => request.ciaddr(),
+    };
+    if !requested.is_unspecified() && ack.yiaddr() != requested {
+        found.push(Discrepancy::AddressMismatch {
+            requested,
+            got: ack.yiaddr(),
+        });
+    }
+    if opts.get(OptionCode::AddressLeaseTime).is_none() {
+        found.push(Discrepancy::MissingLeaseTime);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::v4::Flags;
+
+    #[test]
+    fn test_decline() {
+        let yiaddr = Ipv4Addr::new(192, 168, 0, 10);
+        let server = Ipv4Addr::new(192, 168, 0, 1);
+        let mut ack = Message::default();
+        ack.set_opcode(Opcode::BootReply)
+            .set_flags(Flags::default().set_broadcast())
+            .set_secs(5)
+            .set_ciaddr(yiaddr)
+            .set_giaddr([10, 0, 0, 1])
+            .set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        ack.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Ack));
+        ack.opts_mut()
+            .insert(DhcpOption::AddressLeaseTime(3600.into()));
+        ack.opts_mut().insert(DhcpOption::ServerIdentifier(server));
+        // no address
+        assert_eq!(Message::decline(&ack), None);
+        ack.set_yiaddr(yiaddr);
+
+        let decline = Message::decline(&ack).unwrap();
+        assert_eq!(decline.opcode(), Opcode::BootRequest);
+        assert_eq!(decline.chaddr(), ack.chaddr());
+        assert_eq!(decline.secs(), 0);
+        assert_eq!(decline.flags(), Flags::default());
+        for addr in [
+            decline.ciaddr(),
+            decline.yiaddr(),
+            decline.siaddr(),
+            decline.giaddr(),
+        ] {
+            assert!(addr.is_unspecified());
+        }
+        assert_eq!(decline.opts().len(), 3);
+        assert_eq!(
+            decline.opts().get(OptionCode::RequestedIpAddress),
+            Some(&DhcpOption::RequestedIpAddress(yiaddr))
+        );
+        assert_eq!(
+            decline.opts().get(OptionCode::ServerIdentifier),
+            Some(&DhcpOption::ServerIdentifier(server))
+        );
+
+        ack.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1, 2]));
+        let decline = Message::decline(&ack).unwrap();
+        assert_eq!(
+            decline.opts().get(OptionCode::ClientIdentifier),
+            Some(&DhcpOption::ClientIdentifier(vec![1, 2]))
+        );
+
+        let release = Message::release_from(&ack).unwrap();
+        assert_eq!(release.opts().msg_type(), Some(MessageType::Release));
+        assert_eq!(release.ciaddr(), yiaddr);
+        assert!(release.yiaddr().is_unspecified());
+        assert_eq!(release.chaddr(), ack.chaddr());
+        assert_eq!(release.opts().get(OptionCode::RequestedIpAddress), None);
+        assert_eq!(release.opts().len(), 3);
+
+        ack.opts_mut().remove(OptionCode::ServerIdentifier);
+        assert_eq!(Message::release_from(&ack), None);
+        assert_eq!(Message::decline(&ack), None);
+        ack.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Offer));
+        ack.opts_mut().insert(DhcpOption::ServerIdentifier(server));
+        assert_eq!(Message::decline(&ack), None);
+    }
+
+    #[test]
+    fn test_verify_ack() {
+        let server = Ipv4Addr::new(192, 168, 0, 1);
+        let mut request = Message::default();
+        request.set_ciaddr([192, 168, 0, 10]);
+        request
+            .opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+
+        let mut ack = request.derive_reply();
+        ack.set_yiaddr([192, 168, 0, 10]);
+        ack.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Ack));
+        ack.opts_mut().insert(DhcpOption::ServerIdentifier(server));
+        ack.opts_mut()
+            .insert(DhcpOption::AddressLeaseTime(3600.into()));
+        assert_eq!(verify_ack(&request, &ack), []);
+
+        // selecting, the request names another server
+        request
+            .opts_mut()
+            .insert(DhcpOption::ServerIdentifier([192, 168, 0, 2].into()));
+        ack.opts_mut().remove(OptionCode::AddressLeaseTime);
+        ack.set_xid(request.xid().wrapping_add(1));
+        assert_eq!(
+            verify_ack(&request, &ack),
+            [
+                Discrepancy::XidMismatch {
+                    expected: request.xid(),
+                    got: ack.xid()
+                },
+                Discrepancy::ServerIdMismatch {
+                    expected: Some([192, 168, 0, 2].into()),
+                    got: Some(server)
+                },
+                Discrepancy::MissingLeaseTime,
+            ]
+        );
+
+        // an inform has no address or lease time
+        let mut inform = Message::default();
+        inform
+            .opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Inform));
+        let mut ack = inform.derive_reply();
+        ack.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Nak));
+        assert_eq!(
+            verify_ack(&inform, &ack),
+            [
+                Discrepancy::NotAck(Some(MessageType::Nak)),
+                Discrepancy::ServerIdMismatch {
+                    expected: None,
+                    got: None
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Sets of option codes
+use std::fmt;
+
+use crate::v4::{DhcpOptions, OptionCode};
+
+/// A set of option codes, one bit per code, for allow and deny lists. Sets can be
+/// built in a `const` from the code numbers.
+///
+/// ```rust
+/// use dhcproto::v4::{DhcpOption, DhcpOptions, MessageType, OptionCode, OptionCodeSet};
+///
+/// // options a relay strips before forwarding
+/// const DENY: OptionCodeSet = OptionCodeSet::from_codes(&[82, 90]);
+///
+/// let mut opts = DhcpOptions::new();
+/// opts.insert(DhcpOption::MessageType(MessageType::Discover));
+/// opts.insert(DhcpOption::Hostname("host".into()));
+/// opts.insert(DhcpOption::RelayAgentInformation(Default::default()));
+/// opts.strip_codes(&DENY);
+/// assert!(opts.get(OptionCode::RelayAgentInformation).is_none());
+/// assert_eq!(opts.len(), 2);
+///
+/// let allow: OptionCodeSet = [OptionCode::MessageType].into_iter().collect();
+/// opts.retain_codes(&allow);
+/// assert_eq!(opts.len(), 1);
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OptionCodeSet([u64; 4]);
+
+impl OptionCodeSet {
+    /// an empty set
+    pub const fn new() -> Self {
+        Self([0; 4])
+    }
+
+    /// a set of the code numbers in `codes`
+    pub const fn from_codes(codes: &[u8]) -> Self {
+        let mut set = Self::new();
+        let mut i = 0;
+        while i < codes.len() {
+            set = set.with(codes[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// this set with the code number `code` added
+    pub const fn with(mut self, code: u8) -> Self {
+        self.0[code as usize / 64] |= 1 << (code % 64);
+        self
+    }
+
+    /// `true` if the set has the code number `code`
+    pub const fn contains_code(&self, code: u8) -> bool {
+        self.0[code as usize / 64] & (1 << (code % 64)) != 0
+    }
+
+    /// `true` if the set has `code`
+    pub fn contains(&self, code: OptionCode) -> bool {
+        self.contains_code(code.into())
+    }
+
+    /// add `code`, returning `true` if it wasn't in the set
+    pub fn insert(&mut self, code: OptionCode) -> bool {
+        let new = !self.contains(code);
+        *self = self.with(code.into());
+        new
+    }
+
+    /// remove `code`, returning `true` if it was in the set
+    pub fn remove(&mut self, code: OptionCode) -> bool {
+        let code = u8::from(code);
+        let had = self.contains_code(code);
+        self.0[code as usize / 64] &= !(1 << (code % 64));
+        had
+    }
+
+    /// number of codes in the set
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// `true` if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// iterate over the codes in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = OptionCode> + '_ {
+        (0..=u8::MAX)
+            .filter(|code| self.contains_code(*code))
+            .map(OptionCode::from)
+    }
+}
+
+impl fmt::Debug for OptionCodeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<OptionCode> for OptionCodeSet {
+    fn from_iter<T: IntoIterator<Item = OptionCode>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<OptionCode> for OptionCodeSet {
+    fn extend<T: IntoIterator<Item = OptionCode>>(&mut self, iter: T) {
+        for code in iter {
+            self.insert(code);
+        }
+    }
+}
+
+impl DhcpOptions {
+    /// keep only the options with a code in `set`
+    pub fn retain_codes(&mut self, set: &OptionCodeSet) {
+        self.retain(|code, _| set.contains(*code))
+    }
+
+    /// remove the options with a code in `set`
+    pub fn strip_codes(&mut self, set: &OptionCodeSet) {
+        self.retain(|code, _| !set.contains(*code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_set() {
+        const SET: OptionCodeSet = OptionCodeSet::from_codes(&[0, 63, 64, 255]);
+        assert_eq!(SET.len(), 4);
+        assert!(SET.contains(OptionCode::Pad));
+        assert!(SET.contains(OptionCode::End));
+        assert!(SET.contains_code(64));
+        assert!(!SET.contains_code(65));
+
+        let mut set = SET;
+        assert!(!set.insert(OptionCode::End));
+        assert!(set.remove(OptionCode::End));
+        assert!(!set.remove(OptionCode::End));
+        assert_eq!(set.iter().map(u8::from).collect::<Vec<_>>(), [0, 63, 64]);
+        assert!(OptionCodeSet::new().is_empty());
+    }
+}
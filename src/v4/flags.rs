@@ -10,6 +10,13 @@ use crate::{
 use serde::{Deserialize, Serialize};
 
 /// Represents available flags on message
+///
+/// ```
+/// # use dhcproto::v4::Flags;
+/// let flags = Flags::from_bits(Flags::BROADCAST);
+/// assert!(flags.broadcast());
+/// assert_eq!(flags, Flags::default().set_broadcast());
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Default, Clone, PartialEq, Eq)]
 pub struct Flags(u16);
@@ -18,6 +25,7 @@ impl fmt::Debug for Flags {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Flags")
             .field("broadcast", &self.broadcast())
+            .field("reserved", &format_args!("{:#06x}", self.reserved()))
             .finish()
     }
 }
@@ -29,14 +37,55 @@ impl fmt::Display for Flags {
 }
 
 impl Flags {
+    /// the broadcast bit
+    pub const BROADCAST: u16 = 0x80_00;
+    /// bits reserved by RFC 2131, they must be zero
+    /// <https://datatracker.ietf.org/doc/html/rfc2131#section-2>
+    pub const RESERVED: u16 = !Self::BROADCAST;
+
     /// Create new Flags from u16
+    #[deprecated(since = "0.13.0", note = "use `Flags::from_bits`")]
     pub fn new(n: u16) -> Self {
         Self(n)
     }
+    /// Flags with exactly `bits` set. Reserved ones are kept, so a message can carry
+    /// bits RFC 2131 says must be zero, e.g. to test how a server handles them
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+    /// all bits, including the reserved ones
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+    /// these flags with the reserved bits cleared. Receivers must ignore the reserved
+    /// bits, so compare normalized flags to check two messages ask for the same thing
+    ///
+    /// ```
+    /// # use dhcproto::v4::Flags;
+    /// let sent = Flags::from_bits(0x80_21);
+    /// assert_ne!(sent, Flags::default().set_broadcast());
+    /// assert_eq!(sent.normalized(), Flags::default().set_broadcast());
+    /// ```
+    pub const fn normalized(&self) -> Self {
+        Self(self.0 & Self::BROADCAST)
+    }
+    /// the reserved bits, some deployments use them
+    pub fn reserved(&self) -> u16 {
+        self.0 & Self::RESERVED
+    }
+    /// `true` if none of the reserved bits are set
+    pub fn is_valid(&self) -> bool {
+        self.reserved() == 0
+    }
     /// get the status of the broadcast flag
     pub fn broadcast(&self) -> bool {
         (self.0 & 0x80_00) >> (u16::BITS - 1) == 1
     }
+    /// `true` if the broadcast flag isn't set, i.e. the client can receive unicast
+    /// replies before it's configured
+    pub fn unicast(&self) -> bool {
+        !self.broadcast()
+    }
     /// set the broadcast bit, returns a new Flags
     pub fn set_broadcast(mut self) -> Self {
         self.0 |= 0x80_00;
@@ -79,7 +128,27 @@ mod tests {
         assert_eq!(flag.0, 0x80_00);
         assert!(flag.broadcast());
 
-        let flag = Flags::new(0x00_20).set_broadcast();
+        let flag = Flags::from_bits(0x00_20).set_broadcast();
         assert_eq!(flag.0, 0x80_20);
     }
+
+    #[test]
+    fn test_bits() {
+        let flag = Flags::default();
+        assert!(flag.unicast());
+        assert!(flag.is_valid());
+        let flag = Flags::from_bits(0x80_21);
+        assert!(!flag.unicast());
+        assert_eq!(flag.bits(), 0x80_21);
+        assert_eq!(flag.reserved(), 0x00_21);
+        assert!(!flag.is_valid());
+        assert!(Flags::default().set_broadcast().is_valid());
+        assert_eq!(
+            format!("{flag:?}"),
+            "Flags { broadcast: true, reserved: 0x0021 }"
+        );
+        assert_eq!(flag.normalized().bits(), Flags::BROADCAST);
+        assert!(flag.normalized().is_valid());
+        assert_eq!(Flags::from_bits(0x00_21).normalized(), Flags::default());
+    }
 }
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use hickory_proto::rr::Name;
+use crate::Domain;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -11,12 +11,12 @@ pub struct ClientFQDN {
     pub(crate) flags: FqdnFlags,
     pub(crate) r1: u8,
     pub(crate) r2: u8,
-    pub(crate) domain: Name,
+    pub(crate) domain: Domain,
 }
 
 impl ClientFQDN {
     // creates a new client fqdn setting the rcode1/rcode2 fields to 255
-    pub fn new(flags: FqdnFlags, domain: Name) -> Self {
+    pub fn new(flags: FqdnFlags, domain: Domain) -> Self {
         Self {
             flags,
             r1: 0xFF,
@@ -45,14 +45,14 @@ impl ClientFQDN {
         self.r2 = rcode2;
         self
     }
-    pub fn domain(&self) -> &Name {
+    pub fn domain(&self) -> &Domain {
         &self.domain
     }
-    pub fn set_domain(&mut self, domain: Name) -> &mut Self {
+    pub fn set_domain(&mut self, domain: Domain) -> &mut Self {
         self.domain = domain;
         self
     }
-    pub fn domain_mut(&mut self) -> &mut Name {
+    pub fn domain_mut(&mut self) -> &mut Domain {
         &mut self.domain
     }
 }
@@ -0,0 +1,139 @@
+//! Typed entry point for servers
+//!
+//! Implement [`Handler`] for the messages a server answers and [`dispatch`] calls the
+//! method for each received message's type. Messages the handler doesn't override, and
+//! ones without a `MessageType` option or that clients don't send, go to
+//! [`Handler::unhandled`].
+//!
+//! ```rust
+//! use dhcproto::v4::{self, handler::{self, Handler}, DhcpOption, MessageType};
+//!
+//! struct Server;
+//!
+//! impl Handler for Server {
+//!     type Output = Option<MessageType>;
+//!
+//!     fn discover(&mut self, _msg: &v4::Message) -> Self::Output {
+//!         Some(MessageType::Offer)
+//!     }
+//!
+//!     fn unhandled(&mut self, _msg: &v4::Message) -> Self::Output {
+//!         None
+//!     }
+//! }
+//!
+//! let mut discover = v4::Message::default();
+//! discover
+//!     .opts_mut()
+//!     .insert(DhcpOption::MessageType(MessageType::Discover));
+//! assert_eq!(handler::dispatch(&mut Server, &discover), Some(MessageType::Offer));
+//! assert_eq!(handler::dispatch(&mut Server, &v4::Message::default()), None);
+//! ```
+use crate::v4::{Message, MessageType};
+
+/// Handles the messages sent to a server, every method defaults to
+/// [`Handler::unhandled`]
+pub trait Handler {
+    /// what handling a message returns, e.g. the reply
+    type Output;
+
+    /// DHCPDISCOVER
+    fn discover(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPREQUEST
+    fn request(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPDECLINE
+    fn decline(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPRELEASE
+    fn release(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPINFORM
+    fn inform(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPLEASEQUERY
+    fn lease_query(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPBULKLEASEQUERY
+    fn bulk_lease_query(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPACTIVELEASEQUERY
+    fn active_lease_query(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// any other message, including ones without a `MessageType`
+    fn unhandled(&mut self, msg: &Message) -> Self::Output;
+}
+
+/// Call the `handler` method for the type of `msg`
+pub fn dispatch<H: Handler + ?Sized>(handler: &mut H, msg: &Message) -> H::Output {
+    match msg.opts().msg_type() {
+        Some(MessageType::Discover) => handler.discover(msg),
+        Some(MessageType::Request) => handler.request(msg),
+        Some(MessageType::Decline) => handler.decline(msg),
+        Some(MessageType::Release) => handler.release(msg),
+        Some(MessageType::Inform) => handler.inform(msg),
+        Some(MessageType::LeaseQuery) => handler.lease_query(msg),
+        Some(MessageType::BulkLeaseQuery) => handler.bulk_lease_query(msg),
+        Some(MessageType::ActiveLeaseQuery) => handler.active_lease_query(msg),
+        _ => handler.unhandled(msg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::DhcpOption;
+
+    #[derive(Default)]
+    struct Counts {
+        handled: Vec<MessageType>,
+        unhandled: usize,
+    }
+
+    impl Handler for Counts {
+        type Output = ();
+
+        fn request(&mut self, _msg: &Message) {
+            self.handled.push(MessageType::Request);
+        }
+        fn release(&mut self, _msg: &Message) {
+            self.handled.push(MessageType::Release);
+        }
+        fn unhandled(&mut self, _msg: &Message) {
+            self.unhandled += 1;
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut counts = Counts::default();
+        for msg_type in [
+            MessageType::Request,
+            MessageType::Release,
+            MessageType::Offer,
+            MessageType::Inform,
+        ] {
+            let mut msg = Message::default();
+            msg.opts_mut().insert(DhcpOption::MessageType(msg_type));
+            dispatch(&mut counts, &msg);
+        }
+        dispatch(&mut counts, &Message::default());
+        assert_eq!(counts.handled, [MessageType::Request, MessageType::Release]);
+        assert_eq!(counts.unhandled, 3);
+
+        assert_eq!(
+            MessageType::Request.expected_reply(),
+            [MessageType::Ack, MessageType::Nak]
+        );
+        assert!(MessageType::Offer.expected_reply().is_empty());
+    }
+}
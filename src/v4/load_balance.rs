@@ -0,0 +1,169 @@
+//! DHCP load balancing - <https://www.rfc-editor.org/rfc/rfc3074.html>
+//!
+//! Servers sharing a pool split clients between them by hashing each client into one
+//! of 256 buckets. [`extract_lb_key`] reads only what the hash & routing need from
+//! a raw packet, without decoding the whole message, and [`hash`] is the RFC 3074
+//! hash function.
+//!
+//! ```rust
+//! use dhcproto::{v4::{self, load_balance::{extract_lb_key, hash}}, Encodable};
+//!
+//! let mut msg = v4::Message::default();
+//! msg.set_chaddr(&[0, 1, 2, 3, 4, 5]);
+//! msg.opts_mut()
+//!     .insert(v4::DhcpOption::MessageType(v4::MessageType::Discover));
+//! let buf = msg.to_vec()?;
+//!
+//! let key = extract_lb_key(&buf)?;
+//! assert_eq!(key.chaddr, [0, 1, 2, 3, 4, 5]);
+//! assert_eq!(key.msg_type, Some(v4::MessageType::Discover));
+//! // hashed on chaddr, as there's no client identifier
+//! assert_eq!(key.bucket(), hash(&[0, 1, 2, 3, 4, 5]));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use std::net::Ipv4Addr;
+
+use crate::{
+    error::{DecodeError, DecodeResult},
+    v4::{iter_raw, MessageType, OptionCode, MAGIC},
+};
+
+// offsets in the fixed header
+const HLEN: usize = 2;
+const GIADDR: usize = 24;
+const CHADDR: usize = 28;
+const COOKIE: usize = 236;
+const OPTIONS: usize = 240;
+
+/// The fields of a v4 message a load balancer keys on, borrowed from the packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LbKey<'a> {
+    /// `chaddr`, `hlen` bytes long (at most 16)
+    pub chaddr: &'a [u8],
+    /// `giaddr`, the relay the packet came through
+    pub giaddr: Ipv4Addr,
+    /// the `MessageType` option, `None` if it's missing (e.g. BOOTP)
+    pub msg_type: Option<MessageType>,
+    /// the `ClientIdentifier` option
+    pub client_id: Option<&'a [u8]>,
+}
+
+impl<'a> LbKey<'a> {
+    /// The bytes the client is hashed on: its `ClientIdentifier` if it sent one,
+    /// otherwise `chaddr`
+    pub fn hash_input(&self) -> &'a [u8] {
+        self.client_id.unwrap_or(self.chaddr)
+    }
+
+    /// The client's hash bucket, [`hash`] of [`LbKey::hash_input`]
+    pub fn bucket(&self) -> u8 {
+        hash(self.hash_input())
+    }
+}
+
+/// Read the load balancing key from a raw message. Only the fixed header & the options
+/// area are looked at, options in an overloaded `sname`/`file` are not. Returns
+/// [`DecodeError::Truncated`] if `buf` is shorter than the fixed header.
+pub fn extract_lb_key(buf: &[u8]) -> DecodeResult<LbKey<'_>> {
+    let header = buf.get(..COOKIE).ok_or(DecodeError::Truncated)?;
+    let hlen = (header[HLEN] as usize).min(16);
+    let giaddr: [u8; 4] = header[GIADDR..GIADDR + 4].try_into().unwrap();
+    let mut key = LbKey {
+        chaddr: &header[CHADDR..CHADDR + hlen],
+        giaddr: giaddr.into(),
+        msg_type: None,
+        client_id: None,
+    };
+    if buf.get(COOKIE..OPTIONS) == Some(&MAGIC[..]) {
+        for (code, data) in iter_raw(&buf[OPTIONS..]).map_while(Result::ok) {
+            match (OptionCode::from(code), data) {
+                (OptionCode::MessageType, [ty]) => key.msg_type = Some((*ty).into()),
+                (OptionCode::ClientIdentifier, id) => key.client_id = Some(id),
+                _ => {}
+            }
+        }
+    }
+    Ok(key)
+}
+
+/// The RFC 3074 hash: a Pearson hash of `key` into one of 256 buckets
+/// <https://www.rfc-editor.org/rfc/rfc3074.html#section-6>
+pub fn hash(key: &[u8]) -> u8 {
+    // the length seeds the hash, only its low byte is used
+    key.iter()
+        .rev()
+        .fold(key.len() as u8, |hash, b| MIXING[(hash ^ b) as usize])
+}
+
+// the permutation of 0..=255 from RFC 3074 section 6
+#[rustfmt::skip]
+const MIXING: [u8; 256] = [
+    251, 175, 119, 215, 81, 14, 79, 191, 103, 49, 181, 143, 186, 157, 0,
+    232, 31, 32, 55, 60, 152, 58, 17, 237, 174, 70, 160, 144, 220, 90, 57,
+    223, 59, 3, 18, 140, 111, 166, 203, 196, 134, 243, 124, 95, 222, 179,
+    197, 65, 180, 48, 36, 15, 107, 46, 233, 130, 165, 30, 123, 161, 209, 23,
+    97, 16, 40, 91, 219, 61, 100, 10, 210, 109, 250, 127, 22, 138, 29,
+    108, 244, 67, 207, 9, 178, 204, 74, 98, 126, 249, 167, 116, 34, 77,
+    193, 200, 121, 5, 20, 113, 71, 35, 128, 13, 182, 94, 25, 226, 227,
+    199, 75, 27, 41, 245, 230, 224, 43, 225, 177, 26, 155, 150, 212, 142,
+    218, 115, 241, 73, 88, 105, 39, 114, 62, 255, 192, 201, 145, 214, 168,
+    158, 221, 148, 154, 122, 12, 84, 82, 163, 44, 139, 228, 236, 205, 242,
+    217, 11, 187, 146, 159, 64, 86, 239, 195, 42, 106, 198, 118, 112, 184,
+    172, 87, 2, 173, 117, 176, 229, 247, 253, 137, 185, 99, 164, 102, 147,
+    45, 66, 231, 52, 141, 211, 194, 206, 246, 238, 56, 110, 78, 248, 63,
+    240, 189, 93, 92, 51, 53, 183, 19, 171, 72, 50, 33, 104, 101, 69, 8,
+    252, 83, 120, 76, 135, 85, 54, 202, 125, 188, 213, 96, 235, 136, 208,
+    162, 129, 190, 132, 156, 38, 47, 1, 7, 254, 24, 4, 216, 131, 89, 21,
+    28, 133, 37, 153, 149, 80, 170, 68, 6, 169, 234, 151,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::{DhcpOption, Encodable, Message};
+
+    #[test]
+    fn test_lb_key() -> Result<(), Box<dyn std::error::Error>> {
+        let mut msg = Message::default();
+        msg.set_chaddr(&[0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff])
+            .set_giaddr([10, 0, 0, 1]);
+        let buf = msg.to_vec()?;
+        let key = extract_lb_key(&buf)?;
+        assert_eq!(key.giaddr, Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!((key.msg_type, key.client_id), (None, None));
+        assert_eq!(key.hash_input(), msg.chaddr());
+
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        msg.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1, 2, 3]));
+        let buf = msg.to_vec()?;
+        let key = extract_lb_key(&buf)?;
+        assert_eq!(key.msg_type, Some(MessageType::Request));
+        assert_eq!(key.hash_input(), [1, 2, 3]);
+        assert_eq!(key.bucket(), hash(&[1, 2, 3]));
+
+        assert!(extract_lb_key(&buf[..200]).is_err());
+        // a truncated options area still gives the header fields
+        assert_eq!(extract_lb_key(&buf[..242])?.chaddr, msg.chaddr());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut sorted = MIXING;
+        sorted.sort_unstable();
+        assert!(sorted.iter().enumerate().all(|(i, b)| i == *b as usize));
+        // single byte keys: MIXING[1 ^ b]
+        assert_eq!(hash(&[0]), 175);
+        assert_eq!(hash(&[1]), 251);
+        assert_eq!(hash(&[]), 0);
+        // every bucket is reachable from 6 byte MACs
+        let mut seen = [false; 256];
+        for i in 0..=u16::MAX {
+            let [a, b] = i.to_be_bytes();
+            seen[hash(&[0, 0x11, 0x22, 0x33, a, b]) as usize] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+}
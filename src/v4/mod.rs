@@ -83,23 +83,37 @@ use std::{fmt, net::Ipv4Addr, str::Utf8Error};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod audit;
 pub mod bulk_query;
+pub mod client;
+mod code_set;
 mod flags;
 pub mod fqdn;
+pub mod handler;
 mod htype;
+pub mod load_balance;
 mod opcode;
 mod options;
 pub mod relay;
+mod reply;
+pub mod retransmit;
+#[cfg(test)]
+mod sample;
+pub mod transparent;
+pub mod vendor;
 
 // re-export submodules from proto::msg
-pub use self::{flags::*, htype::*, opcode::*, options::*};
+pub use self::{code_set::*, flags::*, htype::*, opcode::*, options::*, reply::*};
 pub use crate::{
     decoder::{Decodable, Decoder},
     encoder::{Encodable, Encoder},
     error::*,
+    time::LeaseTime,
 };
 
 pub const MAGIC: [u8; 4] = [99, 130, 83, 99];
+/// minimum size of a BOOTP packet, [`Encoder::new_padded`] pads messages to this size
+/// <https://datatracker.ietf.org/doc/html/rfc1542#section-2.1>
 pub const MIN_PACKET_SIZE: usize = 300;
 
 /// default dhcpv4 server port
@@ -281,9 +295,10 @@ impl Message {
         self
     }
 
-    /// Get the message's chaddr.
+    /// Get the message's chaddr, the first `hlen` bytes of the field. All 16 bytes if
+    /// `hlen` is larger than that.
     pub fn chaddr(&self) -> &[u8] {
-        &self.chaddr[..(self.hlen as usize)]
+        &self.chaddr[..(self.hlen as usize).min(self.chaddr.len())]
     }
 
     /// Set the message's chaddr. `chaddr` can only up to 16 bytes in length
@@ -463,11 +478,50 @@ impl Message {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// Remove the options with `codes`. To keep the layout of the other options use
+    /// [`TransparentMessage::strip_options`](transparent::TransparentMessage::strip_options)
+    pub fn strip_options(&mut self, codes: &[OptionCode]) -> &mut Self {
+        for code in codes {
+            self.opts.remove(*code);
+        }
+        self
+    }
+
+    /// Insert `opts`, replacing options with the same code
+    pub fn append_options<I: IntoIterator<Item = DhcpOption>>(&mut self, opts: I) -> &mut Self {
+        for opt in opts {
+            self.opts.insert(opt);
+        }
+        self
+    }
+
+    /// Get the magic cookie at the start of the options area. When decoding with a
+    /// lenient decoder this may not be [`MAGIC`], and is all zeros if the packet ended
+    /// before the cookie.
+    pub fn magic(&self) -> [u8; 4] {
+        self.magic
+    }
+
+    /// Returns `true` if the message has the RFC 2132 magic cookie, i.e. it is a DHCP
+    /// message and not a plain BOOTP one
+    /// <https://datatracker.ietf.org/doc/html/rfc2132#section-2>
+    pub fn is_rfc2132_compliant(&self) -> bool {
+        self.magic == MAGIC
+    }
 }
 
 impl Decodable for Message {
+    /// Decodes a message. A strict decoder returns [`DecodeError::InvalidMagic`] if the
+    /// magic cookie is wrong, or [`DecodeError::Truncated`] if it's missing. Otherwise
+    /// the message is kept with no options, because without the cookie the vendor area
+    /// isn't DHCP options (e.g. a BOOTP packet), check [`Message::is_rfc2132_compliant`].
+    ///
+    /// Options overloaded into `sname` & `file` aren't decoded, those fields are kept as
+    /// bytes. A strict decoder returns [`DecodeError::UnsupportedOption`] for a message
+    /// with an `OptionOverload` option instead.
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
-        Ok(Message {
+        let mut msg = Message {
             opcode: Opcode::decode(decoder)?,
             htype: decoder.read_u8()?.into(),
             hlen: decoder.read_u8()?,
@@ -482,15 +536,40 @@ impl Decodable for Message {
             chaddr: decoder.read::<16>()?,
             sname: decoder.read_nul_bytes::<64>()?,
             fname: decoder.read_nul_bytes::<128>()?,
-            // TODO: check magic bytes against expected?
-            magic: decoder.read::<4>()?,
-            opts: DhcpOptions::decode(decoder)?,
-        })
+            magic: [0; 4],
+            opts: DhcpOptions::default(),
+        };
+        // BOOTP packets may end without a vendor area
+        msg.magic = match decoder.read::<4>() {
+            Ok(magic) => magic,
+            Err(DecodeError::Truncated) if !decoder.is_strict() => return Ok(msg),
+            Err(err) => return Err(err),
+        };
+        if msg.magic == MAGIC {
+            msg.opts = DhcpOptions::decode(decoder)?;
+            if decoder.is_strict() && msg.opts.get(OptionCode::OptionOverload).is_some() {
+                return Err(DecodeError::UnsupportedOption(
+                    u8::from(OptionCode::OptionOverload).into(),
+                ));
+            }
+        } else if decoder.is_strict() {
+            return Err(DecodeError::InvalidMagic(msg.magic));
+        }
+        Ok(msg)
     }
 }
 
-impl Encodable for Message {
-    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+impl TryFrom<&[u8]> for Message {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Message::from_bytes(bytes)
+    }
+}
+
+impl Message {
+    // the fixed size header fields, up to `sname`
+    pub(crate) fn encode_fixed(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         self.opcode.encode(e)?;
         self.htype.encode(e)?;
         e.write_u8(self.hlen)?;
@@ -502,12 +581,25 @@ impl Encodable for Message {
         e.write_u32(self.yiaddr.into())?;
         e.write_u32(self.siaddr.into())?;
         e.write_u32(self.giaddr.into())?;
-        e.write_slice(&self.chaddr[..])?;
+        e.write_slice(&self.chaddr[..])
+    }
+}
+
+impl Encodable for Message {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        let start = e.len_filled();
+        self.encode_fixed(e)?;
         e.write_fill(&self.sname, 64)?;
         e.write_fill(&self.fname, 128)?;
 
         e.write(self.magic)?;
         self.opts.encode(e)?;
+        if e.is_padded() {
+            let len = e.len_filled() - start;
+            for _ in len..MIN_PACKET_SIZE {
+                e.write_u8(0)?;
+            }
+        }
         Ok(())
     }
 }
@@ -549,6 +641,7 @@ impl fmt::Display for Message {
 mod tests {
 
     use super::*;
+    use crate::EncodeProfile;
 
     type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -563,6 +656,7 @@ mod tests {
         msg.encode(&mut e)?;
         println!("{buf:?}");
         println!("{input:?}");
+        assert_eq!(msg.encoded_len()?, buf.len());
         // decode again
         let res = Message::decode(&mut Decoder::new(&buf))?;
         // check Messages are equal after decoding/encoding
@@ -600,6 +694,181 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_encoded_len_long_opts() -> Result<()> {
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Offer));
+        // split over two options by the encoder
+        msg.opts_mut()
+            .insert(DhcpOption::Router(vec![Ipv4Addr::LOCALHOST; 100]));
+        msg.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1; 300]));
+        assert_eq!(msg.encoded_len()?, msg.to_vec()?.len());
+        // the interop profile doesn't split long options
+        assert!(msg.encoded_len_with(&EncodeProfile::interop()).is_err());
+        msg.opts_mut().remove(OptionCode::ClientIdentifier);
+        msg.opts_mut().remove(OptionCode::Router);
+        let mut buf = Vec::new();
+        msg.encode(&mut Encoder::new(&mut buf).profile(EncodeProfile::interop()))?;
+        assert_eq!(msg.encoded_len_with(&EncodeProfile::interop())?, buf.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_padded() -> Result<()> {
+        let padded = |msg: &Message| -> Result<Vec<u8>> {
+            let mut buf = Vec::new();
+            msg.encode(&mut Encoder::new_padded(&mut buf))?;
+            Ok(buf)
+        };
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        assert_eq!(msg.to_vec()?.len(), 244);
+        let buf = padded(&msg)?;
+        assert_eq!(buf.len(), MIN_PACKET_SIZE);
+        assert_eq!(buf[243], 255);
+        assert!(buf[244..].iter().all(|b| *b == 0));
+        assert_eq!(Message::decode(&mut Decoder::new(&buf))?, msg);
+
+        // 299, 300 & 301 bytes unpadded
+        for (id_len, len) in [(53, 300), (54, 300), (55, 301)] {
+            msg.opts_mut()
+                .insert(DhcpOption::ClientIdentifier(vec![1; id_len]));
+            assert_eq!(msg.to_vec()?.len(), 246 + id_len);
+            let buf = padded(&msg)?;
+            assert_eq!(buf.len(), len);
+            assert_eq!(Message::decode(&mut Decoder::new(&buf))?, msg);
+        }
+
+        // padding is relative to where the message starts
+        let mut buf = vec![1; 10];
+        let mut e = Encoder::new_padded(&mut buf);
+        e.write_slice(&[0; 10])?;
+        Message::default().encode(&mut e)?;
+        assert_eq!(e.len_filled(), 10 + MIN_PACKET_SIZE);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_profile() -> Result<()> {
+        let encode = |msg: &Message, profile| -> Result<Vec<u8>> {
+            let mut buf = Vec::new();
+            msg.encode(&mut Encoder::new(&mut buf).profile(profile))?;
+            Ok(buf)
+        };
+        let mut msg = Message::default();
+        let mut info = relay::RelayAgentInformation::default();
+        info.insert(relay::RelayInfo::AgentCircuitId(vec![1]));
+        msg.opts_mut()
+            .insert(DhcpOption::RelayAgentInformation(info));
+        for code in (224..230).rev() {
+            msg.opts_mut()
+                .insert(DhcpOption::Unknown(UnknownOption::from_raw(
+                    code,
+                    vec![code],
+                )));
+        }
+        msg.opts_mut().insert(DhcpOption::Hostname("host".into()));
+
+        // the same bytes every time, in code order & agent info last
+        let strict = encode(&msg, EncodeProfile::strict())?;
+        let codes = iter_raw(&strict[240..])
+            .map(|opt| opt.map(|(code, _)| code))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        assert_eq!(codes, [12, 224, 225, 226, 227, 228, 229, 82]);
+        assert_eq!(&strict[240..246], [12, 4, b'h', b'o', b's', b't']);
+        assert_eq!(strict.len(), msg.to_vec()?.len());
+
+        let interop = encode(&msg, EncodeProfile::interop())?;
+        assert_eq!(interop.len(), MIN_PACKET_SIZE);
+        assert_eq!(&interop[240..247], [12, 5, b'h', b'o', b's', b't', 0]);
+        assert_eq!(Message::decode(&mut Decoder::new(&interop))?, msg);
+
+        // split by default & strict, rejected by interop
+        msg.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1; 300]));
+        assert!(encode(&msg, EncodeProfile::strict()).is_ok());
+        assert!(matches!(
+            encode(&msg, EncodeProfile::interop()),
+            Err(e) if matches!(
+                e.downcast_ref::<EncodeError>(),
+                Some(EncodeError::TooLong { len: 300, max: 255 })
+            )
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn bytes_roundtrip() -> Result<()> {
+        let msg = Message::try_from(&bootreq()[..])?;
+        assert_eq!(msg, Message::from_bytes(&bootreq())?);
+        assert_eq!(msg.to_vec()?, bootreq());
+        #[cfg(feature = "bytes")]
+        assert_eq!(msg.to_bytes()?, bootreq());
+        Ok(())
+    }
+
+    #[test]
+    fn test_magic_cookie() -> Result<()> {
+        let msg = Message::decode(&mut Decoder::new_strict(&offer()))?;
+        assert_eq!(msg.magic(), MAGIC);
+        assert!(msg.is_rfc2132_compliant());
+
+        // BOOTP request with an empty vendor area
+        let mut bootp = bootreq();
+        bootp.truncate(236);
+        bootp.extend([0; 64]);
+        let msg = Message::decode(&mut Decoder::new(&bootp))?;
+        assert!(!msg.is_rfc2132_compliant());
+        assert_eq!(msg.magic(), [0; 4]);
+        assert!(msg.opts().is_empty());
+        // header is unaffected
+        assert_eq!(
+            msg.fname(),
+            Message::decode(&mut Decoder::new(&bootreq()))?.fname()
+        );
+        assert!(matches!(
+            Message::decode(&mut Decoder::new_strict(&bootp)),
+            Err(DecodeError::InvalidMagic([0, 0, 0, 0]))
+        ));
+
+        // RFC 1048 vendor area with a different cookie isn't parsed as options
+        let mut bootp = bootreq();
+        bootp.truncate(236);
+        bootp.extend([1, 2, 3, 4, 53, 1, 1, 255]);
+        let msg = Message::decode(&mut Decoder::new(&bootp))?;
+        assert_eq!(msg.magic(), [1, 2, 3, 4]);
+        assert!(msg.opts().is_empty());
+
+        // no vendor area at all
+        let mut bootp = bootreq();
+        bootp.truncate(236);
+        let msg = Message::decode(&mut Decoder::new(&bootp))?;
+        assert!(!msg.is_rfc2132_compliant());
+        assert!(Message::decode(&mut Decoder::new_strict(&bootp)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_option_overload() -> Result<()> {
+        let mut msg = Message::decode(&mut Decoder::new(&offer()))?;
+        // options continue in `file`
+        msg.opts_mut().insert(DhcpOption::OptionOverload(1));
+        let bytes = msg.to_vec()?;
+        let decoded = Message::decode(&mut Decoder::new(&bytes))?;
+        assert_eq!(
+            decoded.opts().get(OptionCode::OptionOverload),
+            Some(&DhcpOption::OptionOverload(1))
+        );
+        assert!(matches!(
+            Message::decode(&mut Decoder::new_strict(&bytes)),
+            Err(DecodeError::UnsupportedOption(52))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_set_chaddr() -> Result<()> {
         let mut msg = Message::new(
@@ -614,6 +883,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_chaddr_hlen() -> Result<()> {
+        let mut msg = Message::default();
+        msg.set_htype(HType::IEEE802)
+            .set_chaddr(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(msg
+            .to_string()
+            .contains("chaddr: \"01:02:03:04:05:06:07:08\""));
+
+        // hlen larger than chaddr
+        let mut buf = msg.to_vec()?;
+        buf[2] = 20;
+        let msg = Message::from_bytes(&buf)?;
+        assert_eq!(msg.hlen(), 20);
+        assert_eq!(msg.chaddr().len(), 16);
+        assert!(msg.to_string().contains("07:08:00"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_opcode() {
+        assert!(Opcode::BootRequest.is_request());
+        assert!(Opcode::BootReply.is_reply());
+        assert!(!Opcode::Unknown(3).is_request() && !Opcode::Unknown(3).is_reply());
+        assert_eq!(Opcode::BootReply.to_string(), "BootReply");
+        assert_eq!(Opcode::Unknown(3).to_string(), "3");
+        assert_eq!(HType::Eth.to_string(), "Eth");
+        assert_eq!(HType::Unknown(200).to_string(), "200");
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_json() -> Result<()> {
@@ -18,6 +18,19 @@ pub enum Opcode {
     Unknown(u8),
 }
 
+impl Opcode {
+    /// `true` for a BOOTREQUEST, sent by clients & relays
+    pub fn is_request(&self) -> bool {
+        *self == Opcode::BootRequest
+    }
+    /// `true` for a BOOTREPLY, sent by servers
+    pub fn is_reply(&self) -> bool {
+        *self == Opcode::BootReply
+    }
+}
+
+display_unknown!(Opcode);
+
 impl Decodable for Opcode {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(decoder.read_u8()?.into())
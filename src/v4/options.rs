@@ -1,139 +1,171 @@
-use std::{borrow::Cow, collections::HashMap, iter, net::Ipv4Addr};
+use std::{
+    borrow::Cow,
+    fmt, iter,
+    net::Ipv4Addr,
+    ops::RangeInclusive,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 
 use crate::{
-    decoder::{Decodable, Decoder},
+    byte_string::ByteString,
+    decoder::{Decodable, Decoder, ObservedCode},
+    diff::OptionsDiff,
+    dns_servers::{self, DnsServerError},
+    domain::{Domain, DomainWriter},
     encoder::{Encodable, Encoder},
-    error::{DecodeResult, EncodeResult},
+    error::{DecodeResult, EncodeResult, ParseNameError},
+    merge::{MergePolicy, Precedence},
+    sztp,
+    time::{self, LeaseTime},
+    tz::{self, Timezone},
+    uri::Uri,
     v4::bulk_query,
     v4::{fqdn, relay},
 };
 
-use hickory_proto::{
-    rr::Name,
-    serialize::binary::{BinDecodable, BinDecoder, BinEncodable, BinEncoder, EncodeMode},
-};
 use ipnet::Ipv4Net;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Option codes that were site-local until RFC 3942 made them available as standard
+/// options, see [`Decoder::site_local_opaque`]
+/// <https://www.rfc-editor.org/rfc/rfc3942.html>
+pub const RFC3942_RECLASSIFIED: RangeInclusive<u8> = 128..=223;
 
 // declares DHCP Option codes.
 // generates:
-// * the `OptionCode` enum and its From<u8>, Into<u8>
+// * the `OptionCode` newtype & a constant per option, `OptionCode::KNOWN`, const `from_u8`/`as_u8` and its From<u8>, Into<u8>
 // * the DhcpOption enum
 // * From<&DhcpOption> for OptionCode
+// * a marker type per option in the `typed` module, implementing `TypedOption`
 //
-// Syntax is {N, Name, "DocString" [,(T0,..TN,)]}
+// Syntax is {N, Name, "DocString" [,deprecated] [,"name"] [,(T0,..TN,)] [,[B0,..BN]]}
 // where:
 // * N is the numeric code associated with this option
 // * Name is the name to use for the enum variants
 // * "Docstring" is the documentation string that will be added to the variant in the OptionCode enum
+// * deprecated marks options that shouldn't be sent, it's added to the docs & `OptionCode::metadata`
+// * "name" is the canonical ISC/kea option name, if it isn't the kebab-case of Name
 // * (T0,..TN) is the associated variables (if any). e.g. Ipv4Addr for "SubnetMask" or bool for "IpForwarding".
 //   can contain more than one type but needs to be enclosed in parenthesis even if it's just a single variable.
+// * [B0,..BN] is an example of the option on the wire, code & length included. It's added to the variant's
+//   docs as an example that decodes it & encodes it again, so it's checked by the doc tests
+// `roundtrip_tests = Trait` generates a test per option that encodes a sample of it & decodes it again, so
+// every field type needs a `Sample` impl in `v4::sample`
 dhcproto_macros::declare_codes!(
-    {0,   Pad, "Padding"},
-    {1,   SubnetMask, "Subnet Mask", (Ipv4Addr)},
-    {2,   TimeOffset, "Time Offset", (i32)},
-    {3,   Router, "Router", (Vec<Ipv4Addr>)},
-    {4,   TimeServer, "Router", (Vec<Ipv4Addr>)},
-    {5,   NameServer, "Name Server", (Vec<Ipv4Addr>)},
-    {6,   DomainNameServer, "Name Server", (Vec<Ipv4Addr>)},
-    {7,   LogServer, "Log Server", (Vec<Ipv4Addr>)},
-    {8,   QuoteServer, "Quote Server", (Vec<Ipv4Addr>)},
-    {9,   LprServer, "LPR Server", (Vec<Ipv4Addr>)},
-    {10,  ImpressServer, "Impress server", (Vec<Ipv4Addr>)},
-    {11,  ResourceLocationServer, "Resource Location Server", (Vec<Ipv4Addr>)},
-    {12,  Hostname, "Host name", (String)},
-    {13,  BootFileSize, "Boot file size", (u16)},
-    {14,  MeritDumpFile, "Merit Dump File", (String)},
-    {15,  DomainName, "Domain Name", (String)},
-    {16,  SwapServer, "Swap server", (Ipv4Addr)},
-    {17,  RootPath, "Root Path", (String)},
-    {18,  ExtensionsPath, "Extensions path", (String)},
-    {19,  IpForwarding, "IP forwarding", (bool)},
-    {20,  NonLocalSrcRouting, "Non-local source routing", (bool)},
-    {21,  PolicyFilter, "Policy Filter", (Vec<(Ipv4Addr, Ipv4Addr)>)},
-    {22,  MaxDatagramSize, "Max Datagram reassembly size", (u16)},
-    {23,  DefaultIpTtl, "Ip TTL", (u8)},
-    {24,  PathMtuAgingTimeout, "Path MTU Aging Timeout", (u32)},
-    {25,  PathMtuPlateauTable, "Path MTU Plateau Table", (Vec<u16>)},
-    {26,  InterfaceMtu, "Interface MTU", (u16)},
-    {27,  AllSubnetsLocal, "All Subnets Local", (bool)},
-    {28,  BroadcastAddr, "Broadcast address", (Ipv4Addr)},
-    {29,  PerformMaskDiscovery, "Perform mask discovery", (bool)},
-    {30,  MaskSupplier, "Mask supplier", (bool)},
-    {31,  PerformRouterDiscovery, "Perform router discovery", (bool)},
-    {32,  RouterSolicitationAddr, "Router solicitation address", (Ipv4Addr)},
-    {33,  StaticRoutingTable, "Static routing table", (Vec<(Ipv4Addr, Ipv4Addr)>)},
-    {34,  TrailerEncapsulated, "Trailer Encapsulated", (bool)},
-    {35,  ArpCacheTimeout, "ARP timeout", (u32)},
-    {36,  EthernetEncapsulation, "Ethernet encapsulation", (bool)},
-    {37,  DefaultTcpTtl, "Default TCP TTL", (u8)},
-    {38,  TcpKeepaliveInterval, "TCP keepalive interval", (u32)},
-    {39,  TcpKeepaliveGarbage, "TCP keealive garbage", (bool)},
-    {40,  NisDomain, "Network information service domain", (String)},
-    {41,  NisServers, "NIS servers", (Vec<Ipv4Addr>)},
-    {42,  NtpServers, "NTP servers", (Vec<Ipv4Addr>)},
-    {43,  VendorExtensions, "Vendor Extensions - can contain encapsulated options", (Vec<u8>)}, // TODO: Hashmap<u8, UnknownOption>?
-    {44,  NetBiosNameServers, "NetBIOS over TCP/IP name server", (Vec<Ipv4Addr>)},
-    {45,  NetBiosDatagramDistributionServer, "NetBIOS over TCP/IP Datagram Distribution Server", (Vec<Ipv4Addr>)},
-    {46,  NetBiosNodeType, "NetBIOS over TCP/IP Node Type", (NodeType)},
-    {47,  NetBiosScope, "NetBIOS over TCP/IP Scope", (String)},
-    {48,  XFontServer, "X Window System Font Server", (Vec<Ipv4Addr>)},
-    {49,  XDisplayManager, "Window System Display Manager", (Vec<Ipv4Addr>)},
-    {50,  RequestedIpAddress, "Requested IP Address", (Ipv4Addr)},
-    {51,  AddressLeaseTime, "IP Address Lease Time", (u32)},
-    {52,  OptionOverload, "Option Overload", (u8)},
-    {53,  MessageType, "Message Type", (MessageType)},
-    {54,  ServerIdentifier, "Server Identifier", (Ipv4Addr)},
-    {55,  ParameterRequestList, "Parameter Request List", (Vec<OptionCode>)},
-    {56,  Message, "Message", (String)},
-    {57,  MaxMessageSize, "Maximum DHCP Message Size", (u16)},
-    {58,  Renewal, "Renewal (T1) Time Value", (u32)},
-    {59,  Rebinding, "Rebinding (T2) Time Value", (u32)},
-    {60,  ClassIdentifier, "Class-identifier", (Vec<u8>)},
-    {61,  ClientIdentifier, "Client Identifier", (Vec<u8>)},
-    {62,  NwipDomainName, "Netware/IP Domain Name", (String)},
-    {63,  NwipInformation, "Netware/IP Information - <https://www.rfc-editor.org/rfc/rfc2242.html>", (Vec<u8>)}, // TODO: https://www.rfc-editor.org/rfc/rfc2242.html sub opts
-    {64,  NispServiceDomain, "NIS+ Domain Option", (String)},
-    {65,  NispServers, "NIS+ Server Addr", (Vec<Ipv4Addr>)},
+    roundtrip_tests = crate::v4::sample::Sample,
+    {0,   Pad, "Padding - <https://www.rfc-editor.org/rfc/rfc2132.html>", [0]},
+    {1,   SubnetMask, "Subnet Mask - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Ipv4Addr), [1, 4, 255, 255, 255, 0]},
+    {2,   TimeOffset, "Time Offset, deprecated by `TZPosixString` & `TZDatabaseString`, see [`DhcpOptions::timezone`] - <https://www.rfc-editor.org/rfc/rfc2132.html>", deprecated, (i32)},
+    {3,   Router, "Router - <https://www.rfc-editor.org/rfc/rfc2132.html>", "routers", (Vec<Ipv4Addr>), [3, 4, 192, 168, 0, 1]},
+    {4,   TimeServer, "Time Server (RFC 868), not NTP - <https://www.rfc-editor.org/rfc/rfc2132.html>", "time-servers", (Vec<Ipv4Addr>)},
+    {5,   NameServer, "Name Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "ien116-name-servers", (Vec<Ipv4Addr>)},
+    {6,   DomainNameServer, "Name Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "domain-name-servers", (Vec<Ipv4Addr>), [6, 8, 8, 8, 8, 8, 1, 1, 1, 1]},
+    {7,   LogServer, "Log Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "log-servers", (Vec<Ipv4Addr>)},
+    {8,   QuoteServer, "Quote Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "cookie-servers", (Vec<Ipv4Addr>)},
+    {9,   LprServer, "LPR Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "lpr-servers", (Vec<Ipv4Addr>)},
+    {10,  ImpressServer, "Impress server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "impress-servers", (Vec<Ipv4Addr>)},
+    {11,  ResourceLocationServer, "Resource Location Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "resource-location-servers", (Vec<Ipv4Addr>)},
+    {12,  Hostname, "Host name - <https://www.rfc-editor.org/rfc/rfc2132.html>", "host-name", (ByteString), [12, 4, 104, 111, 115, 116]},
+    {13,  BootFileSize, "Boot file size - <https://www.rfc-editor.org/rfc/rfc2132.html>", "boot-size", (u16)},
+    {14,  MeritDumpFile, "Merit Dump File - <https://www.rfc-editor.org/rfc/rfc2132.html>", "merit-dump", (ByteString)},
+    {15,  DomainName, "Domain Name - <https://www.rfc-editor.org/rfc/rfc2132.html>", (ByteString)},
+    {16,  SwapServer, "Swap server - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Ipv4Addr)},
+    {17,  RootPath, "Root Path - <https://www.rfc-editor.org/rfc/rfc2132.html>", (ByteString)},
+    {18,  ExtensionsPath, "Extensions path - <https://www.rfc-editor.org/rfc/rfc2132.html>", (ByteString)},
+    {19,  IpForwarding, "IP forwarding - <https://www.rfc-editor.org/rfc/rfc2132.html>", (bool), [19, 1, 1]},
+    {20,  NonLocalSrcRouting, "Non-local source routing - <https://www.rfc-editor.org/rfc/rfc2132.html>", "non-local-source-routing", (bool)},
+    {21,  PolicyFilter, "Policy Filter - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<(Ipv4Addr, Ipv4Addr)>)},
+    {22,  MaxDatagramSize, "Max Datagram reassembly size - <https://www.rfc-editor.org/rfc/rfc2132.html>", "max-dgram-reassembly", (u16)},
+    {23,  DefaultIpTtl, "Ip TTL - <https://www.rfc-editor.org/rfc/rfc2132.html>", (u8)},
+    {24,  PathMtuAgingTimeout, "Path MTU Aging Timeout - <https://www.rfc-editor.org/rfc/rfc2132.html>", (u32)},
+    {25,  PathMtuPlateauTable, "Path MTU Plateau Table - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<u16>)},
+    {26,  InterfaceMtu, "Interface MTU - <https://www.rfc-editor.org/rfc/rfc2132.html>", (u16), [26, 2, 5, 220]},
+    {27,  AllSubnetsLocal, "All Subnets Local - <https://www.rfc-editor.org/rfc/rfc2132.html>", (bool)},
+    {28,  BroadcastAddr, "Broadcast address - <https://www.rfc-editor.org/rfc/rfc2132.html>", "broadcast-address", (Ipv4Addr)},
+    {29,  PerformMaskDiscovery, "Perform mask discovery - <https://www.rfc-editor.org/rfc/rfc2132.html>", (bool)},
+    {30,  MaskSupplier, "Mask supplier - <https://www.rfc-editor.org/rfc/rfc2132.html>", (bool)},
+    {31,  PerformRouterDiscovery, "Perform router discovery - <https://www.rfc-editor.org/rfc/rfc2132.html>", "router-discovery", (bool)},
+    {32,  RouterSolicitationAddr, "Router solicitation address - <https://www.rfc-editor.org/rfc/rfc2132.html>", "router-solicitation-address", (Ipv4Addr)},
+    {33,  StaticRoutingTable, "Static routing table - <https://www.rfc-editor.org/rfc/rfc2132.html>", "static-routes", (Vec<(Ipv4Addr, Ipv4Addr)>)},
+    {34,  TrailerEncapsulated, "Trailer Encapsulated - <https://www.rfc-editor.org/rfc/rfc2132.html>", "trailer-encapsulation", (bool)},
+    {35,  ArpCacheTimeout, "ARP timeout - <https://www.rfc-editor.org/rfc/rfc2132.html>", (u32)},
+    {36,  EthernetEncapsulation, "Ethernet encapsulation - <https://www.rfc-editor.org/rfc/rfc2132.html>", "ieee802-3-encapsulation", (bool)},
+    {37,  DefaultTcpTtl, "Default TCP TTL - <https://www.rfc-editor.org/rfc/rfc2132.html>", (u8)},
+    {38,  TcpKeepaliveInterval, "TCP keepalive interval - <https://www.rfc-editor.org/rfc/rfc2132.html>", (u32)},
+    {39,  TcpKeepaliveGarbage, "TCP keealive garbage - <https://www.rfc-editor.org/rfc/rfc2132.html>", (bool)},
+    {40,  NisDomain, "Network information service domain - <https://www.rfc-editor.org/rfc/rfc2132.html>", (ByteString)},
+    {41,  NisServers, "NIS servers - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {42,  NtpServers, "NTP servers, see [`ntp`](crate::ntp) - <https://datatracker.ietf.org/doc/html/rfc2132#section-8.3>", (Vec<Ipv4Addr>)},
+    {43,  VendorExtensions, "Vendor Extensions - can contain encapsulated options - <https://www.rfc-editor.org/rfc/rfc2132.html>", "vendor-encapsulated-options", (Vec<u8>)}, // TODO: Hashmap<u8, UnknownOption>?
+    {44,  NetBiosNameServers, "NetBIOS over TCP/IP name server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "netbios-name-servers", (Vec<Ipv4Addr>)},
+    {45,  NetBiosDatagramDistributionServer, "NetBIOS over TCP/IP Datagram Distribution Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "netbios-dd-server", (Vec<Ipv4Addr>)},
+    {46,  NetBiosNodeType, "NetBIOS over TCP/IP Node Type - <https://www.rfc-editor.org/rfc/rfc2132.html>", "netbios-node-type", (NodeType)},
+    {47,  NetBiosScope, "NetBIOS over TCP/IP Scope - <https://www.rfc-editor.org/rfc/rfc2132.html>", "netbios-scope", (ByteString)},
+    {48,  XFontServer, "X Window System Font Server - <https://www.rfc-editor.org/rfc/rfc2132.html>", "font-servers", (Vec<Ipv4Addr>)},
+    {49,  XDisplayManager, "Window System Display Manager - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {50,  RequestedIpAddress, "Requested IP Address - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-requested-address", (Ipv4Addr)},
+    {51,  AddressLeaseTime, "IP Address Lease Time - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-lease-time", (LeaseTime), [51, 4, 0, 1, 81, 128]},
+    {52,  OptionOverload, "Option Overload - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-option-overload", (u8)},
+    {53,  MessageType, "Message Type - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-message-type", (MessageType), [53, 1, 1]},
+    {54,  ServerIdentifier, "Server Identifier - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-server-identifier", (Ipv4Addr), [54, 4, 192, 168, 0, 1]},
+    {55,  ParameterRequestList, "Parameter Request List - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-parameter-request-list", (Vec<OptionCode>), [55, 4, 1, 3, 6, 15]},
+    {56,  Message, "Message - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-message", (ByteString)},
+    {57,  MaxMessageSize, "Maximum DHCP Message Size - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-max-message-size", (u16)},
+    {58,  Renewal, "Renewal (T1) Time Value - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-renewal-time", (LeaseTime)},
+    {59,  Rebinding, "Rebinding (T2) Time Value - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-rebinding-time", (LeaseTime)},
+    {60,  ClassIdentifier, "Class-identifier - <https://www.rfc-editor.org/rfc/rfc2132.html>", "vendor-class-identifier", (Vec<u8>)},
+    {61,  ClientIdentifier, "Client Identifier - <https://www.rfc-editor.org/rfc/rfc2132.html>", "dhcp-client-identifier", (Vec<u8>), [61, 7, 1, 0, 17, 34, 51, 68, 85]},
+    {62,  NwipDomainName, "Netware/IP Domain Name - <https://www.rfc-editor.org/rfc/rfc2132.html>", (ByteString)},
+    {63,  NwipInformation, "Netware/IP Information - <https://www.rfc-editor.org/rfc/rfc2242.html>", "nwip-suboptions", (Vec<u8>)}, // TODO: https://www.rfc-editor.org/rfc/rfc2242.html sub opts
+    {64,  NispServiceDomain, "NIS+ Domain Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", "nisplus-domain-name", (ByteString)},
+    {65,  NispServers, "NIS+ Server Addr - <https://www.rfc-editor.org/rfc/rfc2132.html>", "nisplus-servers", (Vec<Ipv4Addr>)},
     {66,  TFTPServerName, "TFTP Server Name - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<u8>)},
-    {67,  BootfileName, "Bootfile Name - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<u8>)},
-    {68,  MobileIpHomeAgent, "Mobile IP Home Agent", (Vec<Ipv4Addr>)},
-    {69,  SmtpServer, "SMTP Server Option", (Vec<Ipv4Addr>)},
-    {70,  Pop3Server, "Pop3 Server Option", (Vec<Ipv4Addr>)},
-    {71,  NntpServer, "NNTP Server Option", (Vec<Ipv4Addr>)},
-    {72,  WwwServer, "WWW Server Option", (Vec<Ipv4Addr>)},
-    {73,  DefaultFingerServer, "Default Finger Option", (Vec<Ipv4Addr>)},
-    {74,  IrcServer, "IRC Server Option", (Vec<Ipv4Addr>)},
-    {75,  StreetTalkServer, "StreetTalk Server Option", (Vec<Ipv4Addr>)},
-    {76,  StreetTalkDirectoryAssistance, "StreetTalk Directory Insistance (STDA) Option", (Vec<Ipv4Addr>)},
+    {67,  BootfileName, "Bootfile Name - <https://www.rfc-editor.org/rfc/rfc2132.html>", "boot-file-name", (Vec<u8>)},
+    {68,  MobileIpHomeAgent, "Mobile IP Home Agent - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {69,  SmtpServer, "SMTP Server Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {70,  Pop3Server, "Pop3 Server Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", "pop-server", (Vec<Ipv4Addr>)},
+    {71,  NntpServer, "NNTP Server Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {72,  WwwServer, "WWW Server Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {73,  DefaultFingerServer, "Default Finger Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", "finger-server", (Vec<Ipv4Addr>)},
+    {74,  IrcServer, "IRC Server Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", (Vec<Ipv4Addr>)},
+    {75,  StreetTalkServer, "StreetTalk Server Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", "streettalk-server", (Vec<Ipv4Addr>)},
+    {76,  StreetTalkDirectoryAssistance, "StreetTalk Directory Insistance (STDA) Option - <https://www.rfc-editor.org/rfc/rfc2132.html>", "streettalk-directory-assistance-server", (Vec<Ipv4Addr>)},
     // TODO: split user-class into individual classes [len | <class>, ...]
     {77,  UserClass, "User Class Option - <https://www.rfc-editor.org/rfc/rfc3004.html>", (Vec<u8>)},
-    {80,  RapidCommit, "Rapid Commit - <https://www.rfc-editor.org/rfc/rfc4039.html>"},
-    {81,  ClientFQDN, "FQDN - <https://datatracker.ietf.org/doc/html/rfc4702>", (fqdn::ClientFQDN)},
-    {82,  RelayAgentInformation, "Relay Agent Information - <https://datatracker.ietf.org/doc/html/rfc3046>", (relay::RelayAgentInformation)},
-    {88,  BcmsControllerNames, "Broadcast Multicast Controller Names - <https://www.rfc-editor.org/rfc/rfc4280.html#section-4.1>", (Vec<Name>)},
-    {89,  BcmsControllerAddrs, "Broadcast Mutlicast Controller Address - <https://www.rfc-editor.org/rfc/rfc4280.html#section-4.3>", (Vec<Ipv4Addr>)},
+    {80,  RapidCommit, "Rapid Commit - <https://www.rfc-editor.org/rfc/rfc4039.html>", [80, 0]},
+    {81,  ClientFQDN, "FQDN - <https://datatracker.ietf.org/doc/html/rfc4702>", "fqdn", (fqdn::ClientFQDN)},
+    {82,  RelayAgentInformation, "Relay Agent Information - <https://datatracker.ietf.org/doc/html/rfc3046>", "dhcp-agent-options", (relay::RelayAgentInformation)},
+    {88,  BcmsControllerNames, "Broadcast Multicast Controller Names - <https://www.rfc-editor.org/rfc/rfc4280.html#section-4.1>", (Vec<Domain>)},
+    {89,  BcmsControllerAddrs, "Broadcast Mutlicast Controller Address - <https://www.rfc-editor.org/rfc/rfc4280.html#section-4.3>", "bcms-controller-address", (Vec<Ipv4Addr>)},
     {91,  ClientLastTransactionTime, "client-last-transaction-time - <https://www.rfc-editor.org/rfc/rfc4388.html#section-6.1>", (u32)},
     {92,  AssociatedIp, "associated-ip - <https://www.rfc-editor.org/rfc/rfc4388.html#section-6.1>", (Vec<Ipv4Addr>)},
-    {93,  ClientSystemArchitecture, "Client System Architecture - <https://www.rfc-editor.org/rfc/rfc4578.html>", (Architecture)},
-    {94,  ClientNetworkInterface, "Client Network Interface - <https://www.rfc-editor.org/rfc/rfc4578.html>", (u8, u8, u8)},
-    {97,  ClientMachineIdentifier, "Client Machine Identifier - <https://www.rfc-editor.org/rfc/rfc4578.html>", (Vec<u8>)},
-    {106, Ipv6OnlyPreferred, "IPv6-Only Preferred - <https://datatracker.ietf.org/doc/html/rfc8925>", (u32)},
-    {114, CaptivePortal, "Captive Portal - <https://datatracker.ietf.org/doc/html/rfc8910>", (url::Url)},
-    {116, DisableSLAAC, "Disable Stateless Autoconfig for Ipv4 - <https://datatracker.ietf.org/doc/html/rfc2563>", (AutoConfig)},
+    {93,  ClientSystemArchitecture, "Client System Architecture - <https://www.rfc-editor.org/rfc/rfc4578.html>", "client-system", (Architecture), [93, 2, 0, 7]},
+    {94,  ClientNetworkInterface, "Client Network Interface - <https://www.rfc-editor.org/rfc/rfc4578.html>", "client-ndi", (u8, u8, u8)},
+    {97,  ClientMachineIdentifier, "Client Machine Identifier - <https://www.rfc-editor.org/rfc/rfc4578.html>", "uuid-guid", (Vec<u8>)},
+    {100, TZPosixString, "POSIX timezone string, see [`tz::is_posix_tz`](crate::tz::is_posix_tz) - <https://datatracker.ietf.org/doc/html/rfc4833>", "pcode", (ByteString)},
+    {101, TZDatabaseString, "TZ database name, see [`tz::is_tzdb_name`](crate::tz::is_tzdb_name) - <https://datatracker.ietf.org/doc/html/rfc4833>", "tcode", (ByteString)},
+    {106, Ipv6OnlyPreferred, "IPv6-Only Preferred - <https://datatracker.ietf.org/doc/html/rfc8925>", "v6-only-preferred", (u32)},
+    {114, CaptivePortal, "Captive Portal - <https://datatracker.ietf.org/doc/html/rfc8910>", "v4-captive-portal", (Uri)},
+    {116, DisableSLAAC, "Disable Stateless Autoconfig for Ipv4 - <https://datatracker.ietf.org/doc/html/rfc2563>", "auto-config", (AutoConfig)},
     {118, SubnetSelection, "Subnet selection - <https://datatracker.ietf.org/doc/html/rfc3011>", (Ipv4Addr)},
-    {119, DomainSearch, "Domain Search - <https://www.rfc-editor.org/rfc/rfc3397.html>", (Vec<Name>)},
-    {121, ClasslessStaticRoute, "Classless Static Route - <https://www.rfc-editor.org/rfc/rfc3442>", (Vec<(Ipv4Net, Ipv4Addr)>)},
+    {119, DomainSearch, "Domain Search - <https://www.rfc-editor.org/rfc/rfc3397.html>", (Vec<Domain>)},
+    {120, SipServers, "SIP Servers - <https://datatracker.ietf.org/doc/html/rfc3361>", (SipServers)},
+    {121, ClasslessStaticRoute, "Classless Static Route - <https://www.rfc-editor.org/rfc/rfc3442>", (Vec<(Ipv4Net, Ipv4Addr)>), [121, 8, 24, 10, 0, 0, 192, 168, 0, 1]},
+    {143, SztpRedirect, "SZTP Redirect - <https://datatracker.ietf.org/doc/html/rfc8572#section-8.3>", "v4-sztp-redirect", (Vec<Uri>)},
     {150, TFTPServerAddress, "TFTP Server Address - <https://www.rfc-editor.org/rfc/rfc5859.html>", (Ipv4Addr)},
-    {151, BulkLeaseQueryStatusCode, "BLQ status-code - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.2>", (bulk_query::Code, String)},
-    {152, BulkLeaseQueryBaseTime, "BLQ base time - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.3>", (u32)},
-    {153, BulkLeasQueryStartTimeOfState, "BLQ start time of state - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.4>", (u32)},
-    {154, BulkLeaseQueryQueryStartTime, "BLQ query start time - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.5>", (u32)},
-    {155, BulkLeaseQueryQueryEndTime, "BLQ query end time- <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.6>", (u32)},
-    {156, BulkLeaseQueryDhcpState, "BLQ DHCP state - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.7>", (bulk_query::QueryState)},
-    {157, BulkLeaseQueryDataSource, "BLQ data source - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.8>", (bulk_query::DataSourceFlags)},
-    {255, End, "end-of-list marker"}
+    {151, BulkLeaseQueryStatusCode, "BLQ status-code - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.2>", "status-code", (bulk_query::Code, String)},
+    {152, BulkLeaseQueryBaseTime, "BLQ base time, the server's current time in seconds since the epoch - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.3>", "base-time", (u32)},
+    {153, BulkLeasQueryStartTimeOfState, "BLQ start time of state, seconds before base time that the lease entered its current state - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.4>", "start-time-of-state", (u32)},
+    {154, BulkLeaseQueryQueryStartTime, "BLQ query start time, only return leases changed at or after this time, in seconds since the epoch - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.5>", "query-start-time", (u32)},
+    {155, BulkLeaseQueryQueryEndTime, "BLQ query end time, only return leases changed at or before this time, in seconds since the epoch - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.6>", "query-end-time", (u32)},
+    {156, BulkLeaseQueryDhcpState, "BLQ DHCP state - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.7>", "dhcp-state", (bulk_query::QueryState)},
+    {157, BulkLeaseQueryDataSource, "BLQ data source - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.8>", "data-source", (bulk_query::DataSourceFlags)},
+    {158, PcpServers, "PCP servers, one list of addresses per server - <https://datatracker.ietf.org/doc/html/rfc7291#section-4>", (Vec<Vec<Ipv4Addr>>)},
+    {249, MicrosoftClasslessStaticRoute, "Microsoft Classless Static Route, sent by Windows clients instead of `ClasslessStaticRoute` & in the same format, see [`DhcpOptions::classless_static_routes`] - <https://www.rfc-editor.org/rfc/rfc3442>", "ms-classless-static-route", (Vec<(Ipv4Net, Ipv4Addr)>)},
+    {255, End, "end-of-list marker - <https://www.rfc-editor.org/rfc/rfc2132.html>", [255]}
 );
 /// ex
 /// ```rust
@@ -153,9 +185,21 @@ dhcproto_macros::declare_codes!(
 ///          v4::OptionCode::DomainName,
 ///       ]));
 /// ```
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+///
+/// Options are kept in a `Vec` sorted by code, one per code. With the `smallvec`
+/// feature the first [`INLINE_OPTIONS`] are stored inline, so decoding a typical
+/// message doesn't allocate for its option list.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
-pub struct DhcpOptions(HashMap<OptionCode, DhcpOption>);
+pub struct DhcpOptions(Storage);
+
+/// the number of options [`DhcpOptions`] holds without allocating, with the `smallvec`
+/// feature
+pub const INLINE_OPTIONS: usize = 16;
+
+#[cfg(not(feature = "smallvec"))]
+type Storage = Vec<(OptionCode, DhcpOption)>;
+#[cfg(feature = "smallvec")]
+type Storage = smallvec::SmallVec<[(OptionCode, DhcpOption); INLINE_OPTIONS]>;
 
 impl DhcpOptions {
     /// Create new [`DhcpOptions`]
@@ -164,34 +208,109 @@ impl DhcpOptions {
     pub fn new() -> Self {
         Self::default()
     }
-    /// Get the data for a particular [`OptionCode`]
+    /// Get the data for a particular [`OptionCode`]. An option inserted as
+    /// [`DhcpOption::Unknown`] with a known code is returned as its typed variant, see
+    /// [`DhcpOptions::insert`]
     ///
     /// [`OptionCode`]: crate::v4::OptionCode
     pub fn get(&self, code: OptionCode) -> Option<&DhcpOption> {
-        self.0.get(&code)
+        let i = self.position(code).ok()?;
+        Some(&self.0[i].1)
+    }
+    // index of `code`, or where it would be inserted
+    fn position(&self, code: OptionCode) -> Result<usize, usize> {
+        self.0.binary_search_by_key(&code, |(code, _)| *code)
+    }
+    /// Get the value of an option by its marker type in [`typed`], without matching on
+    /// the [`DhcpOption`]
+    ///
+    /// ```
+    /// # use dhcproto::v4::{typed, DhcpOption, DhcpOptions};
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::SubnetMask([255, 255, 255, 0].into()));
+    /// opts.insert(DhcpOption::ClientNetworkInterface(1, 2, 3));
+    ///
+    /// assert_eq!(opts.get_typed::<typed::SubnetMask>(), Some(&[255, 255, 255, 0].into()));
+    /// assert_eq!(opts.get_typed::<typed::ClientNetworkInterface>(), Some((&1, &2, &3)));
+    /// assert_eq!(opts.get_typed::<typed::Router>(), None);
+    /// ```
+    pub fn get_typed<T: TypedOption>(&self) -> Option<T::Value<'_>> {
+        self.get(T::CODE).and_then(T::value)
+    }
+    /// Get the option with `code` converted to `T`, with an error saying why if it
+    /// isn't there or isn't the variant `T` converts from, e.g. because its data
+    /// couldn't be decoded and it was kept as [`DhcpOption::Unknown`]
+    ///
+    /// ```
+    /// # use dhcproto::v4::{AutoConfig, DhcpOption, DhcpOptions, MessageType, OptionCode};
+    /// # use dhcproto::v4::{UnknownOption, WrongVariant};
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::MessageType(MessageType::Ack));
+    /// // not a valid auto-config value
+    /// opts.insert(DhcpOption::Unknown(UnknownOption::new(OptionCode::DisableSLAAC, vec![5])));
+    ///
+    /// assert_eq!(opts.get_as::<MessageType>(OptionCode::MessageType), Ok(MessageType::Ack));
+    /// assert_eq!(
+    ///     opts.get_as::<MessageType>(OptionCode::Router),
+    ///     Err(WrongVariant::Missing(OptionCode::Router))
+    /// );
+    /// assert_eq!(
+    ///     opts.get_as::<AutoConfig>(OptionCode::DisableSLAAC),
+    ///     Err(WrongVariant::Unknown(OptionCode::DisableSLAAC))
+    /// );
+    /// let raw = opts.get_as::<&UnknownOption>(OptionCode::DisableSLAAC).unwrap();
+    /// assert_eq!(raw.data(), [5]);
+    /// ```
+    pub fn get_as<'a, T>(&'a self, code: OptionCode) -> Result<T, WrongVariant>
+    where
+        T: TryFrom<&'a DhcpOption>,
+    {
+        let opt = self.get(code).ok_or(WrongVariant::Missing(code))?;
+        T::try_from(opt).map_err(|_| WrongVariant::of(opt))
     }
     /// Get the mutable data for a particular [`OptionCode`]
     ///
     /// [`OptionCode`]: crate::v4::OptionCode
     pub fn get_mut(&mut self, code: OptionCode) -> Option<&mut DhcpOption> {
-        self.0.get_mut(&code)
+        let i = self.position(code).ok()?;
+        Some(&mut self.0[i].1)
     }
     /// remove option
     pub fn remove(&mut self, code: OptionCode) -> Option<DhcpOption> {
-        self.0.remove(&code)
+        let i = self.position(code).ok()?;
+        Some(self.0.remove(i).1)
     }
     /// insert a new [`DhcpOption`]
     ///
+    /// Options are keyed by their code, a [`DhcpOption::Unknown`] with a known code
+    /// replaces the typed option with that code. Its data is decoded with
+    /// [`DhcpOption::into_known`], it's kept as `Unknown` if the data isn't valid or
+    /// the code is one of [`RFC3942_RECLASSIFIED`], which a
+    /// [`Decoder::site_local_opaque`] decoder keeps opaque on purpose.
+    ///
     /// ```
-    /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions};
+    /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions, OptionCode, UnknownOption};
     /// let mut opts = DhcpOptions::new();
     /// opts.insert(DhcpOption::MessageType(MessageType::Discover));
+    /// opts.insert(DhcpOption::Unknown(UnknownOption::new(OptionCode::Router, vec![10, 0, 0, 1])));
+    /// assert_eq!(opts.get(OptionCode::Router), Some(&DhcpOption::Router(vec![[10, 0, 0, 1].into()])));
     /// ```
     /// [`DhcpOption`]: crate::v4::DhcpOption
     pub fn insert(&mut self, opt: DhcpOption) -> Option<DhcpOption> {
-        self.0.insert((&opt).into(), opt)
+        let opt = into_keyed(opt);
+        self.insert_keyed((&opt).into(), opt)
+    }
+    // insert `opt` under `code`, returning the option it replaces
+    fn insert_keyed(&mut self, code: OptionCode, opt: DhcpOption) -> Option<DhcpOption> {
+        match self.position(code) {
+            Ok(i) => Some(std::mem::replace(&mut self.0[i].1, opt)),
+            Err(i) => {
+                self.0.insert(i, (code, opt));
+                None
+            }
+        }
     }
-    /// iterate over entries
+    /// iterate over entries, in code order
     /// ```
     /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions};
     /// let mut opts = DhcpOptions::new();
@@ -202,11 +321,11 @@ impl DhcpOptions {
     /// }
     /// ```
     pub fn iter(&self) -> impl Iterator<Item = (&OptionCode, &DhcpOption)> {
-        self.0.iter()
+        self.0.iter().map(|(code, opt)| (code, opt))
     }
-    /// iterate mutably over entries
+    /// iterate mutably over entries, in code order
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&OptionCode, &mut DhcpOption)> {
-        self.0.iter_mut()
+        self.0.iter_mut().map(|(code, opt)| (&*code, opt))
     }
     /// return message type
     /// ```
@@ -219,9 +338,83 @@ impl DhcpOptions {
         let opt = self.get(OptionCode::MessageType)?;
         match opt {
             DhcpOption::MessageType(mtype) => Some(*mtype),
-            _ => unreachable!("cannot return different option for MessageType"),
+            // kept as `Unknown` if its data isn't a valid message type
+            _ => None,
         }
     }
+    /// The client's timezone, by precedence: a valid TZ database name (101), a valid
+    /// POSIX `TZ` string (100), then the `TimeOffset` (2) that RFC 4833 deprecates.
+    /// Invalid or non UTF-8 strings are skipped.
+    /// <https://datatracker.ietf.org/doc/html/rfc4833>
+    /// ```
+    /// # use dhcproto::{tz::Timezone, v4::{DhcpOption, DhcpOptions}};
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::TimeOffset(3600));
+    /// assert_eq!(opts.timezone(), Some(Timezone::Offset(3600)));
+    /// opts.insert(DhcpOption::TZDatabaseString("Europe/Zurich".into()));
+    /// assert_eq!(opts.timezone(), Some(Timezone::Tzdb("Europe/Zurich".into())));
+    /// ```
+    pub fn timezone(&self) -> Option<Timezone> {
+        let string = |code, valid: fn(&str) -> bool| match self.get(code) {
+            Some(DhcpOption::TZDatabaseString(s) | DhcpOption::TZPosixString(s)) => {
+                s.as_str().filter(|s| valid(s)).map(str::to_owned)
+            }
+            _ => None,
+        };
+        if let Some(name) = string(OptionCode::TZDatabaseString, tz::is_tzdb_name) {
+            return Some(Timezone::Tzdb(name));
+        }
+        if let Some(posix) = string(OptionCode::TZPosixString, tz::is_posix_tz) {
+            return Some(Timezone::Posix(posix));
+        }
+        match self.get(OptionCode::TimeOffset) {
+            Some(DhcpOption::TimeOffset(offset)) => Some(Timezone::Offset(*offset)),
+            _ => None,
+        }
+    }
+    /// The classless static routes of `ClasslessStaticRoute` (121) & its Microsoft
+    /// alias `MicrosoftClasslessStaticRoute` (249), which networks often send both of.
+    /// Routes from 121 come first & take precedence, a 249 route is only added if 121
+    /// has no route to the same destination.
+    /// <https://datatracker.ietf.org/doc/html/rfc3442>
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, DhcpOptions};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut opts = DhcpOptions::new();
+    /// opts.insert(DhcpOption::ClasslessStaticRoute(vec![
+    ///     ("10.0.0.0/8".parse()?, "192.168.0.1".parse()?),
+    /// ]));
+    /// opts.insert(DhcpOption::MicrosoftClasslessStaticRoute(vec![
+    ///     ("10.0.0.0/8".parse()?, "192.168.0.2".parse()?),
+    ///     ("172.16.0.0/12".parse()?, "192.168.0.2".parse()?),
+    /// ]));
+    /// assert_eq!(
+    ///     opts.classless_static_routes(),
+    ///     [
+    ///         ("10.0.0.0/8".parse()?, "192.168.0.1".parse()?),
+    ///         ("172.16.0.0/12".parse()?, "192.168.0.2".parse()?),
+    ///     ]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn classless_static_routes(&self) -> Vec<(Ipv4Net, Ipv4Addr)> {
+        let mut routes = match self.get(OptionCode::ClasslessStaticRoute) {
+            Some(DhcpOption::ClasslessStaticRoute(routes)) => routes.clone(),
+            _ => Vec::new(),
+        };
+        if let Some(DhcpOption::MicrosoftClasslessStaticRoute(ms)) =
+            self.get(OptionCode::MicrosoftClasslessStaticRoute)
+        {
+            let from_121 = routes.len();
+            for route in ms {
+                if !routes[..from_121].iter().any(|(dest, _)| *dest == route.0) {
+                    routes.push(*route);
+                }
+            }
+        }
+        routes
+    }
     /// determine if options contains a specific message type
     /// ```
     /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions};
@@ -255,12 +448,24 @@ impl DhcpOptions {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+    /// Add the options of `other`, `policy` decides which option is kept for codes in
+    /// both, see [`merge`](crate::merge)
+    pub fn merge(&mut self, other: &DhcpOptions, policy: &MergePolicy<OptionCode>) {
+        for (code, opt) in other.iter() {
+            match (policy.precedence(code), self.position(*code)) {
+                (Precedence::KeepExisting, Ok(_)) => {}
+                (_, Ok(i)) => self.0[i].1 = opt.clone(),
+                (_, Err(i)) => self.0.insert(i, (*code, opt.clone())),
+            }
+        }
+    }
     /// Retains only the elements specified by the predicate
     pub fn retain<F>(&mut self, pred: F)
     where
         F: FnMut(&OptionCode, &mut DhcpOption) -> bool,
     {
-        self.0.retain(pred)
+        let mut pred = pred;
+        self.0.retain_mut(|(code, opt)| pred(code, opt))
     }
     /// Returns number of Options
     /// ```
@@ -272,40 +477,146 @@ impl DhcpOptions {
     pub fn len(&self) -> usize {
         self.0.len()
     }
+    /// Returns the options added, removed or changed in `other` compared to `self`,
+    /// ordered by option code
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, DhcpOptions};
+    /// let mut old = DhcpOptions::new();
+    /// old.insert(DhcpOption::AddressLeaseTime(3600.into()));
+    /// let mut new = old.clone();
+    /// new.insert(DhcpOption::AddressLeaseTime(7200.into()));
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.changed, vec![(old.get(51.into()).unwrap(), new.get(51.into()).unwrap())]);
+    /// assert!(diff.added.is_empty() && diff.removed.is_empty());
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a DhcpOptions) -> OptionsDiff<'a, DhcpOption> {
+        let mut codes = self
+            .0
+            .iter()
+            .chain(other.0.iter())
+            .map(|(code, _)| *code)
+            .collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.dedup();
+
+        let mut diff = OptionsDiff::default();
+        for code in codes {
+            match (self.get(code), other.get(code)) {
+                (Some(old), Some(new)) if old != new => diff.changed.push((old, new)),
+                (Some(old), None) => diff.removed.push(old),
+                (None, Some(new)) => diff.added.push(new),
+                _ => {}
+            }
+        }
+        diff
+    }
 }
 
 impl IntoIterator for DhcpOptions {
     type Item = (OptionCode, DhcpOption);
-    type IntoIter = std::collections::hash_map::IntoIter<OptionCode, DhcpOption>;
+    type IntoIter = OptionsIntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        OptionsIntoIter(self.0.into_iter())
+    }
+}
+
+/// An owning iterator over [`DhcpOptions`], in code order
+#[derive(Debug)]
+pub struct OptionsIntoIter(<Storage as IntoIterator>::IntoIter);
+
+impl Iterator for OptionsIntoIter {
+    type Item = (OptionCode, DhcpOption);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+// serialized as a map of code to option
+#[cfg(feature = "serde")]
+impl Serialize for DhcpOptions {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Map<'a>(&'a DhcpOptions);
+
+        impl Serialize for Map<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map(self.0.iter())
+            }
+        }
+
+        serializer.serialize_newtype_struct("DhcpOptions", &Map(self))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DhcpOptions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "DhcpOptions")]
+        struct Map(std::collections::HashMap<OptionCode, DhcpOption>);
+
+        let map = Map::deserialize(deserializer)?;
+        Ok(map.0.into_iter().collect())
+    }
+}
+
+// the option as it's stored in `DhcpOptions`, site-local codes stay opaque
+fn into_keyed(opt: DhcpOption) -> DhcpOption {
+    match &opt {
+        DhcpOption::Unknown(unknown) if RFC3942_RECLASSIFIED.contains(&unknown.code) => opt,
+        _ => opt.into_known(),
     }
 }
 
 impl FromIterator<DhcpOption> for DhcpOptions {
     fn from_iter<T: IntoIterator<Item = DhcpOption>>(iter: T) -> Self {
-        DhcpOptions(
-            iter.into_iter()
-                .map(|opt| ((&opt).into(), opt))
-                .collect::<HashMap<OptionCode, DhcpOption>>(),
-        )
+        iter.into_iter()
+            .map(into_keyed)
+            .map(|opt| ((&opt).into(), opt))
+            .collect()
     }
 }
 
 impl FromIterator<(OptionCode, DhcpOption)> for DhcpOptions {
     fn from_iter<T: IntoIterator<Item = (OptionCode, DhcpOption)>>(iter: T) -> Self {
-        DhcpOptions(iter.into_iter().collect::<HashMap<_, _>>())
+        let mut opts = DhcpOptions::new();
+        for (code, opt) in iter {
+            opts.insert_keyed(code, opt);
+        }
+        opts
     }
 }
 
 impl Decodable for DhcpOptions {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         // represented as a vector in the actual message
-        let mut opts = HashMap::new();
-        // should we error the whole parser if we fail to parse an
-        // option or just stop parsing options? -- here we will just stop
-        while let Ok(opt) = DhcpOption::decode(decoder) {
+        let mut opts = DhcpOptions::new();
+        // running out of data without an `End` isn't a malformed option
+        while decoder.remaining() > 0 {
+            let start = decoder.offset();
+            let opt = match DhcpOption::decode(decoder) {
+                Ok(opt) => opt,
+                // the option was read whole by its length, only its value is malformed.
+                // A lenient decoder skips it so one bad option doesn't drop the rest
+                Err(err) if !decoder.is_strict() && decoder.offset() > start => {
+                    decoder.skipped(start, err);
+                    continue;
+                }
+                // a strict decoder, or the option runs past the end of the data
+                Err(err) => {
+                    decoder.stopped(&err);
+                    if decoder.is_strict() {
+                        return Err(err);
+                    }
+                    break;
+                }
+            };
             // we throw away PAD bytes here
             match opt {
                 DhcpOption::End => {
@@ -313,49 +624,150 @@ impl Decodable for DhcpOptions {
                 }
                 DhcpOption::Pad => {}
                 _ => {
-                    opts.insert(OptionCode::from(&opt), opt);
+                    let code = OptionCode::from(&opt);
+                    if let Some(observer) = decoder.observed() {
+                        match opt {
+                            DhcpOption::Unknown(_) => observer.on_unknown(ObservedCode::V4(code)),
+                            _ => observer.on_option(ObservedCode::V4(code)),
+                        }
+                    }
+                    opts.insert_keyed(code, opt);
                 }
             }
         }
-        Ok(DhcpOptions(opts))
+        Ok(opts)
     }
 }
 
 impl Encodable for DhcpOptions {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         if self.0.is_empty() {
-            Ok(())
-        } else {
-            // encode all opts adding the `End` afterwards
-            // sum all bytes written
-            match self.get(OptionCode::RelayAgentInformation) {
-                // agent info must be placed last before `End`
-                Some(agent_info) => self
-                    .0
-                    .iter()
-                    .filter(|opt| *opt.0 != OptionCode::RelayAgentInformation)
-                    .chain(iter::once((&OptionCode::RelayAgentInformation, agent_info)))
-                    .chain(iter::once((&OptionCode::End, &DhcpOption::End)))
-                    .try_for_each(|(_, opt)| opt.encode(e)),
-                None => self
-                    .0
-                    .iter()
-                    .chain(iter::once((&OptionCode::End, &DhcpOption::End)))
-                    .try_for_each(|(_, opt)| opt.encode(e)),
+            return Ok(());
+        }
+        // options are kept in code order, agent info must be placed last before `End`
+        let agent_info = self.get(OptionCode::RelayAgentInformation);
+        self.0
+            .iter()
+            .filter(|(code, _)| *code != OptionCode::RelayAgentInformation)
+            .map(|(_, opt)| opt)
+            .chain(agent_info)
+            .chain(iter::once(&DhcpOption::End))
+            .try_for_each(|opt| opt.encode(e))
+    }
+}
+
+option_code_common!(OptionCode, u8);
+
+/// An option with a known code and value type, implemented by the marker types in
+/// [`typed`]
+pub trait TypedOption {
+    /// the option's code
+    const CODE: OptionCode;
+    /// a reference to the option's value, a tuple of references if it has
+    /// more than one field or `()` if it has none
+    type Value<'a>;
+    /// get the value from `opt`, `None` if it's a different option
+    fn value(opt: &DhcpOption) -> Option<Self::Value<'_>>;
+}
+
+/// Why an option couldn't be returned as the requested type, see
+/// [`DhcpOptions::get_as`]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrongVariant {
+    /// there is no option with the code
+    #[error("option {0} is missing")]
+    Missing(OptionCode),
+    /// the option's data couldn't be decoded, it was kept as [`DhcpOption::Unknown`]
+    #[error("option {0} couldn't be decoded")]
+    Unknown(OptionCode),
+    /// the option is a variant the type doesn't convert from
+    #[error("option {0} is not the requested type")]
+    Mismatch(OptionCode),
+}
+
+impl WrongVariant {
+    // the error for `opt` not converting to the requested type
+    fn of(opt: &DhcpOption) -> Self {
+        match opt {
+            DhcpOption::Unknown(unknown) => WrongVariant::Unknown(unknown.code()),
+            opt => WrongVariant::Mismatch(opt.into()),
+        }
+    }
+}
+
+// `TryFrom<&DhcpOption>` for the types held by a single variant, for `DhcpOptions::get_as`
+macro_rules! try_from_option {
+    ($($variant:ident => $ty:ty, |$v:ident| $val:expr;)*) => {$(
+        impl<'a> TryFrom<&'a DhcpOption> for $ty {
+            type Error = WrongVariant;
+
+            fn try_from(opt: &'a DhcpOption) -> Result<Self, Self::Error> {
+                match opt {
+                    DhcpOption::$variant($v) => Ok($val),
+                    opt => Err(WrongVariant::of(opt)),
+                }
             }
         }
+    )*};
+}
+
+try_from_option! {
+    MessageType => MessageType, |v| *v;
+    NetBiosNodeType => NodeType, |v| *v;
+    ClientSystemArchitecture => Architecture, |v| *v;
+    DisableSLAAC => AutoConfig, |v| *v;
+    BulkLeaseQueryDhcpState => bulk_query::QueryState, |v| *v;
+    BulkLeaseQueryDataSource => bulk_query::DataSourceFlags, |v| *v;
+    ClientFQDN => &'a fqdn::ClientFQDN, |v| v;
+    RelayAgentInformation => &'a relay::RelayAgentInformation, |v| v;
+    SipServers => &'a SipServers, |v| v;
+    Unknown => &'a UnknownOption, |v| v;
+}
+
+impl OptionCode {
+    /// iterate over every option code known to this crate, in code order
+    pub fn iter_known() -> impl Iterator<Item = OptionCode> {
+        Self::KNOWN.iter().copied()
+    }
+}
+
+/// Parses an option code from its canonical name (`"domain-name-servers"`) or variant name
+/// (`"DomainNameServer"`), ignoring case, or from its number (`"6"`)
+impl FromStr for OptionCode {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.parse::<u8>() {
+            return Ok(n.into());
+        }
+        Self::iter_known()
+            .find(|code| {
+                [code.name(), code.variant_name()]
+                    .into_iter()
+                    .flatten()
+                    .any(|name| name.eq_ignore_ascii_case(s))
+            })
+            .ok_or_else(|| ParseNameError::new(s))
     }
 }
 
-impl PartialOrd for OptionCode {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+/// Prints the canonical name, or the number for unknown codes
+impl fmt::Display for OptionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.as_u8()),
+        }
     }
 }
 
-impl Ord for OptionCode {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        u8::from(*self).cmp(&u8::from(*other))
+#[cfg(feature = "redact")]
+impl OptionCode {
+    /// options whose contents are masked in `Debug` output, see [`redact`](crate::redact).
+    /// Authentication (90) isn't decoded, its key material is masked in the
+    /// [`UnknownOption`] that holds it
+    fn is_redacted(&self) -> bool {
+        *self == OptionCode::ClientIdentifier || self.as_u8() == 90
     }
 }
 
@@ -399,6 +811,8 @@ pub enum Architecture {
     Unknown(u16),
 }
 
+display_unknown!(Architecture);
+
 impl From<u16> for Architecture {
     fn from(n: u16) -> Self {
         use Architecture::*;
@@ -453,6 +867,8 @@ pub enum NodeType {
     Unknown(u8),
 }
 
+display_unknown!(NodeType);
+
 impl From<u8> for NodeType {
     fn from(n: u8) -> Self {
         use NodeType::*;
@@ -490,6 +906,12 @@ pub enum AutoConfig {
     AutoConfigure = 1,
 }
 
+impl fmt::Display for AutoConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
 impl TryFrom<u8> for AutoConfig {
     type Error = crate::error::DecodeError;
 
@@ -497,14 +919,85 @@ impl TryFrom<u8> for AutoConfig {
         match value {
             0 => Ok(AutoConfig::DoNotAutoConfigure),
             1 => Ok(AutoConfig::AutoConfigure),
-            _ => Err(super::DecodeError::IoError(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            _ => Err(super::DecodeError::invalid(
                 "invalid number in disable SLAAC autoconfig",
-            ))),
+            )),
         }
     }
 }
 
+/// SIP server list, sent either as domain names or IPv4 addresses
+/// <https://datatracker.ietf.org/doc/html/rfc3361#section-3>
+///
+/// On the wire the first byte (`enc`) says which form the rest of the option takes.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SipServers {
+    /// `enc` 0 - list of domain names, in order of preference
+    Domains(Vec<Domain>),
+    /// `enc` 1 - list of IPv4 addresses, in order of preference
+    Addrs(Vec<Ipv4Addr>),
+}
+
+impl SipServers {
+    /// the `enc` byte for this list
+    pub fn enc(&self) -> u8 {
+        match self {
+            SipServers::Domains(_) => 0,
+            SipServers::Addrs(_) => 1,
+        }
+    }
+}
+
+impl Decodable for SipServers {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let len = decoder.buffer().len();
+        match decoder.read_u8()? {
+            0 => Ok(SipServers::Domains(decoder.read_domains(len - 1)?)),
+            1 => Ok(SipServers::Addrs(decoder.read_ipv4s(len - 1)?)),
+            _ => Err(super::DecodeError::invalid(
+                "SIP servers enc must be 0 or 1",
+            )),
+        }
+    }
+}
+
+impl Encodable for SipServers {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u8(self.enc())?;
+        match self {
+            SipServers::Domains(names) => {
+                let mut buf = Vec::new();
+                let mut writer = DomainWriter::new(&mut buf, true);
+                for name in names {
+                    writer.write(name);
+                }
+                e.write_slice(&buf)?;
+            }
+            SipServers::Addrs(addrs) => {
+                for addr in addrs {
+                    e.write_u32((*addr).into())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// decoder over an option value that must be exactly `expected` bytes
+fn read_fixed<'a>(
+    decoder: &mut Decoder<'a>,
+    len: usize,
+    expected: usize,
+) -> DecodeResult<Decoder<'a>> {
+    if len != expected {
+        return Err(super::DecodeError::invalid(
+            "option length doesn't match its fixed size",
+        ));
+    }
+    decoder.sub_decoder(len)
+}
+
 #[inline]
 fn decode_inner(
     code: OptionCode,
@@ -525,13 +1018,13 @@ fn decode_inner(
         OptionCode::LprServer => LprServer(decoder.read_ipv4s(len)?),
         OptionCode::ImpressServer => ImpressServer(decoder.read_ipv4s(len)?),
         OptionCode::ResourceLocationServer => ResourceLocationServer(decoder.read_ipv4s(len)?),
-        OptionCode::Hostname => Hostname(decoder.read_string(len)?),
+        OptionCode::Hostname => Hostname(decoder.read_option_string(len)?),
         OptionCode::BootFileSize => BootFileSize(decoder.read_u16()?),
-        OptionCode::MeritDumpFile => MeritDumpFile(decoder.read_string(len)?),
-        OptionCode::DomainName => DomainName(decoder.read_string(len)?),
+        OptionCode::MeritDumpFile => MeritDumpFile(decoder.read_option_string(len)?),
+        OptionCode::DomainName => DomainName(decoder.read_option_string(len)?),
         OptionCode::SwapServer => SwapServer(decoder.read_ipv4(len)?),
-        OptionCode::RootPath => RootPath(decoder.read_string(len)?),
-        OptionCode::ExtensionsPath => ExtensionsPath(decoder.read_string(len)?),
+        OptionCode::RootPath => RootPath(decoder.read_option_string(len)?),
+        OptionCode::ExtensionsPath => ExtensionsPath(decoder.read_option_string(len)?),
         OptionCode::IpForwarding => IpForwarding(decoder.read_bool()?),
         OptionCode::NonLocalSrcRouting => NonLocalSrcRouting(decoder.read_bool()?),
         OptionCode::PolicyFilter => PolicyFilter(decoder.read_pair_ipv4s(len)?),
@@ -556,10 +1049,10 @@ fn decode_inner(
         OptionCode::TrailerEncapsulated => TrailerEncapsulated(decoder.read_bool()?),
         OptionCode::ArpCacheTimeout => ArpCacheTimeout(decoder.read_u32()?),
         OptionCode::EthernetEncapsulation => EthernetEncapsulation(decoder.read_bool()?),
-        OptionCode::DefaultTcpTtl => DefaultIpTtl(decoder.read_u8()?),
+        OptionCode::DefaultTcpTtl => DefaultTcpTtl(decoder.read_u8()?),
         OptionCode::TcpKeepaliveInterval => TcpKeepaliveInterval(decoder.read_u32()?),
         OptionCode::TcpKeepaliveGarbage => TcpKeepaliveGarbage(decoder.read_bool()?),
-        OptionCode::NisDomain => NisDomain(decoder.read_string(len)?),
+        OptionCode::NisDomain => NisDomain(decoder.read_option_string(len)?),
         OptionCode::NisServers => NisServers(decoder.read_ipv4s(len)?),
         OptionCode::NtpServers => NtpServers(decoder.read_ipv4s(len)?),
         OptionCode::VendorExtensions => VendorExtensions(decoder.read_slice(len)?.to_vec()),
@@ -568,11 +1061,11 @@ fn decode_inner(
             NetBiosDatagramDistributionServer(decoder.read_ipv4s(len)?)
         }
         OptionCode::NetBiosNodeType => NetBiosNodeType(decoder.read_u8()?.into()),
-        OptionCode::NetBiosScope => NetBiosScope(decoder.read_string(len)?),
+        OptionCode::NetBiosScope => NetBiosScope(decoder.read_option_string(len)?),
         OptionCode::XFontServer => XFontServer(decoder.read_ipv4s(len)?),
         OptionCode::XDisplayManager => XDisplayManager(decoder.read_ipv4s(len)?),
         OptionCode::RequestedIpAddress => RequestedIpAddress(decoder.read_ipv4(len)?),
-        OptionCode::AddressLeaseTime => AddressLeaseTime(decoder.read_u32()?),
+        OptionCode::AddressLeaseTime => AddressLeaseTime(decoder.read_u32()?.into()),
         OptionCode::OptionOverload => OptionOverload(decoder.read_u8()?),
         OptionCode::MessageType => MessageType(decoder.read_u8()?.into()),
         OptionCode::ServerIdentifier => ServerIdentifier(decoder.read_ipv4(len)?),
@@ -583,15 +1076,15 @@ fn decode_inner(
                 .map(|code| (*code).into())
                 .collect(),
         ),
-        OptionCode::Message => Message(decoder.read_string(len)?),
+        OptionCode::Message => Message(decoder.read_option_string(len)?),
         OptionCode::MaxMessageSize => MaxMessageSize(decoder.read_u16()?),
-        OptionCode::Renewal => Renewal(decoder.read_u32()?),
-        OptionCode::Rebinding => Rebinding(decoder.read_u32()?),
+        OptionCode::Renewal => Renewal(decoder.read_u32()?.into()),
+        OptionCode::Rebinding => Rebinding(decoder.read_u32()?.into()),
         OptionCode::ClassIdentifier => ClassIdentifier(decoder.read_slice(len)?.to_vec()),
         OptionCode::ClientIdentifier => ClientIdentifier(decoder.read_slice(len)?.to_vec()),
-        OptionCode::NwipDomainName => NwipDomainName(decoder.read_string(len)?),
+        OptionCode::NwipDomainName => NwipDomainName(decoder.read_option_string(len)?),
         OptionCode::NwipInformation => NwipInformation(decoder.read_slice(len)?.to_vec()),
-        OptionCode::NispServiceDomain => NispServiceDomain(decoder.read_string(len)?),
+        OptionCode::NispServiceDomain => NispServiceDomain(decoder.read_option_string(len)?),
         OptionCode::NispServers => NispServers(decoder.read_ipv4s(len)?),
         OptionCode::TFTPServerName => TFTPServerName(decoder.read_slice(len)?.to_vec()),
         OptionCode::BootfileName => BootfileName(decoder.read_slice(len)?.to_vec()),
@@ -613,7 +1106,7 @@ fn decode_inner(
             RapidCommit
         }
         OptionCode::RelayAgentInformation => {
-            let mut dec = Decoder::new(decoder.read_slice(len)?);
+            let mut dec = decoder.sub_decoder(len)?;
             RelayAgentInformation(relay::RelayAgentInformation::decode(&mut dec)?)
         }
         OptionCode::BcmsControllerNames => BcmsControllerNames(decoder.read_domains(len)?),
@@ -631,11 +1124,17 @@ fn decode_inner(
         OptionCode::ClientMachineIdentifier => {
             ClientMachineIdentifier(decoder.read_slice(len)?.to_vec())
         }
+        OptionCode::TZPosixString => TZPosixString(decoder.read_option_string(len)?),
+        OptionCode::TZDatabaseString => TZDatabaseString(decoder.read_option_string(len)?),
         OptionCode::Ipv6OnlyPreferred => Ipv6OnlyPreferred(decoder.read_u32()?),
         OptionCode::CaptivePortal => CaptivePortal(decoder.read_str(len)?.parse()?),
         OptionCode::DisableSLAAC => DisableSLAAC(decoder.read_u8()?.try_into()?),
         OptionCode::SubnetSelection => SubnetSelection(decoder.read_ipv4(len)?),
         OptionCode::DomainSearch => DomainSearch(decoder.read_domains(len)?),
+        OptionCode::SipServers => {
+            SipServers(self::SipServers::decode(&mut decoder.sub_decoder(len)?)?)
+        }
+        OptionCode::SztpRedirect => SztpRedirect(sztp::read_uris(&mut decoder.sub_decoder(len)?)?),
         OptionCode::TFTPServerAddress => TFTPServerAddress(decoder.read_ipv4(len)?),
         OptionCode::BulkLeaseQueryStatusCode => {
             let code = decoder.read_u8()?.into();
@@ -644,33 +1143,44 @@ fn decode_inner(
             BulkLeaseQueryStatusCode(code, message)
         }
         OptionCode::BulkLeaseQueryBaseTime => {
-            debug_assert!(len == 4);
-            BulkLeaseQueryBaseTime(decoder.read_u32()?)
+            BulkLeaseQueryBaseTime(read_fixed(decoder, len, 4)?.read_u32()?)
         }
         OptionCode::BulkLeasQueryStartTimeOfState => {
-            debug_assert!(len == 4);
-            BulkLeasQueryStartTimeOfState(decoder.read_u32()?)
+            BulkLeasQueryStartTimeOfState(read_fixed(decoder, len, 4)?.read_u32()?)
         }
         OptionCode::BulkLeaseQueryQueryStartTime => {
-            debug_assert!(len == 4);
-            BulkLeaseQueryQueryStartTime(decoder.read_u32()?)
+            BulkLeaseQueryQueryStartTime(read_fixed(decoder, len, 4)?.read_u32()?)
         }
         OptionCode::BulkLeaseQueryQueryEndTime => {
-            debug_assert!(len == 4);
-            BulkLeaseQueryQueryEndTime(decoder.read_u32()?)
+            BulkLeaseQueryQueryEndTime(read_fixed(decoder, len, 4)?.read_u32()?)
         }
-        OptionCode::BulkLeaseQueryDhcpState => BulkLeaseQueryDhcpState(decoder.read_u8()?.into()),
-        OptionCode::BulkLeaseQueryDataSource => {
-            BulkLeaseQueryDataSource(bulk_query::DataSourceFlags::new(decoder.read_u8()?))
+        OptionCode::BulkLeaseQueryDhcpState => {
+            BulkLeaseQueryDhcpState(read_fixed(decoder, len, 1)?.read_u8()?.into())
+        }
+        OptionCode::BulkLeaseQueryDataSource => BulkLeaseQueryDataSource(
+            bulk_query::DataSourceFlags::new(read_fixed(decoder, len, 1)?.read_u8()?),
+        ),
+        OptionCode::PcpServers => {
+            let mut dec = decoder.sub_decoder(len)?;
+            let mut servers = Vec::new();
+            while !dec.buffer().is_empty() {
+                let list_len = dec.read_u8()? as usize;
+                servers.push(dec.read_ipv4s(list_len)?);
+            }
+            PcpServers(servers)
         }
         OptionCode::ClientFQDN => {
             debug_assert!(len >= 3);
-            let flags = decoder.read_u8()?.into();
+            let flags: fqdn::FqdnFlags = decoder.read_u8()?.into();
             let rcode1 = decoder.read_u8()?;
             let rcode2 = decoder.read_u8()?;
 
-            let mut name_decoder = BinDecoder::new(decoder.read_slice(len - 3)?);
-            let name = Name::read(&mut name_decoder)?;
+            let name = if flags.e() {
+                Domain::read(decoder.read_slice(len - 3)?, &mut 0)?
+            } else {
+                // the deprecated ASCII encoding, as written by `encode`
+                decoder.read_str(len - 3)?.parse()?
+            };
             ClientFQDN(fqdn::ClientFQDN {
                 flags,
                 r1: rcode1,
@@ -678,10 +1188,10 @@ fn decode_inner(
                 domain: name,
             })
         }
-        OptionCode::ClasslessStaticRoute => {
+        OptionCode::ClasslessStaticRoute | OptionCode::MicrosoftClasslessStaticRoute => {
             let mut routes = Vec::new();
 
-            let mut route_dec = Decoder::new(decoder.read_slice(len)?);
+            let mut route_dec = decoder.sub_decoder(len)?;
             while let Ok(prefix_len) = route_dec.read_u8() {
                 if prefix_len > 32 {
                     break;
@@ -699,13 +1209,20 @@ fn decode_inner(
                 routes.push((dest, gw));
             }
 
-            ClasslessStaticRoute(routes)
+            if code == OptionCode::ClasslessStaticRoute {
+                ClasslessStaticRoute(routes)
+            } else {
+                MicrosoftClasslessStaticRoute(routes)
+            }
         }
         OptionCode::End => End,
         // not yet implemented
-        OptionCode::Unknown(code) => {
+        code => {
             let data = decoder.read_slice(len)?.to_vec();
-            Unknown(UnknownOption { code, data })
+            Unknown(UnknownOption {
+                code: code.as_u8(),
+                data,
+            })
         }
     })
 }
@@ -722,12 +1239,20 @@ impl Decodable for DhcpOption {
 
         impl<'a> Opt<'a> {
             #[inline]
-            fn as_option(&self) -> DecodeResult<DhcpOption> {
-                let mut opt_decoder = Decoder::new(&self.buf);
+            // `settings` is the decoder the option was read from
+            fn as_option(&self, settings: &Decoder<'_>) -> DecodeResult<DhcpOption> {
+                if settings.is_site_local_opaque() && RFC3942_RECLASSIFIED.contains(&self.code) {
+                    return Ok(DhcpOption::Unknown(UnknownOption {
+                        code: self.code,
+                        data: self.buf[2..].to_vec(),
+                    }));
+                }
+                let mut opt_decoder = settings.detached(&self.buf);
                 let code = opt_decoder.read_u8()?.into();
                 let _len = opt_decoder.read_u8()?; // throw out potentially invalid len
 
                 decode_inner(code, opt_decoder.buffer().len(), &mut opt_decoder)
+                    .map_err(|err| err.with_code(self.code.into()))
             }
             // can't implement Decodable b/c of lifetime issues
             fn decode(dec: &mut Decoder<'a>) -> DecodeResult<Self> {
@@ -746,7 +1271,7 @@ impl Decodable for DhcpOption {
             match code.into() {
                 OptionCode::End => {
                     return match last {
-                        Some(prev) => prev.as_option(),
+                        Some(prev) => prev.as_option(decoder),
                         None => {
                             decoder.read_u8()?;
                             Ok(O::End)
@@ -755,7 +1280,7 @@ impl Decodable for DhcpOption {
                 }
                 OptionCode::Pad => {
                     return match last {
-                        Some(prev) => prev.as_option(),
+                        Some(prev) => prev.as_option(decoder),
                         None => {
                             decoder.read_u8()?;
                             Ok(O::Pad)
@@ -775,15 +1300,219 @@ impl Decodable for DhcpOption {
                         Some(prev) => {
                             // got different option, decode the one we've got
                             // need to stop here so we don't consume the next option's buffer
-                            return prev.as_option();
+                            return prev.as_option(decoder);
                         }
                     });
                 }
             }
         }
-        last.ok_or(crate::error::DecodeError::NotEnoughBytes)?
-            .as_option()
+        last.ok_or(crate::error::DecodeError::Truncated)?
+            .as_option(decoder)
+    }
+}
+
+impl DhcpOption {
+    /// A `DomainNameServer` of the first `max` distinct `servers` in order, see
+    /// [`dns_servers::clean`]
+    pub fn dns_servers(servers: &[Ipv4Addr], max: usize) -> Result<Self, DnsServerError> {
+        Ok(DhcpOption::DomainNameServer(dns_servers::clean(
+            servers, max,
+        )?))
+    }
+
+    /// Decode the data of an [`DhcpOption::Unknown`] with a code this library knows into
+    /// its typed variant. Other options, unknown codes and data that isn't valid for the
+    /// option are returned unchanged.
+    ///
+    /// ```
+    /// # use dhcproto::v4::{DhcpOption, OptionCode, UnknownOption};
+    /// let opt = DhcpOption::Unknown(UnknownOption::new(OptionCode::Router, vec![192, 168, 0, 1]));
+    /// assert_eq!(opt.into_known(), DhcpOption::Router(vec![[192, 168, 0, 1].into()]));
+    /// ```
+    pub fn into_known(self) -> Self {
+        let DhcpOption::Unknown(UnknownOption { code, data }) = &self else {
+            return self;
+        };
+        match OptionCode::from(*code) {
+            OptionCode::Pad | OptionCode::End => self,
+            code if !code.is_known() => self,
+            code => match decode_inner(code, data.len(), &mut Decoder::new(data)) {
+                Ok(DhcpOption::Unknown(_)) | Err(_) => self,
+                Ok(opt) => opt,
+            },
+        }
+    }
+
+    /// A canonical form of the option, for telling whether a configured value really
+    /// changed, see [`DhcpOption::eq_semantic`]:
+    /// * trailing NULs, which some clients append, are trimmed from strings
+    /// * host & domain names are lowercased
+    /// * lists whose order carries no meaning are sorted: `PolicyFilter`,
+    ///   `PathMtuPlateauTable`, `AssociatedIp`, `ClasslessStaticRoute` &
+    ///   `MicrosoftClasslessStaticRoute`
+    ///
+    /// Lists in order of preference, like `Router` or `DomainNameServer`, are kept as is.
+    ///
+    /// ```
+    /// # use dhcproto::v4::DhcpOption;
+    /// let opt = DhcpOption::Hostname("Printer-2\0".into());
+    /// assert_eq!(opt.normalized(), DhcpOption::Hostname("printer-2".into()));
+    /// ```
+    pub fn normalized(&self) -> Self {
+        use DhcpOption as O;
+        let mut opt = self.clone();
+        match &mut opt {
+            O::Hostname(name) | O::DomainName(name) => {
+                name.trim_nuls();
+                name.make_ascii_lowercase();
+            }
+            O::MeritDumpFile(s)
+            | O::RootPath(s)
+            | O::ExtensionsPath(s)
+            | O::NisDomain(s)
+            | O::NetBiosScope(s)
+            | O::Message(s)
+            | O::NwipDomainName(s)
+            | O::NispServiceDomain(s)
+            | O::TZPosixString(s)
+            | O::TZDatabaseString(s) => s.trim_nuls(),
+            O::BulkLeaseQueryStatusCode(_, s) => trim_nuls(s),
+            O::TFTPServerName(bytes) | O::BootfileName(bytes) => {
+                let len = bytes.len() - bytes.iter().rev().take_while(|b| **b == 0).count();
+                bytes.truncate(len);
+            }
+            O::DomainSearch(domains)
+            | O::BcmsControllerNames(domains)
+            | O::SipServers(SipServers::Domains(domains)) => {
+                for domain in domains {
+                    *domain = domain.to_ascii_lowercase();
+                }
+            }
+            O::ClientFQDN(fqdn) => fqdn.domain = fqdn.domain.to_ascii_lowercase(),
+            O::PolicyFilter(pairs) => pairs.sort_unstable(),
+            O::PathMtuPlateauTable(mtus) => mtus.sort_unstable(),
+            O::AssociatedIp(addrs) => addrs.sort_unstable(),
+            O::ClasslessStaticRoute(routes) | O::MicrosoftClasslessStaticRoute(routes) => {
+                routes.sort_unstable()
+            }
+            _ => {}
+        }
+        opt
     }
+
+    /// `true` if the options are equal once [normalized](DhcpOption::normalized)
+    ///
+    /// ```
+    /// # use dhcproto::v4::DhcpOption;
+    /// let a = DhcpOption::AssociatedIp(vec![[10, 0, 0, 2].into(), [10, 0, 0, 1].into()]);
+    /// let b = DhcpOption::AssociatedIp(vec![[10, 0, 0, 1].into(), [10, 0, 0, 2].into()]);
+    /// assert_ne!(a, b);
+    /// assert!(a.eq_semantic(&b));
+    /// ```
+    pub fn eq_semantic(&self, other: &DhcpOption) -> bool {
+        self == other || self.normalized() == other.normalized()
+    }
+
+    /// The time of an option holding an absolute time, seconds since the Unix epoch:
+    /// `BulkLeaseQueryBaseTime`, `BulkLeaseQueryQueryStartTime` &
+    /// `BulkLeaseQueryQueryEndTime` <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2>.
+    /// `None` for other options.
+    ///
+    /// ```
+    /// # use dhcproto::v4::DhcpOption;
+    /// # use std::time::{Duration, SystemTime};
+    /// let opt = DhcpOption::BulkLeaseQueryBaseTime(1_700_000_000);
+    /// assert_eq!(
+    ///     opt.as_system_time(),
+    ///     Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    /// );
+    /// // relative to the base time, not the epoch
+    /// assert_eq!(DhcpOption::BulkLeasQueryStartTimeOfState(60).as_system_time(), None);
+    /// ```
+    pub fn as_system_time(&self) -> Option<SystemTime> {
+        match self {
+            DhcpOption::BulkLeaseQueryBaseTime(secs)
+            | DhcpOption::BulkLeaseQueryQueryStartTime(secs)
+            | DhcpOption::BulkLeaseQueryQueryEndTime(secs) => Some(time::from_epoch_secs(*secs)),
+            _ => None,
+        }
+    }
+
+    /// The time of an option holding a time relative to when the message was sent,
+    /// seconds before the base time: `ClientLastTransactionTime`
+    /// <https://www.rfc-editor.org/rfc/rfc4388.html#section-6.1> &
+    /// `BulkLeasQueryStartTimeOfState`
+    /// <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.4>. `None` for other
+    /// options.
+    ///
+    /// ```
+    /// # use dhcproto::v4::DhcpOption;
+    /// # use std::time::Duration;
+    /// let opt = DhcpOption::ClientLastTransactionTime(60);
+    /// assert_eq!(opt.as_duration(), Some(Duration::from_secs(60)));
+    /// ```
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            DhcpOption::ClientLastTransactionTime(secs)
+            | DhcpOption::BulkLeasQueryStartTimeOfState(secs) => {
+                Some(Duration::from_secs(*secs as u64))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Iterate over the options in `buf`, the options area of a message after the magic
+/// cookie, as `(code, data)` without decoding them into [`DhcpOptions`]. For fast paths
+/// that need only one or two options to decide whether to decode the whole message.
+///
+/// `Pad` is skipped and iteration stops at `End` or the end of `buf`. The parts of an
+/// option split per RFC 3396 are yielded in order as consecutive items with the same
+/// code, the caller joins them if needed. An option whose length runs past the end of
+/// `buf` yields [`DecodeError::Truncated`](crate::error::DecodeError::Truncated) and
+/// ends the iteration.
+///
+/// ```
+/// use dhcproto::{v4::{self, iter_raw, OptionCode}, Encodable};
+///
+/// let mut msg = v4::Message::default();
+/// msg.opts_mut()
+///     .insert(v4::DhcpOption::MessageType(v4::MessageType::Discover));
+/// let buf = msg.to_vec()?;
+///
+/// let msg_type = iter_raw(&buf[240..])
+///     .filter_map(Result::ok)
+///     .find(|(code, _)| *code == u8::from(OptionCode::MessageType));
+/// assert_eq!(msg_type, Some((53, &[1][..])));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn iter_raw(buf: &[u8]) -> impl Iterator<Item = DecodeResult<(u8, &[u8])>> {
+    let mut pos = 0;
+    iter::from_fn(move || loop {
+        match *buf.get(pos)? {
+            0 => pos += 1,
+            255 => {
+                pos = buf.len();
+                return None;
+            }
+            code => {
+                let data = buf
+                    .get(pos + 1)
+                    .and_then(|&len| buf.get(pos + 2..pos + 2 + len as usize));
+                let Some(data) = data else {
+                    pos = buf.len();
+                    return Some(Err(crate::error::DecodeError::Truncated));
+                };
+                pos += 2 + data.len();
+                return Some(Ok((code, data)));
+            }
+        }
+    })
+}
+
+// drop the NULs some clients terminate strings with
+fn trim_nuls(s: &mut String) {
+    s.truncate(s.trim_end_matches('\0').len());
 }
 
 /// Splits `bytes` into chunks of up to u8::MAX (255 is the max opt length),
@@ -810,25 +1539,24 @@ pub fn encode_long_opt_bytes(
     bytes: &[u8],
     e: &mut Encoder<'_>,
 ) -> EncodeResult<()> {
+    check_split(bytes.len(), e)?;
     for chunk in bytes.chunks(u8::MAX as usize) {
-        e.write_u8(code.into())?;
-        e.write_u8(chunk.len() as u8)?;
-        e.write_slice(chunk)?;
+        e.write_opt_v4(code.into(), |e| e.write_slice(chunk))?;
     }
     Ok(())
 }
 
-/// Encodes a list of domain `Name`s but chunked into u8::MAX pieces,
+/// Encodes a list of [`Domain`]s, compressed, but chunked into u8::MAX pieces,
 /// where each chunk is prepended by the length of the chunk and the code.
 pub fn encode_long_opt_domains(
     code: OptionCode,
-    names: &[Name],
+    names: &[Domain],
     e: &mut Encoder<'_>,
 ) -> EncodeResult<()> {
     let mut buf = Vec::new();
-    let mut name_encoder = BinEncoder::new(&mut buf);
+    let mut writer = DomainWriter::new(&mut buf, true);
     for name in names {
-        name.emit(&mut name_encoder)?;
+        writer.write(name);
     }
     encode_long_opt_bytes(code, &buf, e)?;
     Ok(())
@@ -869,12 +1597,23 @@ where
 {
     // TODO: consider using `mem::size_of::<T>()` so we don't need factor
     // although, we would need to make OptionCode repr(u8)
+    check_split(data.len() * factor, e)?;
     for chunk in data.chunks(u8::MAX as usize / factor) {
-        e.write_u8(code.into())?;
-        e.write_u8((chunk.len() * factor) as u8)?;
-        for thing in chunk {
-            f(thing, e)?;
-        }
+        e.write_opt_v4(code.into(), |e| {
+            for thing in chunk {
+                f(thing, e)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+// an encoder whose profile doesn't split long options can only write `len` in one
+fn check_split(len: usize, e: &Encoder<'_>) -> EncodeResult<()> {
+    let max = u8::MAX as usize;
+    if len > max && !e.encode_profile().split_long_options {
+        return Err(super::EncodeError::TooLong { len, max });
     }
     Ok(())
 }
@@ -893,8 +1632,7 @@ impl Encodable for DhcpOption {
                 e.write_u8(code.into())?;
             }
             O::RapidCommit => {
-                e.write_u8(code.into())?;
-                e.write_u8(0)?;
+                e.write_opt_v4(code.into(), |_| Ok(()))?;
             }
             O::SubnetMask(addr)
             | O::SwapServer(addr)
@@ -904,14 +1642,10 @@ impl Encodable for DhcpOption {
             | O::ServerIdentifier(addr)
             | O::SubnetSelection(addr)
             | O::TFTPServerAddress(addr) => {
-                e.write_u8(code.into())?;
-                e.write_u8(4)?;
-                e.write_u32((*addr).into())?
+                e.write_opt_v4(code.into(), |e| e.write_u32((*addr).into()))?;
             }
             O::TimeOffset(offset) => {
-                e.write_u8(code.into())?;
-                e.write_u8(4)?;
-                e.write_i32(*offset)?
+                e.write_opt_v4(code.into(), |e| e.write_i32(*offset))?;
             }
             O::TimeServer(ips)
             | O::NameServer(ips)
@@ -951,16 +1685,24 @@ impl Encodable for DhcpOption {
             | O::NetBiosScope(s)
             | O::Message(s)
             | O::NwipDomainName(s)
-            | O::NispServiceDomain(s) => {
-                encode_long_opt_bytes(code, s.as_bytes(), e)?;
+            | O::NispServiceDomain(s)
+            | O::TZPosixString(s)
+            | O::TZDatabaseString(s) => {
+                // a decoder may have kept the NUL a client sent, don't pass it on
+                let len = s.len() - s.iter().rev().take_while(|b| **b == 0).count();
+                if e.encode_profile().trailing_nul {
+                    let mut buf = s[..len].to_vec();
+                    buf.push(0);
+                    encode_long_opt_bytes(code, &buf, e)?;
+                } else {
+                    encode_long_opt_bytes(code, &s[..len], e)?;
+                }
             }
             O::BootFileSize(num)
             | O::MaxDatagramSize(num)
             | O::InterfaceMtu(num)
             | O::MaxMessageSize(num) => {
-                e.write_u8(code.into())?;
-                e.write_u8(2)?;
-                e.write_u16(*num)?
+                e.write_opt_v4(code.into(), |e| e.write_u16(*num))?;
             }
             O::IpForwarding(b)
             | O::NonLocalSrcRouting(b)
@@ -971,14 +1713,10 @@ impl Encodable for DhcpOption {
             | O::EthernetEncapsulation(b)
             | O::TcpKeepaliveGarbage(b)
             | O::TrailerEncapsulated(b) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8((*b).into())?
+                e.write_opt_v4(code.into(), |e| e.write_u8((*b).into()))?;
             }
             O::DefaultIpTtl(byte) | O::DefaultTcpTtl(byte) | O::OptionOverload(byte) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8(*byte)?
+                e.write_opt_v4(code.into(), |e| e.write_u8(*byte))?;
             }
             O::StaticRoutingTable(pair_ips) | O::PolicyFilter(pair_ips) => {
                 encode_long_opt_chunks(
@@ -994,9 +1732,6 @@ impl Encodable for DhcpOption {
             }
             O::ArpCacheTimeout(num)
             | O::TcpKeepaliveInterval(num)
-            | O::AddressLeaseTime(num)
-            | O::Renewal(num)
-            | O::Rebinding(num)
             | O::ClientLastTransactionTime(num)
             | O::BulkLeaseQueryBaseTime(num)
             | O::BulkLeasQueryStartTimeOfState(num)
@@ -1004,9 +1739,10 @@ impl Encodable for DhcpOption {
             | O::BulkLeaseQueryQueryEndTime(num)
             | O::PathMtuAgingTimeout(num)
             | O::Ipv6OnlyPreferred(num) => {
-                e.write_u8(code.into())?;
-                e.write_u8(4)?;
-                e.write_u32(*num)?;
+                e.write_opt_v4(code.into(), |e| e.write_u32(*num))?;
+            }
+            O::AddressLeaseTime(time) | O::Renewal(time) | O::Rebinding(time) => {
+                e.write_opt_v4(code.into(), |e| e.write_u32(time.secs()))?;
             }
             O::VendorExtensions(bytes)
             | O::ClassIdentifier(bytes)
@@ -1022,14 +1758,10 @@ impl Encodable for DhcpOption {
                 encode_long_opt_chunks(code, 1, codes, |code, e| e.write_u8((*code).into()), e)?;
             }
             O::NetBiosNodeType(ntype) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8((*ntype).into())?;
+                e.write_opt_v4(code.into(), |e| e.write_u8((*ntype).into()))?;
             }
             O::MessageType(mtype) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8((*mtype).into())?;
+                e.write_opt_v4(code.into(), |e| e.write_u8((*mtype).into()))?;
             }
             O::RelayAgentInformation(relay) => {
                 let mut buf = Vec::new();
@@ -1039,37 +1771,53 @@ impl Encodable for DhcpOption {
                 encode_long_opt_bytes(code, &buf, e)?;
             }
             O::ClientSystemArchitecture(arch) => {
-                e.write_u8(code.into())?;
-                e.write_u8(2)?;
-                e.write_u16((*arch).into())?;
+                e.write_opt_v4(code.into(), |e| e.write_u16((*arch).into()))?;
             }
             O::ClientNetworkInterface(ty, major, minor) => {
-                e.write_u8(code.into())?;
-                e.write_u8(3)?;
-                e.write_u8(*ty)?;
-                e.write_u8(*major)?;
-                e.write_u8(*minor)?;
+                e.write_opt_v4(code.into(), |e| {
+                    e.write_u8(*ty)?;
+                    e.write_u8(*major)?;
+                    e.write_u8(*minor)
+                })?;
+            }
+            O::CaptivePortal(uri) => {
+                encode_long_opt_bytes(code, uri.as_str().as_bytes(), e)?;
             }
-            O::CaptivePortal(url) => {
-                let url = url.to_string();
-                encode_long_opt_bytes(code, url.as_bytes(), e)?;
+            O::PcpServers(servers) => {
+                let mut buf = Vec::new();
+                for addrs in servers {
+                    let list_len = addrs.len() * 4;
+                    buf.push(
+                        u8::try_from(list_len).map_err(|_| super::EncodeError::TooLong {
+                            len: list_len,
+                            max: u8::MAX as usize,
+                        })?,
+                    );
+                    for addr in addrs {
+                        buf.extend(addr.octets());
+                    }
+                }
+                encode_long_opt_bytes(code, &buf, e)?;
+            }
+            O::SipServers(servers) => {
+                encode_long_opt_bytes(code, &servers.to_vec()?, e)?;
+            }
+            O::SztpRedirect(uris) => {
+                let mut buf = Vec::new();
+                sztp::write_uris(&mut Encoder::new(&mut buf), uris)?;
+                encode_long_opt_bytes(code, &buf, e)?;
             }
             O::BulkLeaseQueryStatusCode(status_code, msg) => {
-                e.write_u8(code.into())?;
-                let msg = msg.as_bytes();
-                e.write_u8(msg.len() as u8 + 1)?;
-                e.write_u8((*status_code).into())?;
-                e.write_slice(msg)?
+                e.write_opt_v4(code.into(), |e| {
+                    e.write_u8((*status_code).into())?;
+                    e.write_slice(msg.as_bytes())
+                })?;
             }
             O::BulkLeaseQueryDhcpState(state) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8((*state).into())?
+                e.write_opt_v4(code.into(), |e| e.write_u8((*state).into()))?;
             }
             O::BulkLeaseQueryDataSource(src) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8((*src).into())?
+                e.write_opt_v4(code.into(), |e| e.write_u8((*src).into()))?;
             }
             O::DomainSearch(names) | O::BcmsControllerNames(names) => {
                 encode_long_opt_domains(code, names, e)?
@@ -1083,17 +1831,15 @@ impl Encodable for DhcpOption {
                 } = fqdn;
                 let mut buf = vec![(*flags).into(), *r1, *r2];
                 if flags.e() {
-                    // emits in canonical format
-                    // start encoding at byte 3 because we had some preamble
-                    let mut name_encoder = BinEncoder::with_offset(&mut buf, 3, EncodeMode::Normal);
-                    domain.emit_as_canonical(&mut name_encoder, true)?;
+                    // canonical wire format, without compression
+                    DomainWriter::new(&mut buf, false).write(domain);
                 } else {
                     // TODO: not sure if this is correct
-                    buf.extend(domain.to_ascii().as_bytes());
+                    buf.extend(domain.to_string().as_bytes());
                 }
                 encode_long_opt_bytes(code, &buf, e)?;
             }
-            O::ClasslessStaticRoute(routes) => {
+            O::ClasslessStaticRoute(routes) | O::MicrosoftClasslessStaticRoute(routes) => {
                 let mut buf = Vec::new();
                 let mut route_enc = Encoder::new(&mut buf);
                 for (dest, gw) in routes {
@@ -1109,9 +1855,7 @@ impl Encodable for DhcpOption {
                 encode_long_opt_chunks(code, 2, nums, |num, e| e.write_u16(*num), e)?;
             }
             O::DisableSLAAC(val) => {
-                e.write_u8(code.into())?;
-                e.write_u8(1)?;
-                e.write_u8(*val as u8)?;
+                e.write_opt_v4(code.into(), |e| e.write_u8(*val as u8))?;
             }
             // not yet implemented
             O::Unknown(opt) => {
@@ -1124,23 +1868,44 @@ impl Encodable for DhcpOption {
 
 /// An as-of-yet unimplemented option type
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(not(feature = "redact"), derive(Debug))]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct UnknownOption {
     code: u8,
     data: Vec<u8>,
 }
 
+#[cfg(feature = "redact")]
+impl fmt::Debug for UnknownOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = self.code().is_redacted();
+        f.debug_struct("UnknownOption")
+            .field("code", &self.code)
+            .field("data", &crate::redact::Redacted::new(&self.data, redact))
+            .finish()
+    }
+}
+
 impl UnknownOption {
+    /// create an option with `code` and undecoded `data`
     pub fn new(code: OptionCode, data: Vec<u8>) -> Self {
         Self {
             code: code.into(),
             data,
         }
     }
+    /// create an option from a raw option code, e.g. one this library doesn't know
+    pub fn from_raw(code: u8, data: Vec<u8>) -> Self {
+        Self { code, data }
+    }
     /// return the option code
     pub fn code(&self) -> OptionCode {
         self.code.into()
     }
+    /// return the raw option code
+    pub fn raw_code(&self) -> u8 {
+        self.code
+    }
     /// return the data for this option
     pub fn data(&self) -> &[u8] {
         &self.data
@@ -1162,11 +1927,7 @@ impl Decodable for UnknownOption {
 
 impl Encodable for UnknownOption {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
-        // TODO: account for >255 len
-        e.write_u8(self.code)?;
-        e.write_u8(self.data.len() as u8)?;
-        e.write_slice(&self.data)?;
-        Ok(())
+        e.write_opt_v4(self.code, |e| e.write_slice(&self.data))
     }
 }
 
@@ -1266,6 +2027,104 @@ impl From<MessageType> for u8 {
     }
 }
 
+impl MessageType {
+    /// the message type name from the RFC, e.g. `DHCPDISCOVER`. `None` if unknown
+    pub fn name(&self) -> Option<&'static str> {
+        use MessageType as M;
+        Some(match self {
+            M::Discover => "DHCPDISCOVER",
+            M::Offer => "DHCPOFFER",
+            M::Request => "DHCPREQUEST",
+            M::Decline => "DHCPDECLINE",
+            M::Ack => "DHCPACK",
+            M::Nak => "DHCPNAK",
+            M::Release => "DHCPRELEASE",
+            M::Inform => "DHCPINFORM",
+            M::ForceRenew => "DHCPFORCERENEW",
+            M::LeaseQuery => "DHCPLEASEQUERY",
+            M::LeaseUnassigned => "DHCPLEASEUNASSIGNED",
+            M::LeaseUnknown => "DHCPLEASEUNKNOWN",
+            M::LeaseActive => "DHCPLEASEACTIVE",
+            M::BulkLeaseQuery => "DHCPBULKLEASEQUERY",
+            M::LeaseQueryDone => "DHCPLEASEQUERYDONE",
+            M::ActiveLeaseQuery => "DHCPACTIVELEASEQUERY",
+            M::LeaseQueryStatus => "DHCPLEASEQUERYSTATUS",
+            M::Tls => "DHCPTLS",
+            M::Unknown(_) => return None,
+        })
+    }
+
+    /// The message types a server may answer this one with, empty if it gets no reply
+    /// (Decline, Release) or isn't sent to a server.
+    ///
+    /// ```rust
+    /// use dhcproto::v4::MessageType;
+    ///
+    /// assert_eq!(MessageType::Discover.expected_reply(), [MessageType::Offer]);
+    /// assert!(MessageType::Request
+    ///     .expected_reply()
+    ///     .contains(&MessageType::Nak));
+    /// assert!(MessageType::Release.expected_reply().is_empty());
+    /// ```
+    pub fn expected_reply(&self) -> &'static [MessageType] {
+        use MessageType as M;
+        match self {
+            M::Discover => &[M::Offer],
+            M::Request => &[M::Ack, M::Nak],
+            M::Inform => &[M::Ack],
+            // https://www.rfc-editor.org/rfc/rfc4388#section-6.1
+            M::LeaseQuery => &[M::LeaseUnassigned, M::LeaseUnknown, M::LeaseActive],
+            // https://www.rfc-editor.org/rfc/rfc6926#section-6.2
+            M::BulkLeaseQuery => &[
+                M::LeaseActive,
+                M::LeaseUnassigned,
+                M::LeaseQueryDone,
+                M::LeaseQueryStatus,
+            ],
+            // https://www.rfc-editor.org/rfc/rfc7724#section-6.1
+            M::ActiveLeaseQuery => &[
+                M::LeaseActive,
+                M::LeaseUnassigned,
+                M::LeaseQueryStatus,
+                M::Tls,
+            ],
+            M::Tls => &[M::Tls],
+            _ => &[],
+        }
+    }
+}
+
+/// Prints the RFC name, or the number for unknown types
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", u8::from(*self)),
+        }
+    }
+}
+
+/// Parses a message type from its RFC name with or without the `DHCP` prefix
+/// (`"DHCPDISCOVER"`, `"discover"`), ignoring case, or from its number (`"1"`)
+impl FromStr for MessageType {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.parse::<u8>() {
+            return Ok(n.into());
+        }
+        (1..=u8::MAX)
+            .map(MessageType::from)
+            .take_while(|ty| !matches!(ty, MessageType::Unknown(_)))
+            .find(|ty| {
+                ty.name().is_some_and(|name| {
+                    name.eq_ignore_ascii_case(s) || name[4..].eq_ignore_ascii_case(s)
+                })
+            })
+            .ok_or_else(|| ParseNameError::new(s))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::VecDeque;
@@ -1286,6 +2145,41 @@ mod tests {
         assert_eq!(decoded, orig);
         Ok(())
     }
+    #[test]
+    fn test_encode_lengths() {
+        let msg = "a".repeat(300);
+        let opt = DhcpOption::BulkLeaseQueryStatusCode(bulk_query::Code::Success, msg.clone());
+        assert!(matches!(
+            opt.to_vec(),
+            Err(crate::error::EncodeError::TooLong { len: 301, max: 255 })
+        ));
+        let opt = DhcpOption::BulkLeaseQueryStatusCode(bulk_query::Code::Success, msg[..2].into());
+        assert_eq!(opt.to_vec().unwrap(), [151, 3, 0, b'a', b'a']);
+        assert!(UnknownOption::new(OptionCode::new(240), msg.into())
+            .to_vec()
+            .is_err());
+        assert_eq!(DhcpOption::RapidCommit.to_vec().unwrap(), [80, 0]);
+    }
+
+    #[test]
+    fn test_iter_raw() {
+        let buf = [
+            53, 1, 3, 0, 0, 12, 2, b'a', b'b', 12, 1, b'c', 255, 61, 1, 1,
+        ];
+        let opts = iter_raw(&buf).collect::<DecodeResult<Vec<_>>>().unwrap();
+        assert_eq!(opts, [(53, &[3][..]), (12, &b"ab"[..]), (12, &b"c"[..])]);
+        // no `End`
+        assert_eq!(iter_raw(&buf[..3]).count(), 1);
+
+        let mut iter = iter_raw(&[53, 1, 3, 61, 4, 1]);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(matches!(
+            iter.next(),
+            Some(Err(crate::error::DecodeError::Truncated))
+        ));
+        assert!(iter.next().is_none());
+    }
+
     #[test]
     fn test_opts() -> Result<()> {
         let (input, len) = binput();
@@ -1356,13 +2250,24 @@ mod tests {
     #[test]
     fn test_str() -> Result<()> {
         test_opt(
-            DhcpOption::Hostname("foobar.com".to_string()),
+            DhcpOption::Hostname("foobar.com".into()),
             vec![12, 10, 102, 111, 111, 98, 97, 114, 46, 99, 111, 109],
         )?;
 
         Ok(())
     }
     #[test]
+    fn test_timezone() -> Result<()> {
+        let mut res = vec![100, 7];
+        res.extend(b"CET-1CE");
+        test_opt(DhcpOption::TZPosixString("CET-1CE".into()), res)?;
+
+        let mut res = vec![101, 13];
+        res.extend(b"Europe/Zurich");
+        test_opt(DhcpOption::TZDatabaseString("Europe/Zurich".into()), res)?;
+        Ok(())
+    }
+    #[test]
     fn test_byte() -> Result<()> {
         test_opt(DhcpOption::DefaultIpTtl(10), vec![23, 1, 10])?;
 
@@ -1370,7 +2275,7 @@ mod tests {
     }
     #[test]
     fn test_num() -> Result<()> {
-        test_opt(DhcpOption::Renewal(30), vec![58, 4, 0, 0, 0, 30])?;
+        test_opt(DhcpOption::Renewal(30.into()), vec![58, 4, 0, 0, 0, 30])?;
         Ok(())
     }
     #[test]
@@ -1416,13 +2321,447 @@ mod tests {
         res.extend(url);
 
         test_opt(
-            DhcpOption::CaptivePortal("https://foobar.com".parse()?), // url parse will add trailing slash
+            DhcpOption::CaptivePortal("https://foobar.com/".parse()?),
             res,
         )?;
 
         Ok(())
     }
 
+    #[test]
+    fn test_sztp_redirect() -> Result<()> {
+        let mut res = vec![143, 44, 0, 20];
+        res.extend(b"https://a.example/b/");
+        res.extend([0, 20]);
+        res.extend(b"https://c.example/d/");
+
+        test_opt(
+            DhcpOption::SztpRedirect(vec![
+                "https://a.example/b/".parse()?,
+                "https://c.example/d/".parse()?,
+            ]),
+            res,
+        )?;
+
+        // non-https URIs are rejected
+        let mut bad = vec![143, 21, 0, 19];
+        bad.extend(b"http://a.example/b/");
+        assert!(DhcpOption::decode(&mut Decoder::new(&bad)).is_err());
+        assert!(DhcpOption::SztpRedirect(vec!["http://a.example/".parse()?])
+            .to_vec()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_typed() {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::MessageType(MessageType::Ack));
+        opts.insert(DhcpOption::Router(vec![[10, 0, 0, 1].into()]));
+        opts.insert(DhcpOption::RapidCommit);
+        assert_eq!(
+            opts.get_typed::<typed::MessageType>(),
+            Some(&MessageType::Ack)
+        );
+        assert_eq!(
+            opts.get_typed::<typed::Router>().map(Vec::as_slice),
+            Some(&[Ipv4Addr::from([10, 0, 0, 1])][..])
+        );
+        assert_eq!(opts.get_typed::<typed::RapidCommit>(), Some(()));
+        assert_eq!(typed::Renewal::CODE, OptionCode::Renewal);
+        assert!(typed::Renewal::value(&DhcpOption::Rebinding(10.into())).is_none());
+    }
+
+    #[test]
+    fn test_get_as() {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::MessageType(MessageType::Ack));
+        opts.insert(DhcpOption::NetBiosNodeType(NodeType::H));
+        opts.insert(DhcpOption::Unknown(UnknownOption::new(
+            OptionCode::DisableSLAAC,
+            vec![5],
+        )));
+        assert_eq!(opts.get_as(OptionCode::NetBiosNodeType), Ok(NodeType::H));
+        assert_eq!(
+            opts.get_as::<NodeType>(OptionCode::MessageType),
+            Err(WrongVariant::Mismatch(OptionCode::MessageType))
+        );
+        assert_eq!(
+            opts.get_as::<AutoConfig>(OptionCode::DisableSLAAC),
+            Err(WrongVariant::Unknown(OptionCode::DisableSLAAC))
+        );
+        assert_eq!(
+            opts.get_as::<Architecture>(OptionCode::ClientSystemArchitecture),
+            Err(WrongVariant::Missing(OptionCode::ClientSystemArchitecture))
+        );
+    }
+
+    #[test]
+    fn test_known_codes() {
+        const ROUTER: OptionCode = OptionCode::from_u8(3);
+        assert_eq!(ROUTER, OptionCode::Router);
+        assert_eq!(OptionCode::Router.as_u8(), 3);
+        for code in OptionCode::iter_known() {
+            assert_eq!(OptionCode::from(u8::from(code)), code);
+            assert!(code.is_known());
+        }
+        assert!(OptionCode::KNOWN.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!("router".parse(), Ok(OptionCode::Router));
+        assert_eq!("250".parse(), Ok(OptionCode::new(250)));
+        assert!("Foo".parse::<OptionCode>().is_err());
+    }
+
+    #[test]
+    fn test_code_newtype() {
+        assert_eq!(OptionCode::new(3), OptionCode::Router);
+        assert_eq!(OptionCode::from(3), OptionCode::Router);
+        assert!(!OptionCode::new(250).is_known());
+        assert_eq!(format!("{:?}", OptionCode::Router), "Router");
+        assert_eq!(format!("{:?}", OptionCode::new(250)), "Unknown(250)");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_code_serde() -> std::result::Result<(), serde_json::Error> {
+        // same format as the enum with an `Unknown` variant
+        assert_eq!(serde_json::to_string(&OptionCode::Router)?, r#""Router""#);
+        assert_eq!(
+            serde_json::to_string(&OptionCode::new(250))?,
+            r#"{"Unknown":250}"#
+        );
+        for code in [OptionCode::Router, OptionCode::End, OptionCode::new(250)] {
+            let json = serde_json::to_string(&code)?;
+            assert_eq!(serde_json::from_str::<OptionCode>(&json)?, code);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_trailing_nul() -> Result<()> {
+        let opt = DhcpOption::decode(&mut Decoder::new(&[15, 4, b'l', b'a', b'n', 0]))?;
+        assert_eq!(opt, DhcpOption::DomainName("lan".into()));
+        // only a single NUL is dropped
+        let opt = DhcpOption::decode(&mut Decoder::new(&[12, 3, b'a', 0, 0]))?;
+        assert_eq!(opt, DhcpOption::Hostname("a\0".into()));
+        // & a NUL is never written
+        assert_eq!(opt.to_vec()?, [12, 1, b'a']);
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_utf8_string() -> Result<()> {
+        // Latin-1 hostname
+        let bytes = [12, 4, b'c', b'a', b'f', 0xe9];
+        let opt = DhcpOption::decode(&mut Decoder::new(&bytes))?;
+        let DhcpOption::Hostname(name) = &opt else {
+            panic!("expected Hostname, got {opt:?}");
+        };
+        assert_eq!(name.as_str(), None);
+        assert_eq!(name.to_string_lossy(), "caf\u{fffd}");
+        assert_eq!(opt.to_vec()?, bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::InterfaceMtu(1500));
+        opts.insert(DhcpOption::DomainName("global".into()));
+        let mut other = DhcpOptions::new();
+        other.insert(DhcpOption::InterfaceMtu(9000));
+        other.insert(DhcpOption::DomainName("subnet".into()));
+        other.insert(DhcpOption::TimeOffset(0));
+
+        let mut kept = opts.clone();
+        kept.merge(&other, &MergePolicy::keep_existing());
+        assert_eq!(kept.len(), 3);
+        assert_eq!(
+            kept.get(OptionCode::InterfaceMtu),
+            Some(&DhcpOption::InterfaceMtu(1500))
+        );
+
+        let policy = MergePolicy::prefer_other()
+            .with_override(OptionCode::DomainName, Precedence::KeepExisting);
+        opts.merge(&other, &policy);
+        assert_eq!(
+            opts.get(OptionCode::InterfaceMtu),
+            Some(&DhcpOption::InterfaceMtu(9000))
+        );
+        assert_eq!(
+            opts.get(OptionCode::DomainName),
+            Some(&DhcpOption::DomainName("global".into()))
+        );
+        assert!(opts.get(OptionCode::TimeOffset).is_some());
+    }
+
+    #[test]
+    fn test_code_order() {
+        let mut opts = DhcpOptions::new();
+        for code in [
+            54, 1, 53, 3, 51, 6, 12, 82, 15, 28, 2, 42, 44, 46, 47, 119, 121,
+        ] {
+            opts.insert(DhcpOption::Unknown(UnknownOption::from_raw(code, vec![])));
+        }
+        assert!(opts.len() > INLINE_OPTIONS);
+        let codes = opts.iter().map(|(code, _)| *code).collect::<Vec<_>>();
+        assert!(codes.windows(2).all(|w| w[0] < w[1]));
+        opts.retain(|code, _| u8::from(*code) % 2 == 0);
+        assert!(opts.remove(OptionCode::new(54)).is_some());
+        assert!(opts.remove(OptionCode::new(54)).is_none());
+        let codes = opts
+            .into_iter()
+            .map(|(code, _)| code.as_u8())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, [2, 6, 12, 28, 42, 44, 46, 82]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_options_serde() -> std::result::Result<(), serde_json::Error> {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::MessageType(MessageType::Ack));
+        opts.insert(DhcpOption::SubnetMask([255, 255, 255, 0].into()));
+        // a map of code to option
+        let json = serde_json::to_string(&opts)?;
+        assert!(json.starts_with(r#"{"SubnetMask":"#));
+        assert_eq!(serde_json::from_str::<DhcpOptions>(&json)?, opts);
+        Ok(())
+    }
+
+    #[test]
+    fn test_effective_timezone() {
+        let mut opts = DhcpOptions::new();
+        assert_eq!(opts.timezone(), None);
+        opts.insert(DhcpOption::TimeOffset(-18000));
+        opts.insert(DhcpOption::TZPosixString("EST5EDT".into()));
+        assert_eq!(opts.timezone(), Some(Timezone::Posix("EST5EDT".into())));
+        // an invalid name falls back to the next option
+        opts.insert(DhcpOption::TZDatabaseString("../etc/passwd".into()));
+        assert_eq!(opts.timezone(), Some(Timezone::Posix("EST5EDT".into())));
+        opts.insert(DhcpOption::TZPosixString(vec![0xff].into()));
+        assert_eq!(opts.timezone(), Some(Timezone::Offset(-18000)));
+        opts.insert(DhcpOption::TZDatabaseString("America/New_York".into()));
+        assert_eq!(
+            opts.timezone(),
+            Some(Timezone::Tzdb("America/New_York".into()))
+        );
+    }
+
+    #[test]
+    fn test_normalized() {
+        let search = DhcpOption::DomainSearch(vec!["Eng.Example.COM".parse().unwrap()]);
+        match search.normalized() {
+            DhcpOption::DomainSearch(domains) => {
+                assert_eq!(domains[0].to_string(), "eng.example.com.")
+            }
+            opt => panic!("unexpected {opt:?}"),
+        }
+        let routers = DhcpOption::Router(vec![[10, 0, 0, 2].into(), [10, 0, 0, 1].into()]);
+        assert_eq!(routers.normalized(), routers);
+        let reversed = DhcpOption::Router(vec![[10, 0, 0, 1].into(), [10, 0, 0, 2].into()]);
+        assert!(!routers.eq_semantic(&reversed));
+
+        let gw = Ipv4Addr::new(10, 0, 0, 1);
+        let a = DhcpOption::ClasslessStaticRoute(vec![
+            ("10.2.0.0/16".parse().unwrap(), gw),
+            ("10.1.0.0/16".parse().unwrap(), gw),
+        ]);
+        let b = DhcpOption::ClasslessStaticRoute(vec![
+            ("10.1.0.0/16".parse().unwrap(), gw),
+            ("10.2.0.0/16".parse().unwrap(), gw),
+        ]);
+        assert!(a.eq_semantic(&b));
+        assert!(DhcpOption::TFTPServerName(b"tftp\0\0".to_vec())
+            .eq_semantic(&DhcpOption::TFTPServerName(b"tftp".to_vec())));
+        assert!(!DhcpOption::Message("a\0".into()).eq_semantic(&DhcpOption::Message("A".into())));
+    }
+
+    #[test]
+    fn test_metadata() {
+        use crate::metadata::ValueKind;
+        for code in OptionCode::iter_known() {
+            let meta = code.metadata().unwrap();
+            assert_eq!(Some(meta.name), code.name());
+            assert!(meta.rfc.is_some_and(|rfc| rfc.starts_with("https://")));
+        }
+        let meta = OptionCode::ClientFQDN.metadata().unwrap();
+        assert_eq!(
+            meta.rfc,
+            Some("https://datatracker.ietf.org/doc/html/rfc4702")
+        );
+        assert_eq!(meta.kind, ValueKind::Structured);
+        assert_eq!(
+            OptionCode::RapidCommit.metadata().unwrap().kind,
+            ValueKind::Empty
+        );
+        assert_eq!(
+            OptionCode::AddressLeaseTime.metadata().unwrap().kind,
+            ValueKind::U32
+        );
+        assert_eq!(
+            OptionCode::DomainSearch.metadata().unwrap().kind,
+            ValueKind::Domains
+        );
+        assert!(OptionCode::TimeOffset.metadata().unwrap().deprecated);
+        assert!(!OptionCode::TZPosixString.metadata().unwrap().deprecated);
+        assert!(OptionCode::new(250).metadata().is_none());
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            "domain-name-servers".parse(),
+            Ok(OptionCode::DomainNameServer)
+        );
+        assert_eq!("Routers".parse(), Ok(OptionCode::Router));
+        assert_eq!(
+            OptionCode::DomainNameServer.to_string(),
+            "domain-name-servers"
+        );
+        assert_eq!(OptionCode::TFTPServerName.to_string(), "tftp-server-name");
+        assert_eq!(OptionCode::new(250).to_string(), "250");
+        for code in OptionCode::iter_known() {
+            assert_eq!(code.to_string().parse(), Ok(code));
+        }
+
+        assert_eq!("DHCPDISCOVER".parse(), Ok(MessageType::Discover));
+        assert_eq!("ack".parse(), Ok(MessageType::Ack));
+        assert_eq!(
+            MessageType::LeaseQueryDone.to_string(),
+            "DHCPLEASEQUERYDONE"
+        );
+        assert_eq!("200".parse(), Ok(MessageType::Unknown(200)));
+        assert!("DHCPFOO".parse::<MessageType>().is_err());
+    }
+
+    #[test]
+    fn test_pcp_servers() -> Result<()> {
+        test_opt(
+            DhcpOption::PcpServers(vec![
+                vec![[10, 0, 0, 1].into(), [10, 0, 0, 2].into()],
+                vec![[10, 0, 0, 3].into()],
+            ]),
+            vec![158, 14, 8, 10, 0, 0, 1, 10, 0, 0, 2, 4, 10, 0, 0, 3],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict_malformed_option() -> Result<()> {
+        // a 5 byte SubnetMask, then a Hostname
+        let input = [
+            1, 5, 255, 255, 255, 0, 0, 12, 4, b'h', b'o', b's', b't', 255,
+        ];
+        // lenient skips the SubnetMask, strict returns its error
+        let opts = DhcpOptions::decode(&mut Decoder::new(&input))?;
+        assert!(opts.get(OptionCode::SubnetMask).is_none());
+        assert!(opts.get(OptionCode::Hostname).is_some());
+        assert!(DhcpOptions::decode(&mut Decoder::new_strict(&input)).is_err());
+        // a missing `End` isn't an error
+        let opts = DhcpOptions::decode(&mut Decoder::new_strict(&input[7..13]))?;
+        assert_eq!(
+            opts.get(OptionCode::Hostname),
+            Some(&DhcpOption::Hostname("host".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_msg_type_unknown() {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::Unknown(UnknownOption::new(
+            OptionCode::MessageType,
+            vec![],
+        )));
+        assert_eq!(opts.msg_type(), None);
+        assert!(!opts.has_msg_type(MessageType::Discover));
+    }
+
+    #[test]
+    fn test_site_local_opaque() -> Result<()> {
+        // TFTP server address, then a site-local use of 151
+        let input = [150, 4, 192, 168, 0, 1, 151, 0, 224, 1, 0, 255];
+        let opts = DhcpOptions::decode(&mut Decoder::new(&input))?;
+        assert_eq!(
+            opts.get(OptionCode::TFTPServerAddress),
+            Some(&DhcpOption::TFTPServerAddress([192, 168, 0, 1].into()))
+        );
+        // 151 is too short to be a BLQ status code, it's skipped & the rest are kept
+        assert!(opts.get(OptionCode::BulkLeaseQueryStatusCode).is_none());
+        assert!(opts.get(OptionCode::new(224)).is_some());
+        let mut decoder = Decoder::new(&input).recover(true);
+        DhcpOptions::decode(&mut decoder)?;
+        let errors = decoder.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 6);
+
+        let opts = DhcpOptions::decode(&mut Decoder::new(&input).site_local_opaque(true))?;
+        assert_eq!(
+            opts.get(OptionCode::TFTPServerAddress),
+            Some(&DhcpOption::Unknown(UnknownOption::new(
+                OptionCode::TFTPServerAddress,
+                vec![192, 168, 0, 1]
+            )))
+        );
+        assert_eq!(
+            opts.get(OptionCode::BulkLeaseQueryStatusCode),
+            Some(&DhcpOption::Unknown(UnknownOption::new(
+                OptionCode::BulkLeaseQueryStatusCode,
+                vec![]
+            )))
+        );
+        assert!(opts.get(OptionCode::new(224)).is_some());
+        // re-encodes the same bytes
+        assert_eq!(
+            opts.get(OptionCode::TFTPServerAddress).unwrap().to_vec()?,
+            input[..6]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_blq_fixed_len() -> Result<()> {
+        test_opt(
+            DhcpOption::BulkLeaseQueryBaseTime(1_700_000_000),
+            vec![152, 4, 0x65, 0x53, 0xf1, 0x00],
+        )?;
+        // too short used to panic in debug builds
+        assert!(DhcpOption::decode(&mut Decoder::new(&[152, 2, 0, 0])).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[156, 2, 1, 1])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sip_servers() -> Result<()> {
+        test_opt(
+            DhcpOption::SipServers(SipServers::Addrs(vec![
+                [192, 168, 0, 1].into(),
+                [192, 168, 0, 2].into(),
+            ])),
+            vec![120, 9, 1, 192, 168, 0, 1, 192, 168, 0, 2],
+        )?;
+        test_opt(
+            DhcpOption::SipServers(SipServers::Domains(vec![Domain::from_str("sip.example.")?])),
+            vec![
+                120, 14, 0, 3, b's', b'i', b'p', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0,
+            ],
+        )?;
+        // enc must be 0 or 1
+        let err = DhcpOption::decode(&mut Decoder::new(&[120, 5, 2, 1, 2, 3, 4])).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::DecodeError::InvalidValue {
+                code: Some(120),
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "invalid value in option 120: SIP servers enc must be 0 or 1"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_rapid_commit() -> Result<()> {
         test_opt(DhcpOption::RapidCommit, vec![80, 0])?;
@@ -1455,8 +2794,8 @@ mod tests {
     fn test_domainsearch() -> Result<()> {
         test_opt(
             DhcpOption::DomainSearch(vec![
-                Name::from_str("eng.apple.com.").unwrap(),
-                Name::from_str("marketing.apple.com.").unwrap(),
+                Domain::from_str("eng.apple.com.").unwrap(),
+                Domain::from_str("marketing.apple.com.").unwrap(),
             ]),
             vec![
                 119, 27, 3, b'e', b'n', b'g', 5, b'a', b'p', b'p', b'l', b'e', 3, b'c', b'o', b'm',
@@ -1474,7 +2813,7 @@ mod tests {
                 flags: fqdn::FqdnFlags::default().set_e(true),
                 r1: 0,
                 r2: 0,
-                domain: Name::from_str("www.google.com.").unwrap(),
+                domain: Domain::from_str("www.google.com.").unwrap(),
             }),
             vec![
                 81, 19, 0x04, 0, 0, 3, b'w', b'w', b'w', 6, b'g', b'o', b'o', b'g', b'l', b'e', 3,
@@ -1498,6 +2837,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_unknown_known_code() {
+        let router = DhcpOption::Router(vec![Ipv4Addr::new(10, 0, 0, 1)]);
+        let unknown =
+            DhcpOption::Unknown(UnknownOption::new(OptionCode::Router, vec![10, 0, 0, 1]));
+        assert_eq!(OptionCode::from(&unknown), OptionCode::Router);
+        assert_eq!(unknown.clone().into_known(), router);
+
+        let mut opts = DhcpOptions::new();
+        opts.insert(unknown.clone());
+        assert_eq!(opts.get(OptionCode::Router), Some(&router));
+        assert_eq!(opts.iter().count(), 1);
+        assert_eq!(
+            [unknown]
+                .into_iter()
+                .collect::<DhcpOptions>()
+                .get(OptionCode::Router),
+            Some(&router)
+        );
+
+        // invalid data & unknown codes are kept
+        let bad = DhcpOption::Unknown(UnknownOption::new(OptionCode::SubnetMask, vec![255]));
+        assert_eq!(bad.clone().into_known(), bad);
+        opts.insert(bad.clone());
+        assert_eq!(opts.get(OptionCode::SubnetMask), Some(&bad));
+        let unknown = DhcpOption::Unknown(UnknownOption::from_raw(240, vec![1]));
+        assert_eq!(unknown.clone().into_known(), unknown);
+        assert!(matches!(&unknown, DhcpOption::Unknown(opt) if opt.raw_code() == 240));
+        assert_eq!(router.clone().into_known(), router);
+
+        // site-local codes kept opaque by the decoder stay that way
+        let tftp = DhcpOption::Unknown(UnknownOption::new(
+            OptionCode::TFTPServerAddress,
+            vec![10, 0, 0, 2],
+        ));
+        opts.insert(tftp.clone());
+        assert_eq!(opts.get(OptionCode::TFTPServerAddress), Some(&tftp));
+        let collected = [tftp.clone()].into_iter().collect::<DhcpOptions>();
+        assert_eq!(collected.get(OptionCode::TFTPServerAddress), Some(&tftp));
+    }
+
     #[test]
     fn test_nis_server_addr() -> Result<()> {
         test_opt(
@@ -1528,6 +2908,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_ms_classless_static_route() -> Result<()> {
+        test_opt(
+            DhcpOption::MicrosoftClasslessStaticRoute(vec![(
+                "10.0.0.0/8".parse()?,
+                "192.168.1.1".parse()?,
+            )]),
+            vec![
+                249, 6, // Option & length
+                8, 10, 192, 168, 1, 1, // 10.0.0.0/8 -> 192.168.1.1
+            ],
+        )?;
+
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::MicrosoftClasslessStaticRoute(vec![(
+            "10.0.0.0/8".parse()?,
+            "192.168.1.1".parse()?,
+        )]));
+        assert_eq!(
+            opts.classless_static_routes(),
+            [("10.0.0.0/8".parse()?, "192.168.1.1".parse()?)]
+        );
+        assert!(DhcpOptions::new().classless_static_routes().is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_classless_static_route_long_opt() -> Result<()> {
         let buf = vec![
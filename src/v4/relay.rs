@@ -1,7 +1,13 @@
 //! # relay
-use std::{collections::HashMap, fmt, net::Ipv4Addr};
+use std::{fmt, net::Ipv4Addr};
 
-use crate::{Decodable, Encodable};
+use thiserror::Error;
+
+use crate::{
+    sub_options::{decode_sub_option, encode_sub_option, SubOption, SubOptionCode, SubOptions},
+    v4::{Message, Opcode},
+    Decodable, Encodable,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -22,76 +28,166 @@ use serde::{Deserialize, Serialize};
 /// ```
 ///
 /// [`DhcpOption::RelayAgentInformation`]: crate::v4::DhcpOption::RelayAgentInformation
+///
+/// A decoded value keeps the bytes it was decoded from and encodes them unchanged until
+/// it's modified, so a server echoing the option returns exactly what the relay agent
+/// sent. <https://datatracker.ietf.org/doc/html/rfc3046#section-2.2>
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
-pub struct RelayAgentInformation(HashMap<RelayCode, RelayInfo>);
+#[derive(Default, Clone)]
+pub struct RelayAgentInformation {
+    opts: SubOptions<RelayCode, RelayInfo>,
+    // bytes as received, `None` once modified
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raw: Option<Vec<u8>>,
+}
 
 impl RelayAgentInformation {
     /// Get the data for a particular [`RelayCode`]
     ///
     /// [`RelayCode`]: crate::v4::relay::RelayCode
     pub fn get(&self, code: RelayCode) -> Option<&RelayInfo> {
-        self.0.get(&code)
+        self.opts.get(code)
     }
     /// Get the mutable data for a particular [`RelayCode`]
     ///
     /// [`RelayCode`]: crate::v4::relay::RelayCode
     pub fn get_mut(&mut self, code: RelayCode) -> Option<&mut RelayInfo> {
-        self.0.get_mut(&code)
+        self.raw = None;
+        self.opts.get_mut(code)
     }
     /// remove sub option
     pub fn remove(&mut self, code: RelayCode) -> Option<RelayInfo> {
-        self.0.remove(&code)
+        self.raw = None;
+        self.opts.remove(code)
     }
     /// insert a new [`RelayInfo`]
     ///
     /// [`RelayInfo`]: crate::v4::relay::RelayInfo
     pub fn insert(&mut self, info: RelayInfo) -> Option<RelayInfo> {
-        self.0.insert((&info).into(), info)
+        self.raw = None;
+        self.opts.insert(info)
     }
     /// iterate over entries
     pub fn iter(&self) -> impl Iterator<Item = (&RelayCode, &RelayInfo)> {
-        self.0.iter()
+        self.opts.iter()
     }
     /// iterate mutably over entries
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&RelayCode, &mut RelayInfo)> {
-        self.0.iter_mut()
+        self.raw = None;
+        self.opts.iter_mut()
     }
     /// clear all options
     pub fn clear(&mut self) {
-        self.0.clear()
+        self.raw = None;
+        self.opts.clear()
     }
     /// Returns `true` if there are no options
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.opts.is_empty()
     }
     /// Retans only the elements specified by the predicate
     pub fn retain<F>(&mut self, pred: F)
     where
         F: FnMut(&RelayCode, &mut RelayInfo) -> bool,
     {
-        self.0.retain(pred)
+        self.raw = None;
+        self.opts.retain(pred)
+    }
+    /// access the underlying sub-option container
+    pub fn sub_options(&self) -> &SubOptions<RelayCode, RelayInfo> {
+        &self.opts
+    }
+    /// the bytes this was decoded from, `None` if it was built or has been modified
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+}
+
+impl fmt::Debug for RelayAgentInformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RelayAgentInformation")
+            .field(&self.opts)
+            .finish()
+    }
+}
+
+/// Compares the sub-options, not the bytes kept when decoding, so equal values can
+/// encode differently: a decoded value echoes what was received, including sub-options
+/// it couldn't decode, while a built one encodes its sub-options
+impl PartialEq for RelayAgentInformation {
+    fn eq(&self, other: &Self) -> bool {
+        self.opts == other.opts
+    }
+}
+
+impl Eq for RelayAgentInformation {}
+
+impl From<SubOptions<RelayCode, RelayInfo>> for RelayAgentInformation {
+    fn from(opts: SubOptions<RelayCode, RelayInfo>) -> Self {
+        Self { opts, raw: None }
     }
 }
 
 impl Decodable for RelayAgentInformation {
     fn decode(d: &mut crate::Decoder<'_>) -> super::DecodeResult<Self> {
-        let mut opts = HashMap::new();
-        while let Ok(opt) = RelayInfo::decode(d) {
-            opts.insert(RelayCode::from(&opt), opt);
-        }
-        Ok(RelayAgentInformation(opts))
+        // the whole value, including anything after a malformed sub-option
+        let raw = d.buffer().to_vec();
+        let opts = SubOptions::decode(d)?;
+        d.read_slice(d.remaining())?;
+        Ok(RelayAgentInformation {
+            opts,
+            raw: Some(raw),
+        })
     }
 }
 
 impl Encodable for RelayAgentInformation {
     fn encode(&self, e: &mut crate::Encoder<'_>) -> super::EncodeResult<()> {
-        self.0.iter().try_for_each(|(_, info)| info.encode(e))
+        match &self.raw {
+            Some(raw) => e.write_slice(raw),
+            None => self.opts.encode(e),
+        }
+    }
+}
+
+/// Returns `true` if `reply` echoes the `RelayAgentInformation` of `request` byte for
+/// byte, or neither has one. A relay agent drops replies where it doesn't match.
+/// <https://datatracker.ietf.org/doc/html/rfc3046#section-2.2>
+///
+/// ```rust
+/// use dhcproto::{v4::{self, relay, DhcpOption, OptionCode}, Decodable};
+///
+/// // circuit id, then remote id, as the relay agent sent them
+/// let bytes = [2, 1, 0xbb, 1, 1, 0xaa];
+/// let info = relay::RelayAgentInformation::decode(&mut dhcproto::Decoder::new(&bytes))?;
+/// let mut request = v4::Message::default();
+/// request.opts_mut().insert(DhcpOption::RelayAgentInformation(info));
+///
+/// let mut reply = request.derive_reply();
+/// let echoed = request.opts().get(OptionCode::RelayAgentInformation).unwrap();
+/// reply.opts_mut().insert(echoed.clone());
+/// assert!(relay::verify_option82_echo(&request, &reply));
+///
+/// reply.opts_mut().remove(OptionCode::RelayAgentInformation);
+/// assert!(!relay::verify_option82_echo(&request, &reply));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn verify_option82_echo(request: &Message, reply: &Message) -> bool {
+    fn bytes(msg: &Message) -> Option<Option<Vec<u8>>> {
+        match msg.opts().get(super::OptionCode::RelayAgentInformation) {
+            Some(super::DhcpOption::RelayAgentInformation(info)) => info.to_vec().ok().map(Some),
+            _ => Some(None),
+        }
+    }
+    match (bytes(request), bytes(reply)) {
+        (Some(sent), Some(echoed)) => sent == echoed,
+        _ => false,
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "redact"), derive(Debug))]
+#[derive(Clone, PartialEq, Eq)]
 pub enum RelayInfo {
     /// 1 - <https://datatracker.ietf.org/doc/html/rfc3046>
     AgentCircuitId(Vec<u8>),
@@ -121,98 +217,85 @@ pub enum RelayInfo {
     // VirtualSubnetControl(u8),
 }
 
-impl Decodable for RelayInfo {
-    fn decode(d: &mut crate::Decoder<'_>) -> super::DecodeResult<Self> {
-        use RelayInfo::*;
-        // read the code first, determines the variant
-        Ok(match d.read_u8()?.into() {
-            RelayCode::AgentCircuitId => {
-                let len = d.read_u8()? as usize;
-                let data = d.read_slice(len)?.to_vec();
-                AgentCircuitId(data)
-            }
-            RelayCode::AgentRemoteId => {
-                let len = d.read_u8()? as usize;
-                let data = d.read_slice(len)?.to_vec();
-                AgentRemoteId(data)
-            }
-            RelayCode::DocsisDeviceClass => {
-                let _ = d.read_u8()?;
-                let device_id = d.read_u32()?;
-                DocsisDeviceClass(device_id)
-            }
-            RelayCode::LinkSelection => {
-                let len = d.read_u8()? as usize;
-                LinkSelection(d.read_ipv4(len)?)
-            }
-            RelayCode::SubscriberId => {
-                let len = d.read_u8()? as usize;
-                let data = d.read_slice(len)?.to_vec();
-                SubscriberId(data)
-            }
-            RelayCode::RelayAgentFlags => {
-                let _len = d.read_u8()?;
-                let flags = d.read_u8()?;
-                RelayAgentFlags(flags.into())
-            }
-            RelayCode::ServerIdentifierOverride => {
-                let len = d.read_u8()? as usize;
-                ServerIdentifierOverride(d.read_ipv4(len)?)
+#[cfg(feature = "redact")]
+impl fmt::Debug for RelayInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use crate::redact::Redacted;
+        use RelayInfo as I;
+        match self {
+            I::AgentCircuitId(id) => f.debug_tuple("AgentCircuitId").field(id).finish(),
+            // remote & subscriber ids identify the customer
+            I::AgentRemoteId(id) => f
+                .debug_tuple("AgentRemoteId")
+                .field(&Redacted::always(id))
+                .finish(),
+            I::SubscriberId(id) => f
+                .debug_tuple("SubscriberId")
+                .field(&Redacted::always(id))
+                .finish(),
+            I::DocsisDeviceClass(n) => f.debug_tuple("DocsisDeviceClass").field(n).finish(),
+            I::LinkSelection(ip) => f.debug_tuple("LinkSelection").field(ip).finish(),
+            I::RelayAgentFlags(flags) => f.debug_tuple("RelayAgentFlags").field(flags).finish(),
+            I::ServerIdentifierOverride(ip) => {
+                f.debug_tuple("ServerIdentifierOverride").field(ip).finish()
             }
+            I::Unknown(unknown) => f.debug_tuple("Unknown").field(unknown).finish(),
+        }
+    }
+}
+
+impl SubOptionCode for RelayCode {
+    type Raw = u8;
+}
+
+impl SubOption<RelayCode> for RelayInfo {
+    fn code(&self) -> RelayCode {
+        self.into()
+    }
+
+    fn decode_value(code: RelayCode, d: &mut crate::Decoder<'_>) -> super::DecodeResult<Self> {
+        use RelayInfo::*;
+        let len = d.buffer().len();
+        Ok(match code {
+            RelayCode::AgentCircuitId => AgentCircuitId(d.read_slice(len)?.to_vec()),
+            RelayCode::AgentRemoteId => AgentRemoteId(d.read_slice(len)?.to_vec()),
+            RelayCode::DocsisDeviceClass => DocsisDeviceClass(d.read_u32()?),
+            RelayCode::LinkSelection => LinkSelection(d.read_ipv4(len)?),
+            RelayCode::SubscriberId => SubscriberId(d.read_slice(len)?.to_vec()),
+            RelayCode::RelayAgentFlags => RelayAgentFlags(d.read_u8()?.into()),
+            RelayCode::ServerIdentifierOverride => ServerIdentifierOverride(d.read_ipv4(len)?),
             // we have codes for these but not full type definitions yet
-            code @ (RelayCode::Authentication
-            | RelayCode::VirtualSubnet
-            | RelayCode::VirtualSubnetControl
-            | RelayCode::RadiusAttributes
-            | RelayCode::VendorSpecificInformation) => {
-                let length = d.read_u8()?;
-                let bytes = d.read_slice(length as usize)?.to_vec();
-                Unknown(UnknownInfo {
-                    code: code.into(),
-                    data: bytes,
-                })
-            }
-            // not yet implemented
-            RelayCode::Unknown(code) => {
-                let length = d.read_u8()?;
-                let bytes = d.read_slice(length as usize)?.to_vec();
-                Unknown(UnknownInfo { code, data: bytes })
-            }
+            code => Unknown(UnknownInfo {
+                code: code.into(),
+                data: d.read_slice(len)?.to_vec(),
+            }),
         })
     }
-}
 
-impl Encodable for RelayInfo {
-    fn encode(&self, e: &mut crate::Encoder<'_>) -> super::EncodeResult<()> {
+    fn encode_value(&self, e: &mut crate::Encoder<'_>) -> super::EncodeResult<()> {
         use RelayInfo as R;
-        let code: RelayCode = self.into();
-        e.write_u8(code.into())?;
         match self {
-            R::AgentCircuitId(id) | R::AgentRemoteId(id) | R::SubscriberId(id) => {
-                // length of bytes stored in Vec
-                e.write_u8(id.len() as u8)?;
-                e.write_slice(id)?
-            }
-            R::DocsisDeviceClass(n) => {
-                e.write_u8(4)?;
-                e.write_u32(*n)?
-            }
+            R::AgentCircuitId(id) | R::AgentRemoteId(id) | R::SubscriberId(id) => e.write_slice(id),
+            R::DocsisDeviceClass(n) => e.write_u32(*n),
             R::LinkSelection(addr) | R::ServerIdentifierOverride(addr) => {
-                e.write_u8(4)?;
-                e.write_u32((*addr).into())?
-            }
-            R::RelayAgentFlags(flags) => {
-                e.write_u8(1)?;
-                e.write_u8((*flags).into())?
+                e.write_u32((*addr).into())
             }
+            R::RelayAgentFlags(flags) => e.write_u8((*flags).into()),
             // not yet implemented
-            R::Unknown(opt) => {
-                // length of bytes stored in Vec
-                e.write_u8(opt.data.len() as u8)?;
-                e.write_slice(&opt.data)?
-            }
-        };
-        Ok(())
+            R::Unknown(opt) => e.write_slice(&opt.data),
+        }
+    }
+}
+
+impl Decodable for RelayInfo {
+    fn decode(d: &mut crate::Decoder<'_>) -> super::DecodeResult<Self> {
+        decode_sub_option(d)
+    }
+}
+
+impl Encodable for RelayInfo {
+    fn encode(&self, e: &mut crate::Encoder<'_>) -> super::EncodeResult<()> {
+        encode_sub_option(self, e)
     }
 }
 
@@ -263,12 +346,25 @@ impl From<RelayFlags> for u8 {
 
 /// An as-of-yet unimplemented relay info
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(not(feature = "redact"), derive(Debug))]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct UnknownInfo {
     code: u8,
     data: Vec<u8>,
 }
 
+// the authentication sub-option isn't decoded, mask its key material
+#[cfg(feature = "redact")]
+impl fmt::Debug for UnknownInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = self.code() == RelayCode::Authentication;
+        f.debug_struct("UnknownInfo")
+            .field("code", &self.code)
+            .field("data", &crate::redact::Redacted::new(&self.data, redact))
+            .finish()
+    }
+}
+
 impl UnknownInfo {
     pub fn new(code: RelayCode, data: Vec<u8>) -> Self {
         Self {
@@ -310,6 +406,8 @@ pub enum RelayCode {
     Unknown(u8),
 }
 
+display_unknown!(RelayCode);
+
 impl From<u8> for RelayCode {
     fn from(n: u8) -> Self {
         use RelayCode::*;
@@ -362,7 +460,166 @@ impl From<&RelayInfo> for RelayCode {
             R::SubscriberId(_) => RelayCode::SubscriberId,
             R::RelayAgentFlags(_) => RelayCode::RelayAgentFlags,
             R::ServerIdentifierOverride(_) => RelayCode::ServerIdentifierOverride,
-            R::Unknown(unknown) => RelayCode::Unknown(unknown.code),
+            R::Unknown(unknown) => unknown.code(),
+        }
+    }
+}
+
+/// largest `hops` a relayed message may have
+/// <https://datatracker.ietf.org/doc/html/rfc1542#section-4.1.1>
+pub const MAX_HOPS: u8 = 16;
+
+/// Returned when relaying a message through a [`Chain`] fails
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RelayError {
+    /// relaying the message would take `hops` over the maximum
+    #[error("hops {hops} exceeds the maximum of {max}")]
+    TooManyHops {
+        /// hops after relaying
+        hops: u8,
+        /// configured maximum
+        max: u8,
+    },
+
+    /// only BOOTREQUEST messages are relayed towards the server
+    #[error("expected a BOOTREQUEST, got {0:?}")]
+    NotRequest(Opcode),
+
+    /// only BOOTREPLY messages are relayed back to the client
+    #[error("expected a BOOTREPLY, got {0:?}")]
+    NotReply(Opcode),
+
+    /// giaddr was changed after the first relay agent set it
+    #[error("giaddr {found} doesn't match the first relay agent {expected}")]
+    GiaddrMismatch {
+        /// giaddr set by the first relay agent
+        expected: Ipv4Addr,
+        /// giaddr in the message
+        found: Ipv4Addr,
+    },
+
+    /// the reply is for a different transaction
+    #[error("xid {found:#x} doesn't match the relayed request {expected:#x}")]
+    XidMismatch {
+        /// xid of the relayed request
+        expected: u32,
+        /// xid of the reply
+        found: u32,
+    },
+}
+
+/// Models a request passing through one or more relay agents
+///
+/// Each hop increments `hops`, refusing to go past the maximum. Only the first relay
+/// agent sets `giaddr`, later agents leave it alone, so the server sends its reply
+/// straight back to the first agent.
+/// <https://datatracker.ietf.org/doc/html/rfc1542#section-4.1.1>
+///
+/// ```rust
+/// use dhcproto::v4::{relay::Chain, Message};
+///
+/// let mut msg = Message::default();
+/// let mut chain = Chain::new();
+/// chain.forward(&mut msg, [10, 0, 0, 1].into())?;
+/// chain.forward(&mut msg, [10, 1, 0, 1].into())?;
+/// assert_eq!(msg.hops(), 2);
+/// assert_eq!(msg.giaddr(), std::net::Ipv4Addr::new(10, 0, 0, 1));
+///
+/// let mut reply = msg.clone();
+/// reply.set_opcode(dhcproto::v4::Opcode::BootReply);
+/// assert!(chain.check_reply(&reply).is_ok());
+/// # Ok::<(), dhcproto::v4::relay::RelayError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    max_hops: u8,
+    agents: Vec<Ipv4Addr>,
+    xid: Option<u32>,
+}
+
+impl Default for Chain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chain {
+    /// create a new chain allowing up to [`MAX_HOPS`] hops
+    pub fn new() -> Self {
+        Self {
+            max_hops: MAX_HOPS,
+            agents: Vec::new(),
+            xid: None,
+        }
+    }
+
+    /// set the maximum hops, values over [`MAX_HOPS`] are lowered to it
+    pub fn max_hops(&mut self, max_hops: u8) -> &mut Self {
+        self.max_hops = max_hops.min(MAX_HOPS);
+        self
+    }
+
+    /// the relay agents the request has passed through, in order
+    pub fn agents(&self) -> &[Ipv4Addr] {
+        &self.agents
+    }
+
+    /// the giaddr the server should reply to, `None` before the first hop
+    pub fn giaddr(&self) -> Option<Ipv4Addr> {
+        self.agents.first().copied()
+    }
+
+    /// relay `msg` through the agent at `agent`, incrementing `hops` and setting
+    /// `giaddr` if no earlier agent has
+    pub fn forward(&mut self, msg: &mut Message, agent: Ipv4Addr) -> Result<(), RelayError> {
+        if msg.opcode() != Opcode::BootRequest {
+            return Err(RelayError::NotRequest(msg.opcode()));
+        }
+        let hops = msg.hops().saturating_add(1);
+        if hops > self.max_hops {
+            return Err(RelayError::TooManyHops {
+                hops,
+                max: self.max_hops,
+            });
+        }
+        match self.giaddr() {
+            Some(expected) if msg.giaddr() != expected => {
+                return Err(RelayError::GiaddrMismatch {
+                    expected,
+                    found: msg.giaddr(),
+                });
+            }
+            Some(_) => {}
+            None if msg.giaddr().is_unspecified() => {
+                msg.set_giaddr(agent);
+            }
+            // the message was already relayed before reaching this chain
+            None => self.agents.push(msg.giaddr()),
+        }
+        msg.set_hops(hops);
+        self.agents.push(agent);
+        self.xid = Some(msg.xid());
+        Ok(())
+    }
+
+    /// check that `reply` is a BOOTREPLY for the relayed request, addressed to the first
+    /// relay agent
+    pub fn check_reply(&self, reply: &Message) -> Result<(), RelayError> {
+        if reply.opcode() != Opcode::BootReply {
+            return Err(RelayError::NotReply(reply.opcode()));
+        }
+        if let Some(expected) = self.xid.filter(|xid| *xid != reply.xid()) {
+            return Err(RelayError::XidMismatch {
+                expected,
+                found: reply.xid(),
+            });
+        }
+        match self.giaddr() {
+            Some(expected) if expected != reply.giaddr() => Err(RelayError::GiaddrMismatch {
+                expected,
+                found: reply.giaddr(),
+            }),
+            _ => Ok(()),
         }
     }
 }
@@ -433,6 +690,58 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn test_relay_info_order() -> Result<()> {
+        let bytes = [5, 4, 192, 168, 0, 1, 1, 2, 0xaa, 0xbb, 8, 1, 0xff];
+        let info = RelayAgentInformation::decode(&mut crate::Decoder::new(&bytes))?;
+        assert_eq!(
+            info.get(RelayCode::AgentCircuitId),
+            Some(&RelayInfo::AgentCircuitId(vec![0xaa, 0xbb]))
+        );
+        assert!(info.get(RelayCode::Authentication).is_some());
+        // wire order is kept when re-encoding
+        assert_eq!(info.to_vec()?, bytes);
+        Ok(())
+    }
+    #[test]
+    fn test_option82_echo() -> Result<()> {
+        let bytes = [2, 1, 0xbb, 1, 1, 0xaa];
+        let info = RelayAgentInformation::decode(&mut crate::Decoder::new(&bytes))?;
+        assert_eq!(info.raw(), Some(&bytes[..]));
+        let mut built = RelayAgentInformation::default();
+        built.insert(RelayInfo::AgentRemoteId(vec![0xbb]));
+        built.insert(RelayInfo::AgentCircuitId(vec![0xaa]));
+        assert_eq!(built, info);
+        assert_eq!(built.raw(), None);
+
+        let mut req = Message::default();
+        req.opts_mut()
+            .insert(crate::v4::DhcpOption::RelayAgentInformation(info.clone()));
+        let reply = crate::v4::ReplyBuilder::new(&req, |_| None).offer([10, 0, 0, 2].into());
+        assert!(verify_option82_echo(&req, &reply));
+        assert!(reply.to_vec()?.windows(bytes.len()).any(|w| w == bytes));
+
+        let mut changed = info;
+        changed.insert(RelayInfo::AgentCircuitId(vec![0xcc]));
+        assert_eq!(changed.raw(), None);
+        let mut reply = reply;
+        reply
+            .opts_mut()
+            .insert(crate::v4::DhcpOption::RelayAgentInformation(changed));
+        assert!(!verify_option82_echo(&req, &reply));
+        assert!(verify_option82_echo(
+            &Message::default(),
+            &Message::default()
+        ));
+
+        // a malformed sub-option is echoed as received
+        let bytes = [1, 1, 0xaa, 2, 5, 0xbb];
+        let info = RelayAgentInformation::decode(&mut crate::Decoder::new(&bytes))?;
+        assert_eq!(info.raw(), Some(&bytes[..]));
+        assert_eq!(info.to_vec()?, bytes);
+        assert_eq!(info.iter().count(), 1);
+        Ok(())
+    }
+    #[test]
     fn test_unknown() -> Result<()> {
         test_opt(
             RelayInfo::Unknown(UnknownInfo::new(RelayCode::Unknown(149), vec![1, 2, 3, 4])),
@@ -441,4 +750,44 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_chain() {
+        let mut msg = Message::default();
+        let mut chain = Chain::new();
+        chain.max_hops(2);
+        for agent in [[10, 0, 0, 1], [10, 1, 0, 1]] {
+            chain.forward(&mut msg, agent.into()).unwrap();
+        }
+        assert_eq!(msg.hops(), 2);
+        assert_eq!(msg.giaddr(), Ipv4Addr::from([10, 0, 0, 1]));
+        assert_eq!(
+            chain.forward(&mut msg, [10, 2, 0, 1].into()),
+            Err(RelayError::TooManyHops { hops: 3, max: 2 })
+        );
+        assert_eq!(chain.agents().len(), 2);
+
+        let mut reply = msg.clone();
+        assert_eq!(
+            chain.check_reply(&reply),
+            Err(RelayError::NotReply(Opcode::BootRequest))
+        );
+        reply.set_opcode(Opcode::BootReply);
+        assert_eq!(chain.check_reply(&reply), Ok(()));
+        reply.set_giaddr([10, 1, 0, 1]);
+        assert!(matches!(
+            chain.check_reply(&reply),
+            Err(RelayError::GiaddrMismatch { .. })
+        ));
+
+        // hops is limited to 16 even if the maximum is set higher
+        let mut msg = Message::default();
+        msg.set_hops(MAX_HOPS);
+        assert_eq!(
+            Chain::new()
+                .max_hops(32)
+                .forward(&mut msg, [10, 0, 0, 1].into()),
+            Err(RelayError::TooManyHops { hops: 17, max: 16 })
+        );
+    }
 }
@@ -0,0 +1,325 @@
+//! Building server replies from client requests
+use std::{fmt, net::Ipv4Addr};
+
+use crate::v4::{DhcpOption, DhcpOptions, Message, MessageType, Opcode, OptionCode};
+
+/// Source of option values for a server reply, typically backed by server config.
+///
+/// Implemented for any `Fn(OptionCode) -> Option<DhcpOption>`
+pub trait OptionSource {
+    /// return the value to send for `code`, if there is one
+    fn option(&self, code: OptionCode) -> Option<DhcpOption>;
+}
+
+impl<F> OptionSource for F
+where
+    F: Fn(OptionCode) -> Option<DhcpOption>,
+{
+    fn option(&self, code: OptionCode) -> Option<DhcpOption> {
+        self(code)
+    }
+}
+
+impl Message {
+    /// A copy of this request to fill in as the reply, the first step of building any
+    /// reply. Following RFC 2131 table 3, `xid`, `flags`, `giaddr`, `htype` & `chaddr`
+    /// are kept, the opcode is BootReply, `hops` & `secs` are 0, the addresses are
+    /// cleared (an Ack to a Request in the renewing or rebinding state sets `ciaddr`
+    /// again), `sname` & `file` are cleared and there are no options.
+    /// <https://datatracker.ietf.org/doc/html/rfc2131#section-4.3.1>
+    ///
+    /// [`ReplyBuilder`] builds on this to fill in the message type & options.
+    pub fn derive_reply(&self) -> Message {
+        let mut msg = self.clone();
+        msg.set_opcode(Opcode::BootReply)
+            .set_hops(0)
+            .set_secs(0)
+            .clear_addrs()
+            .set_giaddr(self.giaddr())
+            .set_opts(Default::default());
+        msg.clear_sname();
+        msg.clear_fname();
+        msg
+    }
+
+    /// A Nak refusing `request`, following RFC 2131 table 3. `ciaddr`, `yiaddr` & `siaddr`
+    /// are 0 and the only options are the message type, `ServerIdentifier`, `Message` with
+    /// `reason` (left out if it's empty), and the echoed `ClientIdentifier` &
+    /// `RelayAgentInformation`. When `giaddr` is set the broadcast flag is set, so the
+    /// relay broadcasts the Nak to a client that may not have a usable address.
+    /// <https://datatracker.ietf.org/doc/html/rfc2131#section-4.3.2>
+    ///
+    /// ```rust
+    /// use dhcproto::v4::{DhcpOption, Message, MessageType, OptionCode};
+    ///
+    /// let mut req = Message::default();
+    /// req.set_giaddr([10, 0, 0, 254]);
+    /// req.opts_mut().insert(DhcpOption::MessageType(MessageType::Request));
+    ///
+    /// let nak = Message::nak_for(&req, [10, 0, 0, 1].into(), "wrong network");
+    /// assert_eq!(nak.opts().msg_type(), Some(MessageType::Nak));
+    /// assert!(nak.flags().broadcast());
+    /// assert_eq!(
+    ///     nak.opts().get(OptionCode::Message),
+    ///     Some(&DhcpOption::Message("wrong network".into()))
+    /// );
+    /// ```
+    pub fn nak_for(request: &Message, server_id: Ipv4Addr, reason: &str) -> Message {
+        let mut msg = request.derive_reply();
+        if !msg.giaddr().is_unspecified() {
+            msg.set_flags(msg.flags().set_broadcast());
+        }
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::MessageType(MessageType::Nak));
+        opts.insert(DhcpOption::ServerIdentifier(server_id));
+        if !reason.is_empty() {
+            opts.insert(DhcpOption::Message(reason.into()));
+        }
+        echo_client_opts(request, opts);
+        msg
+    }
+}
+
+// every reply echoes the client id & relay agent info
+// <https://datatracker.ietf.org/doc/html/rfc6842>, <https://datatracker.ietf.org/doc/html/rfc3046#section-2.2>
+fn echo_client_opts(req: &Message, opts: &mut DhcpOptions) {
+    for code in [
+        OptionCode::ClientIdentifier,
+        OptionCode::RelayAgentInformation,
+    ] {
+        if let Some(opt) = req.opts().get(code) {
+            opts.insert(opt.clone());
+        }
+    }
+}
+
+/// Builds an Offer/Ack for an incoming request
+///
+/// The header fields are filled per RFC 2131 table 3 (xid, flags, giaddr & chaddr copied),
+/// the message type is set, and options are resolved from an [`OptionSource`]:
+/// * `ServerIdentifier` is always included
+/// * `AddressLeaseTime` is included unless replying to an Inform
+/// * every code in the request's `ParameterRequestList`, in order
+/// * `ClientIdentifier` is echoed - <https://datatracker.ietf.org/doc/html/rfc6842>
+/// * `RelayAgentInformation` is echoed, and encoded last - <https://datatracker.ietf.org/doc/html/rfc3046#section-2.2>
+///
+/// A decoded `RelayAgentInformation` is echoed as the bytes it was received as, see
+/// [`relay::verify_option82_echo`](crate::v4::relay::verify_option82_echo).
+///
+/// ```rust
+/// use dhcproto::v4::{self, DhcpOption, OptionCode, ReplyBuilder};
+///
+/// let mut req = v4::Message::default();
+/// req.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+/// req.opts_mut().insert(DhcpOption::MessageType(v4::MessageType::Discover));
+/// req.opts_mut().insert(DhcpOption::ParameterRequestList(vec![OptionCode::SubnetMask]));
+///
+/// let offer = ReplyBuilder::new(&req, |code| match code {
+///     OptionCode::ServerIdentifier => Some(DhcpOption::ServerIdentifier([192, 168, 0, 1].into())),
+///     OptionCode::AddressLeaseTime => Some(DhcpOption::AddressLeaseTime(3600.into())),
+///     OptionCode::SubnetMask => Some(DhcpOption::SubnetMask([255, 255, 255, 0].into())),
+///     _ => None,
+/// })
+/// .offer([192, 168, 0, 100].into());
+///
+/// assert_eq!(offer.xid(), req.xid());
+/// assert_eq!(offer.opts().msg_type(), Some(v4::MessageType::Offer));
+/// assert!(offer.opts().get(OptionCode::SubnetMask).is_some());
+/// ```
+pub struct ReplyBuilder<'a, S> {
+    req: &'a Message,
+    source: S,
+}
+
+impl<'a, S> fmt::Debug for ReplyBuilder<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplyBuilder")
+            .field("req", &self.req)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, S: OptionSource> ReplyBuilder<'a, S> {
+    /// create a new builder replying to `req`
+    pub fn new(req: &'a Message, source: S) -> Self {
+        Self { req, source }
+    }
+
+    /// build an Offer for `yiaddr`
+    pub fn offer(&self, yiaddr: Ipv4Addr) -> Message {
+        self.build(MessageType::Offer, yiaddr)
+    }
+
+    /// build an Ack for `yiaddr`. When replying to an Inform, `yiaddr` should be `0.0.0.0`
+    pub fn ack(&self, yiaddr: Ipv4Addr) -> Message {
+        self.build(MessageType::Ack, yiaddr)
+    }
+
+    /// build a reply with the given message type
+    pub fn build(&self, msg_type: MessageType, yiaddr: Ipv4Addr) -> Message {
+        let req = self.req;
+        let mut msg = req.derive_reply();
+        msg.set_yiaddr(yiaddr);
+        // ciaddr is only kept in an ack, it's 0 in an offer
+        if msg_type == MessageType::Ack {
+            msg.set_ciaddr(req.ciaddr());
+        }
+
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::MessageType(msg_type));
+        let mut codes = vec![OptionCode::ServerIdentifier];
+        if req.opts().msg_type() != Some(MessageType::Inform) {
+            codes.push(OptionCode::AddressLeaseTime);
+        }
+        if let Some(DhcpOption::ParameterRequestList(requested)) =
+            req.opts().get(OptionCode::ParameterRequestList)
+        {
+            codes.extend(requested.iter().copied().filter(|code| {
+                !matches!(
+                    *code,
+                    OptionCode::Pad | OptionCode::End | OptionCode::MessageType
+                )
+            }));
+        }
+        for code in codes {
+            if opts.get(code).is_none() {
+                if let Some(opt) = self.source.option(code) {
+                    opts.insert(opt);
+                }
+            }
+        }
+        echo_client_opts(req, opts);
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::{relay, Decodable, Decoder, Encodable, Flags};
+
+    fn source(code: OptionCode) -> Option<DhcpOption> {
+        match code {
+            OptionCode::ServerIdentifier => {
+                Some(DhcpOption::ServerIdentifier([10, 0, 0, 1].into()))
+            }
+            OptionCode::AddressLeaseTime => Some(DhcpOption::AddressLeaseTime(600.into())),
+            OptionCode::Router => Some(DhcpOption::Router(vec![[10, 0, 0, 1].into()])),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_reply() -> Result<(), Box<dyn std::error::Error>> {
+        let mut req = Message::default();
+        let mut info = relay::RelayAgentInformation::default();
+        info.insert(relay::RelayInfo::AgentCircuitId(vec![1, 2]));
+        req.set_chaddr(&[1, 2, 3, 4, 5, 6])
+            .set_flags(Flags::default().set_broadcast())
+            .set_giaddr([10, 0, 0, 254])
+            .set_ciaddr([10, 0, 0, 5]);
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        req.opts_mut().insert(DhcpOption::ParameterRequestList(vec![
+            OptionCode::Router,
+            OptionCode::DomainNameServer,
+        ]));
+        req.opts_mut()
+            .insert(DhcpOption::RelayAgentInformation(info));
+
+        let builder = ReplyBuilder::new(&req, source);
+        let offer = builder.offer([10, 0, 0, 5].into());
+        assert_eq!(offer.opcode(), Opcode::BootReply);
+        assert_eq!(offer.ciaddr(), Ipv4Addr::UNSPECIFIED);
+        assert_eq!(offer.giaddr(), req.giaddr());
+        assert!(offer.flags().broadcast());
+        assert_eq!(offer.chaddr(), req.chaddr());
+        // server id, lease time, message type, router, relay info
+        assert_eq!(offer.opts().len(), 5);
+        assert!(offer.opts().get(OptionCode::DomainNameServer).is_none());
+
+        let ack = builder.ack([10, 0, 0, 5].into());
+        assert_eq!(ack.ciaddr(), req.ciaddr());
+        assert_eq!(ack.opts().msg_type(), Some(MessageType::Ack));
+
+        // option 82 is encoded last, just before End
+        let buf = ack.to_vec()?;
+        let end = buf.iter().rposition(|b| *b == 255).unwrap();
+        assert_eq!(&buf[end - 6..end], &[82, 4, 1, 2, 1, 2]);
+        assert_eq!(Message::decode(&mut Decoder::new(&buf))?, ack);
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_reply() {
+        let mut req = Message::default();
+        req.set_chaddr(&[1, 2, 3, 4, 5, 6])
+            .set_flags(Flags::default().set_broadcast())
+            .set_hops(1)
+            .set_secs(4)
+            .set_giaddr([10, 0, 0, 254])
+            .set_ciaddr([10, 0, 0, 5])
+            .set_sname_str("host")
+            .set_fname_str("boot");
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        let reply = req.derive_reply();
+        assert_eq!(reply.opcode(), Opcode::BootReply);
+        assert_eq!(reply.xid(), req.xid());
+        assert_eq!(reply.flags(), req.flags());
+        assert_eq!(reply.giaddr(), req.giaddr());
+        assert_eq!(reply.chaddr(), req.chaddr());
+        assert_eq!((reply.hops(), reply.secs()), (0, 0));
+        assert_eq!(reply.ciaddr(), Ipv4Addr::UNSPECIFIED);
+        assert!(reply.sname().is_none() && reply.fname().is_none());
+        assert!(reply.opts().is_empty());
+    }
+
+    #[test]
+    fn test_inform() {
+        let mut req = Message::default();
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Inform));
+        let ack = ReplyBuilder::new(&req, source).ack(Ipv4Addr::UNSPECIFIED);
+        assert!(ack.opts().get(OptionCode::AddressLeaseTime).is_none());
+        assert!(ack.opts().get(OptionCode::ServerIdentifier).is_some());
+    }
+
+    #[test]
+    fn test_nak_for() {
+        let mut req = Message::default();
+        req.set_chaddr(&[1, 2, 3, 4, 5, 6])
+            .set_ciaddr([10, 0, 0, 5])
+            .set_siaddr([10, 0, 0, 2]);
+        req.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Request));
+        req.opts_mut()
+            .insert(DhcpOption::ClientIdentifier(vec![1, 2, 3]));
+        req.opts_mut()
+            .insert(DhcpOption::RequestedIpAddress([10, 0, 0, 5].into()));
+
+        let nak = Message::nak_for(&req, [10, 0, 0, 1].into(), "");
+        assert_eq!(nak.xid(), req.xid());
+        assert_eq!(nak.chaddr(), req.chaddr());
+        assert!(!nak.flags().broadcast());
+        assert_eq!(nak.ciaddr(), Ipv4Addr::UNSPECIFIED);
+        assert_eq!(nak.yiaddr(), Ipv4Addr::UNSPECIFIED);
+        assert_eq!(nak.siaddr(), Ipv4Addr::UNSPECIFIED);
+        // message type, server id & client id, no Message for an empty reason
+        assert_eq!(nak.opts().len(), 3);
+        assert!(nak.opts().get(OptionCode::AddressLeaseTime).is_none());
+        assert_eq!(
+            nak.opts().get(OptionCode::ClientIdentifier),
+            req.opts().get(OptionCode::ClientIdentifier)
+        );
+
+        req.set_giaddr([10, 0, 0, 254]);
+        let nak = Message::nak_for(&req, [10, 0, 0, 1].into(), "lease expired");
+        assert!(nak.flags().broadcast());
+        assert_eq!(nak.giaddr(), req.giaddr());
+        assert_eq!(
+            nak.opts().get(OptionCode::Message),
+            Some(&DhcpOption::Message("lease expired".into()))
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Retransmission timing for client requests
+//!
+//! RFC 2131 suggests waiting 4 seconds before the first retransmission and doubling the
+//! delay each time up to 64 seconds, randomized by ±1 second. The `secs` field holds the
+//! seconds since the client started, so each retransmission updates it while the xid
+//! stays the same. <https://datatracker.ietf.org/doc/html/rfc2131#section-4.1>
+//!
+//! [`Retransmit`] only computes delays, sending and sleeping are left to the caller.
+//!
+//! ```rust
+//! use dhcproto::v4::{self, retransmit::Retransmit};
+//!
+//! let discover = v4::Message::default();
+//! let sends = Retransmit::new(discover).jitter(false).collect::<Vec<_>>();
+//! let delays = sends.iter().map(|(delay, _)| delay.as_secs()).collect::<Vec<_>>();
+//! assert_eq!(delays, [0, 4, 8, 16, 32]);
+//! assert_eq!(sends[4].1.secs(), 60);
+//! ```
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::v4::Message;
+
+/// delay before the first retransmission
+pub const INITIAL_DELAY: Duration = Duration::from_secs(4);
+/// longest delay between retransmissions
+pub const MAX_DELAY: Duration = Duration::from_secs(64);
+/// retransmissions after the first send, by default
+pub const DEFAULT_RETRIES: usize = 4;
+
+/// a random transaction id, retransmissions of a request keep the same one
+pub fn random_xid() -> u32 {
+    rand::random()
+}
+
+/// Iterator of `(delay, message)` pairs for sending a request. `delay` is the time to
+/// wait before sending `message`, the first is sent immediately.
+#[derive(Debug, Clone)]
+pub struct Retransmit {
+    msg: Message,
+    attempt: usize,
+    retries: usize,
+    jitter: bool,
+    // time since the first send
+    elapsed: Duration,
+}
+
+impl Retransmit {
+    /// retransmissions of `msg`, its `secs` is the starting value
+    pub fn new(msg: Message) -> Self {
+        Self {
+            msg,
+            attempt: 0,
+            retries: DEFAULT_RETRIES,
+            jitter: true,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// number of retransmissions after the first send, defaults to [`DEFAULT_RETRIES`]
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// randomize delays by ±1 second, defaults to `true`
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// delay before retransmission `n` (from 1) without jitter
+    pub fn base_delay(n: usize) -> Duration {
+        let shift = n.saturating_sub(1).min(4) as u32;
+        (INITIAL_DELAY * 2u32.pow(shift)).min(MAX_DELAY)
+    }
+}
+
+impl Iterator for Retransmit {
+    type Item = (Duration, Message);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.attempt > self.retries {
+            return None;
+        }
+        let delay = match self.attempt {
+            0 => Duration::ZERO,
+            n if self.jitter => {
+                let base = Self::base_delay(n).as_millis() as u64;
+                let ms = rand::thread_rng().gen_range(base - 1000..=base + 1000);
+                Duration::from_millis(ms)
+            }
+            n => Self::base_delay(n),
+        };
+        let start = self.msg.secs();
+        self.elapsed += delay;
+        let secs = u16::try_from(self.elapsed.as_secs()).unwrap_or(u16::MAX);
+        let mut msg = self.msg.clone();
+        msg.set_secs(start.saturating_add(secs));
+        self.attempt += 1;
+        Some((delay, msg))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.retries.saturating_add(1).saturating_sub(self.attempt);
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Retransmit {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retransmit() {
+        let mut msg = Message::default();
+        msg.set_xid(random_xid()).set_secs(2);
+        let sends = Retransmit::new(msg.clone()).retries(6).collect::<Vec<_>>();
+        assert_eq!(sends.len(), 7);
+        assert_eq!(sends[0], (Duration::ZERO, msg.clone()));
+
+        let mut elapsed = Duration::ZERO;
+        for (n, (delay, sent)) in sends.iter().enumerate().skip(1) {
+            let base = Retransmit::base_delay(n);
+            assert!(*delay >= base - Duration::from_secs(1));
+            assert!(*delay <= base + Duration::from_secs(1));
+            elapsed += *delay;
+            assert_eq!(sent.secs(), 2 + elapsed.as_secs() as u16);
+            assert_eq!(sent.xid(), msg.xid());
+        }
+        // capped at 64s
+        assert_eq!(Retransmit::base_delay(5), MAX_DELAY);
+        assert_eq!(Retransmit::base_delay(6), MAX_DELAY);
+        assert_eq!(Retransmit::new(msg.clone()).retries(0).count(), 1);
+        // retrying forever doesn't overflow
+        let mut forever = Retransmit::new(msg).retries(usize::MAX);
+        assert_eq!(forever.len(), usize::MAX);
+        forever.next();
+        assert_eq!(forever.len(), usize::MAX - 1);
+    }
+}
@@ -0,0 +1,75 @@
+//! sample values for the round trip tests `declare_codes!` generates in `options`
+use std::net::Ipv4Addr;
+
+use ipnet::Ipv4Net;
+
+use crate::{
+    byte_string::ByteString,
+    domain::Domain,
+    time::LeaseTime,
+    uri::Uri,
+    v4::{
+        bulk_query,
+        fqdn::{ClientFQDN, FqdnFlags},
+        relay::{RelayAgentInformation, RelayInfo},
+        Architecture, AutoConfig, MessageType, NodeType, OptionCode, SipServers,
+    },
+};
+
+/// a valid, non-default value of `Self`
+pub(crate) trait Sample {
+    fn sample() -> Self;
+}
+
+macro_rules! sample {
+    ($($ty:ty => $value:expr;)*) => {
+        $(impl Sample for $ty {
+            fn sample() -> Self {
+                $value
+            }
+        })*
+    };
+}
+
+sample! {
+    bool => true;
+    u8 => 7;
+    u16 => 1500;
+    u32 => 86_400;
+    i32 => -3600;
+    String => "dhcproto".to_owned();
+    ByteString => ByteString::from(&b"dhcp\xe9"[..]);
+    Ipv4Addr => [192, 0, 2, 1].into();
+    Ipv4Net => "198.51.100.0/24".parse().unwrap();
+    Domain => "example.com".parse().unwrap();
+    Uri => Uri::new("https://example.com/boot").unwrap();
+    LeaseTime => LeaseTime::new(3600);
+    MessageType => MessageType::Offer;
+    OptionCode => OptionCode::DomainNameServer;
+    NodeType => NodeType::H;
+    Architecture => Architecture::Intelx86PC;
+    AutoConfig => AutoConfig::AutoConfigure;
+    SipServers => SipServers::Domains(Sample::sample());
+    ClientFQDN => ClientFQDN::new(FqdnFlags::default().set_s(true), Sample::sample());
+    RelayAgentInformation => {
+        let mut info = RelayAgentInformation::default();
+        info.insert(RelayInfo::AgentCircuitId(vec![1, 2, 3]));
+        info
+    };
+    bulk_query::Code => bulk_query::Code::UnspecFail;
+    bulk_query::QueryState => bulk_query::QueryState::Active;
+    bulk_query::DataSourceFlags => bulk_query::DataSourceFlags::from(1);
+}
+
+// two elements, so list encodings are exercised past the first entry
+impl<T: Sample> Sample for Vec<T> {
+    fn sample() -> Self {
+        vec![T::sample(), T::sample()]
+    }
+}
+
+impl<A: Sample, B: Sample> Sample for (A, B) {
+    fn sample() -> Self {
+        (A::sample(), B::sample())
+    }
+}
@@ -0,0 +1,546 @@
+//! Byte-precise re-encoding for proxies
+//!
+//! Decoding a [`Message`] normalizes its layout: padding is dropped, options are
+//! re-ordered, long options are re-split and bytes after the NUL in `sname`/`file` are
+//! lost. [`TransparentMessage`] keeps the original packet next to the decoded message so
+//! that encoding it reproduces the input byte-for-byte. Only what was changed through
+//! [`TransparentMessage::msg_mut`] is re-encoded:
+//! * header fields are always written from the message, they have a fixed size
+//! * `sname` & `file` are copied from the input unless they were changed
+//! * options keep their original position, padding and splitting unless they were
+//!   changed. Removed options are left out, new options are added before `End`
+//!   (`RelayAgentInformation` last)
+//! * anything after `End` is copied as is
+//!
+//! Relays & proxies that add or strip options can place them with
+//! [`TransparentMessage::insert_at`], [`TransparentMessage::append_options`] and
+//! [`TransparentMessage::strip_options`] without touching the rest of the packet.
+//!
+//! ```rust
+//! use dhcproto::{v4::{self, transparent::TransparentMessage}, Decodable, Encodable};
+//!
+//! let mut msg = v4::Message::default();
+//! msg.opts_mut()
+//!     .insert(v4::DhcpOption::MessageType(v4::MessageType::Discover));
+//! let mut raw = msg.to_vec()?;
+//! // padding after `End`
+//! raw.extend([0; 20]);
+//!
+//! let mut proxied = TransparentMessage::from_bytes(&raw)?;
+//! assert_eq!(proxied.to_vec()?, raw);
+//! proxied.msg_mut().set_hops(1);
+//! assert_eq!(proxied.to_vec()?.len(), raw.len());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use std::ops::Range;
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeResult},
+    v4::{DhcpOption, Message, OptionCode, MAGIC},
+};
+
+// offsets of `sname`, `file` and the vendor area in a message
+const SNAME: Range<usize> = 44..108;
+const FILE: Range<usize> = 108..236;
+const VENDOR: usize = 240;
+
+// a piece of the original options field
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Pad,
+    // consecutive instances of an option, including their code & length bytes
+    Opt {
+        code: OptionCode,
+        range: Range<usize>,
+    },
+    End,
+}
+
+/// A decoded [`Message`] that encodes back to the exact bytes it was decoded from,
+/// apart from what was changed. See the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransparentMessage {
+    msg: Message,
+    original: Message,
+    raw: Vec<u8>,
+    segments: Vec<Segment>,
+    // start of the bytes after the options
+    trailing: usize,
+    // options placed before a segment by `insert_at`/`append_options`, sorted by the
+    // segment index. `segments.len()` places them after the last segment
+    placed: Vec<(usize, OptionCode)>,
+}
+
+// where an option is written, in encoding order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    // in place of its segment
+    Segment(usize),
+    // an entry of `placed`
+    Placed(usize),
+    // a new option that wasn't placed, written before `End`
+    New,
+}
+
+// what is written for the options field
+#[derive(Debug)]
+enum Item<'a> {
+    Pad,
+    End,
+    Raw(OptionCode, Range<usize>),
+    Opt(&'a DhcpOption, Slot),
+}
+
+impl TransparentMessage {
+    /// the decoded message
+    pub fn msg(&self) -> &Message {
+        &self.msg
+    }
+
+    /// the decoded message, changes to it are re-encoded
+    pub fn msg_mut(&mut self) -> &mut Message {
+        &mut self.msg
+    }
+
+    /// the message as it was decoded
+    pub fn original(&self) -> &Message {
+        &self.original
+    }
+
+    /// the bytes the message was decoded from
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// `true` if the message was changed since it was decoded
+    pub fn is_modified(&self) -> bool {
+        self.msg != self.original
+    }
+
+    /// consume into the decoded message
+    pub fn into_inner(self) -> Message {
+        self.msg
+    }
+
+    /// option codes in the order they will be encoded
+    pub fn order(&self) -> Vec<OptionCode> {
+        let mut order = Vec::new();
+        for item in self.items() {
+            let code = match item {
+                Item::Raw(code, _) => code,
+                Item::Opt(opt, _) => opt.into(),
+                Item::Pad | Item::End => continue,
+            };
+            if !order.contains(&code) {
+                order.push(code);
+            }
+        }
+        order
+    }
+
+    /// insert `opt` before the option at `index` in [`TransparentMessage::order`], or
+    /// before `End` if `index` is past the last option. An option with the same code is
+    /// replaced and moved, everything else keeps its original bytes
+    pub fn insert_at(&mut self, index: usize, opt: DhcpOption) -> Option<DhcpOption> {
+        let code = OptionCode::from(&opt);
+        self.placed.retain(|(_, c)| *c != code);
+        let prev = self.msg.opts_mut().insert(opt);
+
+        let mut slots = Vec::new();
+        for item in self.items() {
+            let (c, slot) = match item {
+                Item::Raw(c, _) => (c, self.segment_of(c).map_or(Slot::New, Slot::Segment)),
+                Item::Opt(opt, slot) => (opt.into(), slot),
+                Item::Pad | Item::End => continue,
+            };
+            if c != code && !slots.iter().any(|(prev, _)| *prev == c) {
+                slots.push((c, slot));
+            }
+        }
+        let end = self.end_index();
+        let (at, pos) = match slots.get(index).map(|(_, slot)| *slot) {
+            Some(Slot::Placed(k)) => (self.placed[k].0, k),
+            Some(Slot::Segment(i)) => (i, self.placed_before(i + 1)),
+            Some(Slot::New) | None => (end, self.placed_before(end + 1)),
+        };
+        self.placed.insert(pos, (at, code));
+        prev
+    }
+
+    /// remove the option with `code`, leaving every other option & padding in place
+    pub fn remove_preserving_order(&mut self, code: OptionCode) -> Option<DhcpOption> {
+        self.placed.retain(|(_, c)| *c != code);
+        self.msg.opts_mut().remove(code)
+    }
+
+    /// remove the options with `codes`, e.g. a relay stripping `RelayAgentInformation`
+    /// from a reply, leaving everything else in place
+    pub fn strip_options(&mut self, codes: &[OptionCode]) {
+        for code in codes {
+            self.remove_preserving_order(*code);
+        }
+    }
+
+    /// add `opts` before `End` in the order given, e.g. a relay adding
+    /// `RelayAgentInformation` to a request. Options that are already present are
+    /// replaced and moved
+    pub fn append_options<I: IntoIterator<Item = DhcpOption>>(&mut self, opts: I) {
+        for opt in opts {
+            self.insert_at(usize::MAX, opt);
+        }
+    }
+
+    fn has_options(&self) -> bool {
+        self.raw.len() >= VENDOR && self.original.magic() == MAGIC
+    }
+
+    // index of the first segment of `code`
+    fn segment_of(&self, code: OptionCode) -> Option<usize> {
+        self.segments
+            .iter()
+            .position(|seg| matches!(seg, Segment::Opt { code: c, .. } if *c == code))
+    }
+
+    fn is_placed(&self, code: OptionCode) -> bool {
+        self.placed.iter().any(|(_, c)| *c == code)
+    }
+
+    // index of the `End` segment, or past the last segment if there is none
+    fn end_index(&self) -> usize {
+        self.segments
+            .iter()
+            .position(|seg| *seg == Segment::End)
+            .unwrap_or(self.segments.len())
+    }
+
+    // number of `placed` entries before segment `idx`
+    fn placed_before(&self, idx: usize) -> usize {
+        self.placed.iter().take_while(|(i, _)| *i < idx).count()
+    }
+
+    fn push_placed<'a>(&'a self, idx: usize, items: &mut Vec<Item<'a>>) {
+        for (k, (_, code)) in self
+            .placed
+            .iter()
+            .enumerate()
+            .filter(|(_, (i, _))| *i == idx)
+        {
+            if let Some(opt) = self.msg.opts().get(*code) {
+                items.push(Item::Opt(opt, Slot::Placed(k)));
+            }
+        }
+    }
+
+    // options that weren't in the original message and weren't placed,
+    // `RelayAgentInformation` last
+    fn push_new<'a>(&'a self, items: &mut Vec<Item<'a>>) {
+        let mut new = self
+            .msg
+            .opts()
+            .iter()
+            .filter(|(code, _)| self.segment_of(**code).is_none() && !self.is_placed(**code))
+            .collect::<Vec<_>>();
+        new.sort_unstable_by_key(|(code, _)| {
+            (
+                **code == OptionCode::RelayAgentInformation,
+                u8::from(**code),
+            )
+        });
+        items.extend(new.into_iter().map(|(_, opt)| Item::Opt(opt, Slot::New)));
+    }
+
+    // the options field, walking the original segments
+    fn items(&self) -> Vec<Item<'_>> {
+        let (msg, original) = (&self.msg, &self.original);
+        let mut items = Vec::new();
+        let mut written = Vec::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            self.push_placed(i, &mut items);
+            match segment {
+                Segment::Pad => items.push(Item::Pad),
+                Segment::End => {
+                    self.push_new(&mut items);
+                    items.push(Item::End);
+                }
+                // moved by `insert_at`
+                Segment::Opt { code, .. } if self.is_placed(*code) => {}
+                Segment::Opt { code, range } => {
+                    match (msg.opts().get(*code), original.opts().get(*code)) {
+                        // unchanged, or not decoded in the first place
+                        (cur, old) if cur == old => items.push(Item::Raw(*code, range.clone())),
+                        // changed, encode it in place of its first instance
+                        (Some(cur), _) if !written.contains(code) => {
+                            written.push(*code);
+                            items.push(Item::Opt(cur, Slot::Segment(i)));
+                        }
+                        // removed, or already written
+                        _ => {}
+                    }
+                }
+            }
+        }
+        if self.end_index() == self.segments.len() {
+            let len = items.len();
+            self.push_placed(self.segments.len(), &mut items);
+            self.push_new(&mut items);
+            if items.len() > len {
+                items.push(Item::End);
+            }
+        }
+        items
+    }
+}
+
+// split the options field into segments, stopping at `End` or malformed data
+fn segments(raw: &[u8]) -> (Vec<Segment>, usize) {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut i = VENDOR;
+    while let Some(&code) = raw.get(i) {
+        match code {
+            0 => {
+                segments.push(Segment::Pad);
+                i += 1;
+            }
+            255 => {
+                segments.push(Segment::End);
+                i += 1;
+                break;
+            }
+            _ => {
+                let Some(&len) = raw.get(i + 1) else {
+                    break;
+                };
+                let end = i + 2 + len as usize;
+                if end > raw.len() {
+                    break;
+                }
+                let code = OptionCode::from(code);
+                match segments.last_mut() {
+                    // split long option - <https://www.rfc-editor.org/rfc/rfc3396>
+                    Some(Segment::Opt { code: prev, range }) if *prev == code => {
+                        range.end = end;
+                    }
+                    _ => segments.push(Segment::Opt {
+                        code,
+                        range: i..end,
+                    }),
+                }
+                i = end;
+            }
+        }
+    }
+    (segments, i)
+}
+
+impl Decodable for TransparentMessage {
+    /// Decodes a message, keeping the rest of the decoder's buffer as trailing bytes
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let raw = decoder.buffer().to_vec();
+        let msg = Message::decode(decoder)?;
+        decoder.read_slice(decoder.buffer().len())?;
+        let (segments, trailing) = if raw.len() >= VENDOR && msg.magic() == MAGIC {
+            segments(&raw)
+        } else {
+            (Vec::new(), raw.len().min(FILE.end))
+        };
+        Ok(Self {
+            original: msg.clone(),
+            msg,
+            raw,
+            segments,
+            trailing,
+            placed: Vec::new(),
+        })
+    }
+}
+
+impl Encodable for TransparentMessage {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        let (msg, original) = (&self.msg, &self.original);
+        msg.encode_fixed(e)?;
+        if msg.sname() == original.sname() {
+            e.write_slice(&self.raw[SNAME])?;
+        } else {
+            e.write_fill(&msg.sname(), SNAME.len())?;
+        }
+        if msg.fname() == original.fname() {
+            e.write_slice(&self.raw[FILE])?;
+        } else {
+            e.write_fill(&msg.fname(), FILE.len())?;
+        }
+
+        if !self.has_options() {
+            // no options to keep in place, e.g. a BOOTP vendor area
+            if msg.magic() == original.magic() && msg.opts().is_empty() {
+                return e.write_slice(&self.raw[self.trailing..]);
+            }
+            e.write(msg.magic())?;
+            return msg.opts().encode(e);
+        }
+
+        e.write(msg.magic())?;
+        for item in self.items() {
+            match item {
+                Item::Pad => e.write_u8(OptionCode::Pad.into())?,
+                Item::End => e.write_u8(OptionCode::End.into())?,
+                Item::Raw(_, range) => e.write_slice(&self.raw[range])?,
+                Item::Opt(opt, _) => opt.encode(e)?,
+            }
+        }
+        e.write_slice(&self.raw[self.trailing..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::{
+        relay::{RelayAgentInformation, RelayInfo},
+        DhcpOption, MessageType,
+    };
+
+    fn raw() -> Vec<u8> {
+        let mut raw = vec![1, 1, 6, 0, 1, 2, 3, 4];
+        raw.extend([0; 20]);
+        raw.extend([0xaa; 6]);
+        raw.extend([0; 10]);
+        // sname with bytes after the NUL
+        raw.extend(b"server\0garbage");
+        raw.extend([0; 50]);
+        // file without a NUL
+        raw.extend([b'f'; 128]);
+        raw.extend(MAGIC);
+        raw.extend([53, 1, 1, 0, 0]);
+        // hostname split in two
+        raw.extend([12, 2, b'a', b'b', 12, 1, b'c']);
+        raw.extend([61, 3, 1, 2, 3, 255]);
+        raw.extend([0; 7]);
+        raw
+    }
+
+    #[test]
+    fn test_transparent() -> Result<(), Box<dyn std::error::Error>> {
+        let raw = raw();
+        let mut msg = TransparentMessage::from_bytes(&raw)?;
+        assert_eq!(msg.to_vec()?, raw);
+        // a plain message normalizes the layout
+        assert_ne!(Message::from_bytes(&raw)?.to_vec()?, raw);
+
+        // header fields are re-encoded
+        msg.msg_mut().set_hops(2);
+        let mut expected = raw.clone();
+        expected[3] = 2;
+        assert_eq!(msg.to_vec()?, expected);
+        assert!(msg.is_modified());
+
+        // changed options are re-encoded in place
+        msg.msg_mut()
+            .opts_mut()
+            .insert(DhcpOption::Hostname("xyz".into()));
+        // start of the options after the cookie
+        let opts = raw.len() - 25;
+        expected.splice(opts + 5..opts + 12, [12, 3, b'x', b'y', b'z']);
+        assert_eq!(msg.to_vec()?, expected);
+
+        // removed options are left out, new ones are added before `End`
+        msg.msg_mut()
+            .opts_mut()
+            .remove(OptionCode::ClientIdentifier);
+        let mut info = RelayAgentInformation::default();
+        info.insert(RelayInfo::AgentCircuitId(vec![7]));
+        msg.msg_mut()
+            .opts_mut()
+            .insert(DhcpOption::RelayAgentInformation(info));
+        msg.msg_mut()
+            .opts_mut()
+            .insert(DhcpOption::MaxMessageSize(1500));
+        expected.splice(opts + 10..opts + 15, [57, 2, 5, 220, 82, 3, 1, 1, 7]);
+        assert_eq!(msg.to_vec()?, expected);
+        assert_eq!(msg.msg().opts().msg_type(), Some(MessageType::Discover));
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_strip() -> Result<(), Box<dyn std::error::Error>> {
+        use OptionCode as C;
+        let raw = raw();
+        let opts = raw.len() - 25;
+        let mut msg = TransparentMessage::from_bytes(&raw)?;
+        assert_eq!(
+            msg.order(),
+            [C::MessageType, C::Hostname, C::ClientIdentifier]
+        );
+
+        // a relay adds option 82 & strips it from the reply
+        let mut info = RelayAgentInformation::default();
+        info.insert(RelayInfo::AgentCircuitId(vec![7]));
+        msg.append_options([DhcpOption::RelayAgentInformation(info)]);
+        assert_eq!(
+            msg.order(),
+            [
+                C::MessageType,
+                C::Hostname,
+                C::ClientIdentifier,
+                C::RelayAgentInformation
+            ]
+        );
+        let mut expected = raw.clone();
+        expected.splice(opts + 17..opts + 17, [82, 3, 1, 1, 7]);
+        assert_eq!(msg.to_vec()?, expected);
+        msg.strip_options(&[C::RelayAgentInformation]);
+        assert_eq!(msg.to_vec()?, raw);
+
+        msg.insert_at(1, DhcpOption::MaxMessageSize(1500));
+        msg.insert_at(0, DhcpOption::Router(vec![[10, 0, 0, 1].into()]));
+        msg.insert_at(2, DhcpOption::DomainName("a".into()));
+        assert_eq!(
+            msg.order(),
+            [
+                C::Router,
+                C::MessageType,
+                C::DomainName,
+                C::MaxMessageSize,
+                C::Hostname,
+                C::ClientIdentifier
+            ]
+        );
+        let mut expected = raw.clone();
+        expected.splice(opts + 5..opts + 5, [15, 1, b'a', 57, 2, 5, 220]);
+        expected.splice(opts..opts, [3, 4, 10, 0, 0, 1]);
+        assert_eq!(msg.to_vec()?, expected);
+
+        // moving an existing option
+        assert!(msg.remove_preserving_order(C::Router).is_some());
+        assert!(msg.remove_preserving_order(C::DomainName).is_some());
+        msg.remove_preserving_order(C::MaxMessageSize);
+        msg.insert_at(0, DhcpOption::ClientIdentifier(vec![1, 2, 3]));
+        assert_eq!(
+            msg.order(),
+            [C::ClientIdentifier, C::MessageType, C::Hostname]
+        );
+        let mut expected = raw.clone();
+        expected.drain(opts + 12..opts + 17);
+        expected.splice(opts..opts, [61, 3, 1, 2, 3]);
+        assert_eq!(msg.to_vec()?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transparent_bootp() -> Result<(), Box<dyn std::error::Error>> {
+        // no magic cookie, the vendor area is kept
+        let mut raw = raw();
+        raw.truncate(236);
+        raw.extend([1; 64]);
+        let mut msg = TransparentMessage::from_bytes(&raw)?;
+        assert!(msg.msg().opts().is_empty());
+        assert_eq!(msg.to_vec()?, raw);
+
+        msg.msg_mut().set_sname_str("other");
+        let out = msg.to_vec()?;
+        assert_eq!(&out[SNAME][..6], b"other\0");
+        assert_eq!(out[FILE.end..], raw[FILE.end..]);
+        Ok(())
+    }
+}
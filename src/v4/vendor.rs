@@ -0,0 +1,514 @@
+//! Vendor specific information (option 43) for common vendors
+//!
+//! The layout of option 43 is up to the vendor, identified by the class in option 60.
+//! [`VendorProfile`] recognizes some common classes and [`VendorExtensions`] decodes
+//! option 43 into typed sub-options for them:
+//! * DOCSIS cable modems, `docsis3.0` etc
+//! * Ubiquiti UniFi devices, `ubnt`, told the controller address
+//! * PXE clients, `PXEClient`, as answered by e.g. Windows Deployment Services
+//!
+//! UEFI HTTP boot clients send the class `HTTPClient` and get the boot file URL in
+//! option 67 rather than option 43, see [`DhcpOptions::boot_file_url`].
+//!
+//! ```rust
+//! use dhcproto::v4::{self, vendor::{UnifiInfo, VendorExtensions}};
+//!
+//! let mut msg = v4::Message::default();
+//! let opts = msg.opts_mut();
+//! opts.insert(v4::DhcpOption::ClassIdentifier(b"ubnt".to_vec()));
+//! opts.insert(v4::DhcpOption::VendorExtensions(vec![1, 4, 192, 168, 1, 10]));
+//!
+//! let Some(VendorExtensions::Unifi(info)) = msg.opts().vendor_extensions() else {
+//!     panic!("not a UniFi device");
+//! };
+//! assert_eq!(info.get(1), Some(&UnifiInfo::Controller([192, 168, 1, 10].into())));
+//! ```
+use std::net::Ipv4Addr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeResult},
+    sub_options::{RawSubOption, SubOption, SubOptions},
+    uri::Uri,
+    v4::{DhcpOption, DhcpOptions, OptionCode},
+};
+
+// the class a server answers PXE clients with
+const PXE_CLASS: &[u8] = b"PXEClient";
+// the class of UEFI HTTP boot clients, and the one they're answered with
+const HTTP_BOOT_CLASS: &[u8] = b"HTTPClient";
+
+/// A vendor with a known option 43 layout
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VendorProfile {
+    /// DOCSIS cable modem, class starting with `docsis`
+    Docsis,
+    /// Ubiquiti UniFi device, class `ubnt`
+    Unifi,
+    /// PXE client, class starting with `PXEClient`
+    Pxe,
+}
+
+impl VendorProfile {
+    /// the profile for the vendor class in option 60, ignoring ASCII case
+    pub fn detect(class: &[u8]) -> Option<Self> {
+        let starts_with = |prefix: &str| {
+            class
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+        };
+        if starts_with("docsis") {
+            Some(VendorProfile::Docsis)
+        } else if starts_with("ubnt") {
+            Some(VendorProfile::Unifi)
+        } else if starts_with("PXEClient") {
+            Some(VendorProfile::Pxe)
+        } else {
+            None
+        }
+    }
+
+    /// `true` if the vendor expects option 43 to end with an End (255) sub-option,
+    /// as PXE clients do
+    /// <https://datatracker.ietf.org/doc/html/rfc4578#section-2.4>
+    pub fn terminated(self) -> bool {
+        matches!(self, VendorProfile::Pxe)
+    }
+}
+
+/// Option 43 decoded for a [`VendorProfile`], or as raw sub-options if there's none
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VendorExtensions {
+    /// DOCSIS cable modem information
+    Docsis(SubOptions<u8, DocsisInfo>),
+    /// UniFi controller
+    Unifi(SubOptions<u8, UnifiInfo>),
+    /// PXE vendor options
+    Pxe(SubOptions<u8, PxeInfo>),
+    /// no known layout
+    Raw(SubOptions<u8, RawSubOption<u8>>),
+}
+
+impl VendorExtensions {
+    /// decode the value of option 43 with `profile`
+    pub fn decode_with(profile: Option<VendorProfile>, data: &[u8]) -> DecodeResult<Self> {
+        let mut d = Decoder::new(data);
+        Ok(match profile {
+            Some(VendorProfile::Docsis) => VendorExtensions::Docsis(SubOptions::decode(&mut d)?),
+            Some(VendorProfile::Unifi) => VendorExtensions::Unifi(SubOptions::decode(&mut d)?),
+            Some(VendorProfile::Pxe) => VendorExtensions::Pxe(SubOptions::decode(&mut d)?),
+            None => VendorExtensions::Raw(SubOptions::decode(&mut d)?),
+        })
+    }
+
+    /// the profile these extensions were decoded with
+    pub fn profile(&self) -> Option<VendorProfile> {
+        match self {
+            VendorExtensions::Docsis(_) => Some(VendorProfile::Docsis),
+            VendorExtensions::Unifi(_) => Some(VendorProfile::Unifi),
+            VendorExtensions::Pxe(_) => Some(VendorProfile::Pxe),
+            VendorExtensions::Raw(_) => None,
+        }
+    }
+}
+
+impl Encodable for VendorExtensions {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            VendorExtensions::Docsis(opts) => opts.encode(e),
+            VendorExtensions::Unifi(opts) => opts.encode(e),
+            VendorExtensions::Pxe(opts) => opts.encode(e),
+            VendorExtensions::Raw(opts) => opts.encode(e),
+        }
+    }
+}
+
+impl DhcpOptions {
+    /// Decode option 43 with the profile of the vendor class in option 60. Returns
+    /// `None` without option 43.
+    pub fn vendor_extensions(&self) -> Option<VendorExtensions> {
+        let profile = match self.get(OptionCode::ClassIdentifier) {
+            Some(DhcpOption::ClassIdentifier(class)) => VendorProfile::detect(class),
+            _ => None,
+        };
+        self.vendor_extensions_with(profile)
+    }
+
+    /// Insert the options a server answers a vendor with: option 60 echoing the client's
+    /// `class` and option 43 with `ext`, ended with an End sub-option if
+    /// [`VendorProfile::terminated`]. PXE clients are answered with the class
+    /// `PXEClient` rather than their full class.
+    ///
+    /// ```rust
+    /// use dhcproto::v4::{self, vendor::{PxeInfo, VendorExtensions}, DhcpOption, OptionCode};
+    ///
+    /// let class = b"PXEClient:Arch:00007:UNDI:003016";
+    /// let ext = VendorExtensions::Pxe([PxeInfo::DiscoveryControl(8)].into_iter().collect());
+    /// let mut opts = v4::DhcpOptions::new();
+    /// opts.insert_vendor_reply(class, &ext)?;
+    ///
+    /// assert_eq!(
+    ///     opts.get(OptionCode::ClassIdentifier),
+    ///     Some(&DhcpOption::ClassIdentifier(b"PXEClient".to_vec()))
+    /// );
+    /// assert_eq!(
+    ///     opts.get(OptionCode::VendorExtensions),
+    ///     Some(&DhcpOption::VendorExtensions(vec![6, 1, 8, 255]))
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn insert_vendor_reply(
+        &mut self,
+        class: &[u8],
+        ext: &VendorExtensions,
+    ) -> EncodeResult<()> {
+        let profile = ext.profile();
+        let class = match profile {
+            Some(VendorProfile::Pxe) => PXE_CLASS.to_vec(),
+            _ => class.to_vec(),
+        };
+        let mut data = ext.to_vec()?;
+        if profile.is_some_and(VendorProfile::terminated) {
+            data.push(OptionCode::End.into());
+        }
+        self.insert(DhcpOption::ClassIdentifier(class));
+        self.insert(DhcpOption::VendorExtensions(data));
+        Ok(())
+    }
+
+    /// `true` if option 60 is a UEFI HTTP boot client's class, starting with `HTTPClient`.
+    /// These clients are answered with the class `HTTPClient` and a boot file URL in
+    /// `BootfileName`.
+    pub fn is_http_boot_client(&self) -> bool {
+        matches!(
+            self.get(OptionCode::ClassIdentifier),
+            Some(DhcpOption::ClassIdentifier(class)) if class.starts_with(HTTP_BOOT_CLASS)
+        )
+    }
+
+    /// `BootfileName` (67) as a URL, for UEFI HTTP boot. Returns `None` without option
+    /// 67 or if it isn't a boot file URL, see [`Uri::is_boot_file_url`].
+    ///
+    /// ```rust
+    /// use dhcproto::v4::{self, DhcpOption};
+    ///
+    /// let mut opts = v4::DhcpOptions::new();
+    /// opts.insert(DhcpOption::BootfileName(b"pxelinux.0".to_vec()));
+    /// assert!(opts.boot_file_url().is_none());
+    /// opts.insert(DhcpOption::BootfileName(b"http://10.0.0.1/boot.efi".to_vec()));
+    /// assert_eq!(opts.boot_file_url().unwrap().as_str(), "http://10.0.0.1/boot.efi");
+    /// ```
+    pub fn boot_file_url(&self) -> Option<Uri> {
+        let Some(DhcpOption::BootfileName(name)) = self.get(OptionCode::BootfileName) else {
+            return None;
+        };
+        let uri = Uri::new(std::str::from_utf8(name).ok()?).ok()?;
+        uri.is_boot_file_url().then_some(uri)
+    }
+
+    /// Decode option 43 with `profile`, e.g. for a reply that doesn't carry option 60.
+    /// Returns `None` without option 43.
+    pub fn vendor_extensions_with(
+        &self,
+        profile: Option<VendorProfile>,
+    ) -> Option<VendorExtensions> {
+        match self.get(OptionCode::VendorExtensions) {
+            Some(DhcpOption::VendorExtensions(data)) => {
+                VendorExtensions::decode_with(profile, data).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// DOCSIS cable modem sub-options of option 43
+/// <https://www.cablelabs.com/specifications/CL-SP-CANN-DHCP-Reg>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DocsisInfo {
+    /// 2 - device type, e.g. `ECM`
+    DeviceType(String),
+    /// 4 - serial number
+    SerialNumber(String),
+    /// 5 - hardware version
+    HardwareVersion(String),
+    /// 6 - software version
+    SoftwareVersion(String),
+    /// 7 - boot ROM version
+    BootRomVersion(String),
+    /// 8 - organizationally unique identifier, as hex
+    Oui(String),
+    /// 9 - model number
+    ModelNumber(String),
+    /// 10 - vendor name
+    VendorName(String),
+    /// any other sub-option
+    Unknown(RawSubOption<u8>),
+}
+
+impl SubOption<u8> for DocsisInfo {
+    fn code(&self) -> u8 {
+        match self {
+            DocsisInfo::DeviceType(_) => 2,
+            DocsisInfo::SerialNumber(_) => 4,
+            DocsisInfo::HardwareVersion(_) => 5,
+            DocsisInfo::SoftwareVersion(_) => 6,
+            DocsisInfo::BootRomVersion(_) => 7,
+            DocsisInfo::Oui(_) => 8,
+            DocsisInfo::ModelNumber(_) => 9,
+            DocsisInfo::VendorName(_) => 10,
+            DocsisInfo::Unknown(opt) => opt.code(),
+        }
+    }
+
+    fn decode_value(code: u8, d: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let len = d.buffer().len();
+        let data = d.read_slice(len)?;
+        Ok(match (code, std::str::from_utf8(data).map(str::to_owned)) {
+            (2, Ok(s)) => DocsisInfo::DeviceType(s),
+            (4, Ok(s)) => DocsisInfo::SerialNumber(s),
+            (5, Ok(s)) => DocsisInfo::HardwareVersion(s),
+            (6, Ok(s)) => DocsisInfo::SoftwareVersion(s),
+            (7, Ok(s)) => DocsisInfo::BootRomVersion(s),
+            (8, Ok(s)) => DocsisInfo::Oui(s),
+            (9, Ok(s)) => DocsisInfo::ModelNumber(s),
+            (10, Ok(s)) => DocsisInfo::VendorName(s),
+            _ => DocsisInfo::Unknown(RawSubOption::new(code, data.to_vec())),
+        })
+    }
+
+    fn encode_value(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            DocsisInfo::DeviceType(s)
+            | DocsisInfo::SerialNumber(s)
+            | DocsisInfo::HardwareVersion(s)
+            | DocsisInfo::SoftwareVersion(s)
+            | DocsisInfo::BootRomVersion(s)
+            | DocsisInfo::Oui(s)
+            | DocsisInfo::ModelNumber(s)
+            | DocsisInfo::VendorName(s) => e.write_slice(s.as_bytes()),
+            DocsisInfo::Unknown(opt) => e.write_slice(opt.data()),
+        }
+    }
+}
+
+/// UniFi sub-options of option 43
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnifiInfo {
+    /// 1 - address of the controller to adopt the device
+    Controller(Ipv4Addr),
+    /// any other sub-option
+    Unknown(RawSubOption<u8>),
+}
+
+impl SubOption<u8> for UnifiInfo {
+    fn code(&self) -> u8 {
+        match self {
+            UnifiInfo::Controller(_) => 1,
+            UnifiInfo::Unknown(opt) => opt.code(),
+        }
+    }
+
+    fn decode_value(code: u8, d: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let len = d.buffer().len();
+        Ok(match code {
+            1 if len == 4 => UnifiInfo::Controller(d.read_ipv4(len)?),
+            _ => UnifiInfo::Unknown(RawSubOption::new(code, d.read_slice(len)?.to_vec())),
+        })
+    }
+
+    fn encode_value(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            UnifiInfo::Controller(addr) => e.write_u32((*addr).into()),
+            UnifiInfo::Unknown(opt) => e.write_slice(opt.data()),
+        }
+    }
+}
+
+/// PXE sub-options of option 43
+/// <https://datatracker.ietf.org/doc/html/rfc4578#section-2.4>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PxeInfo {
+    /// 6 - discovery control bits
+    DiscoveryControl(u8),
+    /// 10 - menu prompt shown for `timeout` seconds
+    MenuPrompt {
+        /// seconds to wait for a key press
+        timeout: u8,
+        /// prompt text
+        prompt: String,
+    },
+    /// 71 - boot server type & layer of the boot item
+    BootItem {
+        /// boot server type
+        server_type: u16,
+        /// boot layer
+        layer: u16,
+    },
+    /// any other sub-option, e.g. boot servers (8) or the boot menu (9)
+    Unknown(RawSubOption<u8>),
+}
+
+impl SubOption<u8> for PxeInfo {
+    fn code(&self) -> u8 {
+        match self {
+            PxeInfo::DiscoveryControl(_) => 6,
+            PxeInfo::MenuPrompt { .. } => 10,
+            PxeInfo::BootItem { .. } => 71,
+            PxeInfo::Unknown(opt) => opt.code(),
+        }
+    }
+
+    fn decode_value(code: u8, d: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let len = d.buffer().len();
+        Ok(match code {
+            6 if len == 1 => PxeInfo::DiscoveryControl(d.read_u8()?),
+            10 if len >= 1 && std::str::from_utf8(&d.buffer()[1..]).is_ok() => {
+                PxeInfo::MenuPrompt {
+                    timeout: d.read_u8()?,
+                    prompt: d.read_string(len - 1)?,
+                }
+            }
+            71 if len == 4 => PxeInfo::BootItem {
+                server_type: d.read_u16()?,
+                layer: d.read_u16()?,
+            },
+            _ => PxeInfo::Unknown(RawSubOption::new(code, d.read_slice(len)?.to_vec())),
+        })
+    }
+
+    fn encode_value(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            PxeInfo::DiscoveryControl(bits) => e.write_u8(*bits),
+            PxeInfo::MenuPrompt { timeout, prompt } => {
+                e.write_u8(*timeout)?;
+                e.write_slice(prompt.as_bytes())
+            }
+            PxeInfo::BootItem { server_type, layer } => {
+                e.write_u16(*server_type)?;
+                e.write_u16(*layer)
+            }
+            PxeInfo::Unknown(opt) => e.write_slice(opt.data()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docsis() {
+        assert_eq!(
+            VendorProfile::detect(b"docsis3.0:"),
+            Some(VendorProfile::Docsis)
+        );
+        assert_eq!(VendorProfile::detect(b"MSFT 5.0"), None);
+
+        #[rustfmt::skip]
+        let data = [
+            2, 3, b'E', b'C', b'M',
+            3, 2, b'a', b'b',
+            8, 6, b'0', b'0', b'1', b'0', b'9', b'5',
+        ];
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::ClassIdentifier(b"docsis3.0".to_vec()));
+        opts.insert(DhcpOption::VendorExtensions(data.to_vec()));
+        let ext = opts.vendor_extensions().unwrap();
+        assert_eq!(ext.profile(), Some(VendorProfile::Docsis));
+        let VendorExtensions::Docsis(info) = &ext else {
+            panic!("expected docsis");
+        };
+        assert_eq!(info.get(2), Some(&DocsisInfo::DeviceType("ECM".into())));
+        assert_eq!(info.get(8), Some(&DocsisInfo::Oui("001095".into())));
+        assert_eq!(
+            info.get(3),
+            Some(&DocsisInfo::Unknown(RawSubOption::new(3, b"ab".to_vec())))
+        );
+        assert_eq!(ext.to_vec().unwrap(), data);
+    }
+
+    #[test]
+    fn test_pxe() {
+        #[rustfmt::skip]
+        let data = [
+            6, 1, 8,
+            10, 4, 5, b'g', b'o', b'!',
+            71, 4, 0x80, 0, 0, 0,
+        ];
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::VendorExtensions(data.to_vec()));
+        assert!(matches!(
+            opts.vendor_extensions(),
+            Some(VendorExtensions::Raw(_))
+        ));
+        let Some(VendorExtensions::Pxe(info)) =
+            opts.vendor_extensions_with(Some(VendorProfile::Pxe))
+        else {
+            panic!("expected pxe");
+        };
+        assert_eq!(info.get(6), Some(&PxeInfo::DiscoveryControl(8)));
+        assert_eq!(
+            info.get(10),
+            Some(&PxeInfo::MenuPrompt {
+                timeout: 5,
+                prompt: "go!".into()
+            })
+        );
+        assert_eq!(
+            info.get(71),
+            Some(&PxeInfo::BootItem {
+                server_type: 0x8000,
+                layer: 0
+            })
+        );
+        assert_eq!(info.to_vec().unwrap(), data);
+        assert!(DhcpOptions::new().vendor_extensions().is_none());
+    }
+
+    #[test]
+    fn test_vendor_reply() {
+        let mut unifi = SubOptions::new();
+        unifi.insert(UnifiInfo::Controller([192, 168, 1, 10].into()));
+        let mut opts = DhcpOptions::new();
+        opts.insert_vendor_reply(b"ubnt", &VendorExtensions::Unifi(unifi))
+            .unwrap();
+        assert_eq!(
+            opts.get(OptionCode::ClassIdentifier),
+            Some(&DhcpOption::ClassIdentifier(b"ubnt".to_vec()))
+        );
+        // no End
+        assert_eq!(
+            opts.get(OptionCode::VendorExtensions),
+            Some(&DhcpOption::VendorExtensions(vec![1, 4, 192, 168, 1, 10]))
+        );
+
+        // the End sub-option doesn't get in the way of decoding
+        let ext = VendorExtensions::Pxe([PxeInfo::DiscoveryControl(8)].into_iter().collect());
+        opts.insert_vendor_reply(b"PXEClient:Arch:00007", &ext)
+            .unwrap();
+        assert_eq!(opts.vendor_extensions(), Some(ext));
+    }
+
+    #[test]
+    fn test_http_boot() {
+        let mut opts = DhcpOptions::new();
+        assert!(!opts.is_http_boot_client());
+        opts.insert(DhcpOption::ClassIdentifier(
+            b"HTTPClient:Arch:00016:UNDI:003001".to_vec(),
+        ));
+        assert!(opts.is_http_boot_client());
+        opts.insert(DhcpOption::BootfileName(b"https://boot.example/".to_vec()));
+        assert_eq!(opts.boot_file_url(), None);
+        opts.insert(DhcpOption::BootfileName(vec![0xff]));
+        assert_eq!(opts.boot_file_url(), None);
+    }
+}
@@ -0,0 +1,419 @@
+//! Building client messages
+use std::net::Ipv6Addr;
+
+use crate::v6::{
+    duid::Duid, DhcpOption, DhcpOptions, IAAddr, IAPrefix, LeaseTime, Message, MessageType,
+    OptionCode, IANA, IAPD, IATA,
+};
+
+/// An identity association a client asks for leases on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IA {
+    /// non-temporary addresses
+    NA(IANA),
+    /// temporary addresses
+    TA(IATA),
+    /// delegated prefixes
+    PD(IAPD),
+}
+
+impl From<IANA> for IA {
+    fn from(ia: IANA) -> Self {
+        IA::NA(ia)
+    }
+}
+
+impl From<IATA> for IA {
+    fn from(ia: IATA) -> Self {
+        IA::TA(ia)
+    }
+}
+
+impl From<IAPD> for IA {
+    fn from(ia: IAPD) -> Self {
+        IA::PD(ia)
+    }
+}
+
+impl From<IA> for DhcpOption {
+    fn from(ia: IA) -> Self {
+        match ia {
+            IA::NA(ia) => DhcpOption::IANA(ia),
+            IA::TA(ia) => DhcpOption::IATA(ia),
+            IA::PD(ia) => DhcpOption::IAPD(ia),
+        }
+    }
+}
+
+/// Constructors for client messages that take the options RFC 8415 requires in each, so
+/// what they build is valid as is. Every message has a new xid, a `ClientId` & an
+/// `ElapsedTime` of 0; more options, like an `ORO`, can be added with
+/// [`Message::opts_mut`]. <https://datatracker.ietf.org/doc/html/rfc8415#appendix-B>
+impl Message {
+    /// Build a Solicit for `ias`, which has no `ServerId`.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.1>
+    ///
+    /// ```rust
+    /// use dhcproto::v6::{self, duid::Duid, OptionCode, IANA};
+    ///
+    /// let duid = Duid::link_layer(v6::HType::Eth, "fe80::1".parse().unwrap());
+    /// let ia = IANA { id: 1, t1: 0, t2: 0, opts: Default::default() };
+    /// let solicit = v6::Message::solicit(&duid, [ia.into()]);
+    /// assert_eq!(solicit.client_id(), Some(duid.as_ref()));
+    /// assert!(solicit.opts().get(OptionCode::IANA).is_some());
+    /// assert_eq!(solicit.server_id(), None);
+    /// ```
+    pub fn solicit(client_id: &Duid, ias: impl IntoIterator<Item = IA>) -> Message {
+        client_msg(MessageType::Solicit, client_id, None, ias)
+    }
+
+    /// Build a Request for `ia` & `more` to the server `server_id`, the `ServerId` of the
+    /// Advertise the client chose. A Request needs at least one IA.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.2>
+    pub fn request(
+        client_id: &Duid,
+        server_id: &Duid,
+        ia: IA,
+        more: impl IntoIterator<Item = IA>,
+    ) -> Message {
+        client_msg(
+            MessageType::Request,
+            client_id,
+            Some(server_id),
+            std::iter::once(ia).chain(more),
+        )
+    }
+
+    /// Build a Renew of `ia` & `more` to the server `server_id` that granted them. A
+    /// Renew needs at least one IA.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.4>
+    pub fn renew(
+        client_id: &Duid,
+        server_id: &Duid,
+        ia: IA,
+        more: impl IntoIterator<Item = IA>,
+    ) -> Message {
+        client_msg(
+            MessageType::Renew,
+            client_id,
+            Some(server_id),
+            std::iter::once(ia).chain(more),
+        )
+    }
+
+    /// Build a Rebind of `ia` & `more`, which goes to any server so has no `ServerId`. A
+    /// Rebind needs at least one IA.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.5>
+    pub fn rebind(client_id: &Duid, ia: IA, more: impl IntoIterator<Item = IA>) -> Message {
+        client_msg(
+            MessageType::Rebind,
+            client_id,
+            None,
+            std::iter::once(ia).chain(more),
+        )
+    }
+
+    /// Build an Information-request, which holds no IAs.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.6>
+    pub fn information_request(client_id: &Duid) -> Message {
+        client_msg(MessageType::InformationRequest, client_id, None, [])
+    }
+
+    /// Build a Decline for the addresses in `reply` that the client found to be in use,
+    /// e.g. by duplicate address detection.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.8>
+    ///
+    /// The Decline has a new xid, the `ClientId` & `ServerId` of the reply and an
+    /// `ElapsedTime` of 0. Each IA_NA/IA_TA holding one of `addrs` is included with only
+    /// those addresses, T1/T2 and lifetimes are set to 0 as a client should.
+    ///
+    /// Returns `None` if `reply` isn't a Reply, is missing `ClientId` or `ServerId`, or
+    /// has none of `addrs`.
+    ///
+    /// ```rust
+    /// use dhcproto::v6::{self, DhcpOption, IAAddr, IANA, OptionCode};
+    ///
+    /// let addr = "2001:db8::1".parse().unwrap();
+    /// let mut reply = v6::Message::new(v6::MessageType::Reply);
+    /// reply.opts_mut().insert(DhcpOption::ClientId(vec![1]));
+    /// reply.opts_mut().insert(DhcpOption::ServerId(vec![2]));
+    /// let mut ia = IANA { id: 1, t1: 1800, t2: 2880, opts: Default::default() };
+    /// ia.opts.insert(DhcpOption::IAAddr(IAAddr {
+    ///     addr,
+    ///     preferred_life: 3600.into(),
+    ///     valid_life: 7200.into(),
+    ///     opts: Default::default(),
+    /// }));
+    /// reply.opts_mut().insert(DhcpOption::IANA(ia));
+    ///
+    /// let decline = v6::Message::decline(&reply, &[addr]).unwrap();
+    /// assert_eq!(decline.msg_type(), v6::MessageType::Decline);
+    /// assert_eq!(decline.opts().get(OptionCode::ServerId), Some(&DhcpOption::ServerId(vec![2])));
+    /// ```
+    pub fn decline(reply: &Message, addrs: &[Ipv6Addr]) -> Option<Message> {
+        from_reply(
+            reply,
+            MessageType::Decline,
+            |opt| matches!(opt, DhcpOption::IAAddr(ia_addr) if addrs.contains(&ia_addr.addr)),
+        )
+    }
+
+    /// Build a Release for the leases in `reply`.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.7>
+    ///
+    /// The Release has a new xid, the `ClientId` & `ServerId` of the reply and an
+    /// `ElapsedTime` of 0. Every IA_NA/IA_TA/IA_PD with addresses or prefixes is included,
+    /// T1/T2 and lifetimes are set to 0 as a client should.
+    ///
+    /// Returns `None` if `reply` isn't a Reply, is missing `ClientId` or `ServerId`, or
+    /// has no leases.
+    pub fn release_from(reply: &Message) -> Option<Message> {
+        from_reply(reply, MessageType::Release, |opt| {
+            matches!(opt, DhcpOption::IAAddr(_) | DhcpOption::IAPrefix(_))
+        })
+    }
+}
+
+fn client_msg(
+    msg_type: MessageType,
+    client_id: &Duid,
+    server_id: Option<&Duid>,
+    ias: impl IntoIterator<Item = IA>,
+) -> Message {
+    let mut msg = Message::new(msg_type);
+    let opts = msg.opts_mut();
+    opts.insert(DhcpOption::ClientId(client_id.as_ref().to_vec()));
+    if let Some(id) = server_id {
+        opts.insert(DhcpOption::ServerId(id.as_ref().to_vec()));
+    }
+    opts.insert(DhcpOption::ElapsedTime(0));
+    for ia in ias {
+        opts.insert(ia.into());
+    }
+    msg
+}
+
+// a client message for the IA leases in `reply` for which `lease` returns true
+fn from_reply<F>(reply: &Message, msg_type: MessageType, lease: F) -> Option<Message>
+where
+    F: Fn(&DhcpOption) -> bool,
+{
+    if reply.msg_type() != MessageType::Reply {
+        return None;
+    }
+    let opts = reply.opts();
+    let client_id = opts.get(OptionCode::ClientId)?;
+    let server_id = opts.get(OptionCode::ServerId)?;
+
+    let mut msg = Message::new(msg_type);
+    let client_opts = msg.opts_mut();
+    for opt in opts.iter() {
+        let ia = match opt {
+            DhcpOption::IANA(ia) => leases(&ia.opts, &lease).map(|opts| {
+                DhcpOption::IANA(IANA {
+                    id: ia.id,
+                    t1: 0,
+                    t2: 0,
+                    opts,
+                })
+            }),
+            DhcpOption::IATA(ia) => {
+                leases(&ia.opts, &lease).map(|opts| DhcpOption::IATA(IATA { id: ia.id, opts }))
+            }
+            DhcpOption::IAPD(ia) => leases(&ia.opts, &lease).map(|opts| {
+                DhcpOption::IAPD(IAPD {
+                    id: ia.id,
+                    t1: 0,
+                    t2: 0,
+                    opts,
+                })
+            }),
+            _ => None,
+        };
+        if let Some(ia) = ia {
+            client_opts.insert(ia);
+        }
+    }
+    // no leases
+    client_opts.iter().next()?;
+    client_opts.insert(client_id.clone());
+    client_opts.insert(server_id.clone());
+    client_opts.insert(DhcpOption::ElapsedTime(0));
+    Some(msg)
+}
+
+// the addresses & prefixes of an IA for which `lease` returns true, with their lifetimes
+// set to 0
+fn leases<F>(opts: &DhcpOptions, lease: &F) -> Option<DhcpOptions>
+where
+    F: Fn(&DhcpOption) -> bool,
+{
+    let mut leases = DhcpOptions::new();
+    for opt in opts.iter().filter(|opt| lease(opt)) {
+        match opt {
+            DhcpOption::IAAddr(ia_addr) => leases.insert(DhcpOption::IAAddr(IAAddr {
+                addr: ia_addr.addr,
+                preferred_life: LeaseTime::new(0),
+                valid_life: LeaseTime::new(0),
+                opts: DhcpOptions::new(),
+            })),
+            DhcpOption::IAPrefix(prefix) => leases.insert(DhcpOption::IAPrefix(IAPrefix {
+                preferred_lifetime: LeaseTime::new(0),
+                valid_lifetime: LeaseTime::new(0),
+                prefix_len: prefix.prefix_len,
+                prefix_ip: prefix.prefix_ip,
+                opts: DhcpOptions::new(),
+            })),
+            _ => {}
+        }
+    }
+    leases.iter().next()?;
+    Some(leases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ia_addr(addr: Ipv6Addr) -> DhcpOption {
+        DhcpOption::IAAddr(IAAddr {
+            addr,
+            preferred_life: LeaseTime::new(3600),
+            valid_life: LeaseTime::new(7200),
+            opts: DhcpOptions::new(),
+        })
+    }
+
+    #[test]
+    fn test_decline() {
+        let (a, b, c) = (
+            "2001:db8::1".parse().unwrap(),
+            "2001:db8::2".parse().unwrap(),
+            "2001:db8::3".parse().unwrap(),
+        );
+        let mut reply = Message::new(MessageType::Reply);
+        let mut iana = IANA {
+            id: 1,
+            t1: 1800,
+            t2: 2880,
+            opts: DhcpOptions::new(),
+        };
+        iana.opts.insert(ia_addr(a));
+        iana.opts.insert(ia_addr(b));
+        let mut iata = IATA {
+            id: 2,
+            opts: DhcpOptions::new(),
+        };
+        iata.opts.insert(ia_addr(c));
+        reply.opts_mut().insert(DhcpOption::IANA(iana));
+        reply.opts_mut().insert(DhcpOption::IATA(iata));
+        reply
+            .opts_mut()
+            .insert(DhcpOption::DomainNameServers(vec![a]));
+        assert_eq!(Message::decline(&reply, &[a]), None);
+        reply.opts_mut().insert(DhcpOption::ClientId(vec![1]));
+        assert_eq!(Message::decline(&reply, &[a]), None);
+        reply.opts_mut().insert(DhcpOption::ServerId(vec![2]));
+
+        let decline = Message::decline(&reply, &[b]).unwrap();
+        assert_eq!(decline.msg_type(), MessageType::Decline);
+        let mut expected = DhcpOptions::new();
+        expected.insert(DhcpOption::ClientId(vec![1]));
+        expected.insert(DhcpOption::ServerId(vec![2]));
+        expected.insert(DhcpOption::ElapsedTime(0));
+        let mut ia_opts = DhcpOptions::new();
+        ia_opts.insert(DhcpOption::IAAddr(IAAddr {
+            addr: b,
+            preferred_life: LeaseTime::new(0),
+            valid_life: LeaseTime::new(0),
+            opts: DhcpOptions::new(),
+        }));
+        expected.insert(DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: ia_opts,
+        }));
+        assert_eq!(decline.opts(), &expected);
+
+        let decline = Message::decline(&reply, &[a, c]).unwrap();
+        assert!(decline.opts().get(OptionCode::IANA).is_some());
+        assert!(decline.opts().get(OptionCode::IATA).is_some());
+        assert_eq!(decline.opts().get(OptionCode::DomainNameServers), None);
+        assert_eq!(
+            Message::decline(&reply, &["2001:db8::4".parse().unwrap()]),
+            None
+        );
+
+        // release includes every lease
+        let mut iapd = IAPD {
+            id: 3,
+            t1: 1800,
+            t2: 2880,
+            opts: DhcpOptions::new(),
+        };
+        iapd.opts.insert(DhcpOption::IAPrefix(IAPrefix {
+            preferred_lifetime: LeaseTime::new(3600),
+            valid_lifetime: LeaseTime::new(7200),
+            prefix_len: 56,
+            prefix_ip: "2001:db8:1::".parse().unwrap(),
+            opts: DhcpOptions::new(),
+        }));
+        reply.opts_mut().insert(DhcpOption::IAPD(iapd));
+        let release = Message::release_from(&reply).unwrap();
+        assert_eq!(release.msg_type(), MessageType::Release);
+        assert_eq!(
+            release.opts().get(OptionCode::ElapsedTime),
+            Some(&DhcpOption::ElapsedTime(0))
+        );
+        let Some(DhcpOption::IANA(iana)) = release.opts().get(OptionCode::IANA) else {
+            panic!("missing IA_NA");
+        };
+        assert_eq!((iana.t1, iana.t2), (0, 0));
+        assert_eq!(iana.opts.iter().count(), 2);
+        let Some(DhcpOption::IAPD(iapd)) = release.opts().get(OptionCode::IAPD) else {
+            panic!("missing IA_PD");
+        };
+        assert!(matches!(
+            iapd.opts.get(OptionCode::IAPrefix),
+            Some(DhcpOption::IAPrefix(IAPrefix { valid_lifetime, prefix_len: 56, .. }))
+                if valid_lifetime.secs() == 0
+        ));
+        assert!(release.opts().get(OptionCode::IATA).is_some());
+
+        let mut advertise = reply.clone();
+        advertise.set_msg_type(MessageType::Advertise);
+        assert_eq!(Message::decline(&advertise, &[a]), None);
+        assert_eq!(Message::release_from(&advertise), None);
+    }
+
+    #[test]
+    fn test_client_msgs() {
+        let duid = Duid::unknown(&[1, 2, 3]);
+        let pd = IAPD {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: DhcpOptions::new(),
+        };
+        let server = Duid::unknown(&[9]);
+        let request = Message::request(&duid, &server, pd.clone().into(), []);
+        assert_eq!(request.msg_type(), MessageType::Request);
+        assert_eq!(request.client_id(), Some(&[1, 2, 3][..]));
+        assert_eq!(request.server_id(), Some(&[9][..]));
+        assert_eq!(request.elapsed_time(), Some(0));
+        assert_eq!(request.iapd().collect::<Vec<_>>(), [&pd]);
+
+        let renew = Message::renew(&duid, &server, pd.clone().into(), [IATA::new(2).into()]);
+        assert_eq!(renew.msg_type(), MessageType::Renew);
+        assert_eq!(renew.server_id(), Some(&[9][..]));
+        assert_eq!(renew.iata().count(), 1);
+        let rebind = Message::rebind(&duid, pd.into(), []);
+        assert_eq!(rebind.server_id(), None);
+        assert_eq!(rebind.iapd().count(), 1);
+
+        let info = Message::information_request(&duid);
+        assert_eq!(info.opts().iter().count(), 2);
+        assert_eq!(info.mode(), Some(crate::v6::Mode::Stateless));
+    }
+}
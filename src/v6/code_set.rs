@@ -0,0 +1,189 @@
+//! Sets of option codes
+use std::fmt;
+
+use crate::v6::{DhcpOptions, OptionCode};
+
+/// A set of option codes for allow and deny lists. Codes below 256, which covers the
+/// common options, are one bit each, the rest are kept in a sorted list. Sets of codes
+/// below 256 can be built in a `const`.
+///
+/// ```rust
+/// use dhcproto::v6::{DhcpOption, DhcpOptions, OptionCode, OptionCodeSet};
+///
+/// // options a relay strips before forwarding
+/// const DENY: OptionCodeSet = OptionCodeSet::from_codes(&[9, 18]);
+///
+/// let mut opts = DhcpOptions::new();
+/// opts.insert(DhcpOption::ClientId(vec![1, 2, 3]));
+/// opts.insert(DhcpOption::ElapsedTime(0));
+/// opts.insert(DhcpOption::InterfaceId(vec![4].into()));
+/// opts.strip_codes(&DENY);
+/// assert!(opts.get(OptionCode::InterfaceId).is_none());
+///
+/// let allow: OptionCodeSet = [OptionCode::ClientId].into_iter().collect();
+/// opts.retain_codes(&allow);
+/// assert_eq!(opts.iter().count(), 1);
+/// ```
+#[derive(Default, Clone, PartialEq, Eq, Hash)]
+pub struct OptionCodeSet {
+    low: [u64; 4],
+    // codes >= 256, sorted
+    high: Vec<u16>,
+}
+
+impl OptionCodeSet {
+    /// an empty set
+    pub const fn new() -> Self {
+        Self {
+            low: [0; 4],
+            high: Vec::new(),
+        }
+    }
+
+    /// A set of the code numbers in `codes`
+    ///
+    /// # Panics
+    /// if a code is 256 or more, add those with [`OptionCodeSet::insert`]
+    pub const fn from_codes(codes: &[u16]) -> Self {
+        let mut set = Self::new();
+        let mut i = 0;
+        while i < codes.len() {
+            let code = codes[i];
+            assert!(code < 256, "only codes below 256 can be added in a const");
+            set.low[code as usize / 64] |= 1 << (code % 64);
+            i += 1;
+        }
+        set
+    }
+
+    /// `true` if the set has the code number `code`
+    pub fn contains_code(&self, code: u16) -> bool {
+        if code < 256 {
+            self.low[code as usize / 64] & (1 << (code % 64)) != 0
+        } else {
+            self.high.binary_search(&code).is_ok()
+        }
+    }
+
+    /// `true` if the set has `code`
+    pub fn contains(&self, code: OptionCode) -> bool {
+        self.contains_code(code.into())
+    }
+
+    /// add `code`, returning `true` if it wasn't in the set
+    pub fn insert(&mut self, code: OptionCode) -> bool {
+        let code = u16::from(code);
+        if code < 256 {
+            let new = !self.contains_code(code);
+            self.low[code as usize / 64] |= 1 << (code % 64);
+            new
+        } else {
+            match self.high.binary_search(&code) {
+                Ok(_) => false,
+                Err(i) => {
+                    self.high.insert(i, code);
+                    true
+                }
+            }
+        }
+    }
+
+    /// remove `code`, returning `true` if it was in the set
+    pub fn remove(&mut self, code: OptionCode) -> bool {
+        let code = u16::from(code);
+        if code < 256 {
+            let had = self.contains_code(code);
+            self.low[code as usize / 64] &= !(1 << (code % 64));
+            had
+        } else {
+            match self.high.binary_search(&code) {
+                Ok(i) => {
+                    self.high.remove(i);
+                    true
+                }
+                Err(_) => false,
+            }
+        }
+    }
+
+    /// number of codes in the set
+    pub fn len(&self) -> usize {
+        let low: usize = self.low.iter().map(|bits| bits.count_ones() as usize).sum();
+        low + self.high.len()
+    }
+
+    /// `true` if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.low == [0; 4] && self.high.is_empty()
+    }
+
+    /// iterate over the codes in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = OptionCode> + '_ {
+        (0..256)
+            .filter(|code| self.contains_code(*code))
+            .chain(self.high.iter().copied())
+            .map(OptionCode::from)
+    }
+}
+
+impl fmt::Debug for OptionCodeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<OptionCode> for OptionCodeSet {
+    fn from_iter<T: IntoIterator<Item = OptionCode>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<OptionCode> for OptionCodeSet {
+    fn extend<T: IntoIterator<Item = OptionCode>>(&mut self, iter: T) {
+        for code in iter {
+            self.insert(code);
+        }
+    }
+}
+
+impl DhcpOptions {
+    /// keep only the options with a code in `set`, options nested in IAs are left alone
+    pub fn retain_codes(&mut self, set: &OptionCodeSet) {
+        self.retain(|opt| set.contains(opt.into()))
+    }
+
+    /// remove the options with a code in `set`, options nested in IAs are left alone
+    pub fn strip_codes(&mut self, set: &OptionCodeSet) {
+        self.retain(|opt| !set.contains(opt.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_set() {
+        const SET: OptionCodeSet = OptionCodeSet::from_codes(&[1, 64, 255]);
+        assert_eq!(SET.len(), 3);
+        assert!(SET.contains(OptionCode::ClientId));
+        assert!(!SET.contains(OptionCode::ServerId));
+
+        let mut set = SET.clone();
+        assert!(set.insert(OptionCode::new(1000)));
+        assert!(set.insert(OptionCode::new(300)));
+        assert!(!set.insert(OptionCode::new(300)));
+        assert!(set.contains_code(1000));
+        assert_eq!(
+            set.iter().map(u16::from).collect::<Vec<_>>(),
+            [1, 64, 255, 300, 1000]
+        );
+        assert!(set.remove(OptionCode::new(1000)));
+        assert!(set.remove(OptionCode::ClientId));
+        assert!(!set.remove(OptionCode::ClientId));
+        assert_eq!(set.len(), 3);
+        assert!(OptionCodeSet::new().is_empty());
+    }
+}
@@ -1,18 +1,69 @@
-use std::net::Ipv6Addr;
+use std::{fmt, net::Ipv6Addr, str::FromStr};
 
-#[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::v6::HType;
 use crate::Encoder;
 
 /// Duid helper type
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+///
+/// A DUID displays & parses as colon separated hex, the form servers like Kea use in
+/// their config, so it can be stored as a string & read back:
+///
+/// ```rust
+/// use dhcproto::v6::{duid::Duid, HType};
+///
+/// let duid = Duid::link_layer_time(HType::Eth, 0x2a2b_2c2d, "::1".parse().unwrap());
+/// let s = duid.to_string();
+/// assert!(s.starts_with("00:01:00:01:2a:2b:2c:2d"));
+/// assert_eq!(s.parse::<Duid>()?, duid);
+/// // or stored as its bytes
+/// assert_eq!(Duid::from_bytes(duid.as_bytes())?, duid);
+/// # Ok::<(), dhcproto::v6::duid::DuidParseError>(())
+/// ```
+///
+/// With the `serde` feature it serializes as that string in human readable formats like
+/// JSON, and as bytes otherwise. Both forms and a sequence of bytes deserialize.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Duid(Vec<u8>);
+
+/// Returned when a DUID can't be parsed from a string or bytes
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DuidParseError {
+    /// there are no bytes
+    #[error("DUID is empty")]
+    Empty,
+    /// longer than [`Duid::MAX_LEN`]
+    #[error("DUID is {0} bytes, the max is {max}", max = Duid::MAX_LEN)]
+    TooLong(usize),
+    /// a part of the string isn't a hex byte
+    #[error("invalid hex byte {0:?} in DUID")]
+    InvalidHex(String),
+}
 // TODO: define specific duid types
 
 impl Duid {
+    /// max length of a DUID, a 2 byte type & up to 128 bytes
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-11.1>
+    pub const MAX_LEN: usize = 130;
+
+    /// a DUID from its bytes, checking it isn't empty or longer than [`Duid::MAX_LEN`].
+    /// [`Duid::unknown`] doesn't check
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DuidParseError> {
+        match bytes.len() {
+            0 => Err(DuidParseError::Empty),
+            len if len > Self::MAX_LEN => Err(DuidParseError::TooLong(len)),
+            _ => Ok(Self(bytes.to_vec())),
+        }
+    }
+    /// the DUID's bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+    /// the DUID's bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
     /// new DUID link layer address with time
     pub fn link_layer_time(htype: HType, time: u32, addr: Ipv6Addr) -> Self {
         let mut buf = Vec::new();
@@ -76,3 +127,144 @@ impl From<Vec<u8>> for Duid {
         Self(v)
     }
 }
+
+/// Prints the bytes as lowercase hex separated by colons, e.g. `00:03:00:01:02:42:ac:11:00:02`
+impl fmt::Display for Duid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses hex bytes separated by colons, a single digit is allowed between colons
+/// (`0:3:0:1:...`). Hex without separators (`00030001...`) is accepted too
+impl FromStr for Duid {
+    type Err = DuidParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let bytes = if s.contains(':') {
+            s.split(':')
+                .map(|b| match b.len() {
+                    1 | 2 => u8::from_str_radix(b, 16)
+                        .map_err(|_| DuidParseError::InvalidHex(b.to_owned())),
+                    _ => Err(DuidParseError::InvalidHex(b.to_owned())),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            hex::decode(s).map_err(|_| DuidParseError::InvalidHex(s.to_owned()))?
+        };
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Duid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Duid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Duid;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex DUID string or bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Duid, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, b: &[u8]) -> Result<Duid, E> {
+                Duid::from_bytes(b).map_err(E::custom)
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, b: Vec<u8>) -> Result<Duid, E> {
+                Duid::from_bytes(&b).map_err(E::custom)
+            }
+
+            // what the derived impl used to write, & bytes in JSON
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Duid, A::Error> {
+                use serde::de::Error;
+                // the hint comes from the input, a valid DUID is never longer
+                let hint = seq.size_hint().unwrap_or_default().min(Duid::MAX_LEN);
+                let mut bytes = Vec::with_capacity(hint);
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Duid::from_bytes(&bytes).map_err(A::Error::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            deserializer.deserialize_byte_buf(Visitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duid_str() -> Result<(), DuidParseError> {
+        let duid = Duid::link_layer(HType::Eth, "::2:42ac:1100:2".parse().unwrap());
+        assert_eq!(
+            duid.to_string(),
+            "00:03:00:01:00:00:00:00:00:00:00:00:00:02:42:ac:11:00:00:02"
+        );
+        assert_eq!(duid.to_string().parse::<Duid>()?, duid);
+        assert_eq!("0:3:0:1".parse::<Duid>()?, Duid::unknown(&[0, 3, 0, 1]));
+        assert_eq!(" 0003000A ".parse::<Duid>()?, Duid::unknown(&[0, 3, 0, 10]));
+
+        assert_eq!("".parse::<Duid>(), Err(DuidParseError::Empty));
+        assert_eq!(
+            "00:03::01".parse::<Duid>(),
+            Err(DuidParseError::InvalidHex("".into()))
+        );
+        assert_eq!(
+            "00:003".parse::<Duid>(),
+            Err(DuidParseError::InvalidHex("003".into()))
+        );
+        assert_eq!(
+            "0003g".parse::<Duid>(),
+            Err(DuidParseError::InvalidHex("0003g".into()))
+        );
+        assert_eq!(
+            Duid::from_bytes(&[1; 131]),
+            Err(DuidParseError::TooLong(131))
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_duid_serde() -> Result<(), serde_json::Error> {
+        let duid = Duid::unknown(&[0, 2, 0xab]);
+        assert_eq!(serde_json::to_string(&duid)?, r#""00:02:ab""#);
+        assert_eq!(serde_json::from_str::<Duid>(r#""00:02:ab""#)?, duid);
+        // the old derived form
+        assert_eq!(serde_json::from_str::<Duid>("[0,2,171]")?, duid);
+        assert!(serde_json::from_str::<Duid>(r#""xyz""#).is_err());
+        assert!(serde_json::from_str::<Duid>("[]").is_err());
+        assert!(serde_json::from_str::<Duid>(&format!("{:?}", [1; 131])).is_err());
+        Ok(())
+    }
+}
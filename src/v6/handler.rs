@@ -0,0 +1,149 @@
+//! Typed entry point for servers
+//!
+//! Implement [`Handler`] for the messages a server answers and [`dispatch`] calls the
+//! method for each received message's type. Messages the handler doesn't override, and
+//! ones clients don't send to servers, go to [`Handler::unhandled`]. Relay-forward
+//! messages are a [`RelayMessage`](crate::v6::RelayMessage), the client message is in
+//! its `RelayMsg` option.
+//!
+//! ```rust
+//! use dhcproto::v6::{self, handler::{self, Handler}, MessageType};
+//!
+//! struct Server;
+//!
+//! impl Handler for Server {
+//!     type Output = Option<MessageType>;
+//!
+//!     fn solicit(&mut self, msg: &v6::Message) -> Self::Output {
+//!         Some(if msg.rapid_commit() {
+//!             MessageType::Reply
+//!         } else {
+//!             MessageType::Advertise
+//!         })
+//!     }
+//!
+//!     fn unhandled(&mut self, _msg: &v6::Message) -> Self::Output {
+//!         None
+//!     }
+//! }
+//!
+//! let solicit = v6::Message::new(MessageType::Solicit);
+//! assert_eq!(handler::dispatch(&mut Server, &solicit), Some(MessageType::Advertise));
+//! let renew = v6::Message::new(MessageType::Renew);
+//! assert_eq!(handler::dispatch(&mut Server, &renew), None);
+//! ```
+use crate::v6::{Message, MessageType};
+
+/// Handles the messages sent to a server, every method defaults to
+/// [`Handler::unhandled`]
+pub trait Handler {
+    /// what handling a message returns, e.g. the reply
+    type Output;
+
+    /// SOLICIT
+    fn solicit(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// REQUEST
+    fn request(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// CONFIRM
+    fn confirm(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// RENEW
+    fn renew(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// REBIND
+    fn rebind(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// RELEASE
+    fn release(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DECLINE
+    fn decline(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// INFORMATION-REQUEST
+    fn information_request(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// LEASEQUERY
+    fn lease_query(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// RECONFIGURE-REQUEST
+    fn reconfigure_request(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// DHCPV4-QUERY
+    fn dhcpv4_query(&mut self, msg: &Message) -> Self::Output {
+        self.unhandled(msg)
+    }
+    /// any other message
+    fn unhandled(&mut self, msg: &Message) -> Self::Output;
+}
+
+/// Call the `handler` method for the type of `msg`
+pub fn dispatch<H: Handler + ?Sized>(handler: &mut H, msg: &Message) -> H::Output {
+    match msg.msg_type() {
+        MessageType::Solicit => handler.solicit(msg),
+        MessageType::Request => handler.request(msg),
+        MessageType::Confirm => handler.confirm(msg),
+        MessageType::Renew => handler.renew(msg),
+        MessageType::Rebind => handler.rebind(msg),
+        MessageType::Release => handler.release(msg),
+        MessageType::Decline => handler.decline(msg),
+        MessageType::InformationRequest => handler.information_request(msg),
+        MessageType::LeaseQuery => handler.lease_query(msg),
+        MessageType::ReconfigureRequest => handler.reconfigure_request(msg),
+        MessageType::DHCPv4Query => handler.dhcpv4_query(msg),
+        _ => handler.unhandled(msg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Replies;
+
+    impl Handler for Replies {
+        type Output = &'static [MessageType];
+
+        fn request(&mut self, msg: &Message) -> Self::Output {
+            msg.msg_type().expected_reply()
+        }
+        fn lease_query(&mut self, msg: &Message) -> Self::Output {
+            msg.msg_type().expected_reply()
+        }
+        fn unhandled(&mut self, _msg: &Message) -> Self::Output {
+            &[]
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let request = Message::new(MessageType::Request);
+        assert_eq!(dispatch(&mut Replies, &request), [MessageType::Reply]);
+        let query = Message::new(MessageType::LeaseQuery);
+        assert_eq!(
+            dispatch(&mut Replies, &query),
+            [MessageType::LeaseQueryReply]
+        );
+        let advertise = Message::new(MessageType::Advertise);
+        assert!(dispatch(&mut Replies, &advertise).is_empty());
+        assert_eq!(
+            MessageType::Reconfigure.expected_reply(),
+            [
+                MessageType::Renew,
+                MessageType::Rebind,
+                MessageType::InformationRequest
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Leasequery - <https://datatracker.ietf.org/doc/html/rfc5007>
+//!
+//! [`LeaseQuery`] builds a LEASEQUERY for any of the standard query types. Queries by
+//! address or client id (RFC 5007) can be sent over UDP, the ones by relay-id,
+//! link-address and remote-id are Bulk Leasequery (RFC 5460) and run over TCP, framing
+//! the messages with a 2 byte length is left to the caller.
+//!
+//! ```rust
+//! use dhcproto::v6::{self, lease_query::{LeaseQuery, QueryType}, DhcpOption, LqQueryType, OptionCode};
+//!
+//! let query = LeaseQuery::new(QueryType::RelayId(vec![0, 3, 0, 1, 1, 2, 3, 4, 5, 6]))
+//!     .client_id(vec![0, 1, 2, 3])
+//!     .parameters(vec![OptionCode::DomainNameServers])
+//!     .build();
+//! assert_eq!(query.msg_type(), v6::MessageType::LeaseQuery);
+//! let Some(DhcpOption::LqQuery(lq)) = query.opts().get(OptionCode::LqQuery) else {
+//!     panic!("no query");
+//! };
+//! assert_eq!(lq.query_type, LqQueryType::ByRelayId);
+//! assert!(lq.opts.get(OptionCode::RelayId).is_some());
+//! assert!(lq.opts.get(OptionCode::ORO).is_some());
+//! ```
+use std::net::Ipv6Addr;
+
+use crate::{
+    time::LeaseTime,
+    v6::{
+        DhcpOption, DhcpOptions, IAAddr, LqQuery, LqQueryType, Message, MessageType, OptionCode,
+        RemoteId, ORO,
+    },
+};
+
+/// What a leasequery asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryType {
+    /// the binding of an address
+    Address(Ipv6Addr),
+    /// the bindings of a client DUID
+    ClientId(Vec<u8>),
+    /// bindings received through the relay agent with this DUID
+    /// <https://datatracker.ietf.org/doc/html/rfc5460#section-5.3>
+    RelayId(Vec<u8>),
+    /// bindings on the link with this address, sent as the query's `link-address`
+    LinkAddress(Ipv6Addr),
+    /// bindings with this relay agent remote-id
+    RemoteId(RemoteId),
+}
+
+/// Builds a LEASEQUERY message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaseQuery {
+    query: QueryType,
+    xid: [u8; 3],
+    client_id: Option<Vec<u8>>,
+    link: Ipv6Addr,
+    params: Vec<OptionCode>,
+}
+
+impl LeaseQuery {
+    /// a query with a random xid
+    pub fn new(query: QueryType) -> Self {
+        Self {
+            query,
+            xid: rand::random(),
+            client_id: None,
+            link: Ipv6Addr::UNSPECIFIED,
+            params: Vec::new(),
+        }
+    }
+
+    /// set the xid, replies are matched to the query by it
+    pub fn xid(mut self, xid: [u8; 3]) -> Self {
+        self.xid = xid;
+        self
+    }
+
+    /// the requestor's DUID, sent in `ClientId`
+    pub fn client_id(mut self, duid: Vec<u8>) -> Self {
+        self.client_id = Some(duid);
+        self
+    }
+
+    /// only return bindings on the link with this address, the `link-address` of the
+    /// query. Defaults to `::`, any link. Ignored by [`QueryType::LinkAddress`].
+    pub fn link(mut self, link: Ipv6Addr) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// options to return for each binding, sent in an `ORO` in the query options
+    pub fn parameters(mut self, params: Vec<OptionCode>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// the `LqQuery` option
+    pub fn lq_query(&self) -> LqQuery {
+        let mut opts = DhcpOptions::new();
+        let mut link = self.link;
+        let query_type = match &self.query {
+            QueryType::Address(addr) => {
+                opts.insert(DhcpOption::IAAddr(IAAddr {
+                    addr: *addr,
+                    preferred_life: LeaseTime::default(),
+                    valid_life: LeaseTime::default(),
+                    opts: DhcpOptions::new(),
+                }));
+                LqQueryType::ByAddress
+            }
+            QueryType::ClientId(id) => {
+                opts.insert(DhcpOption::ClientId(id.clone()));
+                LqQueryType::ByClientId
+            }
+            QueryType::RelayId(id) => {
+                opts.insert(DhcpOption::RelayId(id.clone()));
+                LqQueryType::ByRelayId
+            }
+            QueryType::LinkAddress(addr) => {
+                link = *addr;
+                LqQueryType::ByLinkAddress
+            }
+            QueryType::RemoteId(id) => {
+                opts.insert(DhcpOption::RemoteId(id.clone()));
+                LqQueryType::ByRemoteId
+            }
+        };
+        if !self.params.is_empty() {
+            opts.insert(DhcpOption::ORO(ORO {
+                opts: self.params.clone(),
+            }));
+        }
+        LqQuery {
+            query_type,
+            link_addr: link,
+            opts,
+        }
+    }
+
+    /// build the query
+    pub fn build(&self) -> Message {
+        let mut msg = Message::new_with_id(MessageType::LeaseQuery, self.xid);
+        let opts = msg.opts_mut();
+        if let Some(id) = &self.client_id {
+            opts.insert(DhcpOption::ClientId(id.clone()));
+        }
+        opts.insert(DhcpOption::LqQuery(self.lq_query()));
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decodable, Encodable};
+
+    #[test]
+    fn test_lease_query() -> Result<(), Box<dyn std::error::Error>> {
+        let link = "2001:db8::1".parse()?;
+        let remote_id = RemoteId {
+            num: 4491,
+            id: vec![1, 2],
+        };
+        for (query, query_type, code) in [
+            (
+                QueryType::Address("2001:db8::5".parse()?),
+                LqQueryType::ByAddress,
+                OptionCode::IAAddr,
+            ),
+            (
+                QueryType::ClientId(vec![0, 1]),
+                LqQueryType::ByClientId,
+                OptionCode::ClientId,
+            ),
+            (
+                QueryType::RelayId(vec![0, 2]),
+                LqQueryType::ByRelayId,
+                OptionCode::RelayId,
+            ),
+            (
+                QueryType::RemoteId(remote_id),
+                LqQueryType::ByRemoteId,
+                OptionCode::RemoteId,
+            ),
+        ] {
+            let msg = LeaseQuery::new(query).xid([1, 2, 3]).link(link).build();
+            let bytes = msg.to_vec()?;
+            let decoded = Message::from_bytes(&bytes)?;
+            assert_eq!(decoded, msg);
+            let Some(DhcpOption::LqQuery(lq)) = decoded.opts().get(OptionCode::LqQuery) else {
+                panic!("no LqQuery");
+            };
+            assert_eq!(lq.query_type, query_type);
+            assert_eq!(lq.link_addr, link);
+            assert!(lq.opts.get(code).is_some());
+            assert!(lq.opts.get(OptionCode::ORO).is_none());
+        }
+        let lq = LeaseQuery::new(QueryType::LinkAddress(link)).lq_query();
+        assert_eq!(lq.query_type, LqQueryType::ByLinkAddress);
+        assert_eq!(lq.link_addr, link);
+        assert_eq!(lq.opts.iter().count(), 0);
+        // query-type, link-address, then RelayId
+        let lq = DhcpOption::LqQuery(LeaseQuery::new(QueryType::RelayId(vec![9])).lq_query());
+        let mut expected = vec![0, 44, 0, 22, 3];
+        expected.extend([0; 16]);
+        expected.extend([0, 53, 0, 1, 9]);
+        assert_eq!(lq.to_vec()?, expected);
+        Ok(())
+    }
+}
@@ -52,27 +52,39 @@
 //! # Ok(()) }
 //! ```
 //!
+mod client;
+mod code_set;
 pub mod duid;
+pub mod handler;
 mod htype;
+pub mod lease_query;
 mod option_codes;
 mod options;
 mod oro_codes;
+pub mod reconfigure;
+mod reply;
+pub mod retransmit;
+pub mod xid;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use std::{convert::TryInto, fmt, net::Ipv6Addr};
+use std::{convert::TryInto, fmt, net::Ipv6Addr, str::FromStr};
 
 // re-export submodules from v6
+pub use self::client::IA;
+pub use self::code_set::*;
 pub use self::htype::*;
 pub use self::option_codes::*;
 pub use self::options::*;
 pub use self::oro_codes::*;
+pub use self::reply::*;
 
 pub use crate::{
     decoder::{Decodable, Decoder},
     encoder::{Encodable, Encoder},
     error::*,
+    time::LeaseTime,
 };
 
 /// default dhcpv6 server port
@@ -139,7 +151,7 @@ impl Default for Message {
     fn default() -> Self {
         Self {
             msg_type: MessageType::Solicit,
-            xid: rand::random(),
+            xid: xid::random(),
             opts: DhcpOptions::new(),
         }
     }
@@ -214,6 +226,141 @@ impl Message {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// Get the `ClientId` DUID
+    pub fn client_id(&self) -> Option<&[u8]> {
+        match self.opts.get(OptionCode::ClientId)? {
+            DhcpOption::ClientId(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Get the `ServerId` DUID
+    pub fn server_id(&self) -> Option<&[u8]> {
+        match self.opts.get(OptionCode::ServerId)? {
+            DhcpOption::ServerId(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the message has the `RapidCommit` option
+    pub fn rapid_commit(&self) -> bool {
+        self.opts.get(OptionCode::RapidCommit).is_some()
+    }
+
+    /// Iterate over the IA_NA options, a message can have more than one
+    pub fn iana(&self) -> impl Iterator<Item = &IANA> {
+        self.opts.iter().filter_map(|opt| match opt {
+            DhcpOption::IANA(ia) => Some(ia),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the IA_TA options, a message can have more than one
+    pub fn iata(&self) -> impl Iterator<Item = &IATA> {
+        self.opts.iter().filter_map(|opt| match opt {
+            DhcpOption::IATA(ia) => Some(ia),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the IA_PD options, a message can have more than one
+    pub fn iapd(&self) -> impl Iterator<Item = &IAPD> {
+        self.opts.iter().filter_map(|opt| match opt {
+            DhcpOption::IAPD(ia) => Some(ia),
+            _ => None,
+        })
+    }
+
+    /// Get the `ElapsedTime`, in hundredths of a second
+    pub fn elapsed_time(&self) -> Option<u16> {
+        match self.opts.get(OptionCode::ElapsedTime)? {
+            DhcpOption::ElapsedTime(time) => Some(*time),
+            _ => None,
+        }
+    }
+
+    /// Get the message level `StatusCode`, see [`DhcpOptions::status`]
+    pub fn status(&self) -> Option<&StatusCode> {
+        self.opts.status()
+    }
+
+    /// Returns the first failed status code in the message or its IAs,
+    /// see [`DhcpOptions::succeeded`]
+    pub fn succeeded(&self) -> Result<(), &StatusCode> {
+        self.opts.succeeded()
+    }
+
+    /// Returns the first address or prefix in the message's IAs whose preferred lifetime
+    /// is greater than its valid lifetime, see [`DhcpOptions::check_lifetimes`]
+    pub fn check_lifetimes(&self) -> Result<(), &DhcpOption> {
+        self.opts.check_lifetimes()
+    }
+
+    /// Classify the exchange this message is part of from its type & IAs, so a client
+    /// or server can pick the matching behaviour. `None` for messages that aren't part
+    /// of a client/server exchange (e.g. relay or leasequery messages), or that have no
+    /// IAs and aren't an InformationRequest or a Reply with `InformationRefreshTime`. A
+    /// Reply to a Release, Decline or Confirm has no IAs either, so it gives `None`.
+    ///
+    /// ```rust
+    /// use dhcproto::v6::{DhcpOption, Message, MessageType, Mode, IAPD};
+    ///
+    /// let mut msg = Message::new(MessageType::Solicit);
+    /// msg.opts_mut().insert(DhcpOption::RapidCommit);
+    /// msg.opts_mut().insert(DhcpOption::IAPD(IAPD {
+    ///     id: 1,
+    ///     t1: 0,
+    ///     t2: 0,
+    ///     opts: Default::default(),
+    /// }));
+    /// assert_eq!(msg.mode(), Some(Mode::PrefixDelegation { rapid_commit: true }));
+    /// assert_eq!(Message::new(MessageType::InformationRequest).mode(), Some(Mode::Stateless));
+    /// ```
+    pub fn mode(&self) -> Option<Mode> {
+        use MessageType::*;
+        match self.msg_type {
+            Solicit | Advertise | Request | Confirm | Renew | Rebind | Reply | Release
+            | Decline | InformationRequest => {}
+            _ => return None,
+        }
+        let rapid_commit = self.rapid_commit();
+        if self.iana().next().is_some() || self.iata().next().is_some() {
+            Some(Mode::Stateful { rapid_commit })
+        } else if self.iapd().next().is_some() {
+            Some(Mode::PrefixDelegation { rapid_commit })
+        } else if self.msg_type == InformationRequest
+            || (self.msg_type == Reply
+                && self.opts.get(OptionCode::InformationRefreshTime).is_some())
+        {
+            // a Reply without IAs may answer a Release, Decline or Confirm, only one with
+            // InformationRefreshTime is known to answer an InformationRequest
+            // <https://datatracker.ietf.org/doc/html/rfc8415#section-21.23>
+            Some(Mode::Stateless)
+        } else {
+            None
+        }
+    }
+}
+
+/// The kind of exchange a message is part of, see [`Message::mode`]
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-6>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// InformationRequest/Reply, configuration without addresses or prefixes
+    Stateless,
+    /// address assignment, the message has an IA_NA or IA_TA and may also have IA_PDs.
+    /// `rapid_commit` is set for a two message Solicit/Reply exchange
+    Stateful {
+        /// the message has the `RapidCommit` option
+        rapid_commit: bool,
+    },
+    /// prefix delegation only, the message has IA_PDs but no IA_NA or IA_TA
+    PrefixDelegation {
+        /// the message has the `RapidCommit` option
+        rapid_commit: bool,
+    },
 }
 
 /// DHCPv6 message types
@@ -342,6 +489,103 @@ impl From<MessageType> for u8 {
     }
 }
 
+impl MessageType {
+    /// the message type name from the RFC, e.g. `SOLICIT`. `None` if unknown
+    pub fn name(&self) -> Option<&'static str> {
+        use MessageType as M;
+        Some(match self {
+            M::Solicit => "SOLICIT",
+            M::Advertise => "ADVERTISE",
+            M::Request => "REQUEST",
+            M::Confirm => "CONFIRM",
+            M::Renew => "RENEW",
+            M::Rebind => "REBIND",
+            M::Reply => "REPLY",
+            M::Release => "RELEASE",
+            M::Decline => "DECLINE",
+            M::Reconfigure => "RECONFIGURE",
+            M::InformationRequest => "INFORMATION-REQUEST",
+            M::RelayForw => "RELAY-FORW",
+            M::RelayRepl => "RELAY-REPL",
+            M::LeaseQuery => "LEASEQUERY",
+            M::LeaseQueryReply => "LEASEQUERY-REPLY",
+            M::LeaseQueryDone => "LEASEQUERY-DONE",
+            M::LeaseQueryData => "LEASEQUERY-DATA",
+            M::ReconfigureRequest => "RECONFIGURE-REQUEST",
+            M::ReconfigureReply => "RECONFIGURE-REPLY",
+            M::DHCPv4Query => "DHCPV4-QUERY",
+            M::DHCPv4Response => "DHCPV4-RESPONSE",
+            M::Unknown(_) => return None,
+        })
+    }
+
+    /// The message types sent in response to this one, empty if there's none. A Solicit
+    /// gets a Reply with `RapidCommit`, a Reconfigure is answered by the client.
+    ///
+    /// ```rust
+    /// use dhcproto::v6::MessageType;
+    ///
+    /// assert_eq!(
+    ///     MessageType::Solicit.expected_reply(),
+    ///     [MessageType::Advertise, MessageType::Reply]
+    /// );
+    /// assert_eq!(MessageType::Renew.expected_reply(), [MessageType::Reply]);
+    /// assert!(MessageType::Reply.expected_reply().is_empty());
+    /// ```
+    pub fn expected_reply(&self) -> &'static [MessageType] {
+        use MessageType as M;
+        match self {
+            M::Solicit => &[M::Advertise, M::Reply],
+            M::Request
+            | M::Confirm
+            | M::Renew
+            | M::Rebind
+            | M::Release
+            | M::Decline
+            | M::InformationRequest => &[M::Reply],
+            M::Reconfigure => &[M::Renew, M::Rebind, M::InformationRequest],
+            M::RelayForw => &[M::RelayRepl],
+            // over TCP the reply is followed by LEASEQUERY-DATA & LEASEQUERY-DONE
+            // https://datatracker.ietf.org/doc/html/rfc5460#section-5.2
+            M::LeaseQuery => &[M::LeaseQueryReply],
+            M::ReconfigureRequest => &[M::ReconfigureReply],
+            M::DHCPv4Query => &[M::DHCPv4Response],
+            _ => &[],
+        }
+    }
+}
+
+/// Prints the RFC name, or the number for unknown types
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", u8::from(*self)),
+        }
+    }
+}
+
+/// Parses a message type from its RFC name (`"RELAY-FORW"`) or variant name
+/// (`"RelayForw"`), ignoring case, or from its number (`"12"`)
+impl FromStr for MessageType {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.parse::<u8>() {
+            return Ok(n.into());
+        }
+        (1..=u8::MAX)
+            .map(MessageType::from)
+            .take_while(|ty| !matches!(ty, MessageType::Unknown(_)))
+            .find(|ty| {
+                ty.name().is_some_and(|name| {
+                    name.eq_ignore_ascii_case(s) || name.replace('-', "").eq_ignore_ascii_case(s)
+                })
+            })
+            .ok_or_else(|| ParseNameError::new(s))
+    }
+}
+
 impl Decodable for Message {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(Message {
@@ -352,6 +596,14 @@ impl Decodable for Message {
     }
 }
 
+impl TryFrom<&[u8]> for Message {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Message::from_bytes(bytes)
+    }
+}
+
 impl Encodable for Message {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         e.write_u8(self.msg_type.into())?;
@@ -419,6 +671,20 @@ impl RelayMessage {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// Insert the client's ethernet MAC as a [`ClientLinklayerAddr`] option,
+    /// relay agents add this to Relay-forward messages
+    /// <https://datatracker.ietf.org/doc/html/rfc6939#section-6>
+    ///
+    /// [`ClientLinklayerAddr`]: crate::v6::ClientLinklayerAddr
+    pub fn set_client_linklayer_addr(&mut self, mac: [u8; 6]) -> &mut Self {
+        self.opts.remove(OptionCode::ClientLinklayerAddr);
+        self.opts
+            .insert(DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr::eth(
+                mac,
+            )));
+        self
+    }
 }
 
 impl Decodable for RelayMessage {
@@ -433,6 +699,14 @@ impl Decodable for RelayMessage {
     }
 }
 
+impl TryFrom<&[u8]> for RelayMessage {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        RelayMessage::from_bytes(bytes)
+    }
+}
+
 impl Encodable for RelayMessage {
     fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         e.write_u8(self.msg_type.into())?;
@@ -476,6 +750,7 @@ mod tests {
         println!("{input:?}");
         // no PAD bytes or hashmap with ipv6 so the lens will be exact
         assert_eq!(buf.len(), input.len());
+        assert_eq!(msg.encoded_len()?, buf.len());
         // decode again
         let res = Message::decode(&mut Decoder::new(&buf))?;
         // check Messages are equal after decoding/encoding
@@ -507,6 +782,127 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn status() {
+        let status = |status: Status| StatusCode {
+            status,
+            msg: String::new(),
+        };
+        let mut addr_opts = DhcpOptions::new();
+        addr_opts.insert(DhcpOption::StatusCode(status(Status::NotOnLink)));
+        let mut ia_opts = DhcpOptions::new();
+        ia_opts.insert(DhcpOption::IAAddr(IAAddr {
+            addr: Ipv6Addr::LOCALHOST,
+            preferred_life: 0.into(),
+            valid_life: 0.into(),
+            opts: addr_opts,
+        }));
+        let mut msg = Message::new(MessageType::Reply);
+        msg.opts_mut().insert(DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: ia_opts,
+        }));
+        assert!(msg.status().is_none());
+        assert_eq!(msg.succeeded().unwrap_err().status, Status::NotOnLink);
+
+        // IA level status comes before the address
+        let DhcpOption::IANA(ia) = msg.opts_mut().get_mut(OptionCode::IANA).unwrap() else {
+            unreachable!()
+        };
+        ia.opts
+            .insert(DhcpOption::StatusCode(status(Status::NoAddrsAvail)));
+        assert_eq!(ia.status().unwrap().status, Status::NoAddrsAvail);
+        assert_eq!(msg.succeeded().unwrap_err().status, Status::NoAddrsAvail);
+
+        // then the message level
+        msg.opts_mut()
+            .insert(DhcpOption::StatusCode(status(Status::UseMulticast)));
+        assert_eq!(msg.succeeded().unwrap_err().status, Status::UseMulticast);
+
+        let mut msg = Message::new(MessageType::Reply);
+        assert!(msg.succeeded().is_ok());
+        msg.opts_mut()
+            .insert(DhcpOption::StatusCode(status(Status::Success)));
+        assert!(msg.succeeded().is_ok());
+    }
+
+    #[test]
+    fn accessors() {
+        let mut msg = Message::new(MessageType::Solicit);
+        assert!(msg.client_id().is_none());
+        assert!(!msg.rapid_commit());
+        assert_eq!(msg.iana().count(), 0);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::ClientId(vec![1, 2]));
+        opts.insert(DhcpOption::ServerId(vec![3]));
+        opts.insert(DhcpOption::RapidCommit);
+        opts.insert(DhcpOption::ElapsedTime(100));
+        for id in [1, 2] {
+            opts.insert(DhcpOption::IANA(IANA {
+                id,
+                t1: 0,
+                t2: 0,
+                opts: DhcpOptions::new(),
+            }));
+        }
+        assert_eq!(msg.client_id(), Some(&[1, 2][..]));
+        assert_eq!(msg.server_id(), Some(&[3][..]));
+        assert!(msg.rapid_commit());
+        assert_eq!(msg.elapsed_time(), Some(100));
+        assert_eq!(msg.iana().count(), 2);
+        assert_eq!(msg.iapd().count(), 0);
+    }
+
+    #[test]
+    fn test_mode() {
+        // Release-Reply & Confirm-Reply carry a status code but no IAs
+        let reply = |status| {
+            let mut msg = Message::new(MessageType::Reply);
+            msg.opts_mut().insert(DhcpOption::StatusCode(StatusCode {
+                status,
+                msg: String::new(),
+            }));
+            msg
+        };
+        assert_eq!(reply(Status::Success).mode(), None);
+        assert_eq!(reply(Status::NotOnLink).mode(), None);
+        let mut msg = Message::new(MessageType::Reply);
+        msg.opts_mut()
+            .insert(DhcpOption::InformationRefreshTime(86400));
+        assert_eq!(msg.mode(), Some(Mode::Stateless));
+        msg.opts_mut().insert(DhcpOption::IAPD(IAPD {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: DhcpOptions::new(),
+        }));
+        assert_eq!(
+            msg.mode(),
+            Some(Mode::PrefixDelegation {
+                rapid_commit: false
+            })
+        );
+        msg.opts_mut().insert(DhcpOption::RapidCommit);
+        msg.opts_mut().insert(DhcpOption::IATA(IATA::new(2)));
+        assert_eq!(msg.mode(), Some(Mode::Stateful { rapid_commit: true }));
+
+        assert_eq!(Message::new(MessageType::Renew).mode(), None);
+        msg.set_msg_type(MessageType::Reconfigure);
+        assert_eq!(msg.mode(), None);
+    }
+
+    #[test]
+    fn bytes_roundtrip() -> Result<()> {
+        let msg = Message::try_from(&solicit()[..])?;
+        assert_eq!(msg.to_vec()?, solicit());
+        #[cfg(feature = "bytes")]
+        assert_eq!(msg.to_bytes()?, solicit());
+        assert!(Message::try_from(&[1, 2][..]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn xid_num() {
         let mut msg = Message::default();
@@ -519,6 +915,87 @@ mod tests {
         msg.set_xid_num(8);
         assert_eq!(msg.xid_num(), 8);
     }
+    #[test]
+    fn relay_client_linklayer_addr() -> Result<()> {
+        let mut input = vec![12, 0];
+        input.extend_from_slice(&[0; 32]);
+        let mut msg = RelayMessage::decode(&mut Decoder::new(&input))?;
+        msg.set_client_linklayer_addr([0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]);
+
+        let buf = msg.to_vec()?;
+        assert_eq!(
+            &buf[34..],
+            &[0, 79, 0, 8, 0, 1, 0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]
+        );
+        let res = RelayMessage::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(
+            res.opts().get(OptionCode::ClientLinklayerAddr),
+            Some(&DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr {
+                htype: HType::Eth,
+                addr: vec![0xde, 0xad, 0xbe, 0xef, 0x00, 0x01],
+            }))
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_nesting_limit() -> Result<()> {
+        // RelayForw messages each relaying the next, `levels` deep
+        fn nested_relays(levels: usize) -> Vec<u8> {
+            let mut buf = vec![12, 0];
+            buf.extend_from_slice(&[0; 32]);
+            for _ in 1..levels {
+                let mut outer = vec![12, 0];
+                outer.extend_from_slice(&[0; 32]);
+                outer.extend_from_slice(&[0, 9]);
+                outer.extend_from_slice(&(buf.len() as u16).to_be_bytes());
+                outer.extend_from_slice(&buf);
+                buf = outer;
+            }
+            buf
+        }
+        fn depth(msg: &RelayMessage) -> usize {
+            match msg.opts().get(OptionCode::RelayMsg) {
+                Some(DhcpOption::RelayMsg(inner)) => 1 + depth(inner),
+                _ => 1,
+            }
+        }
+
+        let buf = nested_relays(32);
+        assert_eq!(
+            depth(&RelayMessage::decode(&mut Decoder::new_strict(&buf))?),
+            32
+        );
+
+        let buf = nested_relays(33);
+        let err = RelayMessage::decode(&mut Decoder::new_strict(&buf)).unwrap_err();
+        assert!(matches!(err, DecodeError::TooDeep(32)));
+        // lenient decoding drops the relay that went too deep
+        assert_eq!(depth(&RelayMessage::decode(&mut Decoder::new(&buf))?), 32);
+        let mut decoder = Decoder::new(&buf).recover(true);
+        RelayMessage::decode(&mut decoder)?;
+        assert!(matches!(
+            decoder.take_errors()[..],
+            [(_, DecodeError::TooDeep(32))]
+        ));
+
+        // far past the limit, decoding stops at the configured depth
+        let buf = nested_relays(1700);
+        let msg = RelayMessage::decode(&mut Decoder::new(&buf).max_depth(8))?;
+        assert_eq!(depth(&msg), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_msg_type_names() {
+        assert_eq!("RELAY-FORW".parse(), Ok(MessageType::RelayForw));
+        assert_eq!(
+            "informationrequest".parse(),
+            Ok(MessageType::InformationRequest)
+        );
+        assert_eq!(MessageType::LeaseQueryReply.to_string(), "LEASEQUERY-REPLY");
+        assert_eq!(MessageType::Unknown(200).to_string(), "200");
+        assert!("foo".parse::<MessageType>().is_err());
+    }
     #[cfg(feature = "serde")]
     #[test]
     fn test_json_v6() -> Result<()> {
@@ -1,447 +1,319 @@
-use crate::v6::{options::DhcpOption, UnknownOption};
+use std::{fmt, str::FromStr};
 
-#[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use crate::{
+    error::ParseNameError,
+    metadata::{OptionMetadata, ValueKind},
+    v6::{options::DhcpOption, UnknownOption},
+};
 
-/// option code type
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum OptionCode {
-    ClientId,
-    ServerId,
-    IANA,
-    IATA,
-    IAAddr,
-    ORO,
-    Preference,
-    ElapsedTime,
-    RelayMsg,
-    Authentication,
-    ServerUnicast,
-    StatusCode,
-    RapidCommit,
-    UserClass,
-    VendorClass,
-    VendorOpts,
-    InterfaceId,
-    ReconfMsg,
-    ReconfAccept,
-    SipServerD,
-    SipServerA,
-    DomainNameServers,
-    DomainSearchList,
-    IAPD,
-    IAPrefix,
-    NisServers,
-    NispServers,
-    NisDomainName,
-    NispDomainName,
-    SntpServers,
-    InformationRefreshTime,
-    BcmcsServerD,
-    BcmcsServerA,
-    GeoconfCivic,
-    RemoteId,
-    SubscriberId,
-    ClientFqdn,
-    PanaAgent,
-    NewPosixTimezone,
-    NewTzdbTimezone,
-    ERO,
-    LqQuery,
-    ClientData,
-    CltTime,
-    LqRelayData,
-    LqClientLink,
-    Mip6Hnidf,
-    Mip6Vdinf,
-    V6Lost,
-    CapwapAcV6,
-    RelayId,
-    Ipv6AddressMoS,
-    Ipv6FQDNMoS,
-    NtpServer,
-    V6AccessDomain,
-    SipUaCsList,
-    OptBootfileUrl,
-    OptBootfileParam,
-    ClientArchType,
-    Nii,
-    Geolocation,
-    AftrName,
-    ErpLocalDomainName,
-    Rsoo,
-    PdExclude,
-    Vss,
-    Mip6Idinf,
-    Mip6Udinf,
-    Mip6Hnp,
-    Mip6Haa,
-    Mip6Haf,
-    RdnssSelection,
-    KrbPrincipalName,
-    KrbRealmName,
-    KrbDefaultRealmName,
-    KrbKdc,
-    ClientLinklayerAddr,
-    LinkAddress,
-    Radius,
-    SolMaxRt,
-    InfMaxRt,
-    Addrsel,
-    AddrselTable,
-    V6PcpServer,
-    Dhcpv4Msg,
-    Dhcp4ODhcp6Server,
-    S46Rule,
-    S46Br,
-    S46Dmr,
-    S46V4v6bind,
-    S46Portparams,
-    S46ContMape,
-    S46ContMapt,
-    S46ContLw,
-    _4Rd,
-    _4RdMapRule,
-    _4RdNonMapRule,
-    LqBaseTime,
-    LqStartTime,
-    LqEndTime,
-    DhcpCaptivePortal,
-    MplParameters,
-    AniAtt,
-    AniNetworkName,
-    AniApName,
-    AniApBssid,
-    AniOperatorId,
-    AniOperatorRealm,
-    S46Priority,
-    MudUrlV6,
-    V6Prefix64,
-    FBindingStatus,
-    FConnectFlags,
-    Fdnsremovalinfo,
-    FDNSHostName,
-    FDNSZoneName,
-    Fdnsflags,
-    Fexpirationtime,
-    FMaxUnackedBndupd,
-    FMclt,
-    FPartnerLifetime,
-    FPartnerLifetimeSent,
-    FPartnerDownTime,
-    FPartnerRawCltTime,
-    FProtocolVersion,
-    FKeepaliveTime,
-    FReconfigureData,
-    FRelationshipName,
-    FServerFlags,
-    FServerState,
-    FStartTimeOfState,
-    FStateExpirationTime,
-    RelayPort,
-    Ipv6AddressANDSF,
-    Unknown(u16),
+// declares the v6 option codes.
+// generates:
+// * the `OptionCode` newtype, a constant for each code, and its From<u16>, Into<u16>
+// * const `OptionCode::from_u16`/`OptionCode::as_u16` & `OptionCode::KNOWN`
+// * `OptionCode::name`, the canonical (kea style) name of each option
+// * `OptionCode::metadata`, with a link to the defining RFC & the kind of value
+// * with the `redact` feature, `Debug` for `DhcpOption`, masking the value of the
+//   options for which `OptionCode::is_redacted` returns true
+//
+// Syntax is `N => Variant "name" RFC Kind,` where `Kind` is a `ValueKind` variant,
+// `Opaque` for options decoded as `DhcpOption::Unknown`
+macro_rules! option_codes {
+    ($($code:literal => $name:ident $canonical:literal $rfc:literal $kind:ident,)*) => {
+        /// option code type, a number with a constant for each option this crate knows,
+        /// e.g. `OptionCode::ClientId` is `OptionCode::new(1)`
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[repr(transparent)]
+        pub struct OptionCode(u16);
+
+        #[allow(non_upper_case_globals)]
+        impl OptionCode {
+            $(
+                #[doc = concat!($code, " - `", $canonical, "` <https://www.rfc-editor.org/rfc/rfc", $rfc, ".html>")]
+                pub const $name: OptionCode = OptionCode($code);
+            )*
+        }
+
+        impl OptionCode {
+            /// every option code known to this crate, in code order
+            pub const KNOWN: &'static [OptionCode] = &[$(OptionCode::$name,)*];
+
+            /// get the option code for `n`, usable in const context
+            pub const fn from_u16(n: u16) -> Self {
+                Self(n)
+            }
+
+            /// get the numeric code, usable in const context
+            pub const fn as_u16(self) -> u16 {
+                self.0
+            }
+
+            // the variant name, `None` for unknown codes
+            fn variant_name(self) -> Option<&'static str> {
+                match self {
+                    $(OptionCode::$name => Some(stringify!($name)),)*
+                    _ => None,
+                }
+            }
+
+            /// the canonical (kea style) option name, e.g. `dns-servers`.
+            /// `None` for unknown codes
+            pub const fn name(self) -> Option<&'static str> {
+                match self {
+                    $(OptionCode::$name => Some($canonical),)*
+                    _ => None,
+                }
+            }
+
+            /// the name, RFC link & value kind of this option, `None` for unknown codes
+            pub const fn metadata(self) -> Option<OptionMetadata> {
+                match self {
+                    $(OptionCode::$name => Some(OptionMetadata {
+                        name: $canonical,
+                        rfc: Some(concat!("https://www.rfc-editor.org/rfc/rfc", $rfc, ".html")),
+                        kind: ValueKind::$kind,
+                        deprecated: false,
+                    }),)*
+                    _ => None,
+                }
+            }
+        }
+
+        #[cfg(feature = "redact")]
+        impl fmt::Debug for DhcpOption {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                $(debug_option!(self, f, $name, $kind);)*
+                match self {
+                    DhcpOption::Unknown(opt) => f.debug_tuple("Unknown").field(opt).finish(),
+                    // every other variant has a row in the table, mask the value if not
+                    _ => f
+                        .debug_tuple(&OptionCode::from(self).to_string())
+                        .field(&crate::redact::Redacted::always(&()))
+                        .finish(),
+                }
+            }
+        }
+    };
+}
+
+// formats `$opt` and returns if it's the `$name` variant, `Opaque` options have no
+// variant, they're `DhcpOption::Unknown`
+#[cfg(feature = "redact")]
+macro_rules! debug_option {
+    ($opt:ident, $f:ident, $name:ident, Opaque) => {};
+    ($opt:ident, $f:ident, $name:ident, Empty) => {
+        if let DhcpOption::$name = $opt {
+            return $f.write_str(stringify!($name));
+        }
+    };
+    ($opt:ident, $f:ident, $name:ident, $kind:ident) => {
+        if let DhcpOption::$name(value) = $opt {
+            let redact = OptionCode::$name.is_redacted();
+            return $f
+                .debug_tuple(stringify!($name))
+                .field(&crate::redact::Redacted::new(value, redact))
+                .finish();
+        }
+    };
+}
+
+option_codes!(
+    1 => ClientId "client-id" 8415 Bytes,
+    2 => ServerId "server-id" 8415 Bytes,
+    3 => IANA "ia-na" 8415 Structured,
+    4 => IATA "ia-ta" 8415 Structured,
+    5 => IAAddr "iaaddr" 8415 Structured,
+    6 => ORO "oro" 8415 Structured,
+    7 => Preference "preference" 8415 U8,
+    8 => ElapsedTime "elapsed-time" 8415 U16,
+    9 => RelayMsg "relay-msg" 8415 Structured,
+    11 => Authentication "auth" 8415 Structured,
+    12 => ServerUnicast "unicast" 8415 Ipv6Addr,
+    13 => StatusCode "status-code" 8415 Structured,
+    14 => RapidCommit "rapid-commit" 8415 Empty,
+    15 => UserClass "user-class" 8415 Structured,
+    16 => VendorClass "vendor-class" 8415 Structured,
+    17 => VendorOpts "vendor-opts" 8415 Structured,
+    18 => InterfaceId "interface-id" 8415 Structured,
+    19 => ReconfMsg "reconf-msg" 8415 Structured,
+    20 => ReconfAccept "reconf-accept" 8415 Empty,
+    21 => SipServerD "sip-server-dns" 3319 Domains,
+    22 => SipServerA "sip-server-addr" 3319 Ipv6Addrs,
+    23 => DomainNameServers "dns-servers" 3646 Ipv6Addrs,
+    24 => DomainSearchList "domain-search" 3646 Domains,
+    25 => IAPD "ia-pd" 8415 Structured,
+    26 => IAPrefix "iaprefix" 8415 Structured,
+    27 => NisServers "nis-servers" 3898 Opaque,
+    28 => NispServers "nisp-servers" 3898 Opaque,
+    29 => NisDomainName "nis-domain-name" 3898 Opaque,
+    30 => NispDomainName "nisp-domain-name" 3898 Opaque,
+    31 => SntpServers "sntp-servers" 4075 Opaque,
+    32 => InformationRefreshTime "information-refresh-time" 8415 U32,
+    33 => BcmcsServerD "bcmcs-server-dns" 4280 Opaque,
+    34 => BcmcsServerA "bcmcs-server-addr" 4280 Opaque,
+    36 => GeoconfCivic "geoconf-civic" 4776 Opaque,
+    37 => RemoteId "remote-id" 4649 Structured,
+    38 => SubscriberId "subscriber-id" 4580 Opaque,
+    39 => ClientFqdn "client-fqdn" 4704 Structured,
+    40 => PanaAgent "pana-agent" 5192 Opaque,
+    41 => NewPosixTimezone "new-posix-timezone" 4833 String,
+    42 => NewTzdbTimezone "new-tzdb-timezone" 4833 String,
+    43 => ERO "ero" 4994 Opaque,
+    44 => LqQuery "lq-query" 5007 Structured,
+    45 => ClientData "client-data" 5007 Opaque,
+    46 => CltTime "clt-time" 5007 Opaque,
+    47 => LqRelayData "lq-relay-data" 5007 Opaque,
+    48 => LqClientLink "lq-client-link" 5007 Opaque,
+    49 => Mip6Hnidf "mip6-hnidf" 6610 Opaque,
+    50 => Mip6Vdinf "mip6-vdinf" 6610 Opaque,
+    51 => V6Lost "v6-lost" 5223 Opaque,
+    52 => CapwapAcV6 "capwap-ac-v6" 5417 Opaque,
+    53 => RelayId "relay-id" 5460 Bytes,
+    54 => Ipv6AddressMoS "ipv6-address-mos" 5678 Opaque,
+    55 => Ipv6FQDNMoS "ipv6-fqdn-mos" 5678 Opaque,
+    56 => NtpServer "ntp-server" 5908 Structured,
+    57 => V6AccessDomain "v6-access-domain" 5986 Opaque,
+    58 => SipUaCsList "sip-ua-cs-list" 6011 Opaque,
+    59 => OptBootfileUrl "bootfile-url" 5970 Uri,
+    60 => OptBootfileParam "bootfile-param" 5970 Structured,
+    61 => ClientArchType "client-arch-type" 5970 Opaque,
+    62 => Nii "nii" 5970 Opaque,
+    63 => Geolocation "geolocation" 6225 Opaque,
+    64 => AftrName "aftr-name" 6334 Domain,
+    65 => ErpLocalDomainName "erp-local-domain-name" 6440 Opaque,
+    66 => Rsoo "rsoo" 6422 Opaque,
+    67 => PdExclude "pd-exclude" 6603 Opaque,
+    68 => Vss "vss" 6607 Opaque,
+    69 => Mip6Idinf "mip6-idinf" 6610 Opaque,
+    70 => Mip6Udinf "mip6-udinf" 6610 Opaque,
+    71 => Mip6Hnp "mip6-hnp" 6610 Opaque,
+    72 => Mip6Haa "mip6-haa" 6610 Opaque,
+    73 => Mip6Haf "mip6-haf" 6610 Opaque,
+    74 => RdnssSelection "rdnss-selection" 6731 Opaque,
+    75 => KrbPrincipalName "krb-principal-name" 6784 Opaque,
+    76 => KrbRealmName "krb-realm-name" 6784 Opaque,
+    77 => KrbDefaultRealmName "krb-default-realm-name" 6784 Opaque,
+    78 => KrbKdc "krb-kdc" 6784 Opaque,
+    79 => ClientLinklayerAddr "client-linklayer-addr" 6939 Structured,
+    80 => LinkAddress "link-address" 6977 Ipv6Addr,
+    81 => Radius "radius" 7037 Opaque,
+    82 => SolMaxRt "solmax-rt" 8415 Structured,
+    83 => InfMaxRt "inf-max-rt" 8415 Structured,
+    84 => Addrsel "addrsel" 7078 Opaque,
+    85 => AddrselTable "addrsel-table" 7078 Opaque,
+    86 => PcpServer "v6-pcp-server" 7291 Ipv6Addrs,
+    87 => Dhcpv4Msg "dhcpv4-msg" 7341 Opaque,
+    88 => Dhcp4ODhcp6Server "dhcp4o6-server-addr" 7341 Opaque,
+    89 => S46Rule "s46-rule" 7598 Opaque,
+    90 => S46Br "s46-br" 7598 Opaque,
+    91 => S46Dmr "s46-dmr" 7598 Opaque,
+    92 => S46V4v6bind "s46-v4v6bind" 7598 Opaque,
+    93 => S46Portparams "s46-portparams" 7598 Opaque,
+    94 => S46ContMape "s46-cont-mape" 7598 Opaque,
+    95 => S46ContMapt "s46-cont-mapt" 7598 Opaque,
+    96 => S46ContLw "s46-cont-lw" 7598 Opaque,
+    97 => _4Rd "4rd" 7600 Opaque,
+    98 => _4RdMapRule "4rd-map-rule" 7600 Opaque,
+    99 => _4RdNonMapRule "4rd-non-map-rule" 7600 Opaque,
+    100 => LqBaseTime "lq-base-time" 7653 Opaque,
+    101 => LqStartTime "lq-start-time" 7653 Opaque,
+    102 => LqEndTime "lq-end-time" 7653 Opaque,
+    103 => DhcpCaptivePortal "v6-captive-portal" 8910 Opaque,
+    104 => MplParameters "mpl-parameters" 7774 Opaque,
+    105 => AniAtt "ani-att" 7839 Opaque,
+    106 => AniNetworkName "ani-network-name" 7839 Opaque,
+    107 => AniApName "ani-ap-name" 7839 Opaque,
+    108 => AniApBssid "ani-ap-bssid" 7839 Opaque,
+    109 => AniOperatorId "ani-operator-id" 7839 Opaque,
+    110 => AniOperatorRealm "ani-operator-realm" 7839 Opaque,
+    111 => S46Priority "s46-priority" 8026 Opaque,
+    112 => MudUrlV6 "mud-url" 8520 Opaque,
+    113 => V6Prefix64 "v6-prefix64" 8115 Opaque,
+    114 => FBindingStatus "f-binding-status" 8156 Opaque,
+    115 => FConnectFlags "f-connect-flags" 8156 Opaque,
+    116 => Fdnsremovalinfo "f-dns-removal-info" 8156 Opaque,
+    117 => FDNSHostName "f-dns-host-name" 8156 Opaque,
+    118 => FDNSZoneName "f-dns-zone-name" 8156 Opaque,
+    119 => Fdnsflags "f-dns-flags" 8156 Opaque,
+    120 => Fexpirationtime "f-expiration-time" 8156 Opaque,
+    121 => FMaxUnackedBndupd "f-max-unacked-bndupd" 8156 Opaque,
+    122 => FMclt "f-mclt" 8156 Opaque,
+    123 => FPartnerLifetime "f-partner-lifetime" 8156 Opaque,
+    124 => FPartnerLifetimeSent "f-partner-lifetime-sent" 8156 Opaque,
+    125 => FPartnerDownTime "f-partner-down-time" 8156 Opaque,
+    126 => FPartnerRawCltTime "f-partner-raw-clt-time" 8156 Opaque,
+    127 => FProtocolVersion "f-protocol-version" 8156 Opaque,
+    128 => FKeepaliveTime "f-keepalive-time" 8156 Opaque,
+    129 => FReconfigureData "f-reconfigure-data" 8156 Opaque,
+    130 => FRelationshipName "f-relationship-name" 8156 Opaque,
+    131 => FServerFlags "f-server-flags" 8156 Opaque,
+    132 => FServerState "f-server-state" 8156 Opaque,
+    133 => FStartTimeOfState "f-start-time-of-state" 8156 Opaque,
+    134 => FStateExpirationTime "f-state-expiration-time" 8156 Opaque,
+    135 => RelayPort "relay-port" 8357 Opaque,
+    136 => SztpRedirect "v6-sztp-redirect" 8572 Uris,
+    143 => Ipv6AddressANDSF "ipv6-address-andsf" 6153 Opaque,
+);
+
+#[cfg(feature = "redact")]
+impl OptionCode {
+    /// options whose contents are masked in `Debug` output, see [`redact`](crate::redact).
+    /// `Authentication` masks its own key material
+    fn is_redacted(&self) -> bool {
+        *self == OptionCode::ClientId
+    }
+}
+
+impl OptionCode {
+    /// iterate over every option code known to this crate, in code order
+    pub fn iter_known() -> impl Iterator<Item = OptionCode> {
+        Self::KNOWN.iter().copied()
+    }
 }
 
 impl From<OptionCode> for u16 {
     fn from(opt: OptionCode) -> Self {
-        use OptionCode as O;
-        match opt {
-            O::ClientId => 1,
-            O::ServerId => 2,
-            O::IANA => 3,
-            O::IATA => 4,
-            O::IAAddr => 5,
-            O::ORO => 6,
-            O::Preference => 7,
-            O::ElapsedTime => 8,
-            O::RelayMsg => 9,
-            O::Authentication => 11,
-            O::ServerUnicast => 12,
-            O::StatusCode => 13,
-            O::RapidCommit => 14,
-            O::UserClass => 15,
-            O::VendorClass => 16,
-            O::VendorOpts => 17,
-            O::InterfaceId => 18,
-            O::ReconfMsg => 19,
-            O::ReconfAccept => 20,
-            O::SipServerD => 21,
-            O::SipServerA => 22,
-            O::DomainNameServers => 23,
-            O::DomainSearchList => 24,
-            O::IAPD => 25,
-            O::IAPrefix => 26,
-            O::NisServers => 27,
-            O::NispServers => 28,
-            O::NisDomainName => 29,
-            O::NispDomainName => 30,
-            O::SntpServers => 31,
-            O::InformationRefreshTime => 32,
-            O::BcmcsServerD => 33,
-            O::BcmcsServerA => 34,
-            O::GeoconfCivic => 36,
-            O::RemoteId => 37,
-            O::SubscriberId => 38,
-            O::ClientFqdn => 39,
-            O::PanaAgent => 40,
-            O::NewPosixTimezone => 41,
-            O::NewTzdbTimezone => 42,
-            O::ERO => 43,
-            O::LqQuery => 44,
-            O::ClientData => 45,
-            O::CltTime => 46,
-            O::LqRelayData => 47,
-            O::LqClientLink => 48,
-            O::Mip6Hnidf => 49,
-            O::Mip6Vdinf => 50,
-            O::V6Lost => 51,
-            O::CapwapAcV6 => 52,
-            O::RelayId => 53,
-            O::Ipv6AddressMoS => 54,
-            O::Ipv6FQDNMoS => 55,
-            O::NtpServer => 56,
-            O::V6AccessDomain => 57,
-            O::SipUaCsList => 58,
-            O::OptBootfileUrl => 59,
-            O::OptBootfileParam => 60,
-            O::ClientArchType => 61,
-            O::Nii => 62,
-            O::Geolocation => 63,
-            O::AftrName => 64,
-            O::ErpLocalDomainName => 65,
-            O::Rsoo => 66,
-            O::PdExclude => 67,
-            O::Vss => 68,
-            O::Mip6Idinf => 69,
-            O::Mip6Udinf => 70,
-            O::Mip6Hnp => 71,
-            O::Mip6Haa => 72,
-            O::Mip6Haf => 73,
-            O::RdnssSelection => 74,
-            O::KrbPrincipalName => 75,
-            O::KrbRealmName => 76,
-            O::KrbDefaultRealmName => 77,
-            O::KrbKdc => 78,
-            O::ClientLinklayerAddr => 79,
-            O::LinkAddress => 80,
-            O::Radius => 81,
-            O::SolMaxRt => 82,
-            O::InfMaxRt => 83,
-            O::Addrsel => 84,
-            O::AddrselTable => 85,
-            O::V6PcpServer => 86,
-            O::Dhcpv4Msg => 87,
-            O::Dhcp4ODhcp6Server => 88,
-            O::S46Rule => 89,
-            O::S46Br => 90,
-            O::S46Dmr => 91,
-            O::S46V4v6bind => 92,
-            O::S46Portparams => 93,
-            O::S46ContMape => 94,
-            O::S46ContMapt => 95,
-            O::S46ContLw => 96,
-            O::_4Rd => 97,
-            O::_4RdMapRule => 98,
-            O::_4RdNonMapRule => 99,
-            O::LqBaseTime => 100,
-            O::LqStartTime => 101,
-            O::LqEndTime => 102,
-            O::DhcpCaptivePortal => 103,
-            O::MplParameters => 104,
-            O::AniAtt => 105,
-            O::AniNetworkName => 106,
-            O::AniApName => 107,
-            O::AniApBssid => 108,
-            O::AniOperatorId => 109,
-            O::AniOperatorRealm => 110,
-            O::S46Priority => 111,
-            O::MudUrlV6 => 112,
-            O::V6Prefix64 => 113,
-            O::FBindingStatus => 114,
-            O::FConnectFlags => 115,
-            O::Fdnsremovalinfo => 116,
-            O::FDNSHostName => 117,
-            O::FDNSZoneName => 118,
-            O::Fdnsflags => 119,
-            O::Fexpirationtime => 120,
-            O::FMaxUnackedBndupd => 121,
-            O::FMclt => 122,
-            O::FPartnerLifetime => 123,
-            O::FPartnerLifetimeSent => 124,
-            O::FPartnerDownTime => 125,
-            O::FPartnerRawCltTime => 126,
-            O::FProtocolVersion => 127,
-            O::FKeepaliveTime => 128,
-            O::FReconfigureData => 129,
-            O::FRelationshipName => 130,
-            O::FServerFlags => 131,
-            O::FServerState => 132,
-            O::FStartTimeOfState => 133,
-            O::FStateExpirationTime => 134,
-            O::RelayPort => 135,
-            O::Ipv6AddressANDSF => 143,
-            O::Unknown(n) => n,
-        }
+        opt.as_u16()
     }
 }
 
 impl From<u16> for OptionCode {
     fn from(n: u16) -> Self {
-        use OptionCode::*;
-        match n {
-            1 => ClientId,
-            2 => ServerId,
-            3 => IANA,
-            4 => IATA,
-            5 => IAAddr,
-            6 => ORO,
-            7 => Preference,
-            8 => ElapsedTime,
-            9 => RelayMsg,
-            11 => Authentication,
-            12 => ServerUnicast,
-            13 => StatusCode,
-            14 => RapidCommit,
-            15 => UserClass,
-            16 => VendorClass,
-            17 => VendorOpts,
-            18 => InterfaceId,
-            19 => ReconfMsg,
-            20 => ReconfAccept,
-            21 => SipServerD,
-            22 => SipServerA,
-            23 => DomainNameServers,
-            24 => DomainSearchList,
-            25 => IAPD,
-            26 => IAPrefix,
-            27 => NisServers,
-            28 => NispServers,
-            29 => NisDomainName,
-            30 => NispDomainName,
-            31 => SntpServers,
-            32 => InformationRefreshTime,
-            33 => BcmcsServerD,
-            34 => BcmcsServerA,
-            36 => GeoconfCivic,
-            37 => RemoteId,
-            38 => SubscriberId,
-            39 => ClientFqdn,
-            40 => PanaAgent,
-            41 => NewPosixTimezone,
-            42 => NewTzdbTimezone,
-            43 => ERO,
-            44 => LqQuery,
-            45 => ClientData,
-            46 => CltTime,
-            47 => LqRelayData,
-            48 => LqClientLink,
-            49 => Mip6Hnidf,
-            50 => Mip6Vdinf,
-            51 => V6Lost,
-            52 => CapwapAcV6,
-            53 => RelayId,
-            54 => Ipv6AddressMoS,
-            55 => Ipv6FQDNMoS,
-            56 => NtpServer,
-            57 => V6AccessDomain,
-            58 => SipUaCsList,
-            59 => OptBootfileUrl,
-            60 => OptBootfileParam,
-            61 => ClientArchType,
-            62 => Nii,
-            63 => Geolocation,
-            64 => AftrName,
-            65 => ErpLocalDomainName,
-            66 => Rsoo,
-            67 => PdExclude,
-            68 => Vss,
-            69 => Mip6Idinf,
-            70 => Mip6Udinf,
-            71 => Mip6Hnp,
-            72 => Mip6Haa,
-            73 => Mip6Haf,
-            74 => RdnssSelection,
-            75 => KrbPrincipalName,
-            76 => KrbRealmName,
-            77 => KrbDefaultRealmName,
-            78 => KrbKdc,
-            79 => ClientLinklayerAddr,
-            80 => LinkAddress,
-            81 => Radius,
-            82 => SolMaxRt,
-            83 => InfMaxRt,
-            84 => Addrsel,
-            85 => AddrselTable,
-            86 => V6PcpServer,
-            87 => Dhcpv4Msg,
-            88 => Dhcp4ODhcp6Server,
-            89 => S46Rule,
-            90 => S46Br,
-            91 => S46Dmr,
-            92 => S46V4v6bind,
-            93 => S46Portparams,
-            94 => S46ContMape,
-            95 => S46ContMapt,
-            96 => S46ContLw,
-            97 => _4Rd,
-            98 => _4RdMapRule,
-            99 => _4RdNonMapRule,
-            100 => LqBaseTime,
-            101 => LqStartTime,
-            102 => LqEndTime,
-            103 => DhcpCaptivePortal,
-            104 => MplParameters,
-            105 => AniAtt,
-            106 => AniNetworkName,
-            107 => AniApName,
-            108 => AniApBssid,
-            109 => AniOperatorId,
-            110 => AniOperatorRealm,
-            111 => S46Priority,
-            112 => MudUrlV6,
-            113 => V6Prefix64,
-            114 => FBindingStatus,
-            115 => FConnectFlags,
-            116 => Fdnsremovalinfo,
-            117 => FDNSHostName,
-            118 => FDNSZoneName,
-            119 => Fdnsflags,
-            120 => Fexpirationtime,
-            121 => FMaxUnackedBndupd,
-            122 => FMclt,
-            123 => FPartnerLifetime,
-            124 => FPartnerLifetimeSent,
-            125 => FPartnerDownTime,
-            126 => FPartnerRawCltTime,
-            127 => FProtocolVersion,
-            128 => FKeepaliveTime,
-            129 => FReconfigureData,
-            130 => FRelationshipName,
-            131 => FServerFlags,
-            132 => FServerState,
-            133 => FStartTimeOfState,
-            134 => FStateExpirationTime,
-            135 => RelayPort,
-            143 => Ipv6AddressANDSF,
-            _ => Unknown(n),
-        }
+        OptionCode::from_u16(n)
     }
 }
 
-impl PartialOrd for OptionCode {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+/// Parses an option code from its canonical name (`"dns-servers"`) or variant name
+/// (`"DomainNameServers"`), ignoring case, or from its number (`"23"`)
+impl FromStr for OptionCode {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.parse::<u16>() {
+            return Ok(n.into());
+        }
+        Self::iter_known()
+            .find(|code| {
+                [code.name(), code.variant_name()]
+                    .into_iter()
+                    .flatten()
+                    .any(|name| name.eq_ignore_ascii_case(s))
+            })
+            .ok_or_else(|| ParseNameError::new(s))
     }
 }
 
-impl Ord for OptionCode {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        u16::from(*self).cmp(&u16::from(*other))
+/// Prints the canonical name, or the number for unknown codes
+impl fmt::Display for OptionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.as_u16()),
+        }
     }
 }
 
+option_code_common!(OptionCode, u16);
+
 impl From<&DhcpOption> for OptionCode {
     fn from(opt: &DhcpOption) -> Self {
         use DhcpOption as O;
@@ -465,22 +337,87 @@ impl From<&DhcpOption> for OptionCode {
             O::InterfaceId(_) => OptionCode::InterfaceId,
             O::ReconfMsg(_) => OptionCode::ReconfMsg,
             O::ReconfAccept => OptionCode::ReconfAccept,
+            O::SipServerD(_) => OptionCode::SipServerD,
+            O::SipServerA(_) => OptionCode::SipServerA,
             O::DomainNameServers(_) => OptionCode::DomainNameServers,
             O::DomainSearchList(_) => OptionCode::DomainSearchList,
             O::IAPD(_) => OptionCode::IAPD,
             O::IAPrefix(_) => OptionCode::IAPrefix,
             O::InformationRefreshTime(_) => OptionCode::InformationRefreshTime,
+            O::NewPosixTimezone(_) => OptionCode::NewPosixTimezone,
+            O::NewTzdbTimezone(_) => OptionCode::NewTzdbTimezone,
             O::NtpServer(_) => OptionCode::NtpServer,
-            // SolMaxRt(_) => OptionCode::SolMaxRt,
-            // InfMaxRt(_) => OptionCode::InfMaxRt,
-            // LqQuery(_) => OptionCode::LqQuery,
+            O::OptBootfileUrl(_) => OptionCode::OptBootfileUrl,
+            O::OptBootfileParam(_) => OptionCode::OptBootfileParam,
+            O::AftrName(_) => OptionCode::AftrName,
+            O::PcpServer(_) => OptionCode::PcpServer,
+            O::ClientLinklayerAddr(_) => OptionCode::ClientLinklayerAddr,
+            O::SztpRedirect(_) => OptionCode::SztpRedirect,
+            O::SolMaxRt(_) => OptionCode::SolMaxRt,
+            O::InfMaxRt(_) => OptionCode::InfMaxRt,
+            O::RemoteId(_) => OptionCode::RemoteId,
+            O::ClientFqdn(_) => OptionCode::ClientFqdn,
+            O::LqQuery(_) => OptionCode::LqQuery,
+            O::RelayId(_) => OptionCode::RelayId,
+            O::LinkAddress(_) => OptionCode::LinkAddress,
             // ClientData(_) => OptionCode::ClientData,
             // CltTime(_) => OptionCode::CltTime,
             // LqRelayData(_) => OptionCode::LqRelayData,
             // LqClientLink(_) => OptionCode::LqClientLink,
-            // RelayId(_) => OptionCode::RelayId,
-            // LinkAddress(_) => OptionCode::LinkAddress,
-            O::Unknown(UnknownOption { code, .. }) => OptionCode::Unknown(*code),
+            O::Unknown(UnknownOption { code, .. }) => (*code).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_codes() {
+        const DNS: OptionCode = OptionCode::from_u16(23);
+        assert_eq!(DNS, OptionCode::DomainNameServers);
+        assert_eq!(OptionCode::DomainNameServers.as_u16(), 23);
+        for code in OptionCode::iter_known() {
+            assert_eq!(OptionCode::from(u16::from(code)), code);
+            assert!(code.is_known());
+        }
+        assert!(OptionCode::KNOWN.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_metadata() {
+        for code in OptionCode::iter_known() {
+            let meta = code.metadata().unwrap();
+            assert_eq!(Some(meta.name), code.name());
+        }
+        let meta = OptionCode::IAPD.metadata().unwrap();
+        assert_eq!(
+            meta.rfc,
+            Some("https://www.rfc-editor.org/rfc/rfc8415.html")
+        );
+        assert_eq!(meta.kind, ValueKind::Structured);
+        assert_eq!(
+            OptionCode::AftrName.metadata().unwrap().kind,
+            ValueKind::Domain
+        );
+        assert_eq!(OptionCode::Vss.metadata().unwrap().kind, ValueKind::Opaque);
+    }
+
+    #[test]
+    fn test_parse_name() {
+        assert_eq!("ClientId".parse(), Ok(OptionCode::ClientId));
+        assert_eq!(
+            "domainnameservers".parse(),
+            Ok(OptionCode::DomainNameServers)
+        );
+        assert_eq!("1000".parse(), Ok(OptionCode::new(1000)));
+        assert!("Foo".parse::<OptionCode>().is_err());
+        assert_eq!("dns-servers".parse(), Ok(OptionCode::DomainNameServers));
+        assert_eq!(OptionCode::IAPrefix.to_string(), "iaprefix");
+        assert_eq!(OptionCode::new(1000).to_string(), "1000");
+        for code in OptionCode::iter_known() {
+            assert_eq!(code.to_string().parse(), Ok(code));
         }
     }
 }
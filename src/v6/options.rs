@@ -1,18 +1,23 @@
-use hickory_proto::{
-    rr::Name,
-    serialize::binary::{BinDecodable, BinDecoder, BinEncodable, BinEncoder},
-};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use std::{cmp::Ordering, net::Ipv6Addr, ops::RangeInclusive};
+use std::{cmp::Ordering, fmt, net::Ipv6Addr, ops::RangeInclusive};
 
 use crate::v6::option_codes::OptionCode;
 use crate::{
-    decoder::{Decodable, Decoder},
+    decoder::{Decodable, Decoder, ObservedCode},
+    diff::OptionsDiff,
+    dns_servers::{self, DnsServerError},
+    domain::{Domain, DomainWriter},
     encoder::{Encodable, Encoder},
-    error::{DecodeResult, EncodeResult},
-    v6::{MessageType, RelayMessage},
+    enterprise::EnterpriseNumber,
+    error::{DecodeResult, EncodeError, EncodeResult},
+    merge::{MergePolicy, Precedence},
+    sub_options::{decode_sub_option, encode_sub_option, RawSubOption, SubOption, SubOptions},
+    sztp,
+    uri::Uri,
+    v4::fqdn::FqdnFlags,
+    v6::{HType, LeaseTime, MessageType, RelayMessage},
 };
 
 // server can send multiple IA_NA options to request multiple addresses
@@ -65,6 +70,13 @@ impl DhcpOptions {
         let range = range_binsearch(&self.0, |x| OptionCode::from(x).cmp(&code))?;
         Some(self.0.drain(range))
     }
+    /// Retains only the elements specified by the predicate
+    pub fn retain<F>(&mut self, pred: F)
+    where
+        F: FnMut(&DhcpOption) -> bool,
+    {
+        self.0.retain(pred)
+    }
     /// insert a new option into the list of opts
     pub fn insert(&mut self, opt: DhcpOption) {
         let i = self.0.partition_point(|x| x < &opt);
@@ -78,6 +90,130 @@ impl DhcpOptions {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut DhcpOption> {
         self.0.iter_mut()
     }
+    /// Add the options of `other`, `policy` decides which are kept for codes in both,
+    /// see [`merge`](crate::merge). All options with a code are kept or replaced
+    /// together, so e.g. two IA_NAs are never mixed with a third.
+    pub fn merge(&mut self, other: &DhcpOptions, policy: &MergePolicy<OptionCode>) {
+        let mut rest = other.0.as_slice();
+        while let Some(first) = rest.first() {
+            // `other` is sorted, take the run of options with the first code
+            let code = OptionCode::from(first);
+            let (run, tail) = rest.split_at(rest.partition_point(|x| OptionCode::from(x) == code));
+            rest = tail;
+            if self.get(code).is_some() {
+                match policy.precedence(&code) {
+                    Precedence::KeepExisting => continue,
+                    Precedence::PreferOther => self.retain(|x| OptionCode::from(x) != code),
+                }
+            }
+            for opt in run {
+                self.insert(opt.clone());
+            }
+        }
+    }
+    /// Returns the options added, removed or changed in `other` compared to `self`,
+    /// ordered by option code. Options which can appear more than once (like IA_NA)
+    /// are compared in the order they appear.
+    pub fn diff<'a>(&'a self, other: &'a DhcpOptions) -> OptionsDiff<'a, DhcpOption> {
+        let mut diff = OptionsDiff::default();
+        let (mut old, mut new) = (self.0.as_slice(), other.0.as_slice());
+        while !old.is_empty() || !new.is_empty() {
+            // both lists are sorted, take the run of the lowest code from each
+            let code = match (old.first(), new.first()) {
+                (Some(a), Some(b)) => OptionCode::from(a).min(OptionCode::from(b)),
+                (Some(a), None) => OptionCode::from(a),
+                (None, Some(b)) => OptionCode::from(b),
+                (None, None) => unreachable!(),
+            };
+            let split = |opts: &mut &'a [DhcpOption]| {
+                let n = opts.partition_point(|opt| OptionCode::from(opt) <= code);
+                let (run, rest) = opts.split_at(n);
+                *opts = rest;
+                run
+            };
+            let (old_run, new_run) = (split(&mut old), split(&mut new));
+            for i in 0..old_run.len().max(new_run.len()) {
+                match (old_run.get(i), new_run.get(i)) {
+                    (Some(a), Some(b)) if a != b => diff.changed.push((a, b)),
+                    (Some(a), None) => diff.removed.push(a),
+                    (None, Some(b)) => diff.added.push(b),
+                    _ => {}
+                }
+            }
+        }
+        diff
+    }
+    /// the addresses of each PCP server. Every `PcpServer` option is one server, with
+    /// the addresses it can be reached at, so this is the same grouping as the v4
+    /// `PcpServers` option. Like other repeated options, the order the servers were
+    /// received in isn't kept
+    /// <https://datatracker.ietf.org/doc/html/rfc7291#section-3>
+    pub fn pcp_servers(&self) -> Vec<&[Ipv6Addr]> {
+        self.get_all(OptionCode::PcpServer)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|opt| match opt {
+                DhcpOption::PcpServer(addrs) => Some(addrs.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+    /// get the `StatusCode` option at this level, not looking inside any IA options.
+    /// A missing status code means `Success`
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.13>
+    pub fn status(&self) -> Option<&StatusCode> {
+        match self.get(OptionCode::StatusCode) {
+            Some(DhcpOption::StatusCode(status)) => Some(status),
+            _ => None,
+        }
+    }
+    /// Returns the first failed status code, checking this level first, then each
+    /// IA_NA, IA_TA & IA_PD, then the addresses & prefixes inside them. This is the order
+    /// a client handles them in, see
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.10>
+    pub fn succeeded(&self) -> Result<(), &StatusCode> {
+        fn check(opts: &DhcpOptions) -> Result<(), &StatusCode> {
+            match opts.status() {
+                Some(status) if !status.is_success() => Err(status),
+                _ => Ok(()),
+            }
+        }
+        check(self)?;
+        let ias = || {
+            self.iter().filter_map(|opt| match opt {
+                DhcpOption::IANA(ia) => Some(&ia.opts),
+                DhcpOption::IATA(ia) => Some(&ia.opts),
+                DhcpOption::IAPD(ia) => Some(&ia.opts),
+                _ => None,
+            })
+        };
+        ias().try_for_each(check)?;
+        ias()
+            .flat_map(|opts| opts.iter())
+            .try_for_each(|opt| match opt {
+                DhcpOption::IAAddr(addr) => check(&addr.opts),
+                DhcpOption::IAPrefix(prefix) => check(&prefix.opts),
+                _ => Ok(()),
+            })
+    }
+    /// Returns the first `IAAddr` or `IAPrefix` inside an IA_NA, IA_TA or IA_PD whose
+    /// preferred lifetime is greater than its valid lifetime. A client discards those,
+    /// see [`IAAddr::lifetimes_valid`]
+    pub fn check_lifetimes(&self) -> Result<(), &DhcpOption> {
+        self.iter()
+            .filter_map(|opt| match opt {
+                DhcpOption::IANA(ia) => Some(&ia.opts),
+                DhcpOption::IATA(ia) => Some(&ia.opts),
+                DhcpOption::IAPD(ia) => Some(&ia.opts),
+                _ => None,
+            })
+            .flat_map(|opts| opts.iter())
+            .try_for_each(|opt| match opt {
+                DhcpOption::IAAddr(addr) if !addr.lifetimes_valid() => Err(opt),
+                DhcpOption::IAPrefix(prefix) if !prefix.lifetimes_valid() => Err(opt),
+                _ => Ok(()),
+            })
+    }
 }
 
 impl IntoIterator for DhcpOptions {
@@ -100,7 +236,9 @@ impl FromIterator<DhcpOption> for DhcpOptions {
 
 /// DHCPv6 option types
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq)]
+// with `redact`, `Debug` is generated from the code table in `option_codes`
+#[cfg_attr(not(feature = "redact"), derive(Debug))]
+#[derive(Clone, PartialEq, Eq)]
 pub enum DhcpOption {
     /// 1 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.2>
     ClientId(Vec<u8>), // should duid for this be bytes or string?
@@ -136,31 +274,76 @@ pub enum DhcpOption {
     /// 17 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.17>
     VendorOpts(VendorOpts),
     /// 18 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.18>
-    InterfaceId(Vec<u8>),
+    InterfaceId(InterfaceId),
     /// 19 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.19>
     ReconfMsg(MessageType),
     /// 20 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.20>
     ReconfAccept,
+    /// 21 - <https://datatracker.ietf.org/doc/html/rfc3319#section-3.1>
+    SipServerD(Vec<Domain>),
+    /// 22 - <https://datatracker.ietf.org/doc/html/rfc3319#section-3.2>
+    SipServerA(Vec<Ipv6Addr>),
     /// 23 - <https://datatracker.ietf.org/doc/html/rfc3646>
     DomainNameServers(Vec<Ipv6Addr>),
     /// 24 - <https://datatracker.ietf.org/doc/html/rfc3646>
-    DomainSearchList(Vec<Name>),
+    DomainSearchList(Vec<Domain>),
     /// 25 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.21>
     IAPD(IAPD),
     /// 26 - <https://datatracker.ietf.org/doc/html/rfc3633#section-10>
     IAPrefix(IAPrefix),
     InformationRefreshTime(u32),
+    /// 37 - <https://datatracker.ietf.org/doc/html/rfc4649#section-3>
+    RemoteId(RemoteId),
+    /// 39 - <https://datatracker.ietf.org/doc/html/rfc4704#section-4>
+    ClientFqdn(ClientFqdn),
+    /// 41 - <https://datatracker.ietf.org/doc/html/rfc4833>
+    ///
+    /// POSIX timezone string, see [`tz::is_posix_tz`](crate::tz::is_posix_tz)
+    NewPosixTimezone(String),
+    /// 42 - <https://datatracker.ietf.org/doc/html/rfc4833>
+    ///
+    /// TZ database name, see [`tz::is_tzdb_name`](crate::tz::is_tzdb_name)
+    NewTzdbTimezone(String),
+    /// 44 - <https://datatracker.ietf.org/doc/html/rfc5007#section-4.1.2.1>
+    LqQuery(LqQuery),
+    /// 53 - <https://datatracker.ietf.org/doc/html/rfc5460#section-5.4.1>
+    ///
+    /// DUID of a relay agent
+    RelayId(Vec<u8>),
     /// 56 - <https://datatracker.ietf.org/doc/html/rfc5908>
-    NtpServer(Vec<NtpSuboption>),
-    // SolMaxRt(u32),
-    // InfMaxRt(u32),
-    // LqQuery(_),
+    NtpServer(SubOptions<u16, NtpSuboption>),
+    /// 59 - <https://datatracker.ietf.org/doc/html/rfc5970#section-3.1>
+    ///
+    /// URL of the network boot file, see [`Uri::is_boot_file_url`]
+    OptBootfileUrl(Uri),
+    /// 60 - <https://datatracker.ietf.org/doc/html/rfc5970#section-3.2>
+    ///
+    /// parameters for the boot file, e.g. a kernel command line
+    OptBootfileParam(Vec<String>),
+    /// 64 - <https://datatracker.ietf.org/doc/html/rfc6334#section-3>
+    ///
+    /// DS-Lite AFTR tunnel endpoint name
+    AftrName(Domain),
+    /// 79 - <https://datatracker.ietf.org/doc/html/rfc6939>
+    ClientLinklayerAddr(ClientLinklayerAddr),
+    /// 80 - <https://datatracker.ietf.org/doc/html/rfc6977#section-4.2>
+    LinkAddress(Ipv6Addr),
+    /// 82 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.24>
+    SolMaxRt(MaxRt),
+    /// 83 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.25>
+    InfMaxRt(MaxRt),
+    /// 86 - <https://datatracker.ietf.org/doc/html/rfc7291#section-3>
+    ///
+    /// addresses of one PCP server, the option is repeated for each server. v4 packs
+    /// every server into a single `PcpServers` option instead, use
+    /// [`DhcpOptions::pcp_servers`] to get the same per-server grouping
+    PcpServer(Vec<Ipv6Addr>),
+    /// 136 - <https://datatracker.ietf.org/doc/html/rfc8572#section-8.4>
+    SztpRedirect(Vec<Uri>),
     // ClientData(_),
     // CltTime(_),
     // LqRelayData(_),
     // LqClientLink(_),
-    // RelayId(_),
-    // LinkAddress(_),
     /// An unknown or unimplemented option type
     Unknown(UnknownOption),
 }
@@ -177,10 +360,143 @@ impl Ord for DhcpOption {
     }
 }
 
-/// wrapper around interface id
+impl DhcpOption {
+    /// A `DomainNameServers` of the first `max` distinct `servers` in order, see
+    /// [`dns_servers::clean`]
+    ///
+    /// ```rust
+    /// use dhcproto::{dns_servers, v6::DhcpOption};
+    ///
+    /// let a = "2001:db8::53".parse()?;
+    /// let opt = DhcpOption::dns_servers(&[a, a], dns_servers::DEFAULT_MAX)?;
+    /// assert_eq!(opt, DhcpOption::DomainNameServers(vec![a]));
+    /// assert!(DhcpOption::dns_servers(&["ff02::1".parse()?], 3).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn dns_servers(servers: &[Ipv6Addr], max: usize) -> Result<Self, DnsServerError> {
+        Ok(DhcpOption::DomainNameServers(dns_servers::clean(
+            servers, max,
+        )?))
+    }
+}
+
+/// interface id set by a relay agent, usually an interface name or VLAN string but
+/// any bytes are allowed
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.18>
+///
+/// ```rust
+/// use dhcproto::v6::InterfaceId;
+///
+/// let id = InterfaceId::from("Eth0/1.100");
+/// assert!(id.eq_ignore_case("eth0/1.100"));
+/// assert!(id.matches("eth0/*.1??"));
+/// assert_eq!(id.to_string(), "Eth0/1.100");
+/// assert_eq!(InterfaceId::from(vec![0, 1, 0xff]).to_string(), "00:01:ff");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct InterfaceId {
-    pub id: String,
+pub struct InterfaceId(Vec<u8>);
+
+impl InterfaceId {
+    /// the raw interface id
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// the interface id as a string, `None` if it isn't UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+
+    /// compare to `other`, ignoring ASCII case
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other.as_bytes())
+    }
+
+    /// match against a glob `pattern` ignoring ASCII case, `*` matches any run of bytes
+    /// and `?` matches any single byte
+    pub fn matches(&self, pattern: &str) -> bool {
+        let (id, pattern) = (&self.0, pattern.as_bytes());
+        let (mut i, mut p) = (0, 0);
+        // position of the last `*` in the pattern & the id byte it was matched against
+        let mut star = None;
+        while i < id.len() {
+            match pattern.get(p) {
+                Some(b'*') => {
+                    star = Some((p, i));
+                    p += 1;
+                }
+                Some(c) if *c == b'?' || c.eq_ignore_ascii_case(&id[i]) => {
+                    i += 1;
+                    p += 1;
+                }
+                // backtrack, letting the last `*` match one more byte
+                _ => match star {
+                    Some((star_p, star_i)) => {
+                        star = Some((star_p, star_i + 1));
+                        p = star_p + 1;
+                        i = star_i + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|c| *c == b'*')
+    }
+}
+
+/// Prints the id as a string if it is printable UTF-8, otherwise as colon separated hex
+impl fmt::Display for InterfaceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_str() {
+            Some(s) if !s.is_empty() && !s.chars().any(char::is_control) => f.write_str(s),
+            _ => {
+                for (i, b) in self.0.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(":")?;
+                    }
+                    write!(f, "{b:02x}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<&str> for InterfaceId {
+    fn from(id: &str) -> Self {
+        Self(id.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for InterfaceId {
+    fn from(id: String) -> Self {
+        Self(id.into_bytes())
+    }
+}
+
+impl From<&[u8]> for InterfaceId {
+    fn from(id: &[u8]) -> Self {
+        Self(id.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for InterfaceId {
+    fn from(id: Vec<u8>) -> Self {
+        Self(id)
+    }
+}
+
+impl From<InterfaceId> for Vec<u8> {
+    fn from(id: InterfaceId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<[u8]> for InterfaceId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
 }
 
 /// vendor options
@@ -188,10 +504,259 @@ pub struct InterfaceId {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VendorOpts {
     pub num: u32,
-    // encapsulated options values
+    // encapsulated vendor-specific sub-options, code/len are 2 bytes each
+    pub opts: SubOptions<u16, RawSubOption<u16>>,
+}
+
+impl VendorOpts {
+    /// the vendor's enterprise number
+    pub fn enterprise(&self) -> EnterpriseNumber {
+        self.num.into()
+    }
+}
+
+/// client link-layer address, added by relay agents in Relay-forward messages
+/// <https://datatracker.ietf.org/doc/html/rfc6939#section-4>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientLinklayerAddr {
+    /// hardware type, as assigned by IANA
+    pub htype: HType,
+    /// the client's link-layer address
+    pub addr: Vec<u8>,
+}
+
+impl ClientLinklayerAddr {
+    /// create a client link-layer address from an ethernet MAC
+    pub fn eth(mac: [u8; 6]) -> Self {
+        Self {
+            htype: HType::Eth,
+            addr: mac.to_vec(),
+        }
+    }
+}
+
+/// client FQDN, for servers that update DNS on behalf of clients
+/// <https://datatracker.ietf.org/doc/html/rfc4704#section-4>
+///
+/// The `N`, `O` & `S` flags mean the same as in the v4
+/// [`ClientFQDN`](crate::v4::fqdn::ClientFQDN), v6 has no `E` flag as the name is
+/// always in wire format; it's ignored when encoding and clear when decoding.
+///
+/// ```rust
+/// use dhcproto::{v4::fqdn::FqdnFlags, v6::{ClientFqdn, DhcpOption}, Encodable};
+///
+/// let fqdn = ClientFqdn::new(FqdnFlags::default().set_s(true), "host".parse()?).partial(true);
+/// let bytes = DhcpOption::ClientFqdn(fqdn).to_vec()?;
+/// // flags, then the name without its root label
+/// assert_eq!(bytes, [0, 39, 0, 6, 0x01, 4, b'h', b'o', b's', b't']);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientFqdn {
+    /// the `N`, `O` & `S` flags
+    pub flags: FqdnFlags,
+    /// the client's name, may be empty if the client lets the server pick one
+    pub domain: Domain,
+    /// `true` if `domain` is a partial name for the server to complete, sent without its
+    /// root label
+    pub is_partial: bool,
+}
+
+impl ClientFqdn {
+    /// a fully qualified name with `flags`
+    pub fn new(flags: FqdnFlags, domain: Domain) -> Self {
+        Self {
+            flags,
+            domain,
+            is_partial: false,
+        }
+    }
+
+    /// set whether the name is partial
+    pub fn partial(mut self, partial: bool) -> Self {
+        self.is_partial = partial;
+        self
+    }
+
+    // flags in v6 bit positions: N 0x04, O 0x02, S 0x01
+    fn flag_bits(&self) -> u8 {
+        let flags = self.flags;
+        (flags.n() as u8) << 2 | (flags.o() as u8) << 1 | flags.s() as u8
+    }
+
+    fn from_flag_bits(bits: u8) -> FqdnFlags {
+        FqdnFlags::default()
+            .set_n(bits & 0x04 != 0)
+            .set_o(bits & 0x02 != 0)
+            .set_s(bits & 0x01 != 0)
+    }
+}
+
+/// remote-id added by relay agents, identifying the remote host end of a circuit
+/// <https://datatracker.ietf.org/doc/html/rfc4649#section-3>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteId {
+    pub num: u32,
+    pub id: Vec<u8>,
+}
+
+impl RemoteId {
+    /// the vendor's enterprise number
+    pub fn enterprise(&self) -> EnterpriseNumber {
+        self.num.into()
+    }
+}
+
+/// Leasequery query types
+/// <https://datatracker.ietf.org/doc/html/rfc5007#section-4.1.2.1>
+/// <https://datatracker.ietf.org/doc/html/rfc5460#section-5.3>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LqQueryType {
+    /// 1 - bindings of the `IAAddr` in the query options
+    ByAddress,
+    /// 2 - bindings of the `ClientId` in the query options
+    ByClientId,
+    /// 3 - bindings received through the relay with the `RelayId` in the query options
+    ByRelayId,
+    /// 4 - bindings on the link of the query's `link_addr`
+    ByLinkAddress,
+    /// 5 - bindings with the `RemoteId` in the query options
+    ByRemoteId,
+    /// unknown query type
+    Unknown(u8),
+}
+
+impl From<u8> for LqQueryType {
+    fn from(n: u8) -> Self {
+        use LqQueryType as Q;
+        match n {
+            1 => Q::ByAddress,
+            2 => Q::ByClientId,
+            3 => Q::ByRelayId,
+            4 => Q::ByLinkAddress,
+            5 => Q::ByRemoteId,
+            n => Q::Unknown(n),
+        }
+    }
+}
+
+impl From<LqQueryType> for u8 {
+    fn from(query_type: LqQueryType) -> Self {
+        use LqQueryType as Q;
+        match query_type {
+            Q::ByAddress => 1,
+            Q::ByClientId => 2,
+            Q::ByRelayId => 3,
+            Q::ByLinkAddress => 4,
+            Q::ByRemoteId => 5,
+            Q::Unknown(n) => n,
+        }
+    }
+}
+
+/// Leasequery query, see [`lease_query`](crate::v6::lease_query) for building one
+/// <https://datatracker.ietf.org/doc/html/rfc5007#section-4.1.2.1>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LqQuery {
+    pub query_type: LqQueryType,
+    /// link the query is for, unspecified (`::`) for any link
+    pub link_addr: Ipv6Addr,
+    // 17 + opts.len()
     pub opts: DhcpOptions,
 }
 
+impl Decodable for LqQuery {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(LqQuery {
+            query_type: decoder.read_u8()?.into(),
+            link_addr: decoder.read::<16>()?.into(),
+            opts: DhcpOptions::decode(decoder)?,
+        })
+    }
+}
+
+/// value of SOL_MAX_RT or INF_MAX_RT, in seconds
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.24>
+///
+/// Valid values are in [`MaxRt::RANGE`], clients must ignore values outside of it.
+/// A lenient [`Decoder`] keeps invalid values so they can be checked with
+/// [`MaxRt::is_valid`], a strict one returns an error.
+///
+/// ```rust
+/// use dhcproto::v6::MaxRt;
+///
+/// // client is currently using the default SOL_MAX_RT of 3600
+/// let server = MaxRt::new(7200).unwrap();
+/// assert_eq!(server.apply(3600), 7200);
+/// assert!(MaxRt::new(30).is_none());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MaxRt(u32);
+
+impl MaxRt {
+    /// allowed range of values, in seconds
+    pub const RANGE: RangeInclusive<u32> = 60..=86400;
+
+    /// create a new `MaxRt`, returns `None` if `secs` is outside [`MaxRt::RANGE`]
+    pub fn new(secs: u32) -> Option<Self> {
+        Self::RANGE.contains(&secs).then_some(Self(secs))
+    }
+    /// create a `MaxRt` clamped to [`MaxRt::RANGE`]
+    pub fn saturating(secs: u32) -> Self {
+        Self(secs.clamp(*Self::RANGE.start(), *Self::RANGE.end()))
+    }
+    /// the value in seconds
+    pub fn secs(&self) -> u32 {
+        self.0
+    }
+    /// Returns `true` if the value is within [`MaxRt::RANGE`]
+    pub fn is_valid(&self) -> bool {
+        Self::RANGE.contains(&self.0)
+    }
+    /// Returns the value a client should use after receiving this one from a server,
+    /// given the value it is `current`ly using. Out of range values are ignored.
+    pub fn apply(&self, current: u32) -> u32 {
+        if self.is_valid() {
+            self.0
+        } else {
+            current
+        }
+    }
+}
+
+impl TryFrom<u32> for MaxRt {
+    type Error = super::DecodeError;
+
+    fn try_from(secs: u32) -> Result<Self, Self::Error> {
+        Self::new(secs).ok_or(super::DecodeError::invalid(
+            "SOL_MAX_RT/INF_MAX_RT must be in 60..=86400",
+        ))
+    }
+}
+
+impl From<MaxRt> for u32 {
+    fn from(max_rt: MaxRt) -> Self {
+        max_rt.0
+    }
+}
+
+impl Decodable for MaxRt {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let secs = decoder.read_u32()?;
+        if decoder.is_strict() {
+            secs.try_into()
+        } else {
+            Ok(Self(secs))
+        }
+    }
+}
+
 /// vendor class
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -201,6 +766,13 @@ pub struct VendorClass {
     // each item in data is [len (2 bytes) | data]
 }
 
+impl VendorClass {
+    /// the vendor's enterprise number
+    pub fn enterprise(&self) -> EnterpriseNumber {
+        self.num.into()
+    }
+}
+
 /// user class
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -223,6 +795,15 @@ fn decode_data(decoder: &'_ mut Decoder<'_>) -> Vec<Vec<u8>> {
     data
 }
 
+#[inline]
+fn encode_data(data: &[Vec<u8>], e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+    for s in data {
+        e.write_u16(opt_len(s.len())?)?;
+        e.write_slice(s)?;
+    }
+    Ok(())
+}
+
 /// Server Unicast
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -232,6 +813,13 @@ pub struct StatusCode {
     pub msg: String,
 }
 
+impl StatusCode {
+    /// `true` if the status is `Success`
+    pub fn is_success(&self) -> bool {
+        self.status == Status::Success
+    }
+}
+
 /// Status code for Server Unicast
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -263,6 +851,8 @@ pub enum Status {
     Unknown(u16),
 }
 
+display_unknown!(Status);
+
 impl From<u16> for Status {
     fn from(n: u16) -> Self {
         use Status::*;
@@ -327,8 +917,12 @@ impl From<Status> for u16 {
 }
 
 /// Authentication
+///
+/// With the `zeroize` feature, `Authentication` implements `Zeroize` so the key material
+/// in `info` can be cleared once it's no longer needed.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(not(feature = "redact"), derive(Debug))]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Authentication {
     pub proto: u8,
     pub algo: u8,
@@ -338,6 +932,40 @@ pub struct Authentication {
     pub info: Vec<u8>,
 }
 
+impl Authentication {
+    /// The MAC in the authentication information, compare it with
+    /// [`auth::ct_eq`](crate::auth::ct_eq). For RKAP that's the HMAC-MD5 digest after
+    /// the type byte, for other protocols the whole authentication information.
+    pub fn mac(&self) -> &[u8] {
+        if self.proto == crate::v6::reconfigure::RKAP_PROTOCOL {
+            self.info.get(1..).unwrap_or_default()
+        } else {
+            &self.info
+        }
+    }
+}
+
+#[cfg(feature = "redact")]
+impl std::fmt::Debug for Authentication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authentication")
+            .field("proto", &self.proto)
+            .field("algo", &self.algo)
+            .field("rdm", &self.rdm)
+            .field("replay_detection", &self.replay_detection)
+            .field("info", &crate::redact::Redacted::always(&self.info))
+            .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Authentication {
+    fn zeroize(&mut self) {
+        self.replay_detection.zeroize();
+        self.info.zeroize();
+    }
+}
+
 impl Decodable for Authentication {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         let len = decoder.buffer().len();
@@ -387,6 +1015,68 @@ pub struct IATA {
     pub opts: DhcpOptions,
 }
 
+impl IATA {
+    /// create an IA_TA with no options, as sent in a Solicit or Request
+    pub fn new(id: u32) -> Self {
+        IATA {
+            id,
+            opts: DhcpOptions::new(),
+        }
+    }
+    /// Add an `IAAddr` for `addr` as a hint of the address the client would like, with
+    /// zero lifetimes so the server picks them.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.1>
+    ///
+    /// ```rust
+    /// use dhcproto::v6::{self, DhcpOption, IATA};
+    ///
+    /// let mut solicit = v6::Message::new(v6::MessageType::Solicit);
+    /// let ia = IATA::new(1).hint("2001:db8::1".parse().unwrap());
+    /// solicit.opts_mut().insert(DhcpOption::IATA(ia));
+    /// let ia = solicit.iata().next().unwrap();
+    /// assert_eq!(ia.addrs().count(), 1);
+    /// ```
+    pub fn hint(mut self, addr: Ipv6Addr) -> Self {
+        self.opts.insert(DhcpOption::IAAddr(IAAddr {
+            addr,
+            preferred_life: LeaseTime::default(),
+            valid_life: LeaseTime::default(),
+            opts: DhcpOptions::new(),
+        }));
+        self
+    }
+    /// get the `StatusCode` option for this IA, see [`DhcpOptions::status`]
+    pub fn status(&self) -> Option<&StatusCode> {
+        self.opts.status()
+    }
+    /// iterate over the temporary addresses in this IA
+    pub fn addrs(&self) -> impl Iterator<Item = &IAAddr> {
+        ia_addrs(&self.opts)
+    }
+    /// the longest preferred lifetime of the addresses, `None` if there are none
+    pub fn preferred_life(&self) -> Option<LeaseTime> {
+        self.addrs().map(|addr| addr.preferred_life).max()
+    }
+    /// The longest valid lifetime of the addresses, `None` if there are none. IA_TA has
+    /// no T1/T2, the client gets new temporary addresses once these expire.
+    ///
+    /// ```rust
+    /// use dhcproto::{time::LeaseTime, v6::{DhcpOption, IAAddr, IATA}};
+    ///
+    /// let mut ia = IATA::new(1);
+    /// for (addr, valid) in [("2001:db8::1", 600), ("2001:db8::2", 3600)] {
+    ///     let addr = addr.parse().unwrap();
+    ///     let addr = IAAddr::new(addr, LeaseTime::new(300), LeaseTime::new(valid)).unwrap();
+    ///     ia.opts.insert(DhcpOption::IAAddr(addr));
+    /// }
+    /// assert_eq!(ia.preferred_life(), Some(LeaseTime::new(300)));
+    /// assert_eq!(ia.valid_life(), Some(LeaseTime::new(3600)));
+    /// ```
+    pub fn valid_life(&self) -> Option<LeaseTime> {
+        self.addrs().map(|addr| addr.valid_life).max()
+    }
+}
+
 impl Decodable for IATA {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IATA {
@@ -407,6 +1097,32 @@ pub struct IANA {
     pub opts: DhcpOptions,
 }
 
+impl IANA {
+    /// get the `StatusCode` option for this IA, see [`DhcpOptions::status`]
+    pub fn status(&self) -> Option<&StatusCode> {
+        self.opts.status()
+    }
+    /// iterate over the addresses in this IA
+    pub fn addrs(&self) -> impl Iterator<Item = &IAAddr> {
+        ia_addrs(&self.opts)
+    }
+    /// the longest preferred lifetime of the addresses, `None` if there are none
+    pub fn preferred_life(&self) -> Option<LeaseTime> {
+        self.addrs().map(|addr| addr.preferred_life).max()
+    }
+    /// the longest valid lifetime of the addresses, `None` if there are none
+    pub fn valid_life(&self) -> Option<LeaseTime> {
+        self.addrs().map(|addr| addr.valid_life).max()
+    }
+}
+
+fn ia_addrs(opts: &DhcpOptions) -> impl Iterator<Item = &IAAddr> {
+    opts.iter().filter_map(|opt| match opt {
+        DhcpOption::IAAddr(addr) => Some(addr),
+        _ => None,
+    })
+}
+
 impl Decodable for IANA {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IANA {
@@ -429,6 +1145,13 @@ pub struct IAPD {
     pub opts: DhcpOptions,
 }
 
+impl IAPD {
+    /// get the `StatusCode` option for this IA, see [`DhcpOptions::status`]
+    pub fn status(&self) -> Option<&StatusCode> {
+        self.opts.status()
+    }
+}
+
 impl Decodable for IAPD {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IAPD {
@@ -441,50 +1164,124 @@ impl Decodable for IAPD {
 }
 
 /// Identity Association Prefix Delegation Prefix Option
+///
+/// The preferred lifetime must not be greater than the valid lifetime, clients discard
+/// prefixes where it is. A lenient [`Decoder`] keeps them so they can be checked with
+/// [`IAPrefix::lifetimes_valid`], a strict one returns an error.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IAPrefix {
-    pub preferred_lifetime: u32,
-    pub valid_lifetime: u32,
+    pub preferred_lifetime: LeaseTime,
+    pub valid_lifetime: LeaseTime,
     pub prefix_len: u8,
     pub prefix_ip: Ipv6Addr,
     // 25 + opts.len()
     pub opts: DhcpOptions,
 }
 
+impl IAPrefix {
+    /// create a new prefix with no options, returns `None` if `preferred_lifetime` is
+    /// greater than `valid_lifetime`
+    pub fn new(
+        prefix_ip: Ipv6Addr,
+        prefix_len: u8,
+        preferred_lifetime: LeaseTime,
+        valid_lifetime: LeaseTime,
+    ) -> Option<Self> {
+        let prefix = IAPrefix {
+            preferred_lifetime,
+            valid_lifetime,
+            prefix_len,
+            prefix_ip,
+            opts: DhcpOptions::new(),
+        };
+        prefix.lifetimes_valid().then_some(prefix)
+    }
+    /// get the `StatusCode` option for this prefix, see [`DhcpOptions::status`]
+    pub fn status(&self) -> Option<&StatusCode> {
+        self.opts.status()
+    }
+    /// Returns `true` if the preferred lifetime isn't greater than the valid lifetime
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.22>
+    pub fn lifetimes_valid(&self) -> bool {
+        self.preferred_lifetime <= self.valid_lifetime
+    }
+}
+
 impl Decodable for IAPrefix {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
-        Ok(IAPrefix {
-            preferred_lifetime: decoder.read_u32()?,
-            valid_lifetime: decoder.read_u32()?,
+        let prefix = IAPrefix {
+            preferred_lifetime: decoder.read_u32()?.into(),
+            valid_lifetime: decoder.read_u32()?.into(),
             prefix_len: decoder.read_u8()?,
             prefix_ip: decoder.read::<16>()?.into(),
             opts: DhcpOptions::decode(decoder)?,
-        })
+        };
+        check_lifetimes(decoder, prefix.lifetimes_valid())?;
+        Ok(prefix)
     }
 }
 
+// a strict decoder rejects a preferred lifetime greater than the valid lifetime
+fn check_lifetimes(decoder: &Decoder<'_>, valid: bool) -> DecodeResult<()> {
+    if decoder.is_strict() && !valid {
+        return Err(super::DecodeError::invalid(
+            "preferred lifetime is greater than valid lifetime",
+        ));
+    }
+    Ok(())
+}
+
 /// Identity Association Address
+///
+/// The preferred lifetime must not be greater than the valid lifetime, clients discard
+/// addresses where it is. A lenient [`Decoder`] keeps them so they can be checked with
+/// [`IAAddr::lifetimes_valid`], a strict one returns an error.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IAAddr {
     pub addr: Ipv6Addr,
-    pub preferred_life: u32,
-    pub valid_life: u32,
+    pub preferred_life: LeaseTime,
+    pub valid_life: LeaseTime,
     // 24 + opts.len()
     // should this be DhcpOptions ?
     // the RFC suggests it 'encapsulates options'
     pub opts: DhcpOptions,
 }
 
+impl IAAddr {
+    /// create a new address with no options, returns `None` if `preferred_life` is
+    /// greater than `valid_life`
+    pub fn new(addr: Ipv6Addr, preferred_life: LeaseTime, valid_life: LeaseTime) -> Option<Self> {
+        let ia_addr = IAAddr {
+            addr,
+            preferred_life,
+            valid_life,
+            opts: DhcpOptions::new(),
+        };
+        ia_addr.lifetimes_valid().then_some(ia_addr)
+    }
+    /// get the `StatusCode` option for this address, see [`DhcpOptions::status`]
+    pub fn status(&self) -> Option<&StatusCode> {
+        self.opts.status()
+    }
+    /// Returns `true` if the preferred lifetime isn't greater than the valid lifetime
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.6>
+    pub fn lifetimes_valid(&self) -> bool {
+        self.preferred_life <= self.valid_life
+    }
+}
+
 impl Decodable for IAAddr {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
-        Ok(IAAddr {
+        let ia_addr = IAAddr {
             addr: decoder.read::<16>()?.into(),
-            preferred_life: decoder.read_u32()?,
-            valid_life: decoder.read_u32()?,
+            preferred_life: decoder.read_u32()?.into(),
+            valid_life: decoder.read_u32()?.into(),
             opts: DhcpOptions::decode(decoder)?,
-        })
+        };
+        check_lifetimes(decoder, ia_addr.lifetimes_valid())?;
+        Ok(ia_addr)
     }
 }
 
@@ -493,17 +1290,31 @@ impl Decodable for IAAddr {
 pub enum NtpSuboption {
     ServerAddress(Ipv6Addr),
     MulticastAddress(Ipv6Addr),
-    FQDN(Name),
+    FQDN(Domain),
+    /// unrecognized suboption, preserved as-is
+    Unknown(RawSubOption<u16>),
 }
 
-impl Decodable for NtpSuboption {
-    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
-        let code = decoder.read_u16()?;
+impl SubOption<u16> for NtpSuboption {
+    fn code(&self) -> u16 {
+        match self {
+            NtpSuboption::ServerAddress(addr) | NtpSuboption::MulticastAddress(addr) => {
+                if addr.is_multicast() {
+                    2
+                } else {
+                    1
+                }
+            }
+            NtpSuboption::FQDN(_) => 3,
+            NtpSuboption::Unknown(opt) => opt.code(),
+        }
+    }
+
+    fn decode_value(code: u16, decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         match code {
             1 | 2 => {
-                let len = decoder.read_u16()?;
-                if len != 16 {
-                    return Err(super::DecodeError::NotEnoughBytes);
+                if decoder.buffer().len() != 16 {
+                    return Err(super::DecodeError::Truncated);
                 }
                 let addr: Ipv6Addr = decoder.read::<16>()?.into();
                 let option = if addr.is_multicast() {
@@ -513,38 +1324,37 @@ impl Decodable for NtpSuboption {
                 };
                 Ok(option)
             }
-            3 => {
-                let len = decoder.read_u16()? as _;
-                let mut name_decoder = BinDecoder::new(decoder.read_slice(len)?);
-                Ok(NtpSuboption::FQDN(Name::read(&mut name_decoder)?))
-            }
-            _ => Err(super::DecodeError::IoError(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "invalid ntp suboption code",
-            ))),
+            3 => Ok(NtpSuboption::FQDN(Domain::read(decoder.buffer(), &mut 0)?)),
+            _ => Ok(NtpSuboption::Unknown(RawSubOption::decode_value(
+                code, decoder,
+            )?)),
         }
     }
-}
 
-impl Encodable for NtpSuboption {
-    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+    fn encode_value(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
         match self {
             NtpSuboption::ServerAddress(addr) | NtpSuboption::MulticastAddress(addr) => {
-                let code = if addr.is_multicast() { 2 } else { 1 };
-                e.write_u16(code)?;
-                e.write_u16(16)?;
-                e.write::<16>(addr.octets())?;
+                e.write::<16>(addr.octets())
             }
             NtpSuboption::FQDN(name) => {
                 let mut buf = Vec::new();
-                let mut name_encoder = BinEncoder::new(&mut buf);
-                name.emit(&mut name_encoder)?;
-                e.write_u16(3)?;
-                e.write_u16(buf.len() as _)?;
-                e.write_slice(&buf)?;
+                DomainWriter::new(&mut buf, true).write(name);
+                e.write_slice(&buf)
             }
+            NtpSuboption::Unknown(opt) => opt.encode_value(e),
         }
-        Ok(())
+    }
+}
+
+impl Decodable for NtpSuboption {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        decode_sub_option(decoder)
+    }
+}
+
+impl Encodable for NtpSuboption {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        encode_sub_option(self, e)
     }
 }
 
@@ -557,16 +1367,25 @@ pub struct UnknownOption {
 }
 
 impl UnknownOption {
+    /// create an option with `code` and undecoded `data`
     pub fn new(code: OptionCode, data: Vec<u8>) -> Self {
         Self {
             code: code.into(),
             data,
         }
     }
+    /// create an option from a raw option code, e.g. one this library doesn't know
+    pub fn from_raw(code: u16, data: Vec<u8>) -> Self {
+        Self { code, data }
+    }
     /// return the option code
     pub fn code(&self) -> OptionCode {
         self.code.into()
     }
+    /// return the raw option code
+    pub fn raw_code(&self) -> u16 {
+        self.code
+    }
     /// return the data for this option
     pub fn data(&self) -> &[u8] {
         &self.data
@@ -579,14 +1398,129 @@ impl UnknownOption {
 
 impl Decodable for DhcpOptions {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
-        let mut opts = Vec::new();
-        while let Ok(opt) = DhcpOption::decode(decoder) {
-            opts.push(opt);
+        // IAs & relayed messages decode their options through here, so this is where
+        // nesting is limited
+        decoder.nested(decode_opts)
+    }
+}
+
+fn decode_opts(decoder: &mut Decoder<'_>) -> DecodeResult<DhcpOptions> {
+    let mut opts = Vec::new();
+    while !decoder.buffer().is_empty() {
+        if decoder.is_recovering() && !decoder.is_strict() {
+            // decode each option from its own buffer so a malformed one can be
+            // skipped
+            let offset = decoder.offset();
+            let len = match decoder.buffer() {
+                [_, _, hi, lo, ..] => 4 + u16::from_be_bytes([*hi, *lo]) as usize,
+                _ => usize::MAX,
+            };
+            match decoder.sub_decoder(len) {
+                Ok(mut dec) => match DhcpOption::decode(&mut dec) {
+                    Ok(opt) => {
+                        observe(decoder, &opt);
+                        opts.push(opt);
+                    }
+                    Err(err) => {
+                        decoder.skipped(offset, err);
+                    }
+                },
+                Err(err) => {
+                    decoder.skipped(offset, err);
+                    break;
+                }
+            }
+            continue;
+        }
+        match DhcpOption::decode(decoder) {
+            Ok(opt) => {
+                observe(decoder, &opt);
+                opts.push(opt);
+            }
+            Err(err) => {
+                decoder.stopped(&err);
+                if decoder.is_strict() {
+                    return Err(err);
+                }
+                break;
+            }
         }
-        // sorts by OptionCode
-        opts.sort_unstable();
-        Ok(DhcpOptions(opts))
     }
+    // sorts by OptionCode
+    opts.sort_unstable();
+    Ok(DhcpOptions(opts))
+}
+
+// report a decoded option to the decoder's observer, if it has one
+fn observe(decoder: &Decoder<'_>, opt: &DhcpOption) {
+    if let Some(observer) = decoder.observed() {
+        let code = ObservedCode::V6(opt.into());
+        match opt {
+            DhcpOption::Unknown(_) => observer.on_unknown(code),
+            _ => observer.on_option(code),
+        }
+    }
+}
+
+/// Iterate over the options in `buf` as `(code, data)` without decoding them into
+/// [`DhcpOptions`], for triaging large messages or pulling the `RelayMsg` out of a
+/// relayed message without allocating. `buf` is the options area, after the 4 byte
+/// header of a message or the 34 byte header of a relay message.
+///
+/// An option whose length runs past the end of `buf` yields
+/// [`DecodeError::Truncated`](crate::error::DecodeError::Truncated) and ends the
+/// iteration. Options nested in IA containers are walked with [`iter_raw_nested`].
+///
+/// ```
+/// use dhcproto::{v6::{self, iter_raw, OptionCode}, Encodable};
+///
+/// let mut msg = v6::Message::new(v6::MessageType::Solicit);
+/// msg.opts_mut().insert(v6::DhcpOption::ClientId(vec![1, 2, 3]));
+/// let buf = msg.to_vec()?;
+///
+/// let client_id = iter_raw(&buf[4..])
+///     .filter_map(Result::ok)
+///     .find(|(code, _)| *code == u16::from(OptionCode::ClientId));
+/// assert_eq!(client_id, Some((1, &[1, 2, 3][..])));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn iter_raw(buf: &[u8]) -> impl Iterator<Item = DecodeResult<(u16, &[u8])>> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let header = buf.get(pos..)?;
+        if header.is_empty() {
+            return None;
+        }
+        let data = header.get(..4).and_then(|header| {
+            let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+            buf.get(pos + 4..pos + 4 + len)
+        });
+        let Some(data) = data else {
+            pos = buf.len();
+            return Some(Err(crate::error::DecodeError::Truncated));
+        };
+        let code = u16::from_be_bytes([buf[pos], buf[pos + 1]]);
+        pos += 4 + data.len();
+        Some(Ok((code, data)))
+    })
+}
+
+/// Iterate over the options nested in the `data` of an IA_NA, IA_TA, IA_PD, IA Address
+/// or IA Prefix option with `code`, as yielded by [`iter_raw`]. `None` if `code` isn't
+/// one of those or `data` is shorter than its fixed fields.
+pub fn iter_raw_nested(
+    code: OptionCode,
+    data: &[u8],
+) -> Option<impl Iterator<Item = DecodeResult<(u16, &[u8])>>> {
+    // length of the fields before the options
+    let fixed = match code {
+        OptionCode::IATA => 4,
+        OptionCode::IANA | OptionCode::IAPD => 12,
+        OptionCode::IAAddr => 24,
+        OptionCode::IAPrefix => 25,
+        _ => return None,
+    };
+    data.get(fixed..).map(iter_raw)
 }
 
 impl Encodable for DhcpOptions {
@@ -597,362 +1531,368 @@ impl Encodable for DhcpOptions {
 
 impl Decodable for DhcpOption {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
-        let code = decoder.read_u16()?.into();
+        let code = decoder.read_u16()?;
         let len = decoder.read_u16()? as usize;
-        Ok(match code {
-            OptionCode::ClientId => DhcpOption::ClientId(decoder.read_slice(len)?.to_vec()),
-            OptionCode::ServerId => DhcpOption::ServerId(decoder.read_slice(len)?.to_vec()),
-            OptionCode::IANA => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IANA(IANA::decode(&mut dec)?)
-            }
-            OptionCode::IATA => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IATA(IATA::decode(&mut dec)?)
-            }
-            OptionCode::IAAddr => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IAAddr(IAAddr::decode(&mut dec)?)
-            }
-            OptionCode::ORO => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::ORO(ORO::decode(&mut dec)?)
-            }
-            OptionCode::Preference => DhcpOption::Preference(decoder.read_u8()?),
-            OptionCode::ElapsedTime => DhcpOption::ElapsedTime(decoder.read_u16()?),
-            OptionCode::RelayMsg => {
-                let mut relay_dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::RelayMsg(RelayMessage::decode(&mut relay_dec)?)
-            }
-            OptionCode::Authentication => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::Authentication(Authentication::decode(&mut dec)?)
-            }
-            OptionCode::ServerUnicast => DhcpOption::ServerUnicast(decoder.read::<16>()?.into()),
-            OptionCode::StatusCode => DhcpOption::StatusCode(StatusCode {
-                status: decoder.read_u16()?.into(),
-                msg: decoder.read_string(len - std::mem::size_of::<u16>())?,
-            }),
-            OptionCode::RapidCommit => DhcpOption::RapidCommit,
-            OptionCode::UserClass => {
-                let buf = decoder.read_slice(len)?;
-                DhcpOption::UserClass(UserClass {
-                    data: decode_data(&mut Decoder::new(buf)),
-                })
-            }
-            OptionCode::VendorClass => {
-                let num = decoder.read_u32()?;
-                let buf = decoder.read_slice(len - 4)?;
-                DhcpOption::VendorClass(VendorClass {
-                    num,
-                    data: decode_data(&mut Decoder::new(buf)),
-                })
-            }
-            OptionCode::VendorOpts => DhcpOption::VendorOpts(VendorOpts {
-                num: decoder.read_u32()?,
-                opts: {
-                    let mut opt_decoder = Decoder::new(decoder.read_slice(len - 4)?);
-                    DhcpOptions::decode(&mut opt_decoder)?
-                },
-            }),
-            OptionCode::InterfaceId => DhcpOption::InterfaceId(decoder.read_slice(len)?.to_vec()),
-            OptionCode::ReconfMsg => DhcpOption::ReconfMsg(decoder.read_u8()?.into()),
-            OptionCode::ReconfAccept => DhcpOption::ReconfAccept,
-            OptionCode::DomainNameServers => {
-                DhcpOption::DomainNameServers(decoder.read_ipv6s(len)?)
-            }
-            OptionCode::IAPD => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IAPD(IAPD::decode(&mut dec)?)
-            }
-            OptionCode::IAPrefix => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IAPrefix(IAPrefix::decode(&mut dec)?)
-            }
-            OptionCode::DomainSearchList => {
-                let mut name_decoder = BinDecoder::new(decoder.read_slice(len)?);
-                let mut names = Vec::new();
-                while let Ok(name) = Name::read(&mut name_decoder) {
-                    names.push(name);
-                }
+        decode_value(code.into(), len, decoder).map_err(|err| err.with_code(code))
+    }
+}
 
-                DhcpOption::DomainSearchList(names)
+// decodes the value of an option, `len` bytes of `decoder`
+fn decode_value(
+    code: OptionCode,
+    len: usize,
+    decoder: &mut Decoder<'_>,
+) -> DecodeResult<DhcpOption> {
+    Ok(match code {
+        OptionCode::ClientId => DhcpOption::ClientId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::ServerId => DhcpOption::ServerId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::IANA => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::IANA(IANA::decode(&mut dec)?)
+        }
+        OptionCode::IATA => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::IATA(IATA::decode(&mut dec)?)
+        }
+        OptionCode::IAAddr => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::IAAddr(IAAddr::decode(&mut dec)?)
+        }
+        OptionCode::ORO => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::ORO(ORO::decode(&mut dec)?)
+        }
+        OptionCode::Preference => DhcpOption::Preference(decoder.read_u8()?),
+        OptionCode::ElapsedTime => DhcpOption::ElapsedTime(decoder.read_u16()?),
+        OptionCode::RelayMsg => {
+            let mut relay_dec = decoder.sub_decoder(len)?;
+            DhcpOption::RelayMsg(RelayMessage::decode(&mut relay_dec)?)
+        }
+        OptionCode::Authentication => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::Authentication(Authentication::decode(&mut dec)?)
+        }
+        OptionCode::ServerUnicast => DhcpOption::ServerUnicast(decoder.read::<16>()?.into()),
+        OptionCode::StatusCode => DhcpOption::StatusCode(StatusCode {
+            status: decoder.read_u16()?.into(),
+            msg: decoder.read_string(len - std::mem::size_of::<u16>())?,
+        }),
+        OptionCode::RapidCommit => DhcpOption::RapidCommit,
+        OptionCode::UserClass => {
+            let buf = decoder.read_slice(len)?;
+            DhcpOption::UserClass(UserClass {
+                data: decode_data(&mut Decoder::new(buf)),
+            })
+        }
+        OptionCode::VendorClass => {
+            let num = decoder.read_u32()?;
+            let buf = decoder.read_slice(len - 4)?;
+            DhcpOption::VendorClass(VendorClass {
+                num,
+                data: decode_data(&mut Decoder::new(buf)),
+            })
+        }
+        OptionCode::VendorOpts => DhcpOption::VendorOpts(VendorOpts {
+            num: decoder.read_u32()?,
+            opts: {
+                let mut opt_decoder = decoder.sub_decoder(len - 4)?;
+                SubOptions::decode(&mut opt_decoder)?
+            },
+        }),
+        OptionCode::InterfaceId => DhcpOption::InterfaceId(decoder.read_slice(len)?.into()),
+        OptionCode::ReconfMsg => DhcpOption::ReconfMsg(decoder.read_u8()?.into()),
+        OptionCode::ReconfAccept => DhcpOption::ReconfAccept,
+        OptionCode::SipServerD => DhcpOption::SipServerD(decoder.read_domains(len)?),
+        OptionCode::SipServerA => DhcpOption::SipServerA(decoder.read_ipv6s(len)?),
+        OptionCode::DomainNameServers => DhcpOption::DomainNameServers(decoder.read_ipv6s(len)?),
+        OptionCode::PcpServer => DhcpOption::PcpServer(decoder.read_ipv6s(len)?),
+        OptionCode::IAPD => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::IAPD(IAPD::decode(&mut dec)?)
+        }
+        OptionCode::IAPrefix => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::IAPrefix(IAPrefix::decode(&mut dec)?)
+        }
+        OptionCode::DomainSearchList => DhcpOption::DomainSearchList(decoder.read_domains(len)?),
+        OptionCode::SolMaxRt => DhcpOption::SolMaxRt(MaxRt::decode(decoder)?),
+        OptionCode::InfMaxRt => DhcpOption::InfMaxRt(MaxRt::decode(decoder)?),
+        OptionCode::NewPosixTimezone => DhcpOption::NewPosixTimezone(decoder.read_string(len)?),
+        OptionCode::NewTzdbTimezone => DhcpOption::NewTzdbTimezone(decoder.read_string(len)?),
+        OptionCode::NtpServer => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::NtpServer(SubOptions::decode(&mut dec)?)
+        }
+        OptionCode::OptBootfileUrl => DhcpOption::OptBootfileUrl(decoder.read_str(len)?.parse()?),
+        OptionCode::OptBootfileParam => {
+            let mut dec = decoder.sub_decoder(len)?;
+            let mut params = Vec::new();
+            while !dec.buffer().is_empty() {
+                let len = dec.read_u16()? as usize;
+                params.push(dec.read_string(len)?);
             }
-            OptionCode::NtpServer => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                let mut suboptions = Vec::new();
-                while !dec.buffer().is_empty() {
-                    suboptions.push(NtpSuboption::decode(&mut dec)?);
+            DhcpOption::OptBootfileParam(params)
+        }
+        OptionCode::AftrName => {
+            DhcpOption::AftrName(Domain::read(decoder.read_slice(len)?, &mut 0)?)
+        }
+        OptionCode::ClientFqdn => {
+            let mut dec = decoder.sub_decoder(len)?;
+            let flags = ClientFqdn::from_flag_bits(dec.read_u8()?);
+            let name = dec.buffer();
+            // a partial name has no root label, it may be empty
+            let mut pos = 0;
+            let (domain, is_partial) = match Domain::read(name, &mut pos) {
+                Ok(domain) if pos == name.len() => (domain, false),
+                _ => {
+                    let mut full = name.to_vec();
+                    full.push(0);
+                    (Domain::read(&full, &mut 0)?, true)
                 }
-
-                DhcpOption::NtpServer(suboptions)
-            }
-            // not yet implemented
-            OptionCode::Unknown(code) => DhcpOption::Unknown(UnknownOption {
-                code,
-                data: decoder.read_slice(len)?.to_vec(),
-            }),
-            _ => DhcpOption::Unknown(UnknownOption {
-                code: code.into(),
-                data: decoder.read_slice(len)?.to_vec(),
-            }),
-        })
-    }
+            };
+            DhcpOption::ClientFqdn(ClientFqdn {
+                flags,
+                domain,
+                is_partial,
+            })
+        }
+        OptionCode::ClientLinklayerAddr => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr {
+                htype: dec.read_u16()?.into(),
+                addr: dec.buffer().to_vec(),
+            })
+        }
+        OptionCode::SztpRedirect => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::SztpRedirect(sztp::read_uris(&mut dec)?)
+        }
+        OptionCode::RemoteId => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::RemoteId(RemoteId {
+                num: dec.read_u32()?,
+                id: dec.buffer().to_vec(),
+            })
+        }
+        OptionCode::LqQuery => {
+            let mut dec = decoder.sub_decoder(len)?;
+            DhcpOption::LqQuery(LqQuery::decode(&mut dec)?)
+        }
+        OptionCode::RelayId => DhcpOption::RelayId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::LinkAddress => DhcpOption::LinkAddress(decoder.read::<16>()?.into()),
+        // not yet implemented
+        _ => DhcpOption::Unknown(UnknownOption {
+            code: code.into(),
+            data: decoder.read_slice(len)?.to_vec(),
+        }),
+    })
 }
+
 impl Encodable for DhcpOption {
     fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
         let code: OptionCode = self.into();
-        e.write_u16(code.into())?;
-        match self {
-            DhcpOption::ClientId(duid) | DhcpOption::ServerId(duid) => {
-                e.write_u16(duid.len() as u16)?;
-                e.write_slice(duid)?;
-            }
-            DhcpOption::IANA(IANA { id, t1, t2, opts })
-            | DhcpOption::IAPD(IAPD { id, t1, t2, opts }) => {
-                // write len
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(12 + buf.len() as u16)?;
-                // write data
-                e.write_u32(*id)?;
-                e.write_u32(*t1)?;
-                e.write_u32(*t2)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::IATA(IATA { id, opts }) => {
-                // write len
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(4 + buf.len() as u16)?;
-                // data
-                e.write_u32(*id)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::IAAddr(IAAddr {
-                addr,
-                preferred_life,
-                valid_life,
-                opts,
-            }) => {
-                // write len
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(24 + buf.len() as u16)?;
-                // data
-                e.write_u128((*addr).into())?;
-                e.write_u32(*preferred_life)?;
-                e.write_u32(*valid_life)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::ORO(ORO { opts }) => {
-                // write len
-                e.write_u16(2 * opts.len() as u16)?;
-                // data
-                for code in opts {
-                    e.write_u16(u16::from(*code))?;
+        e.write_opt_v6(code.into(), |e| {
+            match self {
+                DhcpOption::ClientId(duid) | DhcpOption::ServerId(duid) => {
+                    e.write_slice(duid)?;
                 }
-            }
-            DhcpOption::Preference(pref) => {
-                e.write_u16(1)?;
-                e.write_u8(*pref)?;
-            }
-            DhcpOption::ElapsedTime(elapsed) => {
-                e.write_u16(2)?;
-                e.write_u16(*elapsed)?;
-            }
-            DhcpOption::RelayMsg(msg) => {
-                let mut buf = Vec::new();
-                let mut relay_enc = Encoder::new(&mut buf);
-                msg.encode(&mut relay_enc)?;
-
-                e.write_u16(buf.len() as u16)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::Authentication(Authentication {
-                proto,
-                algo,
-                rdm,
-                replay_detection,
-                info,
-            }) => {
-                e.write_u16(11 + info.len() as u16)?;
-                e.write_u8(*proto)?;
-                e.write_u8(*algo)?;
-                e.write_u8(*rdm)?;
-                e.write_u64(*replay_detection)?;
-                e.write_slice(info)?;
-            }
-            DhcpOption::ServerUnicast(addr) => {
-                e.write_u16(16)?;
-                e.write_u128((*addr).into())?;
-            }
-            DhcpOption::StatusCode(StatusCode { status, msg }) => {
-                e.write_u16(2 + msg.len() as u16)?;
-                e.write_u16((*status).into())?;
-                e.write_slice(msg.as_bytes())?;
-            }
-            DhcpOption::RapidCommit => {
-                e.write_u16(0)?;
-            }
-            DhcpOption::UserClass(UserClass { data }) => {
-                e.write_u16(data.len() as u16)?;
-                for s in data {
-                    e.write_u16(s.len() as u16)?;
-                    e.write_slice(s)?;
+                DhcpOption::IANA(IANA { id, t1, t2, opts })
+                | DhcpOption::IAPD(IAPD { id, t1, t2, opts }) => {
+                    e.write_u32(*id)?;
+                    e.write_u32(*t1)?;
+                    e.write_u32(*t2)?;
+                    opts.encode(e)?;
                 }
-            }
-            DhcpOption::VendorClass(VendorClass { num, data }) => {
-                e.write_u16(4 + data.len() as u16)?;
-                e.write_u32(*num)?;
-                for s in data {
-                    e.write_u16(s.len() as u16)?;
-                    e.write_slice(s)?;
+                DhcpOption::IATA(IATA { id, opts }) => {
+                    e.write_u32(*id)?;
+                    opts.encode(e)?;
                 }
-            }
-            DhcpOption::VendorOpts(VendorOpts { num, opts }) => {
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(4 + buf.len() as u16)?;
-                e.write_u32(*num)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::InterfaceId(id) => {
-                e.write_u16(id.len() as u16)?;
-                e.write_slice(id)?;
-            }
-            DhcpOption::ReconfMsg(msg_type) => {
-                e.write_u16(1)?;
-                e.write_u8((*msg_type).into())?;
-            }
-            DhcpOption::ReconfAccept => {
-                e.write_u16(0)?;
-            }
-            DhcpOption::DomainNameServers(addrs) => {
-                e.write_u16(addrs.len() as u16 * 16)?;
-                for addr in addrs {
+                DhcpOption::IAAddr(IAAddr {
+                    addr,
+                    preferred_life,
+                    valid_life,
+                    opts,
+                }) => {
                     e.write_u128((*addr).into())?;
+                    e.write_u32(preferred_life.secs())?;
+                    e.write_u32(valid_life.secs())?;
+                    opts.encode(e)?;
                 }
-            }
-            DhcpOption::DomainSearchList(names) => {
-                let mut buf = Vec::new();
-                let mut name_encoder = BinEncoder::new(&mut buf);
-                for name in names {
-                    name.emit(&mut name_encoder)?;
+                DhcpOption::ORO(ORO { opts }) => {
+                    for code in opts {
+                        e.write_u16(u16::from(*code))?;
+                    }
                 }
-                e.write_u16(buf.len() as u16)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::IAPrefix(IAPrefix {
-                preferred_lifetime,
-                valid_lifetime,
-                prefix_len,
-                prefix_ip,
-                opts,
-            }) => {
-                let mut buf = Vec::new();
-                let mut opt_enc = Encoder::new(&mut buf);
-                opts.encode(&mut opt_enc)?;
-                // buf now has total len
-                e.write_u16(25 + buf.len() as u16)?;
-                // write data
-                e.write_u32(*preferred_lifetime)?;
-                e.write_u32(*valid_lifetime)?;
-                e.write_u8(*prefix_len)?;
-                e.write_u128((*prefix_ip).into())?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::InformationRefreshTime(time) => {
-                e.write_u16(4)?;
-                e.write_u32(*time)?;
-            }
-            DhcpOption::NtpServer(suboptions) => {
-                let mut buf = Vec::new();
-                let mut subopt_enc = Encoder::new(&mut buf);
-                for suboption in suboptions {
-                    suboption.encode(&mut subopt_enc)?;
+                DhcpOption::Preference(pref) => {
+                    e.write_u8(*pref)?;
                 }
-                e.write_u16(buf.len() as _)?;
-                e.write_slice(&buf)?;
-            }
-            DhcpOption::Unknown(UnknownOption { data, .. }) => {
-                e.write_u16(data.len() as u16)?;
-                e.write_slice(data)?;
-            }
-        };
-        Ok(())
+                DhcpOption::ElapsedTime(elapsed) => {
+                    e.write_u16(*elapsed)?;
+                }
+                DhcpOption::RelayMsg(msg) => {
+                    msg.encode(e)?;
+                }
+                DhcpOption::Authentication(Authentication {
+                    proto,
+                    algo,
+                    rdm,
+                    replay_detection,
+                    info,
+                }) => {
+                    e.write_u8(*proto)?;
+                    e.write_u8(*algo)?;
+                    e.write_u8(*rdm)?;
+                    e.write_u64(*replay_detection)?;
+                    e.write_slice(info)?;
+                }
+                DhcpOption::StatusCode(StatusCode { status, msg }) => {
+                    e.write_u16((*status).into())?;
+                    e.write_slice(msg.as_bytes())?;
+                }
+                DhcpOption::RapidCommit | DhcpOption::ReconfAccept => {}
+                DhcpOption::UserClass(UserClass { data }) => {
+                    encode_data(data, e)?;
+                }
+                DhcpOption::VendorClass(VendorClass { num, data }) => {
+                    e.write_u32(*num)?;
+                    encode_data(data, e)?;
+                }
+                DhcpOption::VendorOpts(VendorOpts { num, opts }) => {
+                    e.write_u32(*num)?;
+                    opts.encode(e)?;
+                }
+                DhcpOption::InterfaceId(id) => {
+                    e.write_slice(id.as_bytes())?;
+                }
+                DhcpOption::ReconfMsg(msg_type) => {
+                    e.write_u8((*msg_type).into())?;
+                }
+                DhcpOption::DomainNameServers(addrs)
+                | DhcpOption::SipServerA(addrs)
+                | DhcpOption::PcpServer(addrs) => {
+                    for addr in addrs {
+                        e.write_u128((*addr).into())?;
+                    }
+                }
+                DhcpOption::DomainSearchList(names) | DhcpOption::SipServerD(names) => {
+                    let mut buf = Vec::new();
+                    let mut writer = DomainWriter::new(&mut buf, true);
+                    for name in names {
+                        writer.write(name);
+                    }
+                    e.write_slice(&buf)?;
+                }
+                DhcpOption::IAPrefix(IAPrefix {
+                    preferred_lifetime,
+                    valid_lifetime,
+                    prefix_len,
+                    prefix_ip,
+                    opts,
+                }) => {
+                    e.write_u32(preferred_lifetime.secs())?;
+                    e.write_u32(valid_lifetime.secs())?;
+                    e.write_u8(*prefix_len)?;
+                    e.write_u128((*prefix_ip).into())?;
+                    opts.encode(e)?;
+                }
+                DhcpOption::InformationRefreshTime(time) => {
+                    e.write_u32(*time)?;
+                }
+                DhcpOption::SolMaxRt(max_rt) | DhcpOption::InfMaxRt(max_rt) => {
+                    e.write_u32(max_rt.secs())?;
+                }
+                DhcpOption::NewPosixTimezone(tz) | DhcpOption::NewTzdbTimezone(tz) => {
+                    e.write_slice(tz.as_bytes())?;
+                }
+                DhcpOption::NtpServer(suboptions) => {
+                    suboptions.encode(e)?;
+                }
+                DhcpOption::OptBootfileUrl(uri) => {
+                    e.write_slice(uri.as_str().as_bytes())?;
+                }
+                DhcpOption::OptBootfileParam(params) => {
+                    for param in params {
+                        e.write_u16(opt_len(param.len())?)?;
+                        e.write_slice(param.as_bytes())?;
+                    }
+                }
+                DhcpOption::AftrName(name) => {
+                    let mut buf = Vec::new();
+                    DomainWriter::new(&mut buf, false).write(name);
+                    e.write_slice(&buf)?;
+                }
+                DhcpOption::ClientFqdn(fqdn) => {
+                    let mut buf = vec![fqdn.flag_bits()];
+                    DomainWriter::new(&mut buf, false).write(&fqdn.domain);
+                    if fqdn.is_partial {
+                        buf.pop();
+                    }
+                    e.write_slice(&buf)?;
+                }
+                DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr { htype, addr }) => {
+                    e.write_u16((*htype).into())?;
+                    e.write_slice(addr)?;
+                }
+                DhcpOption::SztpRedirect(uris) => {
+                    sztp::write_uris(e, uris)?;
+                }
+                DhcpOption::RemoteId(RemoteId { num, id }) => {
+                    e.write_u32(*num)?;
+                    e.write_slice(id)?;
+                }
+                DhcpOption::LqQuery(LqQuery {
+                    query_type,
+                    link_addr,
+                    opts,
+                }) => {
+                    e.write_u8((*query_type).into())?;
+                    e.write_u128((*link_addr).into())?;
+                    opts.encode(e)?;
+                }
+                DhcpOption::RelayId(id) => {
+                    e.write_slice(id)?;
+                }
+                DhcpOption::ServerUnicast(addr) | DhcpOption::LinkAddress(addr) => {
+                    e.write_u128((*addr).into())?;
+                }
+                DhcpOption::Unknown(UnknownOption { data, .. }) => {
+                    e.write_slice(data)?;
+                }
+            };
+            Ok(())
+        })
     }
 }
 
+// length of an option body, which must fit in 16 bits
+fn opt_len(len: usize) -> EncodeResult<u16> {
+    u16::try_from(len).map_err(|_| EncodeError::TooLong {
+        len,
+        max: u16::MAX as usize,
+    })
+}
+
+// index of the first element equal according to `f`, `arr` must be sorted
 #[inline]
 fn first<T, F>(arr: &[T], f: F) -> Option<usize>
 where
     T: Ord,
     F: Fn(&T) -> Ordering,
 {
-    if arr.is_empty() {
-        return None;
-    }
-
-    let mut l = 0;
-    let mut r = arr.len() - 1;
-    while l <= r {
-        let mid = (l + r) >> 1;
-        // SAFETY: we know it is within the length
-        let mid_cmp = f(unsafe { arr.get_unchecked(mid) });
-        let prev_cmp = if mid > 0 {
-            f(unsafe { arr.get_unchecked(mid - 1) }) == Ordering::Less
-        } else {
-            false
-        };
-        match mid_cmp {
-            Ordering::Less => l = mid + 1,
-            Ordering::Equal if (mid == 0 || prev_cmp) => return Some(mid),
-            Ordering::Greater | Ordering::Equal if mid == 0 => return None,
-            Ordering::Greater | Ordering::Equal => r = mid - 1,
-        }
-    }
-    None
+    let i = arr.partition_point(|x| f(x) == Ordering::Less);
+    (arr.get(i).map(&f) == Some(Ordering::Equal)).then_some(i)
 }
 
+// index of the last element equal according to `f`, `arr` must be sorted
 #[inline]
 fn last<T, F>(arr: &[T], f: F) -> Option<usize>
 where
     T: Ord,
     F: Fn(&T) -> Ordering,
 {
-    if arr.is_empty() {
-        return None;
-    }
-
-    let n = arr.len();
-    let mut l = 0;
-    let mut r = n - 1;
-    while l <= r {
-        let mid = (l + r) >> 1;
-        // SAFETY: we know it is within the length
-        let mid_cmp = f(unsafe { arr.get_unchecked(mid) });
-        let nxt_cmp = if mid < n && mid != n - 1 {
-            f(unsafe { arr.get_unchecked(mid + 1) }) == Ordering::Greater
-        } else {
-            false
-        };
-        match mid_cmp {
-            Ordering::Greater => r = mid - 1,
-            Ordering::Equal if (mid == n - 1 || nxt_cmp) => return Some(mid),
-            Ordering::Less | Ordering::Equal if mid == n - 1 => return None,
-            Ordering::Less | Ordering::Equal => l = mid + 1,
-        }
-    }
-    None
+    let i = arr.partition_point(|x| f(x) != Ordering::Greater);
+    let last = i.checked_sub(1)?;
+    (f(&arr[last]) == Ordering::Equal).then_some(last)
 }
 
 #[inline]
@@ -971,6 +1911,87 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    #[test]
+    fn test_iter_raw() -> Result<(), Box<dyn std::error::Error>> {
+        let mut ia_opts = DhcpOptions::new();
+        ia_opts.insert(DhcpOption::IAAddr(IAAddr {
+            addr: "2001:db8::1".parse()?,
+            preferred_life: 3600.into(),
+            valid_life: 7200.into(),
+            opts: DhcpOptions::new(),
+        }));
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::ClientId(vec![1, 2]));
+        opts.insert(DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: ia_opts,
+        }));
+        let buf = opts.to_vec()?;
+
+        let raw = iter_raw(&buf).collect::<DecodeResult<Vec<_>>>()?;
+        assert_eq!(raw.len(), 2);
+        assert_eq!(raw[0], (1, &[1, 2][..]));
+        let (code, data) = raw[1];
+        assert_eq!(OptionCode::from(code), OptionCode::IANA);
+        let nested = iter_raw_nested(code.into(), data)
+            .unwrap()
+            .collect::<DecodeResult<Vec<_>>>()?;
+        assert_eq!(nested.len(), 1);
+        assert_eq!(OptionCode::from(nested[0].0), OptionCode::IAAddr);
+        assert_eq!(
+            &nested[0].1[..16],
+            &"2001:db8::1".parse::<Ipv6Addr>()?.octets()
+        );
+        assert!(iter_raw_nested(OptionCode::ClientId, &[]).is_none());
+        assert!(iter_raw_nested(OptionCode::IANA, &[0; 4]).is_none());
+
+        let mut iter = iter_raw(&buf[..buf.len() - 1]);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lifetimes() -> Result<(), Box<dyn std::error::Error>> {
+        let addr = "2001:db8::1".parse()?;
+        assert!(IAAddr::new(addr, 7200.into(), 3600.into()).is_none());
+        assert!(IAPrefix::new(addr, 64, 3600.into(), LeaseTime::INFINITE).is_some());
+        let ia_addr = IAAddr::new(addr, 3600.into(), 3600.into()).unwrap();
+
+        let mut ia = IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: DhcpOptions::new(),
+        };
+        ia.opts.insert(DhcpOption::IAAddr(ia_addr.clone()));
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::IANA(ia.clone()));
+        assert!(opts.check_lifetimes().is_ok());
+
+        let inverted = IAAddr {
+            preferred_life: 7200.into(),
+            ..ia_addr
+        };
+        ia.opts.insert(DhcpOption::IAAddr(inverted.clone()));
+        opts.insert(DhcpOption::IANA(ia));
+        assert_eq!(
+            opts.check_lifetimes(),
+            Err(&DhcpOption::IAAddr(inverted.clone()))
+        );
+
+        let raw = DhcpOption::IAAddr(inverted.clone()).to_vec()?;
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&raw))?,
+            DhcpOption::IAAddr(inverted)
+        );
+        assert!(DhcpOption::decode(&mut Decoder::new_strict(&raw)).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_range_binsearch() {
         let arr = vec![0, 1, 1, 1, 1, 4, 6, 7, 9, 9, 10];
@@ -1004,6 +2025,436 @@ mod tests {
         assert_eq!(None, range_binsearch(&arr, |x| x.cmp(&3)));
     }
 
+    #[test]
+    fn test_range_binsearch_linear() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let len = rng.gen_range(0..20);
+            let mut arr = (0..len).map(|_| rng.gen_range(0..6)).collect::<Vec<u8>>();
+            arr.sort_unstable();
+            for target in 0..7 {
+                let first = arr.iter().position(|x| *x == target);
+                let last = arr.iter().rposition(|x| *x == target);
+                let expected = first.zip(last).map(|(first, last)| first..=last);
+                assert_eq!(
+                    range_binsearch(&arr, |x| x.cmp(&target)),
+                    expected,
+                    "{arr:?} {target}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let ia = |id| {
+            DhcpOption::IANA(IANA {
+                id,
+                t1: 0,
+                t2: 0,
+                opts: DhcpOptions::new(),
+            })
+        };
+        let old: DhcpOptions = vec![
+            DhcpOption::ClientId(vec![1]),
+            DhcpOption::Preference(1),
+            ia(1),
+        ]
+        .into_iter()
+        .collect();
+        let new: DhcpOptions = vec![
+            DhcpOption::ClientId(vec![1]),
+            ia(1),
+            ia(2),
+            DhcpOption::ElapsedTime(10),
+        ]
+        .into_iter()
+        .collect();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![&ia(2), &DhcpOption::ElapsedTime(10)]);
+        assert_eq!(diff.removed, vec![&DhcpOption::Preference(1)]);
+        assert!(diff.changed.is_empty());
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(
+            new.diff(&old).to_string(),
+            "- IANA(IANA { id: 2, t1: 0, t2: 0, opts: DhcpOptions([]) })\n- ElapsedTime(10)\n+ Preference(1)\n"
+        );
+    }
+
+    #[test]
+    fn test_max_rt() -> Result<(), Box<dyn std::error::Error>> {
+        // 30 seconds is below the minimum
+        let raw = [0, 82, 0, 4, 0, 0, 0, 30];
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        let DhcpOption::SolMaxRt(max_rt) = opt else {
+            panic!("expected SolMaxRt")
+        };
+        assert!(!max_rt.is_valid());
+        assert_eq!(max_rt.apply(3600), 3600);
+        assert_eq!(opt.to_vec()?, raw);
+
+        assert!(matches!(
+            DhcpOption::decode(&mut Decoder::new_strict(&raw)),
+            Err(crate::error::DecodeError::InvalidValue { code: Some(82), .. })
+        ));
+        // strict errors are not swallowed by the options list
+        assert!(DhcpOptions::decode(&mut Decoder::new_strict(&raw)).is_err());
+
+        let raw = [0, 83, 0, 4, 0, 0, 0x0e, 0x10];
+        let opt = DhcpOption::decode(&mut Decoder::new_strict(&raw))?;
+        assert_eq!(opt, DhcpOption::InfMaxRt(MaxRt::new(3600).unwrap()));
+        assert_eq!(MaxRt::saturating(100_000).secs(), 86400);
+        Ok(())
+    }
+
+    #[test]
+    fn test_timezone() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 42, 0, 13];
+        raw.extend(b"Europe/Zurich");
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(opt, DhcpOption::NewTzdbTimezone("Europe/Zurich".into()));
+        assert_eq!(opt.to_vec()?, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn test_aftr_name() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 64, 0, 18];
+        raw.extend(b"\x04aftr\x07example\x03com\x00");
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(
+            opt,
+            DhcpOption::AftrName(Domain::from_str("aftr.example.com")?)
+        );
+        assert_eq!(opt.to_vec()?, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_fqdn() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 39, 0, 15, 0x05];
+        raw.extend(b"\x04host\x07example\x00");
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        let DhcpOption::ClientFqdn(fqdn) = &opt else {
+            panic!("expected ClientFqdn, got {opt:?}");
+        };
+        assert!(fqdn.flags.n() && fqdn.flags.s() && !fqdn.flags.o() && !fqdn.flags.e());
+        assert_eq!(fqdn.domain, Domain::from_str("host.example")?);
+        assert!(!fqdn.is_partial);
+        assert_eq!(opt.to_vec()?, raw);
+
+        // an empty partial name asks the server to pick one
+        let raw = [0, 39, 0, 1, 0];
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(
+            opt,
+            DhcpOption::ClientFqdn(
+                ClientFqdn::new(FqdnFlags::default(), Domain::root()).partial(true)
+            )
+        );
+        assert_eq!(opt.to_vec()?, raw);
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 39, 0, 3, 0, 5, b'a'])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() {
+        let ia = |id| {
+            DhcpOption::IANA(IANA {
+                id,
+                t1: 0,
+                t2: 0,
+                opts: DhcpOptions::new(),
+            })
+        };
+        let mut opts: DhcpOptions = [ia(1), ia(2), DhcpOption::Preference(0)]
+            .into_iter()
+            .collect();
+        let other: DhcpOptions = [ia(3), DhcpOption::Preference(255), DhcpOption::RapidCommit]
+            .into_iter()
+            .collect();
+
+        let mut kept = opts.clone();
+        kept.merge(&other, &MergePolicy::keep_existing());
+        let expected: DhcpOptions = [
+            ia(1),
+            ia(2),
+            DhcpOption::Preference(0),
+            DhcpOption::RapidCommit,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(kept, expected);
+
+        let policy = MergePolicy::prefer_other()
+            .with_override(OptionCode::Preference, Precedence::KeepExisting);
+        opts.merge(&other, &policy);
+        let expected: DhcpOptions = [ia(3), DhcpOption::Preference(0), DhcpOption::RapidCommit]
+            .into_iter()
+            .collect();
+        assert_eq!(opts, expected);
+    }
+
+    #[test]
+    fn test_pcp_server() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 86, 0, 32];
+        raw.extend(Ipv6Addr::from_str("2001:db8::1")?.octets());
+        raw.extend(Ipv6Addr::from_str("2001:db8::2")?.octets());
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(opt.to_vec()?, raw);
+
+        let mut opts = DhcpOptions::new();
+        opts.insert(opt);
+        opts.insert(DhcpOption::PcpServer(vec!["2001:db8::3".parse()?]));
+        let mut servers = opts.pcp_servers();
+        servers.sort_by_key(|addrs| addrs.len());
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0], ["2001:db8::3".parse::<Ipv6Addr>()?]);
+        assert_eq!(servers[1].len(), 2);
+        assert!(DhcpOptions::new().pcp_servers().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sztp_redirect() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 136, 0, 22, 0, 20];
+        raw.extend(b"https://a.example/b/");
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(
+            opt,
+            DhcpOption::SztpRedirect(vec!["https://a.example/b/".parse()?])
+        );
+        assert_eq!(opt.to_vec()?, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bootfile() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 59, 0, 29];
+        raw.extend(b"http://[2001:db8::1]/boot.efi");
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        let DhcpOption::OptBootfileUrl(uri) = &opt else {
+            panic!("expected OptBootfileUrl");
+        };
+        assert!(uri.is_boot_file_url());
+        assert_eq!(opt.to_vec()?, raw);
+
+        let raw = [
+            0, 60, 0, 14, 0, 5, b'q', b'u', b'i', b'e', b't', 0, 5, b'c', b'o', b'n', b's', b'0',
+        ];
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw[..]))?;
+        assert_eq!(
+            opt,
+            DhcpOption::OptBootfileParam(vec!["quiet".into(), "cons0".into()])
+        );
+        assert_eq!(opt.to_vec()?, raw);
+        // parameter runs past the option
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 60, 0, 3, 0, 5, b'q'])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_iata() -> Result<(), Box<dyn std::error::Error>> {
+        let ia = IATA::new(7).hint("2001:db8::1".parse()?);
+        assert_eq!(ia.valid_life(), Some(LeaseTime::new(0)));
+        let raw = DhcpOption::IATA(ia.clone()).to_vec()?;
+        // 4 byte IAID & 28 byte IA address
+        assert_eq!(raw[..4], [0, 4, 0, 32]);
+        assert_eq!(
+            DhcpOption::decode(&mut Decoder::new(&raw))?,
+            DhcpOption::IATA(ia)
+        );
+
+        let mut opts = DhcpOptions::new();
+        for (addr, preferred, valid) in [("2001:db8::1", 100, 200), ("2001:db8::2", 150, 150)] {
+            let addr = IAAddr::new(addr.parse()?, preferred.into(), valid.into()).unwrap();
+            opts.insert(DhcpOption::IAAddr(addr));
+        }
+        let iana = IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: opts.clone(),
+        };
+        assert_eq!(iana.addrs().count(), 2);
+        assert_eq!(iana.preferred_life(), Some(LeaseTime::new(150)));
+        assert_eq!(iana.valid_life(), Some(LeaseTime::new(200)));
+        let iata = IATA { id: 1, opts };
+        assert_eq!(iata.valid_life(), iana.valid_life());
+        assert_eq!(IATA::new(1).valid_life(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_nested() -> Result<(), Box<dyn std::error::Error>> {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::StatusCode(StatusCode {
+            status: Status::Success,
+            msg: "ok".into(),
+        }));
+        let mut raw = DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 2,
+            t2: 3,
+            opts,
+        })
+        .to_vec()?;
+        // an IAAddr too short for its address before the status code
+        raw.splice(16..16, [0, 5, 0, 2, 0xaa, 0xbb]);
+        raw[3] += 6;
+        let iana = |opt: &DhcpOption| match opt {
+            DhcpOption::IANA(ia) => ia.opts.iter().count(),
+            _ => 0,
+        };
+        assert_eq!(iana(&DhcpOption::from_bytes(&raw)?), 0);
+
+        let mut decoder = Decoder::new(&raw).recover(true);
+        let opt = DhcpOption::decode(&mut decoder)?;
+        assert_eq!(iana(&opt), 1);
+        let errors = decoder.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_nested_len() -> Result<(), Box<dyn std::error::Error>> {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::StatusCode(StatusCode {
+            status: Status::Success,
+            msg: "ok".into(),
+        }));
+        let iana = DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 2,
+            t2: 3,
+            opts,
+        });
+        let raw = iana.to_vec()?;
+        // 12 byte header & 8 byte status code
+        assert_eq!(raw[..4], [0, 3, 0, 20]);
+        assert_eq!(DhcpOption::decode(&mut Decoder::new(&raw))?, iana);
+
+        let big = DhcpOption::Unknown(UnknownOption {
+            code: 999,
+            data: vec![0; 65536],
+        });
+        assert!(matches!(
+            big.to_vec(),
+            Err(EncodeError::TooLong {
+                len: 65536,
+                max: 65535
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sip_servers() -> Result<(), Box<dyn std::error::Error>> {
+        let mut raw = vec![0, 21, 0, 13, 3];
+        raw.extend(b"sip");
+        raw.push(7);
+        raw.extend(b"example");
+        raw.push(0);
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(
+            opt,
+            DhcpOption::SipServerD(vec![Domain::from_str("sip.example.")?])
+        );
+        assert_eq!(opt.to_vec()?, raw);
+
+        let mut raw = vec![0, 22, 0, 16];
+        raw.extend(Ipv6Addr::LOCALHOST.octets());
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(opt, DhcpOption::SipServerA(vec![Ipv6Addr::LOCALHOST]));
+        assert_eq!(opt.to_vec()?, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_len() -> Result<(), Box<dyn std::error::Error>> {
+        let raw = vec![0, 15, 0, 7, 0, 1, b'a', 0, 2, b'b', b'c'];
+        let opt = DhcpOption::decode(&mut Decoder::new(&raw))?;
+        assert_eq!(
+            opt,
+            DhcpOption::UserClass(UserClass {
+                data: vec![b"a".to_vec(), b"bc".to_vec()]
+            })
+        );
+        assert_eq!(opt.to_vec()?, raw);
+
+        let opt = DhcpOption::Unknown(UnknownOption {
+            code: 1000,
+            data: vec![0; 65536],
+        });
+        assert!(matches!(
+            opt.to_vec(),
+            Err(EncodeError::TooLong {
+                len: 65536,
+                max: 65535
+            })
+        ));
+        let opt = DhcpOption::UserClass(UserClass {
+            data: vec![vec![0; 65534]],
+        });
+        assert!(matches!(
+            opt.to_vec(),
+            Err(EncodeError::TooLong {
+                len: 65536,
+                max: 65535
+            })
+        ));
+        // nested options overflowing the IA
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::Unknown(UnknownOption {
+            code: 1000,
+            data: vec![0; 65530],
+        }));
+        let opt = DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts,
+        });
+        assert!(matches!(
+            opt.to_vec(),
+            Err(EncodeError::TooLong { len: 65546, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_option() {
+        let opt = UnknownOption::from_raw(1000, vec![1]);
+        assert_eq!(opt.code(), OptionCode::new(1000));
+        assert_eq!(opt.raw_code(), 1000);
+        // a known code maps to its OptionCode
+        let opt = DhcpOption::Unknown(UnknownOption::from_raw(23, vec![]));
+        assert_eq!(OptionCode::from(&opt), OptionCode::DomainNameServers);
+        let mut opts = DhcpOptions::new();
+        opts.insert(opt.clone());
+        assert_eq!(opts.get(OptionCode::DomainNameServers), Some(&opt));
+    }
+
+    #[test]
+    fn test_interface_id() {
+        let id = InterfaceId::from("ge-0/0/1.100");
+        assert!(id.eq_ignore_case("GE-0/0/1.100"));
+        assert!(!id.eq_ignore_case("ge-0/0/1.10"));
+        for pattern in ["*", "ge-*", "*.100", "GE-0/?/1.*", "g*0*1*"] {
+            assert!(id.matches(pattern), "{pattern}");
+        }
+        for pattern in ["", "ge-", "*.10", "xe-*", "ge-0/0/1.100?"] {
+            assert!(!id.matches(pattern), "{pattern}");
+        }
+        assert!(InterfaceId::from("").matches("*"));
+        assert_eq!(InterfaceId::from("a\tb").to_string(), "61:09:62");
+        assert_eq!(InterfaceId::from(vec![0xff]).as_str(), None);
+    }
+
     #[test]
     fn test_dhcpv6_opts_parsing() {
         #[rustfmt::skip]
@@ -1065,8 +2516,8 @@ mod tests {
                     0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
                     0x00, 0x02, 0x84,
                 ]),
-                preferred_life: 120,
-                valid_life: 120,
+                preferred_life: 120.into(),
+                valid_life: 120.into(),
                 opts: DhcpOptions(Vec::new()),
             })]),
         }));
@@ -1079,13 +2530,16 @@ mod tests {
             0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0a, 0x00, 0x00, 0x14, 0x42, 0xe2, 0xff, 0xfe, 0x17,
             0x84, 0x1a,
         ])]));
-        expected_opts.insert(DhcpOption::NtpServer(vec![
-            NtpSuboption::ServerAddress(Ipv6Addr::from([
-                0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0a, 0x00, 0x00, 0x14, 0x42, 0xe2, 0xff, 0xfe, 0x17,
-                0x84, 0x1a,
-            ])),
-            NtpSuboption::FQDN(Name::from_str("3.de.pool.ntp.org.").unwrap()),
-        ]));
+        expected_opts.insert(DhcpOption::NtpServer(
+            vec![
+                NtpSuboption::ServerAddress(Ipv6Addr::from([
+                    0x20, 0x01, 0x0d, 0xb8, 0x00, 0x0a, 0x00, 0x00, 0x14, 0x42, 0xe2, 0xff, 0xfe,
+                    0x17, 0x84, 0x1a,
+                ])),
+                NtpSuboption::FQDN(Domain::from_str("3.de.pool.ntp.org.").unwrap()),
+            ]
+            .into(),
+        ));
 
         let opts = DhcpOptions::decode(&mut Decoder::new(&raw)).unwrap();
 
@@ -1097,4 +2551,20 @@ mod tests {
         assert_eq!(opts, expected_opts);
         assert_eq!(buffer.as_slice(), raw);
     }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_auth_zeroize() {
+        use zeroize::Zeroize;
+        let mut auth = Authentication {
+            proto: 3,
+            algo: 1,
+            rdm: 0,
+            replay_detection: 42,
+            info: vec![0xaa; 16],
+        };
+        auth.zeroize();
+        assert_eq!(auth.replay_detection, 0);
+        assert!(auth.info.is_empty());
+    }
 }
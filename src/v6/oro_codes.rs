@@ -60,7 +60,7 @@ pub enum OROCode {
     InfMaxRt,
     Addrsel,
     AddrselTable,
-    V6PcpServer,
+    PcpServer,
     Dhcp4ODhcp6Server,
     S46ContMape,
     S46ContMapt,
@@ -72,6 +72,7 @@ pub enum OROCode {
     MplParameters,
     S46Priority,
     V6Prefix64,
+    SztpRedirect,
     Ipv6AddressANDSF,
     /// Avalible for future codes.
     Unknown(u16),
@@ -144,7 +145,7 @@ impl TryFrom<OptionCode> for OROCode {
             OptionCode::InfMaxRt => Ok(OROCode::InfMaxRt),
             OptionCode::Addrsel => Ok(OROCode::Addrsel),
             OptionCode::AddrselTable => Ok(OROCode::AddrselTable),
-            OptionCode::V6PcpServer => Ok(OROCode::V6PcpServer),
+            OptionCode::PcpServer => Ok(OROCode::PcpServer),
             OptionCode::Dhcp4ODhcp6Server => Ok(OROCode::Dhcp4ODhcp6Server),
             OptionCode::S46ContMape => Ok(OROCode::S46ContMape),
             OptionCode::S46ContMapt => Ok(OROCode::S46ContMapt),
@@ -156,8 +157,9 @@ impl TryFrom<OptionCode> for OROCode {
             OptionCode::MplParameters => Ok(OROCode::MplParameters),
             OptionCode::S46Priority => Ok(OROCode::S46Priority),
             OptionCode::V6Prefix64 => Ok(OROCode::V6Prefix64),
+            OptionCode::SztpRedirect => Ok(OROCode::SztpRedirect),
             OptionCode::Ipv6AddressANDSF => Ok(OROCode::Ipv6AddressANDSF),
-            OptionCode::Unknown(u16) => Ok(OROCode::Unknown(u16)),
+            code if !code.is_known() => Ok(OROCode::Unknown(code.into())),
             _ => Err("conversion error, is not a valid OROCode"),
         }
     }
@@ -214,7 +216,7 @@ impl From<OROCode> for OptionCode {
             OROCode::InfMaxRt => OptionCode::InfMaxRt,
             OROCode::Addrsel => OptionCode::Addrsel,
             OROCode::AddrselTable => OptionCode::AddrselTable,
-            OROCode::V6PcpServer => OptionCode::V6PcpServer,
+            OROCode::PcpServer => OptionCode::PcpServer,
             OROCode::Dhcp4ODhcp6Server => OptionCode::Dhcp4ODhcp6Server,
             OROCode::S46ContMape => OptionCode::S46ContMape,
             OROCode::S46ContMapt => OptionCode::S46ContMapt,
@@ -226,8 +228,9 @@ impl From<OROCode> for OptionCode {
             OROCode::MplParameters => OptionCode::MplParameters,
             OROCode::S46Priority => OptionCode::S46Priority,
             OROCode::V6Prefix64 => OptionCode::V6Prefix64,
+            OROCode::SztpRedirect => OptionCode::SztpRedirect,
             OROCode::Ipv6AddressANDSF => OptionCode::Ipv6AddressANDSF,
-            OROCode::Unknown(u16) => OptionCode::Unknown(u16),
+            OROCode::Unknown(u16) => OptionCode::new(u16),
         }
     }
 }
@@ -0,0 +1,265 @@
+//! Server initiated Reconfigure
+//!
+//! A server sends a Reconfigure to make a client Renew, Rebind or send an
+//! Information-request. The client only acts on one if it said it would accept them,
+//! by sending `ReconfAccept`, and the message is authenticated, usually with the
+//! Reconfigure Key Authentication Protocol (RKAP).
+//! <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.11>
+//!
+//! ```rust
+//! use dhcproto::v6::{self, reconfigure::{self, ReconfigureAcceptor}, DhcpOption};
+//!
+//! let mut solicit = v6::Message::new(v6::MessageType::Solicit);
+//! solicit.opts_mut().insert(DhcpOption::ReconfAccept);
+//! let mut acceptor = ReconfigureAcceptor::new(vec![1], vec![2]);
+//! acceptor.sent(&solicit);
+//!
+//! let reconf = v6::Message::reconfigure(
+//!     vec![1],
+//!     vec![2],
+//!     v6::MessageType::Renew,
+//!     reconfigure::rkap_auth(1),
+//! )
+//! .unwrap();
+//! // the hook checks the HMAC-MD5 digest with the reconfigure key
+//! let msg_type = acceptor.accept(&reconf, |_msg, _auth| true).unwrap();
+//! assert_eq!(msg_type, v6::MessageType::Renew);
+//! ```
+use thiserror::Error;
+
+use crate::v6::{Authentication, DhcpOption, Message, MessageType, OptionCode};
+
+/// Reconfigure Key Authentication Protocol
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-20.4>
+pub const RKAP_PROTOCOL: u8 = 3;
+/// HMAC-MD5, the only RKAP algorithm
+pub const HMAC_MD5: u8 = 1;
+/// monotonically increasing counter replay detection method
+pub const MONOTONIC_RDM: u8 = 0;
+/// RKAP info type of the reconfigure key, sent by the server in a Reply
+pub const RKAP_KEY: u8 = 1;
+/// RKAP info type of the HMAC-MD5 digest, sent by the server in a Reconfigure
+pub const RKAP_DIGEST: u8 = 2;
+/// length of the reconfigure key & HMAC-MD5 digest
+pub const RKAP_VALUE_LEN: usize = 16;
+
+/// RKAP authentication for a Reconfigure with a zeroed digest. The server computes the
+/// HMAC-MD5 over the encoded message with the digest zeroed, then writes it into the
+/// last 16 bytes of `info`.
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-20.4.2>
+pub fn rkap_auth(replay_detection: u64) -> Authentication {
+    let mut info = vec![0; 1 + RKAP_VALUE_LEN];
+    info[0] = RKAP_DIGEST;
+    Authentication {
+        proto: RKAP_PROTOCOL,
+        algo: HMAC_MD5,
+        rdm: MONOTONIC_RDM,
+        replay_detection,
+        info,
+    }
+}
+
+impl Message {
+    /// Build a Reconfigure telling the client with `client_id` to send a `reconf`
+    /// message, which must be Renew, Rebind or InformationRequest.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.3.11>
+    ///
+    /// The xid is 0 as the Reconfigure doesn't start a transaction. IA_NA/IA_PD options
+    /// for a Renew or Rebind can be added afterwards.
+    ///
+    /// Returns `None` if `reconf` isn't a message a Reconfigure can ask for.
+    pub fn reconfigure(
+        client_id: Vec<u8>,
+        server_id: Vec<u8>,
+        reconf: MessageType,
+        auth: Authentication,
+    ) -> Option<Message> {
+        if !valid_reconf(reconf) {
+            return None;
+        }
+        let mut msg = Message::new_with_id(MessageType::Reconfigure, [0; 3]);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::ClientId(client_id));
+        opts.insert(DhcpOption::ServerId(server_id));
+        opts.insert(DhcpOption::ReconfMsg(reconf));
+        opts.insert(DhcpOption::Authentication(auth));
+        Some(msg)
+    }
+}
+
+fn valid_reconf(msg_type: MessageType) -> bool {
+    matches!(
+        msg_type,
+        MessageType::Renew | MessageType::Rebind | MessageType::InformationRequest
+    )
+}
+
+/// Reasons a client discards a Reconfigure
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-16.11>
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconfigureError {
+    /// not a Reconfigure
+    #[error("{0:?} is not a Reconfigure")]
+    NotReconfigure(MessageType),
+    /// the client never sent `ReconfAccept`
+    #[error("client didn't accept Reconfigure messages")]
+    NotAccepted,
+    /// `ServerId` missing or not the server the client's leases are from
+    #[error("ServerId missing or doesn't match")]
+    ServerIdMismatch,
+    /// `ClientId` missing or not the client's DUID
+    #[error("ClientId missing or doesn't match")]
+    ClientIdMismatch,
+    /// `ReconfMsg` missing or not Renew, Rebind or InformationRequest
+    #[error("ReconfMsg missing or invalid")]
+    InvalidReconfMsg,
+    /// IA_NA or IA_PD in a Reconfigure asking for an Information-request
+    #[error("IA options in an Information-request Reconfigure")]
+    UnexpectedIa,
+    /// no RKAP `Authentication` option
+    #[error("missing RKAP authentication")]
+    MissingAuth,
+    /// the authentication hook rejected the message
+    #[error("authentication failed")]
+    AuthFailed,
+}
+
+/// Checks a Reconfigure is one the client should act on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconfigureAcceptor {
+    client_id: Vec<u8>,
+    server_id: Vec<u8>,
+    reconf_accept: bool,
+}
+
+impl ReconfigureAcceptor {
+    /// for the client with DUID `client_id` holding leases from `server_id`
+    pub fn new(client_id: Vec<u8>, server_id: Vec<u8>) -> Self {
+        Self {
+            client_id,
+            server_id,
+            reconf_accept: false,
+        }
+    }
+
+    /// record a message the client sent, accepting Reconfigure messages if it included
+    /// `ReconfAccept`
+    pub fn sent(&mut self, msg: &Message) {
+        self.reconf_accept = msg.opts().get(OptionCode::ReconfAccept).is_some();
+    }
+
+    /// set whether the client accepts Reconfigure messages
+    pub fn set_reconf_accept(&mut self, accept: bool) -> &mut Self {
+        self.reconf_accept = accept;
+        self
+    }
+
+    /// Validate a Reconfigure, returning the message the client should send. `verify`
+    /// checks the RKAP digest with the reconfigure key the client got from the server,
+    /// and the replay detection value. [`auth`](crate::auth) has the helpers for the
+    /// digest check.
+    ///
+    /// Whether the Reconfigure was unicast to the client is left to the caller.
+    pub fn accept<F>(&self, msg: &Message, verify: F) -> Result<MessageType, ReconfigureError>
+    where
+        F: FnOnce(&Message, &Authentication) -> bool,
+    {
+        if msg.msg_type() != MessageType::Reconfigure {
+            return Err(ReconfigureError::NotReconfigure(msg.msg_type()));
+        }
+        if !self.reconf_accept {
+            return Err(ReconfigureError::NotAccepted);
+        }
+        let opts = msg.opts();
+        match opts.get(OptionCode::ServerId) {
+            Some(DhcpOption::ServerId(id)) if *id == self.server_id => {}
+            _ => return Err(ReconfigureError::ServerIdMismatch),
+        }
+        match opts.get(OptionCode::ClientId) {
+            Some(DhcpOption::ClientId(id)) if *id == self.client_id => {}
+            _ => return Err(ReconfigureError::ClientIdMismatch),
+        }
+        let reconf = match opts.get(OptionCode::ReconfMsg) {
+            Some(DhcpOption::ReconfMsg(reconf)) if valid_reconf(*reconf) => *reconf,
+            _ => return Err(ReconfigureError::InvalidReconfMsg),
+        };
+        if reconf == MessageType::InformationRequest
+            && (opts.get(OptionCode::IANA).is_some() || opts.get(OptionCode::IAPD).is_some())
+        {
+            return Err(ReconfigureError::UnexpectedIa);
+        }
+        let auth = match opts.get(OptionCode::Authentication) {
+            Some(DhcpOption::Authentication(auth))
+                if auth.proto == RKAP_PROTOCOL && auth.info.first() == Some(&RKAP_DIGEST) =>
+            {
+                auth
+            }
+            _ => return Err(ReconfigureError::MissingAuth),
+        };
+        if !verify(msg, auth) {
+            return Err(ReconfigureError::AuthFailed);
+        }
+        Ok(reconf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v6::IANA;
+
+    #[test]
+    fn test_reconfigure() {
+        assert!(
+            Message::reconfigure(vec![1], vec![2], MessageType::Request, rkap_auth(1)).is_none()
+        );
+        let msg = Message::reconfigure(vec![1], vec![2], MessageType::Renew, rkap_auth(1)).unwrap();
+        assert_eq!(msg.xid(), [0; 3]);
+
+        let mut acceptor = ReconfigureAcceptor::new(vec![1], vec![2]);
+        assert_eq!(
+            acceptor.accept(&msg, |_, _| true),
+            Err(ReconfigureError::NotAccepted)
+        );
+        acceptor.set_reconf_accept(true);
+        assert_eq!(
+            acceptor.accept(&msg, |_, _| false),
+            Err(ReconfigureError::AuthFailed)
+        );
+        assert_eq!(
+            acceptor.accept(&msg, |_, auth| auth.replay_detection == 1),
+            Ok(MessageType::Renew)
+        );
+
+        let mut other = ReconfigureAcceptor::new(vec![3], vec![2]);
+        other.set_reconf_accept(true);
+        assert_eq!(
+            other.accept(&msg, |_, _| true),
+            Err(ReconfigureError::ClientIdMismatch)
+        );
+
+        let mut info = Message::reconfigure(
+            vec![1],
+            vec![2],
+            MessageType::InformationRequest,
+            rkap_auth(2),
+        )
+        .unwrap();
+        info.opts_mut().insert(DhcpOption::IANA(IANA {
+            id: 1,
+            t1: 0,
+            t2: 0,
+            opts: Default::default(),
+        }));
+        assert_eq!(
+            acceptor.accept(&info, |_, _| true),
+            Err(ReconfigureError::UnexpectedIa)
+        );
+        info.opts_mut().remove(OptionCode::IANA);
+        info.opts_mut().remove(OptionCode::Authentication);
+        assert_eq!(
+            acceptor.accept(&info, |_, _| true),
+            Err(ReconfigureError::MissingAuth)
+        );
+    }
+}
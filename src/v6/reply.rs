@@ -0,0 +1,210 @@
+//! Building server replies from client requests
+use crate::v6::{duid::Duid, DhcpOption, Message, MessageType, OptionCode};
+
+impl Message {
+    /// A reply of type `msg_type` to this message to fill in, the first step of building
+    /// any reply. The xid and `ClientId` are kept, all other options are dropped.
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.3>
+    ///
+    /// [`ReplyBuilder`] builds on this to fill in the server's options.
+    pub fn derive_reply(&self, msg_type: MessageType) -> Message {
+        let mut msg = Message::new_with_id(msg_type, self.xid());
+        if let Some(client_id) = self.opts().get(OptionCode::ClientId) {
+            msg.opts_mut().insert(client_id.clone());
+        }
+        msg
+    }
+}
+
+/// Builds an Advertise/Reply for an incoming client message
+///
+/// The xid and `ClientId` are copied from the request and the configured `ServerId` is
+/// inserted. A Solicit gets an Advertise, or a Reply with `RapidCommit` echoed when the
+/// client asked for it and [`ReplyBuilder::rapid_commit`] is enabled. Every other message
+/// gets a Reply. <https://datatracker.ietf.org/doc/html/rfc8415#section-18.3>
+///
+/// IA options (IA_NA, IA_TA, IA_PD) from the request are passed to an allocation
+/// callback, which returns the IA option to include in the reply.
+///
+/// ```rust
+/// use dhcproto::v6::{self, duid::Duid, DhcpOption, OptionCode, ReplyBuilder, IANA};
+///
+/// let mut req = v6::Message::new(v6::MessageType::Solicit);
+/// req.opts_mut().insert(DhcpOption::ClientId(vec![0, 1, 2, 3]));
+/// req.opts_mut().insert(DhcpOption::IANA(IANA { id: 1, t1: 0, t2: 0, opts: Default::default() }));
+///
+/// let advertise = ReplyBuilder::for_request(&req)
+///     .server_id(Duid::unknown(&[4, 5, 6, 7]))
+///     .build(|ia| match ia {
+///         DhcpOption::IANA(ia) => Some(DhcpOption::IANA(IANA { t1: 1800, t2: 2880, ..ia.clone() })),
+///         _ => None,
+///     });
+///
+/// assert_eq!(advertise.msg_type(), v6::MessageType::Advertise);
+/// assert_eq!(advertise.xid(), req.xid());
+/// assert!(advertise.opts().get(OptionCode::IANA).is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplyBuilder<'a> {
+    req: &'a Message,
+    server_id: Option<Duid>,
+    rapid_commit: bool,
+}
+
+impl<'a> ReplyBuilder<'a> {
+    /// create a new builder replying to `req`
+    pub fn for_request(req: &'a Message) -> Self {
+        Self {
+            req,
+            server_id: None,
+            rapid_commit: false,
+        }
+    }
+
+    /// set the server DUID to send in the `ServerId` option
+    pub fn server_id(&mut self, duid: Duid) -> &mut Self {
+        self.server_id = Some(duid);
+        self
+    }
+
+    /// allow replying to a Solicit with `RapidCommit` with a Reply, defaults to `false`
+    /// <https://datatracker.ietf.org/doc/html/rfc8415#section-18.3.1>
+    pub fn rapid_commit(&mut self, allow: bool) -> &mut Self {
+        self.rapid_commit = allow;
+        self
+    }
+
+    /// returns `true` if the reply will use rapid commit
+    fn is_rapid_commit(&self) -> bool {
+        self.rapid_commit
+            && self.req.msg_type() == MessageType::Solicit
+            && self.req.opts().get(OptionCode::RapidCommit).is_some()
+    }
+
+    /// the message type of the reply that will be built
+    pub fn msg_type(&self) -> MessageType {
+        match self.req.msg_type() {
+            MessageType::Solicit if !self.is_rapid_commit() => MessageType::Advertise,
+            _ => MessageType::Reply,
+        }
+    }
+
+    /// build the reply, `allocate` is called with each IA option in the request
+    pub fn build<F>(&self, mut allocate: F) -> Message
+    where
+        F: FnMut(&DhcpOption) -> Option<DhcpOption>,
+    {
+        let mut msg = self.req.derive_reply(self.msg_type());
+        let opts = msg.opts_mut();
+        if let Some(duid) = &self.server_id {
+            opts.insert(DhcpOption::ServerId(duid.as_ref().to_vec()));
+        }
+        if self.is_rapid_commit() {
+            opts.insert(DhcpOption::RapidCommit);
+        }
+        for ia in self.req.opts().iter().filter(|opt| {
+            matches!(
+                opt,
+                DhcpOption::IANA(_) | DhcpOption::IATA(_) | DhcpOption::IAPD(_)
+            )
+        }) {
+            if let Some(opt) = allocate(ia) {
+                opts.insert(opt);
+            }
+        }
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v6::{DhcpOptions, IAAddr, IANA};
+
+    fn solicit() -> Message {
+        let mut req = Message::new(MessageType::Solicit);
+        req.opts_mut()
+            .insert(DhcpOption::ClientId(vec![0, 1, 2, 3]));
+        req.opts_mut().insert(DhcpOption::RapidCommit);
+        for id in [1, 2] {
+            req.opts_mut().insert(DhcpOption::IANA(IANA {
+                id,
+                t1: 0,
+                t2: 0,
+                opts: DhcpOptions::new(),
+            }));
+        }
+        req
+    }
+
+    fn allocate(ia: &DhcpOption) -> Option<DhcpOption> {
+        let DhcpOption::IANA(ia) = ia else {
+            return None;
+        };
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::IAAddr(IAAddr {
+            addr: [0x2001, 0xdb8, 0, 0, 0, 0, 0, ia.id as u16].into(),
+            preferred_life: 3600.into(),
+            valid_life: 7200.into(),
+            opts: DhcpOptions::new(),
+        }));
+        Some(DhcpOption::IANA(IANA {
+            id: ia.id,
+            t1: 1800,
+            t2: 2880,
+            opts,
+        }))
+    }
+
+    #[test]
+    fn test_advertise() {
+        let req = solicit();
+        let msg = ReplyBuilder::for_request(&req)
+            .server_id(Duid::unknown(&[9, 9]))
+            .build(allocate);
+        assert_eq!(msg.msg_type(), MessageType::Advertise);
+        assert_eq!(msg.xid(), req.xid());
+        assert_eq!(
+            msg.opts().get(OptionCode::ClientId),
+            req.opts().get(OptionCode::ClientId)
+        );
+        assert_eq!(
+            msg.opts().get(OptionCode::ServerId),
+            Some(&DhcpOption::ServerId(vec![9, 9]))
+        );
+        assert!(msg.opts().get(OptionCode::RapidCommit).is_none());
+        assert_eq!(msg.opts().get_all(OptionCode::IANA).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_derive_reply() {
+        let req = solicit();
+        let reply = req.derive_reply(MessageType::Advertise);
+        assert_eq!(reply.msg_type(), MessageType::Advertise);
+        assert_eq!(reply.xid(), req.xid());
+        assert_eq!(reply.opts().iter().count(), 1);
+        assert_eq!(
+            reply.opts().get(OptionCode::ClientId),
+            req.opts().get(OptionCode::ClientId)
+        );
+    }
+
+    #[test]
+    fn test_rapid_commit() {
+        let req = solicit();
+        let msg = ReplyBuilder::for_request(&req)
+            .rapid_commit(true)
+            .build(allocate);
+        assert_eq!(msg.msg_type(), MessageType::Reply);
+        assert!(msg.opts().get(OptionCode::RapidCommit).is_some());
+
+        let mut req = solicit();
+        req.set_msg_type(MessageType::Renew);
+        let msg = ReplyBuilder::for_request(&req)
+            .rapid_commit(true)
+            .build(|_| None);
+        assert_eq!(msg.msg_type(), MessageType::Reply);
+        assert!(msg.opts().get(OptionCode::RapidCommit).is_none());
+        assert!(msg.opts().get(OptionCode::IANA).is_none());
+    }
+}
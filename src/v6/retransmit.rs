@@ -0,0 +1,422 @@
+//! Retransmission timing for client messages
+//!
+//! Each client message has an initial retransmission time (IRT), a maximum
+//! retransmission time (MRT), count (MRC) and duration (MRD). The timeout starts around
+//! IRT and doubles each time up to MRT, randomized by ±10%. Every retransmission
+//! updates `ElapsedTime` while the xid stays the same.
+//! <https://datatracker.ietf.org/doc/html/rfc8415#section-15>
+//!
+//! Servers can raise or lower the MRT of Solicit and Information-request messages with
+//! `SolMaxRt` & `InfMaxRt`, [`Retransmit::update_max_rt`] applies them from the
+//! Advertise or Reply messages the client receives.
+//! <https://datatracker.ietf.org/doc/html/rfc8415#section-18.2.1>
+//!
+//! [`Retransmit`] only computes delays, sending and sleeping are left to the caller.
+//! Its `ElapsedTime` assumes each message is sent right after its delay; give it an
+//! [`ElapsedTimeTracker`] to stamp the time actually elapsed since the exchange began.
+//!
+//! ```rust
+//! use dhcproto::v6::{self, retransmit::Retransmit};
+//!
+//! let request = v6::Message::new(v6::MessageType::Request);
+//! let sends = Retransmit::new(request).unwrap().jitter(false).collect::<Vec<_>>();
+//! // the first send and 9 retransmissions (MRC 10)
+//! assert_eq!(sends.len(), 10);
+//! let delays = sends.iter().map(|(delay, _)| delay.as_secs()).collect::<Vec<_>>();
+//! assert_eq!(delays, [0, 1, 2, 4, 8, 16, 30, 30, 30, 30]);
+//! assert_eq!(sends[3].1.elapsed_time(), Some(700));
+//! ```
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::v6::{DhcpOption, Message, MessageType, OptionCode};
+
+/// default SOL_MAX_RT, the MRT of Solicit
+pub const SOL_MAX_RT: Duration = Duration::from_secs(3600);
+/// default INF_MAX_RT, the MRT of Information-request
+pub const INF_MAX_RT: Duration = Duration::from_secs(3600);
+
+/// Retransmission parameters of a message
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-7.6>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Params {
+    /// initial retransmission time
+    pub irt: Duration,
+    /// maximum retransmission time, `None` for no maximum
+    pub mrt: Option<Duration>,
+    /// maximum number of sends, `None` for no maximum
+    pub mrc: Option<usize>,
+    /// maximum time from the first send to the last, `None` for no maximum. Renew and
+    /// Rebind end at T2 and when the leases expire, set it to that.
+    pub mrd: Option<Duration>,
+}
+
+impl Params {
+    /// the default parameters of the client message `msg_type`, `None` if clients don't
+    /// send it
+    pub fn new(msg_type: MessageType) -> Option<Self> {
+        let secs = Duration::from_secs;
+        let (irt, mrt, mrc, mrd) = match msg_type {
+            MessageType::Solicit => (secs(1), Some(SOL_MAX_RT), None, None),
+            MessageType::Request => (secs(1), Some(secs(30)), Some(10), None),
+            MessageType::Confirm => (secs(1), Some(secs(4)), None, Some(secs(10))),
+            MessageType::Renew | MessageType::Rebind => (secs(10), Some(secs(600)), None, None),
+            MessageType::InformationRequest => (secs(1), Some(INF_MAX_RT), None, None),
+            MessageType::Release => (secs(1), None, Some(4), None),
+            MessageType::Decline => (secs(1), None, Some(5), None),
+            _ => return None,
+        };
+        Some(Self { irt, mrt, mrc, mrd })
+    }
+}
+
+/// Stamps the `ElapsedTime` of the messages in an exchange with the time since it
+/// began, in hundredths of a second saturating at `0xffff`.
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-21.9>
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use dhcproto::v6::{self, duid::Duid, retransmit::ElapsedTimeTracker};
+///
+/// let start = Instant::now();
+/// let tracker = ElapsedTimeTracker::starting_at(start);
+/// let mut solicit = v6::Message::solicit(&Duid::unknown(&[1]), []);
+/// tracker.stamp_at(&mut solicit, start + Duration::from_millis(1_250));
+/// assert_eq!(solicit.elapsed_time(), Some(125));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElapsedTimeTracker {
+    start: Instant,
+}
+
+impl Default for ElapsedTimeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElapsedTimeTracker {
+    /// an exchange beginning now
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    /// an exchange that began at `start`, when its first message was sent
+    pub fn starting_at(start: Instant) -> Self {
+        Self { start }
+    }
+
+    /// when the exchange began
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// the `ElapsedTime` value at `now`, 0 if `now` is before the start
+    pub fn elapsed_at(&self, now: Instant) -> u16 {
+        let elapsed = now.saturating_duration_since(self.start);
+        u16::try_from(elapsed.as_millis() / 10).unwrap_or(u16::MAX)
+    }
+
+    /// the `ElapsedTime` value now
+    pub fn elapsed(&self) -> u16 {
+        self.elapsed_at(Instant::now())
+    }
+
+    /// set `msg`'s `ElapsedTime` to the time elapsed now
+    pub fn stamp(&self, msg: &mut Message) {
+        self.stamp_at(msg, Instant::now())
+    }
+
+    /// set `msg`'s `ElapsedTime` to the time elapsed at `now`
+    pub fn stamp_at(&self, msg: &mut Message, now: Instant) {
+        set_elapsed(msg, self.elapsed_at(now));
+    }
+}
+
+fn set_elapsed(msg: &mut Message, elapsed: u16) {
+    msg.opts_mut().remove(OptionCode::ElapsedTime);
+    msg.opts_mut().insert(DhcpOption::ElapsedTime(elapsed));
+}
+
+/// Iterator of `(delay, message)` pairs for sending a client message. `delay` is the
+/// time to wait before sending `message`, the first is sent immediately. Iteration ends
+/// after MRC sends or once the next send would be after MRD.
+#[derive(Debug, Clone)]
+pub struct Retransmit {
+    msg: Message,
+    params: Params,
+    attempt: usize,
+    jitter: bool,
+    // the last timeout, RT
+    rt: Duration,
+    // time since the first send
+    elapsed: Duration,
+    tracker: Option<ElapsedTimeTracker>,
+}
+
+impl Retransmit {
+    /// retransmissions of `msg` with the default [`Params`] for its message type, `None`
+    /// if it isn't a message clients send
+    pub fn new(msg: Message) -> Option<Self> {
+        let params = Params::new(msg.msg_type())?;
+        Some(Self::with_params(msg, params))
+    }
+
+    /// retransmissions of `msg` with `params`
+    pub fn with_params(msg: Message, params: Params) -> Self {
+        Self {
+            msg,
+            params,
+            attempt: 0,
+            jitter: true,
+            rt: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            tracker: None,
+        }
+    }
+
+    /// Stamp `ElapsedTime` with `tracker`'s clock at the time each message is due,
+    /// `Instant::now()` plus its delay, rather than the sum of the delays so far. Use it
+    /// when the exchange began before this message, or sends may run late.
+    pub fn tracker(mut self, tracker: ElapsedTimeTracker) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// randomize timeouts by ±10%, defaults to `true`
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// the parameters in effect, including any MRT set by the server
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// the last timeout (RT), zero before the first retransmission
+    pub fn timeout(&self) -> Duration {
+        self.rt
+    }
+
+    /// time from the first send to the last one
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Apply the `SolMaxRt` or `InfMaxRt` in an Advertise or Reply `received` to the
+    /// MRT of a Solicit or Information-request, returning `true` if it changed. Values
+    /// outside of [`MaxRt::RANGE`](crate::v6::MaxRt::RANGE) are ignored.
+    pub fn update_max_rt(&mut self, received: &Message) -> bool {
+        if !matches!(
+            received.msg_type(),
+            MessageType::Advertise | MessageType::Reply
+        ) {
+            return false;
+        }
+        let max_rt = match (self.msg.msg_type(), received.opts()) {
+            (MessageType::Solicit, opts) => match opts.get(OptionCode::SolMaxRt) {
+                Some(DhcpOption::SolMaxRt(max_rt)) => max_rt,
+                _ => return false,
+            },
+            (MessageType::InformationRequest, opts) => match opts.get(OptionCode::InfMaxRt) {
+                Some(DhcpOption::InfMaxRt(max_rt)) => max_rt,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        if !max_rt.is_valid() {
+            return false;
+        }
+        let mrt = Some(Duration::from_secs(max_rt.secs() as u64));
+        let changed = self.params.mrt != mrt;
+        self.params.mrt = mrt;
+        changed
+    }
+
+    // RAND, a random factor in `low..=high`, 0 without jitter
+    fn rand(&self, low: f64, high: f64) -> f64 {
+        if self.jitter {
+            rand::thread_rng().gen_range(low..=high)
+        } else {
+            0.0
+        }
+    }
+
+    // `rt + RAND * rt` for RAND in `low..=high`
+    fn randomize(&self, rt: Duration, low: f64, high: f64) -> Duration {
+        add_rand(rt, rt, self.rand(low, high))
+    }
+}
+
+// `rt + rand * base`, saturating. `rand` is within ±0.1 so the product can't overflow
+fn add_rand(rt: Duration, base: Duration, rand: f64) -> Duration {
+    let delta = base.mul_f64(rand.abs());
+    if rand < 0.0 {
+        rt.saturating_sub(delta)
+    } else {
+        rt.saturating_add(delta)
+    }
+}
+
+impl Iterator for Retransmit {
+    type Item = (Duration, Message);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.params.mrc.is_some_and(|mrc| self.attempt >= mrc) {
+            return None;
+        }
+        let delay = match self.attempt {
+            0 => Duration::ZERO,
+            1 => {
+                // the first Solicit timeout must be greater than IRT
+                let low = if self.msg.msg_type() == MessageType::Solicit {
+                    0.0
+                } else {
+                    -0.1
+                };
+                self.randomize(self.params.irt, low, 0.1)
+            }
+            _ => {
+                // RT = 2*RTprev + RAND*RTprev
+                let rt = add_rand(self.rt.saturating_mul(2), self.rt, self.rand(-0.1, 0.1));
+                match self.params.mrt {
+                    Some(mrt) if rt > mrt => self.randomize(mrt, -0.1, 0.1),
+                    _ => rt,
+                }
+            }
+        };
+        if self
+            .params
+            .mrd
+            .is_some_and(|mrd| self.elapsed.saturating_add(delay) > mrd)
+        {
+            return None;
+        }
+        self.rt = delay;
+        self.elapsed = self.elapsed.saturating_add(delay);
+        let mut msg = self.msg.clone();
+        match self.tracker {
+            Some(tracker) => match Instant::now().checked_add(delay) {
+                Some(due) => tracker.stamp_at(&mut msg, due),
+                // far past what `ElapsedTime` can hold
+                None => set_elapsed(&mut msg, u16::MAX),
+            },
+            // hundredths of a second
+            None => set_elapsed(
+                &mut msg,
+                u16::try_from(self.elapsed.as_millis() / 10).unwrap_or(u16::MAX),
+            ),
+        }
+        self.attempt += 1;
+        Some((delay, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::{Decodable, Decoder},
+        v6::MaxRt,
+    };
+
+    #[test]
+    fn test_retransmit() {
+        let solicit = Message::new(MessageType::Solicit);
+        let mut sends = Retransmit::new(solicit).unwrap();
+        assert_eq!(sends.next().unwrap().1.elapsed_time(), Some(0));
+        for n in 1..20 {
+            let (delay, _) = sends.next().unwrap();
+            if n == 1 {
+                assert!(delay >= Duration::from_secs(1));
+                assert!(delay <= Duration::from_millis(1100));
+            }
+            assert!(delay <= SOL_MAX_RT.mul_f64(1.1));
+        }
+
+        let mut reply = Message::new(MessageType::Advertise);
+        reply
+            .opts_mut()
+            .insert(DhcpOption::SolMaxRt(MaxRt::new(120).unwrap()));
+        assert!(sends.update_max_rt(&reply));
+        assert!(!sends.update_max_rt(&reply));
+        assert_eq!(sends.params().mrt, Some(Duration::from_secs(120)));
+        let (delay, _) = sends.next().unwrap();
+        assert!(delay <= Duration::from_secs(132));
+        assert_eq!(sends.timeout(), delay);
+
+        // not for Request, and out of range values are ignored
+        let mut request = Retransmit::new(Message::new(MessageType::Request)).unwrap();
+        assert!(!request.update_max_rt(&reply));
+        let mut info = Retransmit::new(Message::new(MessageType::InformationRequest)).unwrap();
+        reply.set_msg_type(MessageType::Reply);
+        // as kept by a lenient decoder
+        let mut raw = vec![0, 83, 0, 4];
+        raw.extend(30u32.to_be_bytes());
+        let invalid = DhcpOption::decode(&mut Decoder::new(&raw)).unwrap();
+        reply.opts_mut().insert(invalid);
+        assert!(!info.update_max_rt(&reply));
+        reply.opts_mut().remove(OptionCode::InfMaxRt);
+        reply
+            .opts_mut()
+            .insert(DhcpOption::InfMaxRt(MaxRt::new(60).unwrap()));
+        assert!(info.update_max_rt(&reply));
+        assert_eq!(info.params().mrt, Some(Duration::from_secs(60)));
+
+        assert!(Retransmit::new(Message::new(MessageType::Reply)).is_none());
+    }
+
+    #[test]
+    fn test_elapsed_time_tracker() {
+        let start = Instant::now();
+        let tracker = ElapsedTimeTracker::starting_at(start);
+        assert_eq!(tracker.elapsed_at(start + Duration::from_millis(19)), 1);
+        assert_eq!(tracker.elapsed_at(start + Duration::from_secs(700)), 0xffff);
+        // a clock before the start isn't negative
+        let later = ElapsedTimeTracker::starting_at(start + Duration::from_secs(1));
+        assert_eq!(later.elapsed_at(start), 0);
+
+        // stamped at the time each send is due
+        let request = Message::new(MessageType::Request);
+        let mut sends = Retransmit::new(request)
+            .unwrap()
+            .jitter(false)
+            .tracker(tracker);
+        assert!(sends.next().unwrap().1.elapsed_time() >= Some(0));
+        assert!(sends.next().unwrap().1.elapsed_time() >= Some(100));
+        assert!(sends.next().unwrap().1.elapsed_time() >= Some(200));
+    }
+
+    #[test]
+    fn test_mrd() {
+        let confirm = Message::new(MessageType::Confirm);
+        let sends = Retransmit::new(confirm).unwrap().jitter(false);
+        let delays = sends.map(|(delay, _)| delay.as_secs()).collect::<Vec<_>>();
+        // 0 + 1 + 2 + 4 = 7, another 4 would pass MRD
+        assert_eq!(delays, [0, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_unbounded() {
+        let params = Params {
+            irt: Duration::from_secs(1),
+            mrt: None,
+            mrc: None,
+            mrd: None,
+        };
+        let mut sends = Retransmit::with_params(Message::new(MessageType::Release), params);
+        sends.next();
+        let mut prev = sends.next().unwrap().0;
+        for _ in 0..10 {
+            // RAND applies to RTprev, not to 2*RTprev
+            let (delay, _) = sends.next().unwrap();
+            assert!(delay >= prev.mul_f64(1.9) && delay <= prev.mul_f64(2.1));
+            prev = delay;
+        }
+        // timeouts & the elapsed time saturate instead of overflowing
+        assert!(sends.nth(100).is_some());
+        assert!(sends.timeout() >= Duration::MAX.mul_f64(0.9));
+    }
+}
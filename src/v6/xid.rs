@@ -0,0 +1,236 @@
+//! Transaction ids & matching replies to requests
+//!
+//! A client matches an Advertise or Reply to the message it sent by the transaction id
+//! and must check the reply carries the same `ClientId`, discarding it otherwise.
+//! <https://datatracker.ietf.org/doc/html/rfc8415#section-16>
+//!
+//! [`TransactionTracker`] does the bookkeeping without any I/O, the caller passes the
+//! current time in.
+//!
+//! ```rust
+//! use std::time::{Duration, Instant};
+//! use dhcproto::v6::{self, xid::TransactionTracker, DhcpOption};
+//!
+//! let mut tracker = TransactionTracker::new(Duration::from_secs(10));
+//! let mut solicit = v6::Message::new(v6::MessageType::Solicit);
+//! solicit.opts_mut().insert(DhcpOption::ClientId(vec![0, 1, 2, 3]));
+//! let now = Instant::now();
+//! tracker.start(&solicit, now);
+//!
+//! let mut advertise = v6::Message::new_with_id(v6::MessageType::Advertise, solicit.xid());
+//! advertise.opts_mut().insert(DhcpOption::ClientId(vec![0, 1, 2, 3]));
+//! let pending = tracker.match_reply(&advertise, now).unwrap();
+//! assert_eq!(pending.msg_type, v6::MessageType::Solicit);
+//! ```
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::v6::{DhcpOption, Message, MessageType, OptionCode};
+
+/// a random transaction id
+pub fn random() -> [u8; 3] {
+    rand::random()
+}
+
+/// `true` if both messages have the same transaction id
+pub fn same_xid(a: &Message, b: &Message) -> bool {
+    a.xid() == b.xid()
+}
+
+// the ClientId option's contents, if there is one
+fn client_id(msg: &Message) -> Option<&[u8]> {
+    match msg.opts().get(OptionCode::ClientId) {
+        Some(DhcpOption::ClientId(id)) => Some(id),
+        _ => None,
+    }
+}
+
+/// Returned when a reply doesn't match an outstanding transaction
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchError {
+    /// no transaction has the reply's xid
+    #[error("no outstanding transaction with xid {0:02x?}")]
+    UnknownXid([u8; 3]),
+    /// a transaction has the reply's xid, but a different `ClientId`
+    #[error("ClientId doesn't match the request")]
+    ClientIdMismatch,
+    /// the reply isn't an Advertise or Reply
+    #[error("{0:?} is not a reply")]
+    NotReply(MessageType),
+    /// an Advertise for a request that wasn't a Solicit
+    #[error("Advertise is only sent in response to a Solicit")]
+    UnexpectedAdvertise,
+    /// the transaction timed out, it's removed from the tracker
+    #[error("transaction timed out")]
+    Expired,
+}
+
+/// A request waiting for a reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pending {
+    /// type of the request
+    pub msg_type: MessageType,
+    /// `ClientId` of the request, Information-request may be sent without one
+    pub client_id: Option<Vec<u8>>,
+    /// when the request was first sent
+    pub sent: Instant,
+}
+
+/// Tracks outstanding requests by xid and `ClientId`
+#[derive(Debug, Clone)]
+pub struct TransactionTracker {
+    timeout: Duration,
+    pending: HashMap<([u8; 3], Option<Vec<u8>>), Pending>,
+}
+
+impl TransactionTracker {
+    /// create a tracker, transactions expire `timeout` after they're started
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// track a request sent at `now`. Retransmissions use the same xid, starting them
+    /// again keeps the original send time
+    pub fn start(&mut self, msg: &Message, now: Instant) {
+        let client_id = client_id(msg).map(<[u8]>::to_vec);
+        self.pending
+            .entry((msg.xid(), client_id.clone()))
+            .or_insert(Pending {
+                msg_type: msg.msg_type(),
+                client_id,
+                sent: now,
+            });
+    }
+
+    /// match a received Advertise or Reply. The `ClientId` must be the same as the
+    /// request's, or absent from both. A Reply completes the transaction and removes it,
+    /// an Advertise leaves it so more Advertises can be collected.
+    pub fn match_reply(&mut self, reply: &Message, now: Instant) -> Result<Pending, MatchError> {
+        let xid = reply.xid();
+        let key = (xid, client_id(reply).map(<[u8]>::to_vec));
+        let Some(pending) = self.pending.get(&key) else {
+            return Err(if self.pending.keys().any(|(id, _)| *id == xid) {
+                MatchError::ClientIdMismatch
+            } else {
+                MatchError::UnknownXid(xid)
+            });
+        };
+        if now.saturating_duration_since(pending.sent) > self.timeout {
+            self.pending.remove(&key);
+            return Err(MatchError::Expired);
+        }
+        match reply.msg_type() {
+            MessageType::Advertise if pending.msg_type == MessageType::Solicit => {
+                Ok(pending.clone())
+            }
+            MessageType::Advertise => Err(MatchError::UnexpectedAdvertise),
+            MessageType::Reply => Ok(self.pending.remove(&key).expect("pending transaction")),
+            msg_type => Err(MatchError::NotReply(msg_type)),
+        }
+    }
+
+    /// stop tracking transactions with `xid`, e.g. when the client gives up
+    pub fn remove(&mut self, xid: [u8; 3]) {
+        self.pending.retain(|(id, _), _| *id != xid);
+    }
+
+    /// remove and return transactions that have timed out at `now`
+    pub fn expire(&mut self, now: Instant) -> Vec<Pending> {
+        let timeout = self.timeout;
+        let mut expired = Vec::new();
+        self.pending.retain(|_, pending| {
+            let keep = now.saturating_duration_since(pending.sent) <= timeout;
+            if !keep {
+                expired.push(pending.clone());
+            }
+            keep
+        });
+        expired
+    }
+
+    /// number of outstanding transactions
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// `true` if there are no outstanding transactions
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(msg_type: MessageType, xid: [u8; 3], client_id: Option<&[u8]>) -> Message {
+        let mut msg = Message::new_with_id(msg_type, xid);
+        if let Some(id) = client_id {
+            msg.opts_mut().insert(DhcpOption::ClientId(id.to_vec()));
+        }
+        msg
+    }
+
+    #[test]
+    fn test_tracker() {
+        let now = Instant::now();
+        let mut tracker = TransactionTracker::new(Duration::from_secs(5));
+        let xid = random();
+        tracker.start(&msg(MessageType::Solicit, xid, Some(&[1])), now);
+        tracker.start(&msg(MessageType::InformationRequest, [9; 3], None), now);
+        assert_eq!(tracker.len(), 2);
+
+        // ClientId must match
+        assert_eq!(
+            tracker.match_reply(&msg(MessageType::Advertise, xid, Some(&[2])), now),
+            Err(MatchError::ClientIdMismatch)
+        );
+        assert_eq!(
+            tracker.match_reply(&msg(MessageType::Advertise, xid, None), now),
+            Err(MatchError::ClientIdMismatch)
+        );
+        assert_eq!(
+            tracker.match_reply(&msg(MessageType::Reply, [9; 3], Some(&[1])), now),
+            Err(MatchError::ClientIdMismatch)
+        );
+        assert_eq!(
+            tracker.match_reply(&msg(MessageType::Reply, [7; 3], Some(&[1])), now),
+            Err(MatchError::UnknownXid([7; 3]))
+        );
+
+        // advertise keeps the transaction, reply completes it
+        let advertise = msg(MessageType::Advertise, xid, Some(&[1]));
+        assert!(tracker.match_reply(&advertise, now).is_ok());
+        assert!(tracker.match_reply(&advertise, now).is_ok());
+        let reply = msg(MessageType::Reply, xid, Some(&[1]));
+        assert_eq!(
+            tracker.match_reply(&reply, now).unwrap().msg_type,
+            MessageType::Solicit
+        );
+        assert!(tracker.match_reply(&reply, now).is_err());
+
+        assert_eq!(
+            tracker.match_reply(&msg(MessageType::Advertise, [9; 3], None), now),
+            Err(MatchError::UnexpectedAdvertise)
+        );
+        let later = now + Duration::from_secs(6);
+        assert_eq!(
+            tracker.match_reply(&msg(MessageType::Reply, [9; 3], None), later),
+            Err(MatchError::Expired)
+        );
+        assert!(tracker.is_empty());
+
+        tracker.start(&msg(MessageType::Request, xid, Some(&[1])), now);
+        assert!(tracker.expire(now).is_empty());
+        assert_eq!(tracker.expire(later).len(), 1);
+        assert!(tracker.is_empty());
+        assert!(same_xid(&reply, &advertise));
+    }
+}
@@ -0,0 +1,167 @@
+//! Vendor class matching for client classification
+//!
+//! Clients identify their vendor & type in v4 option 60 (`ClassIdentifier`) or the
+//! v6 `VendorClass` option (16), e.g. `PXEClient:Arch:00007:UNDI:003016`, `MSFT 5.0`,
+//! `udhcp 1.36.1` or `docsis3.0`. [`VendorClasses`] gets those from a message, with
+//! helpers for the checks servers usually make.
+//!
+//! ```rust
+//! use dhcproto::{v4, vendor_class::{PxeClient, VendorClasses}};
+//!
+//! let mut msg = v4::Message::default();
+//! msg.opts_mut().insert(v4::DhcpOption::ClassIdentifier(
+//!     b"PXEClient:Arch:00007:UNDI:003016".to_vec(),
+//! ));
+//! assert!(msg.vendor_class_starts_with("pxeclient"));
+//! assert_eq!(
+//!     msg.pxe_client(),
+//!     Some(PxeClient {
+//!         http: false,
+//!         arch: Some(v4::Architecture::BC),
+//!         undi: Some((3, 16)),
+//!     })
+//! );
+//! ```
+use crate::{v4, v6};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Fields of a PXE or UEFI HTTP boot vendor class,
+/// `PXEClient:Arch:xxxxx:UNDI:yyyzzz` or `HTTPClient:Arch:xxxxx:UNDI:yyyzzz`
+/// <https://datatracker.ietf.org/doc/html/rfc4578#section-2.1>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PxeClient {
+    /// `true` for `HTTPClient`, `false` for `PXEClient`
+    pub http: bool,
+    /// client system architecture
+    pub arch: Option<v4::Architecture>,
+    /// UNDI version as (major, minor)
+    pub undi: Option<(u16, u16)>,
+}
+
+impl PxeClient {
+    /// parse a PXE vendor class, `None` if it isn't one. Missing or malformed fields
+    /// are left as `None`.
+    pub fn parse(class: &[u8]) -> Option<Self> {
+        let class = std::str::from_utf8(class).ok()?;
+        let mut fields = class.split(':');
+        let http = match fields.next()? {
+            kind if kind.eq_ignore_ascii_case("PXEClient") => false,
+            kind if kind.eq_ignore_ascii_case("HTTPClient") => true,
+            _ => return None,
+        };
+        let mut pxe = Self {
+            http,
+            arch: None,
+            undi: None,
+        };
+        while let (Some(key), Some(value)) = (fields.next(), fields.next()) {
+            if key.eq_ignore_ascii_case("Arch") {
+                pxe.arch = value.parse::<u16>().ok().map(v4::Architecture::from);
+            } else if key.eq_ignore_ascii_case("UNDI") && value.len() == 6 && value.is_ascii() {
+                pxe.undi = value[..3].parse().ok().zip(value[3..].parse().ok());
+            }
+        }
+        Some(pxe)
+    }
+}
+
+/// Types that carry vendor classes
+pub trait VendorClasses {
+    /// every vendor class in the message. v6 clients may send several
+    fn vendor_classes(&self) -> Vec<&[u8]>;
+
+    /// `true` if any vendor class starts with `prefix`, ignoring ASCII case
+    fn vendor_class_starts_with(&self, prefix: &str) -> bool {
+        self.vendor_classes().iter().any(|class| {
+            class
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+        })
+    }
+
+    /// the first PXE or HTTP boot vendor class
+    fn pxe_client(&self) -> Option<PxeClient> {
+        self.vendor_classes().into_iter().find_map(PxeClient::parse)
+    }
+}
+
+impl VendorClasses for v4::DhcpOptions {
+    fn vendor_classes(&self) -> Vec<&[u8]> {
+        match self.get(v4::OptionCode::ClassIdentifier) {
+            Some(v4::DhcpOption::ClassIdentifier(class)) => vec![class.as_slice()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl VendorClasses for v4::Message {
+    fn vendor_classes(&self) -> Vec<&[u8]> {
+        self.opts().vendor_classes()
+    }
+}
+
+impl VendorClasses for v6::DhcpOptions {
+    fn vendor_classes(&self) -> Vec<&[u8]> {
+        self.iter()
+            .filter_map(|opt| match opt {
+                v6::DhcpOption::VendorClass(class) => Some(class.data.iter()),
+                _ => None,
+            })
+            .flatten()
+            .map(Vec::as_slice)
+            .collect()
+    }
+}
+
+impl VendorClasses for v6::Message {
+    fn vendor_classes(&self) -> Vec<&[u8]> {
+        self.opts().vendor_classes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_classes() {
+        let mut msg = v6::Message::new(v6::MessageType::Solicit);
+        msg.opts_mut()
+            .insert(v6::DhcpOption::VendorClass(v6::VendorClass {
+                num: 343,
+                data: vec![
+                    b"foo".to_vec(),
+                    b"HTTPClient:Arch:00016:UNDI:003001".to_vec(),
+                ],
+            }));
+        assert!(msg.vendor_class_starts_with("FOO"));
+        assert!(msg.vendor_class_starts_with("httpclient"));
+        assert!(!msg.vendor_class_starts_with("foobar"));
+        assert_eq!(
+            msg.pxe_client(),
+            Some(PxeClient {
+                http: true,
+                arch: Some(v4::Architecture::Unknown(16)),
+                undi: Some((3, 1)),
+            })
+        );
+
+        assert_eq!(
+            PxeClient::parse(b"PXEClient"),
+            Some(PxeClient {
+                http: false,
+                arch: None,
+                undi: None
+            })
+        );
+        assert_eq!(
+            PxeClient::parse(b"PXEClient:Arch:x:UNDI:1").unwrap().undi,
+            None
+        );
+        assert_eq!(PxeClient::parse(b"MSFT 5.0"), None);
+        assert!(v4::Message::default().vendor_classes().is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+//! Regression tests over the captured packets in `tests/corpus`
+//!
+//! Every `.hex` file is decoded leniently, re-encoded and decoded again, and the decoded
+//! message is compared against the `.snap` file next to it. A missing snapshot fails,
+//! run with `UPDATE_SNAPSHOTS=1` to write new or changed snapshots.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use dhcproto::{redact::reveal, v4, v6, Decodable, Decoder, Encodable};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn packets(dir: &str) -> Result<Vec<PathBuf>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/corpus")
+        .join(dir);
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .filter(|path| {
+            path.as_ref().map_or(true, |p: &PathBuf| {
+                p.extension().is_some_and(|ext| ext == "hex")
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    paths.sort();
+    assert!(!paths.is_empty(), "no packets in corpus");
+    Ok(paths)
+}
+
+// hex bytes separated by whitespace, `#` starts a comment
+fn read_hex(path: &Path) -> Result<Vec<u8>> {
+    let hex = fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(str::split_whitespace)
+        .collect::<String>();
+    Ok(hex::decode(hex)?)
+}
+
+fn check_snapshot(path: &Path, actual: &str) -> Result<()> {
+    let snap = path.with_extension("snap");
+    if env::var_os("UPDATE_SNAPSHOTS").is_some_and(|v| v == "1") {
+        fs::write(&snap, actual)?;
+        return Ok(());
+    }
+    assert!(
+        snap.exists(),
+        "{} is missing, run with UPDATE_SNAPSHOTS=1 to write it",
+        snap.display()
+    );
+    let expected = fs::read_to_string(&snap)?;
+    assert_eq!(
+        expected,
+        actual,
+        "{} changed, run with UPDATE_SNAPSHOTS=1 if this is expected",
+        snap.display()
+    );
+    Ok(())
+}
+
+// decode leniently, then check the message survives an encode/decode
+fn round_trip<T>(path: &Path, bytes: &[u8]) -> Result<T>
+where
+    T: Decodable + Encodable + PartialEq + std::fmt::Debug,
+{
+    let msg =
+        T::decode(&mut Decoder::new(bytes)).map_err(|e| format!("{}: {e}", path.display()))?;
+    let decoded = T::decode(&mut Decoder::new(&msg.to_vec()?))?;
+    assert_eq!(msg, decoded, "{} didn't round trip", path.display());
+    Ok(msg)
+}
+
+// `v4::DhcpOptions` is a map, so options are sorted by code to keep snapshots stable
+fn snapshot_v4(msg: &v4::Message) -> String {
+    let mut opts = msg.opts().iter().map(|(_, opt)| opt).collect::<Vec<_>>();
+    opts.sort();
+    format!(
+        "opcode: {:?}\nhtype: {:?}\nhlen: {}\nhops: {}\nxid: {:#010x}\nsecs: {}\nflags: {}\n\
+         ciaddr: {}\nyiaddr: {}\nsiaddr: {}\ngiaddr: {}\nchaddr: {}\nsname: {:?}\nfname: {:?}\n\
+         magic: {:02x?}\nopts: {:#?}\n",
+        msg.opcode(),
+        msg.htype(),
+        msg.hlen(),
+        msg.hops(),
+        msg.xid(),
+        msg.secs(),
+        msg.flags(),
+        msg.ciaddr(),
+        msg.yiaddr(),
+        msg.siaddr(),
+        msg.giaddr(),
+        hex::encode(msg.chaddr()),
+        msg.sname_str(),
+        msg.fname_str(),
+        msg.magic(),
+        reveal(&opts),
+    )
+}
+
+#[test]
+fn corpus_v4() -> Result<()> {
+    for path in packets("v4")? {
+        let bytes = read_hex(&path)?;
+        let msg = round_trip::<v4::Message>(&path, &bytes)?;
+        check_snapshot(&path, &snapshot_v4(&msg))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn corpus_v6() -> Result<()> {
+    for path in packets("v6")? {
+        let bytes = read_hex(&path)?;
+        // relay-forw & relay-repl have a different header
+        let snapshot = match bytes.first().copied().map(v6::MessageType::from) {
+            Some(v6::MessageType::RelayForw | v6::MessageType::RelayRepl) => {
+                format!(
+                    "{:#?}\n",
+                    reveal(&round_trip::<v6::RelayMessage>(&path, &bytes)?)
+                )
+            }
+            _ => format!(
+                "{:#?}\n",
+                reveal(&round_trip::<v6::Message>(&path, &bytes)?)
+            ),
+        };
+        check_snapshot(&path, &snapshot)?;
+    }
+    Ok(())
+}